@@ -1,5 +1,4 @@
 use std::{
-    collections::HashMap,
     iter::zip,
     time::{Duration, Instant},
 };
@@ -34,7 +33,7 @@ fn create_qubits(c: &mut Criterion) {
 }
 
 fn scatter_single_qubit_gates(c: &mut Criterion) {
-    let mut group = c.benchmark_group(format!("scatter_single_qubit_gates"));
+    let mut group = c.benchmark_group("scatter_single_qubit_gates");
     for size in SIZES.iter() {
         group.throughput(criterion::Throughput::Elements(*size as u64));
         group.bench_function(BenchmarkId::from_parameter(size), |b| {
@@ -54,7 +53,7 @@ fn scatter_single_qubit_gates(c: &mut Criterion) {
 }
 
 fn scatter_two_qubit_gates(c: &mut Criterion) {
-    let mut group = c.benchmark_group(format!("scatter_two_qubit_gates"));
+    let mut group = c.benchmark_group("scatter_two_qubit_gates");
     for size in SIZES.iter() {
         group.throughput(criterion::Throughput::Elements(*size as u64));
         group.bench_function(BenchmarkId::from_parameter(size), |b| {
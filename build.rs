@@ -0,0 +1,237 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+/// A complex number as `(re, im)`.
+type Complex = (f64, f64);
+type Matrix<const N: usize> = [[Complex; N]; N];
+
+const R: f64 = std::f64::consts::FRAC_1_SQRT_2;
+const TOLERANCE: f64 = 1e-9;
+
+/// Names of the VOPs in the order of the `Vop` variants.
+///
+/// The VOP called `PL` is the coset representative `IL` applied after the Pauli `P`.
+const NAMES: [&str; 24] = [
+    "IA", "XA", "YA", "ZA", "IB", "XB", "YB", "ZB", "IC", "XC", "YC", "ZC", "ID", "XD", "YD", "ZD",
+    "IE", "XE", "YE", "ZE", "IF", "XF", "YF", "ZF",
+];
+const AXES: [&str; 3] = ["X", "Y", "Z"];
+
+/// I, X, Y and Z.
+const PAULIS: [Matrix<2>; 4] = [
+    [[(1.0, 0.0), (0.0, 0.0)], [(0.0, 0.0), (1.0, 0.0)]],
+    [[(0.0, 0.0), (1.0, 0.0)], [(1.0, 0.0), (0.0, 0.0)]],
+    [[(0.0, 0.0), (0.0, -1.0)], [(0.0, 1.0), (0.0, 0.0)]],
+    [[(1.0, 0.0), (0.0, 0.0)], [(0.0, 0.0), (-1.0, 0.0)]],
+];
+
+/// The VOPs IA to IF, one per coset of the Pauli group, up to a global phase.
+const COSETS: [Matrix<2>; 6] = [
+    [[(1.0, 0.0), (0.0, 0.0)], [(0.0, 0.0), (1.0, 0.0)]],
+    [[(0.0, 0.0), (0.0, 1.0)], [(1.0, 0.0), (0.0, 0.0)]],
+    [[(R, 0.0), (-R, 0.0)], [(-R, 0.0), (-R, 0.0)]],
+    [[(R, 0.0), (0.0, R)], [(0.0, -R), (-R, 0.0)]],
+    [[(0.5, 0.5), (0.5, -0.5)], [(0.5, 0.5), (-0.5, 0.5)]],
+    [[(R, 0.0), (R, 0.0)], [(0.0, R), (0.0, -R)]],
+];
+
+fn cmul((ar, ai): Complex, (br, bi): Complex) -> Complex {
+    (ar * br - ai * bi, ar * bi + ai * br)
+}
+
+fn matmul<const N: usize>(a: &Matrix<N>, b: &Matrix<N>) -> Matrix<N> {
+    let mut out = [[(0.0, 0.0); N]; N];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            for k in 0..N {
+                let (re, im) = cmul(a[i][k], b[k][j]);
+                entry.0 += re;
+                entry.1 += im;
+            }
+        }
+    }
+    out
+}
+
+fn adjoint(a: &Matrix<2>) -> Matrix<2> {
+    let mut out = [[(0.0, 0.0); 2]; 2];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = (a[j][i].0, -a[j][i].1);
+        }
+    }
+    out
+}
+
+fn kron(a: &Matrix<2>, b: &Matrix<2>) -> Matrix<4> {
+    let mut out = [[(0.0, 0.0); 4]; 4];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = cmul(a[i / 2][j / 2], b[i % 2][j % 2]);
+        }
+    }
+    out
+}
+
+/// The phase `c` with `a = c b`, if there is one.
+fn phase(a: &[Complex], b: &[Complex]) -> Option<Complex> {
+    let (idx, &(br, bi)) = b
+        .iter()
+        .enumerate()
+        .find(|(_, (re, im))| re.abs() > TOLERANCE || im.abs() > TOLERANCE)?;
+    let norm = br * br + bi * bi;
+    let (ar, ai) = a[idx];
+    let c = ((ar * br + ai * bi) / norm, (ai * br - ar * bi) / norm);
+    let matches = a.iter().zip(b).all(|(&a, &b)| {
+        let (re, im) = cmul(c, b);
+        (a.0 - re).abs() < TOLERANCE && (a.1 - im).abs() < TOLERANCE
+    });
+    matches.then_some(c)
+}
+
+fn vop(idx: usize) -> Matrix<2> {
+    matmul(&COSETS[idx / 4], &PAULIS[idx % 4])
+}
+
+/// Index of the VOP equal to `m` up to a global phase.
+fn find_vop(m: &Matrix<2>) -> usize {
+    (0..NAMES.len())
+        .find(|&idx| phase(m.as_flattened(), vop(idx).as_flattened()).is_some())
+        .expect("Products of Cliffords are Cliffords")
+}
+
+/// The axis and sign of the Pauli `m` is equal to.
+fn find_axis(m: &Matrix<2>) -> (usize, bool) {
+    (0..AXES.len())
+        .find_map(|axis| {
+            phase(m.as_flattened(), PAULIS[axis + 1].as_flattened()).map(|(re, _)| (axis, re < 0.0))
+        })
+        .expect("Cliffords map Paulis to Paulis")
+}
+
+/// (C ⊗ T) CZ^e |++⟩, with the control as the most significant qubit.
+fn two_qubit_state(edge: bool, control: usize, target: usize) -> [Complex; 4] {
+    let vops = kron(&vop(control), &vop(target));
+    let sign = if edge { -0.5 } else { 0.5 };
+    let graph = [(0.5, 0.0), (0.5, 0.0), (0.5, 0.0), (sign, 0.0)];
+    std::array::from_fn(|i| {
+        (0..4).fold((0.0, 0.0), |acc, k| {
+            let (re, im) = cmul(vops[i][k], graph[k]);
+            (acc.0 + re, acc.1 + im)
+        })
+    })
+}
+
+/// Whether the VOP commutes with CZ, so is left alone by the CZs to other neighbours.
+fn is_in_z(idx: usize) -> bool {
+    matches!(NAMES[idx], "IA" | "ZA" | "YB" | "XB")
+}
+
+fn vop_list(out: &mut String, vops: impl Iterator<Item = usize>, indent: &str) {
+    for idx in vops {
+        writeln!(out, "{indent}Vop::{},", NAMES[idx]).unwrap();
+    }
+}
+
+fn axis_list(out: &mut String, axes: impl Iterator<Item = usize>, indent: &str) {
+    for axis in axes {
+        writeln!(out, "{indent}Axis::{},", AXES[axis]).unwrap();
+    }
+}
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+    let count = NAMES.len();
+    let mut out = String::new();
+
+    out.push_str("pub const VOP_TABLE: [[Vop; SYMMETRIES]; SYMMETRIES] = [\n");
+    for a in 0..count {
+        out.push_str("    [\n");
+        vop_list(
+            &mut out,
+            (0..count).map(|b| find_vop(&matmul(&vop(a), &vop(b)))),
+            "        ",
+        );
+        out.push_str("    ],\n");
+    }
+    out.push_str("];\n\n");
+
+    out.push_str("pub(crate) const ADJ_TABLE: [Vop; SYMMETRIES] = [\n");
+    vop_list(
+        &mut out,
+        (0..count).map(|v| find_vop(&adjoint(&vop(v)))),
+        "    ",
+    );
+    out.push_str("];\n\n");
+
+    // the axis V† X V, which an isolated qubit with VOP V† is an eigenstate of
+    out.push_str("pub(crate) const DETM_TABLE: [Axis; SYMMETRIES] = [\n");
+    axis_list(
+        &mut out,
+        (0..count).map(|v| find_axis(&matmul(&matmul(&adjoint(&vop(v)), &PAULIS[1]), &vop(v))).0),
+        "    ",
+    );
+    out.push_str("];\n\n");
+
+    out.push_str("pub(crate) const CONJ_TABLE: [[Axis; SYMMETRIES]; MEAS_AXES] = [\n");
+    for axis in 0..AXES.len() {
+        out.push_str("    [\n");
+        axis_list(
+            &mut out,
+            (0..count).map(|v| {
+                find_axis(&matmul(
+                    &matmul(&vop(v), &PAULIS[axis + 1]),
+                    &adjoint(&vop(v)),
+                ))
+                .0
+            }),
+            "        ",
+        );
+        out.push_str("    ],\n");
+    }
+    out.push_str("];\n\n");
+
+    // CZ (C ⊗ T) CZ^e |++⟩ written as (C' ⊗ T') CZ^e' |++⟩, taking the first solution
+    // in (e', C', T') order that keeps VOPs commuting with CZ doing so. Where several
+    // solutions remain this can differ from the table that used to be written by hand,
+    // which picked among them without a fixed rule: any of them is the same state, and
+    // only the diagonal constraint matters to `apply_cz`
+    let states: Vec<((bool, usize, usize), [Complex; 4])> = [false, true]
+        .into_iter()
+        .flat_map(|edge| (0..count).flat_map(move |c| (0..count).map(move |t| (edge, c, t))))
+        .map(|(edge, c, t)| ((edge, c, t), two_qubit_state(edge, c, t)))
+        .collect();
+    out.push_str(
+        "pub(crate) const CPHASE_TABLE: [[[(bool, Vop, Vop); SYMMETRIES]; SYMMETRIES]; 2] = [\n",
+    );
+    for edge in [false, true] {
+        out.push_str("    [\n");
+        for control in 0..count {
+            out.push_str("        [\n");
+            for target in 0..count {
+                let state = two_qubit_state(edge, control, target);
+                let mut applied = state;
+                applied[3] = (-state[3].0, -state[3].1);
+                let &((new_edge, new_control, new_target), _) = states
+                    .iter()
+                    .find(|((_, c, t), candidate)| {
+                        (!is_in_z(control) || is_in_z(*c))
+                            && (!is_in_z(target) || is_in_z(*t))
+                            && phase(&applied, candidate).is_some()
+                    })
+                    .expect("Every two-qubit stabilizer state has a graph form");
+                writeln!(
+                    out,
+                    "            ({new_edge}, Vop::{}, Vop::{}),",
+                    NAMES[new_control], NAMES[new_target]
+                )
+                .unwrap();
+            }
+            out.push_str("        ],\n");
+        }
+        out.push_str("    ],\n");
+    }
+    out.push_str("];\n");
+
+    let path = Path::new(&env::var("OUT_DIR").expect("Cargo sets OUT_DIR")).join("tables.rs");
+    fs::write(path, out).expect("OUT_DIR is writable");
+}
@@ -0,0 +1,91 @@
+use pyo3::prelude::*;
+
+use crate::circuit::{Circuit, Op};
+
+/// Depth and parallelism of a `Circuit`, as computed by `Circuit.analyze`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitAnalysis {
+    /// Number of layers, the length of the critical path through the circuit.
+    depth: usize,
+    /// Like `depth`, counting only the layers with a two-qubit gate.
+    two_qubit_depth: usize,
+    /// Number of operations acting on each qubit.
+    gate_counts: Vec<usize>,
+    /// Indices of the operations in each layer. The operations of a layer act on
+    /// distinct qubits and only depend on operations of earlier layers.
+    layers: Vec<Vec<usize>>,
+    /// Average number of operations per layer.
+    parallelism: f64,
+}
+
+/// Split `ops` into layers, placing every operation as early as the operations on
+/// its qubits before it allow.
+pub(crate) fn layers(ops: &[Op]) -> Vec<Vec<usize>> {
+    let mut next_layer: Vec<usize> = Vec::new();
+    let mut layers: Vec<Vec<usize>> = Vec::new();
+    for (idx, op) in ops.iter().enumerate() {
+        let qubits = op.qubits();
+        if let Some(&max) = qubits.iter().max()
+            && next_layer.len() <= max
+        {
+            next_layer.resize(max + 1, 0);
+        }
+        let layer = qubits.iter().map(|&q| next_layer[q]).max().unwrap_or(0);
+        for &qubit in &qubits {
+            next_layer[qubit] = layer + 1;
+        }
+        if layers.len() == layer {
+            layers.push(Vec::new());
+        }
+        layers[layer].push(idx);
+    }
+    layers
+}
+
+pub(crate) fn analyze(circuit: &Circuit) -> CircuitAnalysis {
+    let ops = circuit.ops_slice();
+    let layers = layers(ops);
+    let mut gate_counts = vec![0; circuit.qubit_amount()];
+    for qubit in ops.iter().flat_map(|op| op.qubits()) {
+        if gate_counts.len() <= qubit {
+            gate_counts.resize(qubit + 1, 0);
+        }
+        gate_counts[qubit] += 1;
+    }
+    let two_qubit_depth = layers
+        .iter()
+        .filter(|layer| layer.iter().any(|&idx| ops[idx].qubits().len() == 2))
+        .count();
+
+    CircuitAnalysis {
+        depth: layers.len(),
+        two_qubit_depth,
+        gate_counts,
+        parallelism: match layers.len() {
+            0 => 0.0,
+            depth => ops.len() as f64 / depth as f64,
+        },
+        layers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_layers() {
+        let circuit =
+            Circuit::from_text("new_qubit 4\nh 0\nh 2\ncx 0 1\ncx 2 3\ncz 1 2\nmeasure_z 0")
+                .unwrap();
+        let analysis = analyze(&circuit);
+
+        assert_eq!(analysis.layers, vec![vec![0, 1], vec![2, 3], vec![4, 5]]);
+        assert_eq!(analysis.depth, 3);
+        assert_eq!(analysis.two_qubit_depth, 2);
+        assert_eq!(analysis.gate_counts, vec![3, 2, 3, 1]);
+        assert_eq!(analysis.parallelism, 2.0);
+        assert_eq!(analyze(&Circuit::new(2)).depth, 0);
+    }
+}
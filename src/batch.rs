@@ -0,0 +1,314 @@
+use std::borrow::Cow;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::Circuit,
+    convert::{encode, numpy_array},
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    index::QubitIndex,
+    noise::NoiseModel,
+};
+
+/// A batch of independent simulators over the same qubits, driven by one call per gate.
+///
+/// Every gate is applied to all members, and measurements sample an outcome for every
+/// member on its own, so members start out alike and drift apart through random
+/// outcomes and noise. Members copied from one state share its storage until they
+/// change, like copies of a `GraphSim`. Qubit arguments are resolved like those of a
+/// `GraphSim`, so they can be negative or `Qubit` handles.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct SimBatch {
+    members: Vec<GraphSim>,
+    qubit_amount: usize,
+}
+
+impl SimBatch {
+    fn for_each_member(&mut self, mut op: impl FnMut(&mut GraphSim)) {
+        for sim in self.members.iter_mut() {
+            op(sim);
+        }
+    }
+
+    /// A simulator with the qubits of every member, which all share them: the first
+    /// member, or a fresh simulator for an empty batch.
+    fn reference(&self) -> Cow<'_, GraphSim> {
+        match self.members.first() {
+            Some(sim) => Cow::Borrowed(sim),
+            None => Cow::Owned(GraphSim::new(self.qubit_amount)),
+        }
+    }
+
+    /// The qubit `qubit` refers to on every member, checked like `GraphSim` does.
+    fn resolve(&self, qubit: QubitIndex) -> PyResult<NodeIdx> {
+        self.reference().resolve(qubit)
+    }
+
+    /// The two distinct qubits of a two-qubit gate on every member.
+    fn resolve_pair(&self, first: QubitIndex, second: QubitIndex) -> PyResult<(NodeIdx, NodeIdx)> {
+        self.reference().resolve_pair(first, second)
+    }
+}
+
+#[pymethods]
+impl SimBatch {
+    /// Create a batch of `members` simulators of `qubit_amount` qubits in the |0⟩ state.
+    #[new]
+    pub fn new(members: usize, qubit_amount: usize) -> SimBatch {
+        SimBatch {
+            members: vec![GraphSim::new(qubit_amount); members],
+            qubit_amount,
+        }
+    }
+
+    /// Create a batch of `members` copies of `sim`.
+    ///
+    /// Callbacks, listeners and recordings of `sim` aren't copied.
+    #[staticmethod]
+    pub fn from_sim(sim: &GraphSim, members: usize) -> SimBatch {
+        let mut template = sim.clone();
+        template.clear_measure_callbacks();
+        template.clear_operation_listeners();
+        template.stop_recording();
+        SimBatch {
+            qubit_amount: template.vops().len(),
+            members: vec![template; members],
+        }
+    }
+
+    /// Number of members of the batch.
+    pub fn __len__(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Return a copy of member `index`.
+    pub fn member(&self, index: usize) -> PyResult<GraphSim> {
+        self.members.get(index).cloned().ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Member {index} is out of range for {} members",
+                self.members.len()
+            ))
+        })
+    }
+
+    /// Apply an X (Pauli-X) gate to the given qubit on every member.
+    pub fn x(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.x(qubit));
+        Ok(())
+    }
+
+    /// Apply a Y (Pauli-Y) gate to the given qubit on every member.
+    pub fn y(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.y(qubit));
+        Ok(())
+    }
+
+    /// Apply a Z (Pauli-Z) gate to the given qubit on every member.
+    pub fn z(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.z(qubit));
+        Ok(())
+    }
+
+    /// Apply an H (Hadamard) gate to the given qubit on every member.
+    pub fn h(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.h(qubit));
+        Ok(())
+    }
+
+    /// Apply an S (phase) gate to the given qubit on every member.
+    pub fn s(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.s(qubit));
+        Ok(())
+    }
+
+    /// Apply an S† (inverse phase) gate to the given qubit on every member.
+    pub fn sdag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.sdag(qubit));
+        Ok(())
+    }
+
+    /// Apply a √X gate to the given qubit on every member.
+    pub fn sx(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.sx(qubit));
+        Ok(())
+    }
+
+    /// Apply a √Y gate to the given qubit on every member.
+    pub fn sy(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = self.resolve(qubit)?;
+        self.for_each_member(|sim| sim.sy(qubit));
+        Ok(())
+    }
+
+    /// Apply a controlled-Z (CZ) gate with `control` and `target` qubits on every member.
+    pub fn cz(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        let (control, target) = self.resolve_pair(control, target)?;
+        self.for_each_member(|sim| sim.cz(control, target));
+        Ok(())
+    }
+
+    /// Apply a controlled-X (CX) / CNOT gate with `control` and `target` on every member.
+    pub fn cx(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        let (control, target) = self.resolve_pair(control, target)?;
+        self.for_each_member(|sim| sim.cx(control, target));
+        Ok(())
+    }
+
+    /// Apply a controlled-Y (CY) gate with `control` and `target` on every member.
+    pub fn cy(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        let (control, target) = self.resolve_pair(control, target)?;
+        self.for_each_member(|sim| sim.cy(control, target));
+        Ok(())
+    }
+
+    /// Apply a SWAP gate exchanging the states of `first` and `second` on every member.
+    pub fn swap(&mut self, first: QubitIndex, second: QubitIndex) -> PyResult<()> {
+        let (first, second) = self.resolve_pair(first, second)?;
+        self.for_each_member(|sim| sim.swap(first, second));
+        Ok(())
+    }
+
+    /// Measure `qubit` along `axis` on every member, returning the outcome of each.
+    ///
+    /// Random outcomes are sampled for every member on its own, unless `outcomes` gives
+    /// the outcome to project every member onto when its outcome is random. Members
+    /// with a deterministic outcome keep it either way.
+    #[pyo3(signature = (qubit, axis, outcomes = None))]
+    pub fn measure(
+        &mut self,
+        qubit: QubitIndex,
+        axis: Axis,
+        outcomes: Option<Vec<MeasurementResult>>,
+    ) -> PyResult<Vec<MeasurementResult>> {
+        let qubit = self.resolve(qubit)?;
+        if let Some(outcomes) = &outcomes
+            && outcomes.len() != self.members.len()
+        {
+            return Err(PyValueError::new_err(format!(
+                "Got {} outcomes for {} members",
+                outcomes.len(),
+                self.members.len()
+            )));
+        }
+        Ok(self
+            .members
+            .iter_mut()
+            .enumerate()
+            .map(
+                |(idx, sim)| match (&outcomes, sim.deterministic_outcome(qubit, axis)) {
+                    (Some(outcomes), None) => {
+                        sim.project(qubit, axis, outcomes[idx]);
+                        outcomes[idx]
+                    }
+                    _ => sim.measure(qubit, axis).0,
+                },
+            )
+            .collect())
+    }
+
+    /// Run `circuit` on every member, under `noise_model` if given, with the GIL released.
+    ///
+    /// Raises `ValueError` listing the problems `validate` finds before running anything.
+    /// Returns a numpy `int8` array with a row of measurement outcomes per member,
+    /// encoded like `outcomes_to_array`: +1 and −1, or with `binary` 0 and 1.
+    #[pyo3(signature = (circuit, noise_model = None, binary = false))]
+    pub fn run<'py>(
+        &mut self,
+        py: Python<'py>,
+        circuit: &Circuit,
+        noise_model: Option<&NoiseModel>,
+        binary: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        crate::validate::check(&self.reference(), circuit)?;
+        let measurements = circuit.num_measurements();
+        let data = py.detach(|| {
+            let mut rng = crate::rng::generator();
+            let mut faults = Vec::new();
+            let mut data = Vec::with_capacity(self.members.len() * measurements);
+            for sim in self.members.iter_mut() {
+                let outcomes = match noise_model {
+                    Some(noise) => {
+                        faults.clear();
+                        noise.execute(circuit, sim, &mut rng, &mut faults)
+                    }
+                    None => circuit.execute(sim),
+                };
+                data.extend(outcomes.into_iter().map(|result| encode(result, binary)));
+            }
+            data
+        });
+        numpy_array(py, &data, "int8", &[self.members.len(), measurements])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_members_sample_independently() {
+        let mut batch = SimBatch::new(64, 2);
+        batch.h(0.into()).unwrap();
+        batch.cx(0.into(), 1.into()).unwrap();
+        let first = batch.measure(0.into(), Axis::Z, None).unwrap();
+        let second = batch.measure(1.into(), Axis::Z, None).unwrap();
+        assert_eq!(first, second);
+        assert!(first.contains(&MeasurementResult::PlusOne));
+        assert!(first.contains(&MeasurementResult::MinusOne));
+
+        let mut forced = SimBatch::new(3, 1);
+        forced.h(0.into()).unwrap();
+        let outcomes = vec![MeasurementResult::MinusOne; 3];
+        assert_eq!(
+            forced
+                .measure(0.into(), Axis::X, Some(outcomes.clone()))
+                .unwrap(),
+            vec![MeasurementResult::PlusOne; 3]
+        );
+        assert_eq!(
+            forced
+                .measure(0.into(), Axis::Z, Some(outcomes.clone()))
+                .unwrap(),
+            outcomes
+        );
+        assert!(forced.cx(0.into(), 0.into()).is_err());
+        assert!(forced.measure(0.into(), Axis::Z, Some(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_batch_qubits_are_resolved() {
+        let mut sim = GraphSim::new(3);
+        sim.trace_out(vec![1]);
+        let mut batch = SimBatch::from_sim(&sim, 4);
+        batch.x(QubitIndex::Index(-1)).unwrap();
+        batch.sx(0.into()).unwrap();
+        batch.sx(0.into()).unwrap();
+        batch.swap(0.into(), 2.into()).unwrap();
+        assert_eq!(
+            batch.measure(0.into(), Axis::Z, None).unwrap(),
+            vec![MeasurementResult::MinusOne; 4]
+        );
+        assert_eq!(
+            batch.measure(2.into(), Axis::Z, None).unwrap(),
+            vec![MeasurementResult::MinusOne; 4]
+        );
+
+        assert!(batch.h(1.into()).is_err());
+        assert!(batch.sy(QubitIndex::Index(-4)).is_err());
+        assert!(batch.swap(2.into(), QubitIndex::Index(-1)).is_err());
+        assert!(SimBatch::new(0, 2).cz(0.into(), 2.into()).is_err());
+
+        // circuits touching the traced qubit are rejected before any member runs them
+        let mut circuit = Circuit::new(3);
+        circuit.h(1);
+        assert!(crate::validate::check(&batch.reference(), &circuit).is_err());
+    }
+}
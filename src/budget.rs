@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bit_set::BitSet;
+use pyo3::prelude::*;
+use rand::Rng;
+
+use crate::{
+    circuit::Circuit,
+    decoder::{measurement_indices, odd_parities, propagate_faults},
+    graphsim::NodeIdx,
+    noise::{Fault, NoiseModel},
+};
+
+/// Errors sampled at one operation of a circuit, from `error_budget`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GateBudget {
+    /// Index of the operation in the circuit.
+    op_index: usize,
+    /// Name of the operation, like `Op.name`.
+    name: &'static str,
+    /// Qubits the operation acts on.
+    qubits: Vec<NodeIdx>,
+    /// Shots in which the operation had a fault.
+    faulty_shots: usize,
+    /// Shots in which its faults flipped a detector.
+    detector_flips: usize,
+    /// Shots in which its faults flipped an observable.
+    observable_flips: usize,
+    /// Logical failures its faults contributed to, by flipping an observable that ended
+    /// up flipped.
+    logical_failures: usize,
+}
+
+/// Attribution of the logical failures of a noisy circuit to its operations and qubits.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorBudget {
+    /// Number of shots that were run.
+    shots: usize,
+    /// Shots in which any observable flipped.
+    logical_failures: usize,
+    /// Budget of every operation that had a fault in some shot, in circuit order.
+    gates: Vec<GateBudget>,
+    /// Logical failures that faults on each qubit contributed to.
+    qubits: BTreeMap<NodeIdx, usize>,
+}
+
+/// Measurements and observables flipped by a single fault.
+struct Effect {
+    measurements: BitSet,
+    detector: bool,
+    observables: BitSet,
+}
+
+/// Effect of each fault, propagated through the rest of `circuit` the first time it
+/// comes up.
+struct Effects<'a> {
+    circuit: &'a Circuit,
+    meas_index: Vec<Option<usize>>,
+    cache: HashMap<Fault, Effect>,
+}
+
+impl Effects<'_> {
+    fn get(&mut self, fault: Fault) -> &Effect {
+        let (circuit, meas_index) = (self.circuit, &self.meas_index);
+        self.cache.entry(fault).or_insert_with(|| {
+            let (measurements, _) = propagate_faults(circuit, meas_index, fault.op_index, &[fault]);
+            let flipped = |sets| odd_parities(&measurements, sets);
+            Effect {
+                detector: flipped(&circuit.detectors).contains(&true),
+                observables: flipped(&circuit.observables)
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(idx, flip)| flip.then_some(idx))
+                    .collect(),
+                measurements,
+            }
+        })
+    }
+}
+
+/// Sample `shots` shots of `circuit` under `noise_model` and attribute their failures.
+fn budget<R: Rng + ?Sized>(
+    circuit: &Circuit,
+    noise_model: &NoiseModel,
+    shots: usize,
+    rng: &mut R,
+) -> ErrorBudget {
+    let mut effects = Effects {
+        circuit,
+        meas_index: measurement_indices(circuit),
+        cache: HashMap::new(),
+    };
+    let mut gates: Vec<GateBudget> = circuit
+        .iter()
+        .enumerate()
+        .map(|(op_index, op)| GateBudget {
+            op_index,
+            name: op.name(),
+            qubits: op.qubits(),
+            ..GateBudget::default()
+        })
+        .collect();
+    let mut qubits = BTreeMap::new();
+    let mut logical_failures = 0;
+    let mut faults = Vec::new();
+
+    for _ in 0..shots {
+        faults.clear();
+        for (idx, op) in circuit.iter().enumerate() {
+            noise_model.sample_faults(idx, op, rng, &mut faults);
+        }
+        // frames propagate linearly, so the shot flips the sum of the single faults
+        let mut flipped = BitSet::new();
+        for &fault in &faults {
+            flipped.symmetric_difference_with(&effects.get(fault).measurements);
+        }
+        let failed: BitSet = odd_parities(&flipped, &circuit.observables)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, flip)| flip.then_some(idx))
+            .collect();
+        logical_failures += usize::from(!failed.is_empty());
+
+        // faults of one operation come out together
+        for group in faults.chunk_by(|a, b| a.op_index == b.op_index) {
+            let gate = &mut gates[group[0].op_index];
+            let (mut detector, mut observable, mut contributed) = (false, false, false);
+            for &fault in group {
+                let effect = effects.get(fault);
+                detector |= effect.detector;
+                observable |= !effect.observables.is_empty();
+                let culprit = !effect.observables.is_disjoint(&failed);
+                contributed |= culprit;
+                if culprit {
+                    *qubits.entry(fault.qubit).or_insert(0) += 1;
+                }
+            }
+            gate.faulty_shots += 1;
+            gate.detector_flips += usize::from(detector);
+            gate.observable_flips += usize::from(observable);
+            gate.logical_failures += usize::from(contributed);
+        }
+    }
+
+    gates.retain(|gate| gate.faulty_shots > 0);
+    ErrorBudget {
+        shots,
+        logical_failures,
+        gates,
+        qubits,
+    }
+}
+
+/// Attribute the errors of `shots` noisy shots of `circuit` to its operations and qubits.
+///
+/// Every sampled fault is propagated to the end of the circuit on its own, like a
+/// mechanism of `detector_error_model`, and blamed for a logical failure of its shot when
+/// it flips an observable that ended up flipped. Failures are counted without decoding,
+/// so any flipped observable is one, and the detectors and observables must be
+/// deterministic without noise. A failure can be blamed on several operations, and a
+/// qubit counts once for every fault on it that contributed. The GIL is released while
+/// sampling.
+#[pyfunction]
+pub fn error_budget(
+    py: Python<'_>,
+    circuit: &Circuit,
+    noise_model: &NoiseModel,
+    shots: usize,
+) -> ErrorBudget {
+    py.detach(|| budget(circuit, noise_model, shots, &mut crate::rng::generator()))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::graphsim::Axis;
+
+    #[test]
+    fn test_budget_blames_faulty_measurement() {
+        let mut circuit = Circuit::new(2);
+        circuit.cx(0, 1);
+        circuit.measure(0, Axis::Z);
+        circuit.measure(1, Axis::Z);
+        circuit.observable(vec![1]).unwrap();
+        circuit.detector(vec![0, 1]).unwrap();
+
+        let noise = NoiseModel::new(0.0, 0.0, 0.5, 0.0, None).unwrap();
+        let result = budget(&circuit, &noise, 400, &mut StdRng::seed_from_u64(7));
+        assert_eq!(result.gates.len(), 2);
+        let (first, second) = (&result.gates[0], &result.gates[1]);
+        assert_eq!((first.op_index, first.name), (1, "measure"));
+        assert_eq!(first.logical_failures, 0);
+        assert_eq!(first.faulty_shots, first.detector_flips);
+        assert_eq!(second.logical_failures, result.logical_failures);
+        assert_eq!(second.observable_flips, second.faulty_shots);
+        assert_eq!(
+            result.qubits,
+            BTreeMap::from([(1, result.logical_failures)])
+        );
+        assert!(result.logical_failures > 100);
+    }
+}
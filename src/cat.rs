@@ -0,0 +1,149 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+
+use crate::{
+    circuit::Op,
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    noise::NoiseModel,
+};
+
+/// Outcome of the shots of `verified_cat_state`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatStateResult {
+    /// Number of shots that were run.
+    shots: usize,
+    /// Whether every verification of a shot passed, so its state was kept.
+    success: Vec<bool>,
+    /// Number of shots that passed.
+    accepted: usize,
+    /// Passed shots that still don't hold the cat state, as the checks missed an error.
+    faulty_accepted: usize,
+}
+
+/// Operations preparing (|0…0⟩ + |1…1⟩)/√2 on qubits `0..qubits` from |0…0⟩.
+fn preparation(qubits: usize) -> Vec<Op> {
+    let mut ops = vec![Op::H { qubit: 0 }];
+    ops.extend((1..qubits).map(|target| Op::Cx {
+        control: target - 1,
+        target,
+    }));
+    ops
+}
+
+/// Run one shot of the protocol, returning whether it passed and whether the state
+/// afterwards is the cat state.
+fn shot<R: Rng + ?Sized>(
+    qubits: usize,
+    checks: &[(NodeIdx, NodeIdx)],
+    noise: &NoiseModel,
+    rng: &mut R,
+) -> (bool, bool) {
+    let mut sim = GraphSim::new(qubits);
+    let mut faults = Vec::new();
+    let mut op_index = 0;
+    let mut noisy = |sim: &mut GraphSim, op: Op| {
+        op_index += 1;
+        noise.apply_noisy(op_index - 1, &op, sim, rng, &mut faults)
+    };
+    for op in preparation(qubits) {
+        noisy(&mut sim, op);
+    }
+
+    let mut passed = true;
+    for &(a, b) in checks {
+        let ancilla = sim.alloc_ancilla();
+        for control in [a, b] {
+            let target = ancilla;
+            noisy(&mut sim, Op::Cx { control, target });
+        }
+        let (qubit, axis) = (ancilla, Axis::Z);
+        let parity = noisy(&mut sim, Op::Measure { qubit, axis });
+        passed &= parity == Some(MeasurementResult::PlusOne);
+        // reset for the next check
+        sim.discard(ancilla, MeasurementResult::PlusOne);
+    }
+
+    // undoing the preparation without noise gives back |0…0⟩ exactly for the cat state
+    for op in preparation(qubits).into_iter().rev() {
+        op.apply(&mut sim);
+    }
+    let intact = (0..qubits)
+        .all(|qubit| sim.deterministic_outcome(qubit, Axis::Z) == Some(MeasurementResult::PlusOne));
+    (passed, intact)
+}
+
+/// Prepare a cat state on `qubits` qubits with verification, for `shots` noisy shots.
+///
+/// Every shot prepares (|0…0⟩ + |1…1⟩)/√2 with an H and a chain of CX gates, then
+/// verifies it: for every pair `(a, b)` of `checks`, by default the first and last
+/// qubit, an ancilla measures the parity Z_a Z_b through two CX gates and is reset for
+/// the next one. A shot is kept only if all parities are +1, as in the fault-tolerant
+/// preparation of cat states for syndrome extraction. Gates, measurements and ancillas
+/// suffer from `noise_model`, and kept shots are also checked against the ideal cat
+/// state, to see which errors the verification misses. The GIL is released meanwhile.
+#[pyfunction]
+#[pyo3(signature = (qubits, noise_model, shots, checks = None))]
+pub fn verified_cat_state(
+    py: Python<'_>,
+    qubits: usize,
+    noise_model: &NoiseModel,
+    shots: usize,
+    checks: Option<Vec<(NodeIdx, NodeIdx)>>,
+) -> PyResult<CatStateResult> {
+    if qubits == 0 {
+        return Err(PyValueError::new_err(
+            "A cat state needs at least one qubit",
+        ));
+    }
+    let checks = checks.unwrap_or_else(|| vec![(0, qubits - 1)]);
+    if let Some(&(a, b)) = checks.iter().find(|&&(a, b)| a == b || a.max(b) >= qubits) {
+        return Err(PyValueError::new_err(format!(
+            "Check ({a}, {b}) needs two different qubits out of {qubits}"
+        )));
+    }
+    Ok(py.detach(|| {
+        let mut rng = crate::rng::generator();
+        let (mut success, mut faulty_accepted) = (Vec::with_capacity(shots), 0);
+        for _ in 0..shots {
+            let (passed, intact) = shot(qubits, &checks, noise_model, &mut rng);
+            success.push(passed);
+            faulty_accepted += usize::from(passed && !intact);
+        }
+        CatStateResult {
+            shots,
+            accepted: success.iter().filter(|&&passed| passed).count(),
+            success,
+            faulty_accepted,
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_verification_catches_chain_errors() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(
+            shot(5, &[(0, 4)], &NoiseModel::default(), &mut rng),
+            (true, true)
+        );
+
+        // an X after the first CX flips qubits 1 to 4 alike, which only the check sees
+        let noise = NoiseModel::new(0.0, 0.2, 0.0, 0.0, None).unwrap();
+        let (mut rejected, mut missed) = (0, 0);
+        for _ in 0..200 {
+            match shot(5, &[(0, 4), (0, 2)], &noise, &mut rng) {
+                (false, _) => rejected += 1,
+                (true, false) => missed += 1,
+                (true, true) => {}
+            }
+        }
+        assert!(rejected > 0);
+        assert!(missed < 200 - rejected);
+    }
+}
@@ -0,0 +1,715 @@
+use std::fmt::{Display, Formatter};
+
+use pyo3::{
+    buffer::{Element, PyBuffer},
+    exceptions::PyValueError,
+    prelude::*,
+};
+
+use crate::{
+    gadget::{gadget, parse_pauli},
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    index::{QubitIndex, QubitList},
+};
+
+/// A single operation of a `Circuit`.
+///
+/// Exposed to Python as `graphsim.Op`, e.g. `Op.Cz(control=0, target=1)`.
+#[pyclass(eq, frozen, str)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    X { qubit: NodeIdx },
+    Y { qubit: NodeIdx },
+    Z { qubit: NodeIdx },
+    H { qubit: NodeIdx },
+    S { qubit: NodeIdx },
+    Sdag { qubit: NodeIdx },
+    Sx { qubit: NodeIdx },
+    Sxdag { qubit: NodeIdx },
+    Sy { qubit: NodeIdx },
+    Sydag { qubit: NodeIdx },
+    Cz { control: NodeIdx, target: NodeIdx },
+    Cx { control: NodeIdx, target: NodeIdx },
+    Cy { control: NodeIdx, target: NodeIdx },
+    Swap { first: NodeIdx, second: NodeIdx },
+    Measure { qubit: NodeIdx, axis: Axis },
+    Reset { qubit: NodeIdx },
+}
+
+impl Op {
+    /// Lowercase name matching the `GraphSim` method that performs this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::X { .. } => "x",
+            Op::Y { .. } => "y",
+            Op::Z { .. } => "z",
+            Op::H { .. } => "h",
+            Op::S { .. } => "s",
+            Op::Sdag { .. } => "sdag",
+            Op::Sx { .. } => "sx",
+            Op::Sxdag { .. } => "sxdag",
+            Op::Sy { .. } => "sy",
+            Op::Sydag { .. } => "sydag",
+            Op::Cz { .. } => "cz",
+            Op::Cx { .. } => "cx",
+            Op::Cy { .. } => "cy",
+            Op::Swap { .. } => "swap",
+            Op::Measure { .. } => "measure",
+            Op::Reset { .. } => "reset",
+        }
+    }
+
+    /// The qubits this operation acts on.
+    pub fn qubits(&self) -> Vec<NodeIdx> {
+        match *self {
+            Op::X { qubit }
+            | Op::Y { qubit }
+            | Op::Z { qubit }
+            | Op::H { qubit }
+            | Op::S { qubit }
+            | Op::Sdag { qubit }
+            | Op::Sx { qubit }
+            | Op::Sxdag { qubit }
+            | Op::Sy { qubit }
+            | Op::Sydag { qubit }
+            | Op::Measure { qubit, .. }
+            | Op::Reset { qubit } => vec![qubit],
+            Op::Cz { control, target }
+            | Op::Cx { control, target }
+            | Op::Cy { control, target }
+            | Op::Swap {
+                first: control,
+                second: target,
+            } => vec![control, target],
+        }
+    }
+
+    /// Numeric code of this operation in the array encoding used by `GraphSim.apply_ops`.
+    ///
+    /// The codes are X 0, Y 1, Z 2, H 3, S 4, S† 5, CZ 6, CX 7, CY 8, measurements
+    /// along X, Y and Z 9, 10 and 11, √X 12, √X† 13, √Y 14, √Y† 15, SWAP 16 and
+    /// reset 17.
+    pub fn code(&self) -> u8 {
+        match *self {
+            Op::X { .. } => 0,
+            Op::Y { .. } => 1,
+            Op::Z { .. } => 2,
+            Op::H { .. } => 3,
+            Op::S { .. } => 4,
+            Op::Sdag { .. } => 5,
+            Op::Cz { .. } => 6,
+            Op::Cx { .. } => 7,
+            Op::Cy { .. } => 8,
+            Op::Measure { axis, .. } => 9 + axis as u8,
+            Op::Sx { .. } => 12,
+            Op::Sxdag { .. } => 13,
+            Op::Sy { .. } => 14,
+            Op::Sydag { .. } => 15,
+            Op::Swap { .. } => 16,
+            Op::Reset { .. } => 17,
+        }
+    }
+
+    /// Decode the operation with `code` on qubit `first` and, for two-qubit gates, `second`.
+    pub fn from_code(code: u8, first: NodeIdx, second: NodeIdx) -> Option<Op> {
+        let (control, target, qubit) = (first, second, first);
+        Some(match code {
+            0 => Op::X { qubit },
+            1 => Op::Y { qubit },
+            2 => Op::Z { qubit },
+            3 => Op::H { qubit },
+            4 => Op::S { qubit },
+            5 => Op::Sdag { qubit },
+            6 => Op::Cz { control, target },
+            7 => Op::Cx { control, target },
+            8 => Op::Cy { control, target },
+            9 => Op::Measure {
+                qubit,
+                axis: Axis::X,
+            },
+            10 => Op::Measure {
+                qubit,
+                axis: Axis::Y,
+            },
+            11 => Op::Measure {
+                qubit,
+                axis: Axis::Z,
+            },
+            12 => Op::Sx { qubit },
+            13 => Op::Sxdag { qubit },
+            14 => Op::Sy { qubit },
+            15 => Op::Sydag { qubit },
+            16 => Op::Swap { first, second },
+            17 => Op::Reset { qubit },
+            _ => return None,
+        })
+    }
+
+    /// Perform this operation on `sim`, returning the outcome if it is a measurement.
+    pub fn apply(&self, sim: &mut GraphSim) -> Option<MeasurementResult> {
+        match *self {
+            Op::X { qubit } => sim.x(qubit),
+            Op::Y { qubit } => sim.y(qubit),
+            Op::Z { qubit } => sim.z(qubit),
+            Op::H { qubit } => sim.h(qubit),
+            Op::S { qubit } => sim.s(qubit),
+            Op::Sdag { qubit } => sim.sdag(qubit),
+            Op::Sx { qubit } => sim.sx(qubit),
+            Op::Sxdag { qubit } => sim.sxdag(qubit),
+            Op::Sy { qubit } => sim.sy(qubit),
+            Op::Sydag { qubit } => sim.sydag(qubit),
+            Op::Cz { control, target } => sim.cz(control, target),
+            Op::Cx { control, target } => sim.cx(control, target),
+            Op::Cy { control, target } => sim.cy(control, target),
+            Op::Swap { first, second } => sim.swap(first, second),
+            Op::Measure { qubit, axis } => return Some(sim.measure_op(qubit, axis)),
+            Op::Reset { qubit } => sim.reset_op(qubit),
+        }
+        None
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match *self {
+            Op::Measure { qubit, axis } => write!(fmt, "measure_{} {qubit}", axis),
+            op => {
+                fmt.write_str(op.name())?;
+                for qubit in op.qubits() {
+                    write!(fmt, " {qubit}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl Op {
+    /// Numeric code of the operation, see `GraphSim.apply_ops`.
+    #[getter]
+    fn opcode(&self) -> u8 {
+        self.code()
+    }
+}
+
+/// Read an integer buffer of element type `T` as rows of `(opcode, qubit, qubit)`.
+fn decode_rows<T>(py: Python<'_>, buffer: &PyBuffer<T>) -> PyResult<Vec<Op>>
+where
+    T: Element + Copy + TryInto<usize>,
+{
+    if buffer.dimensions() != 2 || buffer.shape()[1] != 3 {
+        return Err(PyValueError::new_err(format!(
+            "Operations must be an array of shape (n, 3), got shape {:?}",
+            buffer.shape()
+        )));
+    }
+    let values: Vec<usize> = buffer
+        .to_vec(py)?
+        .into_iter()
+        .map(|value| value.try_into())
+        .collect::<Result<_, _>>()
+        .map_err(|_| PyValueError::new_err("Operation entries must be non-negative"))?;
+
+    values
+        .chunks_exact(3)
+        .map(|row| {
+            u8::try_from(row[0])
+                .ok()
+                .and_then(|code| Op::from_code(code, row[1], row[2]))
+                .ok_or_else(|| PyValueError::new_err(format!("Unknown opcode {}", row[0])))
+        })
+        .collect()
+}
+
+/// Decode operations from an integer array of shape `(n, 3)` exposing the buffer
+/// protocol, like a numpy array, without going through Python objects.
+pub(crate) fn decode_ops(ops: &Bound<'_, PyAny>) -> PyResult<Vec<Op>> {
+    if let Ok(buffer) = PyBuffer::<i64>::get(ops) {
+        decode_rows(ops.py(), &buffer)
+    } else if let Ok(buffer) = PyBuffer::<i32>::get(ops) {
+        decode_rows(ops.py(), &buffer)
+    } else if let Ok(buffer) = PyBuffer::<u64>::get(ops) {
+        decode_rows(ops.py(), &buffer)
+    } else if let Ok(buffer) = PyBuffer::<u32>::get(ops) {
+        decode_rows(ops.py(), &buffer)
+    } else {
+        Err(PyValueError::new_err(
+            "Operations must be a buffer of 32 or 64 bit integers",
+        ))
+    }
+}
+
+/// An ordered list of operations on a fixed number of qubits.
+///
+/// Build one from Python with the gate methods (mirroring `GraphSim`) and execute it
+/// with `GraphSim.run`. Detectors and observables are parities of measurement outcomes,
+/// referred to by their index in the outcome list of a run. Like for `GraphSim`, qubit
+/// arguments count from the end of the register when negative.
+#[pyclass]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Circuit {
+    qubit_amount: usize,
+    ops: Vec<Op>,
+    pub(crate) detectors: Vec<Vec<usize>>,
+    pub(crate) observables: Vec<Vec<usize>>,
+}
+
+impl Circuit {
+    fn check_measurements(&self, measurements: &[usize]) -> PyResult<()> {
+        let available = self.num_measurements();
+        match measurements.iter().find(|&&idx| idx >= available) {
+            Some(idx) => Err(PyValueError::new_err(format!(
+                "Measurement {idx} does not exist, the circuit has {available} measurements"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// The two distinct qubits of a two-qubit gate, resolved in the register.
+    fn resolve_pair(&self, first: QubitIndex, second: QubitIndex) -> PyResult<(NodeIdx, NodeIdx)> {
+        let (first, second) = (
+            first.resolve(self.qubit_amount)?,
+            second.resolve(self.qubit_amount)?,
+        );
+        match first == second {
+            true => Err(PyValueError::new_err(format!(
+                "A two-qubit gate needs two different qubits, got {first} twice"
+            ))),
+            false => Ok((first, second)),
+        }
+    }
+
+    /// Add `count` qubits, starting in |0⟩, after the existing ones.
+    pub(crate) fn add_qubits(&mut self, count: usize) {
+        self.qubit_amount += count;
+    }
+
+    pub(crate) fn ops_slice(&self) -> &[Op] {
+        &self.ops
+    }
+
+    /// This circuit with its operations replaced by `ops`, keeping the detectors and
+    /// observables.
+    pub(crate) fn with_ops(&self, ops: Vec<Op>) -> Circuit {
+        Circuit {
+            ops,
+            ..self.clone()
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Op> {
+        self.ops.iter()
+    }
+
+    /// Append an X (Pauli-X) gate.
+    pub fn x(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::X { qubit });
+    }
+
+    /// Append a Y (Pauli-Y) gate.
+    pub fn y(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Y { qubit });
+    }
+
+    /// Append a Z (Pauli-Z) gate.
+    pub fn z(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Z { qubit });
+    }
+
+    /// Append an H (Hadamard) gate.
+    pub fn h(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::H { qubit });
+    }
+
+    /// Append an S (phase) gate.
+    pub fn s(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::S { qubit });
+    }
+
+    /// Append an S† (inverse phase) gate.
+    pub fn sdag(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Sdag { qubit });
+    }
+
+    /// Append a √X gate.
+    pub fn sx(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Sx { qubit });
+    }
+
+    /// Append a √X† gate.
+    pub fn sxdag(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Sxdag { qubit });
+    }
+
+    /// Append a √Y gate.
+    pub fn sy(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Sy { qubit });
+    }
+
+    /// Append a √Y† gate.
+    pub fn sydag(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Sydag { qubit });
+    }
+
+    /// Append a controlled-Z (CZ) gate.
+    pub fn cz(&mut self, control: NodeIdx, target: NodeIdx) {
+        self.ops.push(Op::Cz { control, target });
+    }
+
+    /// Append a controlled-X (CX) / CNOT gate.
+    pub fn cx(&mut self, control: NodeIdx, target: NodeIdx) {
+        self.ops.push(Op::Cx { control, target });
+    }
+
+    /// Append a controlled-Y (CY) gate.
+    pub fn cy(&mut self, control: NodeIdx, target: NodeIdx) {
+        self.ops.push(Op::Cy { control, target });
+    }
+
+    /// Append a SWAP gate.
+    pub fn swap(&mut self, first: NodeIdx, second: NodeIdx) {
+        self.ops.push(Op::Swap { first, second });
+    }
+
+    /// Append a measurement of `qubit` along `axis`.
+    pub fn measure(&mut self, qubit: NodeIdx, axis: Axis) {
+        self.ops.push(Op::Measure { qubit, axis });
+    }
+
+    /// Append a reset of `qubit` to |0⟩, forgetting its state.
+    pub fn reset(&mut self, qubit: NodeIdx) {
+        self.ops.push(Op::Reset { qubit });
+    }
+
+    /// Execute every operation on `sim`, returning the measurement outcomes in order.
+    pub fn execute(&self, sim: &mut GraphSim) -> Vec<MeasurementResult> {
+        self.ops.iter().filter_map(|op| op.apply(sim)).collect()
+    }
+}
+
+#[pymethods]
+impl Circuit {
+    /// Create an empty circuit over `qubit_amount` qubits.
+    #[new]
+    pub fn new(qubit_amount: usize) -> Circuit {
+        Circuit {
+            qubit_amount,
+            ..Default::default()
+        }
+    }
+
+    /// Number of qubits the circuit acts on.
+    #[getter]
+    pub fn qubit_amount(&self) -> usize {
+        self.qubit_amount
+    }
+
+    /// Number of measurements, i.e. the length of the outcome list of a run.
+    pub fn num_measurements(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, Op::Measure { .. }))
+            .count()
+    }
+
+    /// Declare a detector as the parity of the given measurement outcomes.
+    ///
+    /// The parity has to be deterministic without noise. Returns the detector index.
+    pub fn detector(&mut self, measurements: Vec<usize>) -> PyResult<usize> {
+        self.check_measurements(&measurements)?;
+        self.detectors.push(measurements);
+        Ok(self.detectors.len() - 1)
+    }
+
+    /// Declare a logical observable as the parity of the given measurement outcomes.
+    ///
+    /// Returns the observable index.
+    pub fn observable(&mut self, measurements: Vec<usize>) -> PyResult<usize> {
+        self.check_measurements(&measurements)?;
+        self.observables.push(measurements);
+        Ok(self.observables.len() - 1)
+    }
+
+    /// Return the measurement indices of every detector.
+    pub fn detectors(&self) -> Vec<Vec<usize>> {
+        self.detectors.clone()
+    }
+
+    /// Return the measurement indices of every observable.
+    pub fn observables(&self) -> Vec<Vec<usize>> {
+        self.observables.clone()
+    }
+
+    /// Parse a circuit from the line-based text format.
+    ///
+    /// Every line holds one command followed by its integer arguments, and `#` starts
+    /// a comment. `new_qubit [count]` adds qubits in |0⟩, the gates are written as
+    /// their method names (`h 0`, `cx 0 1`, `measure_z 2`) with `edge a b` a synonym
+    /// of `cz a b`, and `detector`/`observable` take measurement indices. Commands are
+    /// case-insensitive and errors raise `ValueError` with the offending line.
+    #[staticmethod]
+    pub fn from_text(source: &str) -> PyResult<Circuit> {
+        crate::text::parse(source)
+    }
+
+    /// Write the circuit in the text format read by `from_text`.
+    ///
+    /// Detectors and observables come after all operations.
+    pub fn to_text(&self) -> String {
+        crate::text::write(self)
+    }
+
+    /// Compute the depth, the operations per qubit and the layers of the circuit.
+    ///
+    /// Every operation is placed in the earliest layer after all earlier operations on
+    /// its qubits, so the layers are as parallel as the operation order allows.
+    pub fn analyze(&self) -> crate::analysis::CircuitAnalysis {
+        crate::analysis::analyze(self)
+    }
+
+    /// Return an equivalent circuit with commuting operations reordered.
+    ///
+    /// Operations only move past operations on other qubits and diagonal gates (Z, S,
+    /// S†, CZ) past each other, while measurements keep their order, so the outcomes,
+    /// detectors and observables are unchanged. Operations sharing qubits are grouped
+    /// together, which makes runs of CZs around common qubits cheaper to simulate.
+    pub fn schedule(&self) -> Circuit {
+        crate::schedule::schedule(self)
+    }
+
+    /// Rewrite the circuit for a device whose qubits are only coupled along the
+    /// undirected edges `coupling_map`.
+    ///
+    /// Logical qubit `q` starts on physical qubit `q`, and SWAPs (three CX gates each)
+    /// are inserted before every two-qubit gate on uncoupled qubits. The `RoutingResult`
+    /// reports which gates were illegal, how many SWAPs were needed and where every
+    /// logical qubit ends up. Raises `ValueError` if a gate acts on qubits that are not
+    /// connected at all.
+    pub fn route(
+        &self,
+        coupling_map: Vec<(NodeIdx, NodeIdx)>,
+    ) -> PyResult<crate::routing::RoutingResult> {
+        crate::routing::route(self, &coupling_map)
+    }
+
+    /// Return the operations of the circuit in order.
+    pub fn ops(&self) -> Vec<Op> {
+        self.ops.clone()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Append an operation to the circuit.
+    ///
+    /// Unlike the gate methods this doesn't check `op`, so an operation on a qubit out of
+    /// range or a two-qubit gate on one qubit is only rejected by `validate` and when the
+    /// circuit is run.
+    pub fn append(&mut self, op: Op) {
+        self.ops.push(op);
+    }
+
+    /// Append an X (Pauli-X) gate.
+    #[pyo3(name = "x")]
+    pub fn py_x(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.x(qubit);
+        Ok(())
+    }
+
+    /// Append a Y (Pauli-Y) gate.
+    #[pyo3(name = "y")]
+    pub fn py_y(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.y(qubit);
+        Ok(())
+    }
+
+    /// Append a Z (Pauli-Z) gate.
+    #[pyo3(name = "z")]
+    pub fn py_z(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.z(qubit);
+        Ok(())
+    }
+
+    /// Append an H (Hadamard) gate.
+    #[pyo3(name = "h")]
+    pub fn py_h(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.h(qubit);
+        Ok(())
+    }
+
+    /// Append an S (phase) gate.
+    #[pyo3(name = "s")]
+    pub fn py_s(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.s(qubit);
+        Ok(())
+    }
+
+    /// Append an S† (inverse phase) gate.
+    #[pyo3(name = "sdag")]
+    pub fn py_sdag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.sdag(qubit);
+        Ok(())
+    }
+
+    /// Append a √X gate.
+    #[pyo3(name = "sx")]
+    pub fn py_sx(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.sx(qubit);
+        Ok(())
+    }
+
+    /// Append a √X† gate.
+    #[pyo3(name = "sxdag")]
+    pub fn py_sxdag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.sxdag(qubit);
+        Ok(())
+    }
+
+    /// Append a √Y gate.
+    #[pyo3(name = "sy")]
+    pub fn py_sy(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.sy(qubit);
+        Ok(())
+    }
+
+    /// Append a √Y† gate.
+    #[pyo3(name = "sydag")]
+    pub fn py_sydag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.sydag(qubit);
+        Ok(())
+    }
+
+    /// Append a controlled-Z (CZ) gate.
+    #[pyo3(name = "cz")]
+    pub fn py_cz(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        let (control, target) = self.resolve_pair(control, target)?;
+        self.cz(control, target);
+        Ok(())
+    }
+
+    /// Append a controlled-X (CX) / CNOT gate.
+    #[pyo3(name = "cx")]
+    pub fn py_cx(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        let (control, target) = self.resolve_pair(control, target)?;
+        self.cx(control, target);
+        Ok(())
+    }
+
+    /// Append a controlled-Y (CY) gate.
+    #[pyo3(name = "cy")]
+    pub fn py_cy(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        let (control, target) = self.resolve_pair(control, target)?;
+        self.cy(control, target);
+        Ok(())
+    }
+
+    /// Append a SWAP gate.
+    #[pyo3(name = "swap")]
+    pub fn py_swap(&mut self, first: QubitIndex, second: QubitIndex) -> PyResult<()> {
+        let (first, second) = self.resolve_pair(first, second)?;
+        self.swap(first, second);
+        Ok(())
+    }
+
+    /// Append a measurement of `qubit` along `axis`.
+    #[pyo3(name = "measure")]
+    pub fn py_measure(&mut self, qubit: QubitIndex, axis: Axis) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.measure(qubit, axis);
+        Ok(())
+    }
+
+    /// Append a reset of `qubit` to |0⟩, forgetting its state.
+    #[pyo3(name = "reset")]
+    pub fn py_reset(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        let qubit = qubit.resolve(self.qubit_amount)?;
+        self.reset(qubit);
+        Ok(())
+    }
+
+    /// Append a measurement of the Pauli product `pauli` through a new ancilla qubit.
+    ///
+    /// `pauli` has one of `I` (or `_`), `X`, `Y` and `Z` for every qubit, optionally after
+    /// a sign, e.g. `"-XZ_Y"`, and is given for `qubits` instead of 0, 1, … if those are
+    /// passed. The ancilla is added after the existing qubits, put in |+⟩ with an H,
+    /// controls a CX, CY or CZ onto every qubit, is rotated back with an H (and an X for
+    /// a negative sign) and measured in Z, giving the eigenvalue of `pauli`. Every call
+    /// adds its own ancilla, as circuits can't reset qubits. Returns the index of the
+    /// outcome among the measurements of the circuit.
+    #[pyo3(signature = (pauli, qubits = None))]
+    pub fn measure_pauli(&mut self, pauli: &str, qubits: Option<QubitList>) -> PyResult<usize> {
+        let qubits = qubits
+            .map(|qubits| qubits.resolve(self.qubit_amount))
+            .transpose()?;
+        let product = parse_pauli(pauli, qubits, self.qubit_amount)?;
+        let outcome = self.num_measurements();
+        let ancilla = self.qubit_amount;
+        self.add_qubits(1);
+        self.ops.extend(gadget(&product, ancilla));
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opcodes_round_trip() {
+        let ops = [
+            Op::Sdag { qubit: 3 },
+            Op::Sxdag { qubit: 2 },
+            Op::Sydag { qubit: 0 },
+            Op::Swap {
+                first: 3,
+                second: 1,
+            },
+            Op::Cy {
+                control: 1,
+                target: 2,
+            },
+            Op::Measure {
+                qubit: 4,
+                axis: Axis::Y,
+            },
+        ];
+        for op in ops {
+            let qubits = op.qubits();
+            let second = qubits.get(1).copied().unwrap_or(0);
+            assert_eq!(Op::from_code(op.code(), qubits[0], second), Some(op));
+        }
+        assert_eq!(Op::from_code(17, 2, 0), Some(Op::Reset { qubit: 2 }));
+        assert_eq!(Op::from_code(18, 0, 0), None);
+    }
+
+    #[test]
+    fn test_two_qubit_gates_need_two_qubits() {
+        let mut circuit = Circuit::new(2);
+        assert!(circuit.py_cz(0.into(), 0.into()).is_err());
+        assert!(circuit.py_cx(1.into(), QubitIndex::Index(-1)).is_err());
+        assert!(circuit.py_cy(0.into(), QubitIndex::Index(-2)).is_err());
+        assert!(circuit.py_swap(1.into(), 1.into()).is_err());
+        assert_eq!(circuit.__len__(), 0);
+
+        circuit.py_cx(QubitIndex::Index(-1), 0.into()).unwrap();
+        assert_eq!(
+            circuit.ops(),
+            vec![Op::Cx {
+                control: 1,
+                target: 0
+            }]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use pyo3::{
+    exceptions::{PyImportError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyByteArray, PyTuple},
+};
+
+use crate::graphsim::{MeasurementResult, NodeIdx, Outcome};
+
+/// Build a writable numpy array of `dtype` and `shape` from its native-endian bytes.
+///
+/// numpy is imported when called, so the crate works without it until an array is
+/// asked for. Without numpy this raises an `ImportError` saying how to install it.
+pub(crate) fn numpy_array<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    dtype: &str,
+    shape: &[usize],
+) -> PyResult<Bound<'py, PyAny>> {
+    let numpy = py.import("numpy").map_err(|err| {
+        let error = PyImportError::new_err(
+            "Returning arrays needs numpy, install it with `pip install quantum-graphsim[numpy]`",
+        );
+        error.set_cause(py, Some(err));
+        error
+    })?;
+    numpy
+        .call_method1("frombuffer", (PyByteArray::new(py, data), dtype))?
+        .call_method1("reshape", (PyTuple::new(py, shape)?,))
+}
+
+/// ±1 for `result`, or with `binary` 0 for +1 and 1 for −1, as the byte of an `int8`.
+pub(crate) fn encode(result: MeasurementResult, binary: bool) -> u8 {
+    let value: i8 = match (result, binary) {
+        (MeasurementResult::PlusOne, false) => 1,
+        (MeasurementResult::MinusOne, false) => -1,
+        (MeasurementResult::PlusOne, true) => 0,
+        (MeasurementResult::MinusOne, true) => 1,
+    };
+    value as u8
+}
+
+/// The result held by `item`, a `MeasurementResult` or an `Outcome`.
+fn extract_result(item: &Bound<'_, PyAny>) -> PyResult<MeasurementResult> {
+    if let Ok(result) = item.extract::<MeasurementResult>() {
+        Ok(result)
+    } else if let Ok(outcome) = item.extract::<PyRef<'_, Outcome>>() {
+        Ok(outcome.result())
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Expected a MeasurementResult or Outcome, got {}",
+            item.get_type().name()?
+        )))
+    }
+}
+
+/// Encode a flat list of results, or a list of equally long lists of them, row by row.
+fn encode_nested(outcomes: &Bound<'_, PyAny>, binary: bool) -> PyResult<(Vec<u8>, Vec<usize>)> {
+    let items: Vec<Bound<'_, PyAny>> = outcomes.try_iter()?.collect::<PyResult<_>>()?;
+    let flat = items
+        .first()
+        .is_none_or(|item| extract_result(item).is_ok() || item.try_iter().is_err());
+    if flat {
+        let data = items
+            .iter()
+            .map(|item| Ok(encode(extract_result(item)?, binary)))
+            .collect::<PyResult<_>>()?;
+        return Ok((data, vec![items.len()]));
+    }
+
+    let mut data = Vec::new();
+    let mut width = None;
+    for row in &items {
+        let start = data.len();
+        for item in row.try_iter()? {
+            data.push(encode(extract_result(&item?)?, binary));
+        }
+        let len = data.len() - start;
+        if *width.get_or_insert(len) != len {
+            return Err(PyValueError::new_err("All rows must have the same length"));
+        }
+    }
+    Ok((data, vec![items.len(), width.unwrap_or(0)]))
+}
+
+/// Convert measurement outcomes to a numpy `int8` array in one call.
+///
+/// `outcomes` is a list of `MeasurementResult`s or `Outcome`s, like the result of
+/// `GraphSim.run`, or a list of such lists of equal length, which gives a 2D array with
+/// one row per inner list. Outcomes become +1 and −1, or with `binary` 0 and 1.
+#[pyfunction]
+#[pyo3(signature = (outcomes, binary = false))]
+pub fn outcomes_to_array<'py>(
+    outcomes: &Bound<'py, PyAny>,
+    binary: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let (data, shape) = encode_nested(outcomes, binary)?;
+    numpy_array(outcomes.py(), &data, "int8", &shape)
+}
+
+/// Convert a `{qubit: outcome}` dict, like the result of `GraphSim.peek_measure_set`,
+/// to numpy arrays `(qubits, values)` sorted by qubit.
+///
+/// `qubits` is an `int64` array and `values` an `int8` array encoded like
+/// `outcomes_to_array`.
+#[pyfunction]
+#[pyo3(signature = (outcomes, binary = false))]
+pub fn outcome_dict_to_arrays<'py>(
+    py: Python<'py>,
+    outcomes: HashMap<NodeIdx, Bound<'py, PyAny>>,
+    binary: bool,
+) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyAny>)> {
+    let mut entries: Vec<(NodeIdx, Bound<'py, PyAny>)> = outcomes.into_iter().collect();
+    entries.sort_unstable_by_key(|&(qubit, _)| qubit);
+    let qubits: Vec<u8> = entries
+        .iter()
+        .flat_map(|&(qubit, _)| (qubit as i64).to_ne_bytes())
+        .collect();
+    let values = entries
+        .iter()
+        .map(|(_, item)| Ok(encode(extract_result(item)?, binary)))
+        .collect::<PyResult<Vec<u8>>>()?;
+    Ok((
+        numpy_array(py, &qubits, "int64", &[entries.len()])?,
+        numpy_array(py, &values, "int8", &[entries.len()])?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encodings() {
+        let (plus, minus) = (MeasurementResult::PlusOne, MeasurementResult::MinusOne);
+        assert_eq!(
+            [encode(plus, false), encode(minus, false)].map(|b| b as i8),
+            [1, -1]
+        );
+        assert_eq!([encode(plus, true), encode(minus, true)], [0, 1]);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    fmt::{Debug, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Index, IndexMut},
+    sync::Arc,
+};
+
+/// Number of elements sharing one copy-on-write chunk.
+const CHUNK: usize = 64;
+
+/// A vector that shares its storage between clones, chunk by chunk.
+///
+/// Cloning only copies one pointer per chunk of 64 elements. The first mutation of an
+/// element in a chunk that is still shared copies that chunk, so a clone that touches
+/// few elements only pays for the chunks it touches.
+#[derive(Clone)]
+pub(crate) struct CowVec<T> {
+    chunks: Vec<Arc<Vec<T>>>,
+    len: usize,
+}
+
+impl<T: Clone> CowVec<T> {
+    /// A vector of `len` copies of `elem`.
+    pub(crate) fn from_elem(elem: T, len: usize) -> Self {
+        std::iter::repeat_n(elem, len).collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn push(&mut self, elem: T) {
+        match self.chunks.last_mut() {
+            Some(chunk) if chunk.len() < CHUNK => Arc::make_mut(chunk).push(elem),
+            _ => self.chunks.push(Arc::new(vec![elem])),
+        }
+        self.len += 1;
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> + Clone {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+
+    /// Iterate mutably over the elements of the chunks no clone shares, which can be
+    /// changed without copying anything.
+    pub(crate) fn unshared_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.chunks
+            .iter_mut()
+            .filter_map(Arc::get_mut)
+            .flat_map(|chunk| chunk.iter_mut())
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+
+    /// Number of chunks this vector shares with other clones.
+    #[cfg(test)]
+    pub(crate) fn shared_chunks(&self) -> usize {
+        self.chunks
+            .iter()
+            .filter(|chunk| Arc::strong_count(chunk) > 1)
+            .count()
+    }
+
+    /// Release the excess capacity of the chunk list and of the chunks no clone shares.
+    /// Returns the number of bytes released.
+    pub(crate) fn shrink_to_fit(&mut self) -> usize {
+        let before = self.chunks.capacity();
+        self.chunks.shrink_to_fit();
+        let mut released = (before - self.chunks.capacity()) * size_of::<Arc<Vec<T>>>();
+        for chunk in self.chunks.iter_mut().filter_map(Arc::get_mut) {
+            let before = chunk.capacity();
+            chunk.shrink_to_fit();
+            released += (before - chunk.capacity()) * size_of::<T>();
+        }
+        released
+    }
+}
+
+impl<T> Default for CowVec<T> {
+    fn default() -> Self {
+        CowVec {
+            chunks: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for CowVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = CowVec::default();
+        for elem in iter {
+            vec.push(elem);
+        }
+        vec
+    }
+}
+
+impl<T> Index<usize> for CowVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.chunks[index / CHUNK][index % CHUNK]
+    }
+}
+
+impl<T: Clone> IndexMut<usize> for CowVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut Arc::make_mut(&mut self.chunks[index / CHUNK])[index % CHUNK]
+    }
+}
+
+impl<T: PartialEq> PartialEq for CowVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .chunks
+                .iter()
+                .zip(&other.chunks)
+                .all(|(a, b)| Arc::ptr_eq(a, b) || a == b)
+    }
+}
+
+impl<T: Eq> Eq for CowVec<T> {}
+
+impl<T: Hash> Hash for CowVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for chunk in &self.chunks {
+            for elem in chunk.iter() {
+                elem.hash(state);
+            }
+        }
+    }
+}
+
+impl<T: Debug> Debug for CowVec<T> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_list()
+            .entries(self.chunks.iter().flat_map(|chunk| chunk.iter()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_shares_untouched_chunks() {
+        let original: CowVec<usize> = (0..200).collect();
+        let mut copy = original.clone();
+        assert_eq!(copy.shared_chunks(), 4);
+
+        copy[150] = 0;
+        assert_eq!(copy.shared_chunks(), 3);
+        assert_eq!(original[150], 150);
+        assert_eq!(copy[150], 0);
+        assert_ne!(original, copy);
+
+        copy[150] = 150;
+        assert_eq!(original, copy);
+        assert_eq!(copy.to_vec(), (0..200).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_shrink_leaves_shared_chunks_alone() {
+        let mut vec: CowVec<Vec<usize>> = (0..200).map(|_| Vec::with_capacity(8)).collect();
+        let copy = vec.clone();
+        for elem in vec.unshared_mut() {
+            elem.shrink_to_fit();
+        }
+        assert_eq!(vec.shared_chunks(), 4);
+        assert!(vec.iter().all(|elem| elem.capacity() == 8));
+
+        vec[0].clear();
+        assert_eq!(vec.shared_chunks(), 3);
+        assert_eq!(vec.unshared_mut().count(), CHUNK);
+        drop(copy);
+        assert_eq!(vec.unshared_mut().count(), 200);
+
+        // a truncated chunk keeps its capacity until it is shrunk
+        let mut vec: CowVec<usize> = (0..100).collect();
+        Arc::make_mut(&mut vec.chunks[1]).truncate(4);
+        vec.len = 68;
+        vec.chunks.shrink_to_fit();
+        let copy = vec.clone();
+        assert_eq!(vec.shrink_to_fit(), 0);
+        drop(copy);
+        let released = vec.shrink_to_fit();
+        assert_eq!(released, (64 - 4) * size_of::<usize>());
+        assert_eq!(vec.shrink_to_fit(), 0);
+    }
+}
@@ -1,1256 +0,0 @@
-use crate::{SYMMETRIES, graphsim::Vop};
-
-pub(crate) const CPHASE_TABLE: [[[(bool, Vop, Vop); SYMMETRIES]; SYMMETRIES]; 2] = [
-    [
-        [
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (false, Vop::ZA, Vop::IC),
-            (false, Vop::ZA, Vop::IC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::ZA, Vop::IC),
-            (false, Vop::ZA, Vop::IC),
-        ],
-        [
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::YA, Vop::IC),
-        ],
-        [
-            (true, Vop::YA, Vop::ZA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::YA, Vop::IA),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::YA, Vop::YB),
-            (true, Vop::YA, Vop::XB),
-            (true, Vop::IA, Vop::ZB),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::YA, Vop::YC),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-        ],
-        [
-            (true, Vop::ZA, Vop::IA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::ZA, Vop::ZA),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::ZA, Vop::XB),
-            (true, Vop::ZA, Vop::YB),
-            (true, Vop::IA, Vop::ZB),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::ZA, Vop::YC),
-            (false, Vop::ZA, Vop::YC),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::IA, Vop::IB),
-            (false, Vop::ZA, Vop::YC),
-            (false, Vop::ZA, Vop::YC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-        ],
-        [
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::XB),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::IB, Vop::YC),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-        ],
-        [
-            (true, Vop::XB, Vop::IA),
-            (true, Vop::XB, Vop::IA),
-            (true, Vop::XB, Vop::ZA),
-            (true, Vop::XB, Vop::ZA),
-            (true, Vop::XB, Vop::XB),
-            (true, Vop::XB, Vop::XB),
-            (true, Vop::XB, Vop::YB),
-            (true, Vop::XB, Vop::YB),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::XB, Vop::YC),
-            (false, Vop::XB, Vop::YC),
-            (true, Vop::XB, Vop::ZA),
-            (true, Vop::XB, Vop::ZA),
-            (true, Vop::XB, Vop::IA),
-            (true, Vop::XB, Vop::IA),
-            (true, Vop::XB, Vop::YB),
-            (true, Vop::XB, Vop::YB),
-            (true, Vop::XB, Vop::XB),
-            (true, Vop::XB, Vop::XB),
-            (false, Vop::XB, Vop::YC),
-            (false, Vop::XB, Vop::YC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-        ],
-        [
-            (true, Vop::YB, Vop::IA),
-            (true, Vop::XB, Vop::XA),
-            (true, Vop::XB, Vop::YA),
-            (true, Vop::YB, Vop::ZA),
-            (true, Vop::XB, Vop::IB),
-            (true, Vop::YB, Vop::XB),
-            (true, Vop::YB, Vop::YB),
-            (true, Vop::XB, Vop::ZB),
-            (false, Vop::XB, Vop::IC),
-            (false, Vop::XB, Vop::IC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (true, Vop::XB, Vop::YA),
-            (true, Vop::XB, Vop::YA),
-            (true, Vop::XB, Vop::XA),
-            (true, Vop::XB, Vop::XA),
-            (true, Vop::XB, Vop::ZB),
-            (true, Vop::XB, Vop::ZB),
-            (true, Vop::XB, Vop::IB),
-            (true, Vop::XB, Vop::IB),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::XB, Vop::IC),
-            (false, Vop::XB, Vop::IC),
-        ],
-        [
-            (true, Vop::YB, Vop::IA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::YB, Vop::ZA),
-            (true, Vop::IB, Vop::ZB),
-            (true, Vop::YB, Vop::XB),
-            (true, Vop::YB, Vop::YB),
-            (true, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::IB),
-            (true, Vop::IB, Vop::IB),
-            (true, Vop::IB, Vop::ZB),
-            (true, Vop::IB, Vop::ZB),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::IB, Vop::IC),
-        ],
-        [
-            (false, Vop::IC, Vop::ZA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::XB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-        ],
-        [
-            (false, Vop::IC, Vop::ZA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::XB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-        ],
-        [
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::ZA),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::XB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-        ],
-        [
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::ZA),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::XB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-        ],
-        [
-            (true, Vop::YA, Vop::ZA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::YA, Vop::IA),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::YA, Vop::YB),
-            (true, Vop::YA, Vop::XB),
-            (true, Vop::IA, Vop::ZB),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::YA, Vop::YC),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-        ],
-        [
-            (true, Vop::YA, Vop::ZA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::YA, Vop::IA),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::YA, Vop::YB),
-            (true, Vop::YA, Vop::XB),
-            (true, Vop::IA, Vop::ZB),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::YA, Vop::YC),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::YA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::XA),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::ZB),
-            (true, Vop::IA, Vop::IB),
-            (true, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::YA, Vop::YC),
-            (false, Vop::IA, Vop::IC),
-            (false, Vop::IA, Vop::IC),
-        ],
-        [
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::YA, Vop::IC),
-        ],
-        [
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::ZA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::IA),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::XB),
-            (true, Vop::IA, Vop::XB),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::IA, Vop::YC),
-            (false, Vop::YA, Vop::IC),
-            (false, Vop::YA, Vop::IC),
-        ],
-        [
-            (true, Vop::YB, Vop::IA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::YB, Vop::ZA),
-            (true, Vop::IB, Vop::ZB),
-            (true, Vop::YB, Vop::XB),
-            (true, Vop::YB, Vop::YB),
-            (true, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::IB),
-            (true, Vop::IB, Vop::IB),
-            (true, Vop::IB, Vop::ZB),
-            (true, Vop::IB, Vop::ZB),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::IB, Vop::IC),
-        ],
-        [
-            (true, Vop::YB, Vop::IA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::YB, Vop::ZA),
-            (true, Vop::IB, Vop::ZB),
-            (true, Vop::YB, Vop::XB),
-            (true, Vop::YB, Vop::YB),
-            (true, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::IB, Vop::XA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::YA),
-            (true, Vop::IB, Vop::IB),
-            (true, Vop::IB, Vop::IB),
-            (true, Vop::IB, Vop::ZB),
-            (true, Vop::IB, Vop::ZB),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::YB, Vop::YC),
-            (false, Vop::IB, Vop::IC),
-            (false, Vop::IB, Vop::IC),
-        ],
-        [
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::XB),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::IB, Vop::YC),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-        ],
-        [
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::XB),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::IB, Vop::YC),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::IA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::ZA),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::XB),
-            (true, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::IB, Vop::YC),
-            (false, Vop::YB, Vop::IC),
-            (false, Vop::YB, Vop::IC),
-        ],
-        [
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::ZA),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::XB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-        ],
-        [
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::ZA),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::XB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::YA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::IA),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::YB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::IB),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::YC),
-            (false, Vop::YC, Vop::IC),
-            (false, Vop::YC, Vop::IC),
-        ],
-        [
-            (false, Vop::IC, Vop::ZA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::XB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-        ],
-        [
-            (false, Vop::IC, Vop::ZA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::XB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::IA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::YA),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::IB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YB),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::YC),
-            (false, Vop::IC, Vop::IC),
-            (false, Vop::IC, Vop::IC),
-        ],
-    ],
-    [
-        [
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::ZA, Vop::IA),
-            (false, Vop::ZA, Vop::YA),
-            (false, Vop::IA, Vop::ZA),
-            (false, Vop::ZA, Vop::IB),
-            (false, Vop::IA, Vop::XB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::ZA, Vop::YB),
-            (true, Vop::XB, Vop::ZF),
-            (true, Vop::XB, Vop::YF),
-            (true, Vop::XB, Vop::XF),
-            (true, Vop::XB, Vop::IF),
-            (false, Vop::XB, Vop::YA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::XB, Vop::IA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::XB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::XB, Vop::IB),
-            (true, Vop::XB, Vop::YC),
-            (true, Vop::XB, Vop::ZC),
-            (true, Vop::XB, Vop::IC),
-            (true, Vop::XB, Vop::XC),
-        ],
-        [
-            (false, Vop::IA, Vop::ZA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::XB),
-            (false, Vop::YA, Vop::IB),
-            (true, Vop::IB, Vop::ZF),
-            (true, Vop::IB, Vop::YF),
-            (true, Vop::IB, Vop::XF),
-            (true, Vop::IB, Vop::IF),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::YB),
-            (true, Vop::IB, Vop::YC),
-            (true, Vop::IB, Vop::ZC),
-            (true, Vop::IB, Vop::IC),
-            (true, Vop::IB, Vop::XC),
-        ],
-        [
-            (false, Vop::YA, Vop::ZA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::XB),
-            (false, Vop::IA, Vop::IB),
-            (true, Vop::IB, Vop::YF),
-            (true, Vop::IB, Vop::ZF),
-            (true, Vop::IB, Vop::IF),
-            (true, Vop::IB, Vop::XF),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::YB),
-            (true, Vop::IB, Vop::ZC),
-            (true, Vop::IB, Vop::YC),
-            (true, Vop::IB, Vop::XC),
-            (true, Vop::IB, Vop::IC),
-        ],
-        [
-            (false, Vop::ZA, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::ZA, Vop::ZA),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::ZA, Vop::XB),
-            (false, Vop::ZA, Vop::YB),
-            (false, Vop::IA, Vop::YB),
-            (true, Vop::XB, Vop::YF),
-            (true, Vop::XB, Vop::ZF),
-            (true, Vop::XB, Vop::IF),
-            (true, Vop::XB, Vop::XF),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::XB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::XB, Vop::IA),
-            (false, Vop::XB, Vop::YB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::XB, Vop::IB),
-            (false, Vop::YB, Vop::IB),
-            (true, Vop::XB, Vop::ZC),
-            (true, Vop::XB, Vop::YC),
-            (true, Vop::XB, Vop::XC),
-            (true, Vop::XB, Vop::IC),
-        ],
-        [
-            (false, Vop::IB, Vop::ZA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::XB),
-            (false, Vop::YB, Vop::IB),
-            (true, Vop::IA, Vop::XF),
-            (true, Vop::IA, Vop::IF),
-            (true, Vop::IA, Vop::ZF),
-            (true, Vop::IA, Vop::YF),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::YB),
-            (true, Vop::IA, Vop::IC),
-            (true, Vop::IA, Vop::XC),
-            (true, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::ZC),
-        ],
-        [
-            (false, Vop::XB, Vop::IA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::XB, Vop::ZA),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::XB, Vop::XB),
-            (false, Vop::XB, Vop::YB),
-            (false, Vop::YB, Vop::YB),
-            (true, Vop::IA, Vop::YF),
-            (true, Vop::IA, Vop::ZF),
-            (true, Vop::IA, Vop::IF),
-            (true, Vop::IA, Vop::XF),
-            (false, Vop::ZA, Vop::YA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::ZA, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::ZA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::ZA, Vop::IB),
-            (true, Vop::IA, Vop::ZC),
-            (true, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::XC),
-            (true, Vop::IA, Vop::IC),
-        ],
-        [
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::XB, Vop::IA),
-            (false, Vop::XB, Vop::YA),
-            (false, Vop::YB, Vop::ZA),
-            (false, Vop::XB, Vop::IB),
-            (false, Vop::YB, Vop::XB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::XB, Vop::YB),
-            (true, Vop::IA, Vop::ZF),
-            (true, Vop::IA, Vop::YF),
-            (true, Vop::IA, Vop::XF),
-            (true, Vop::IA, Vop::IF),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::ZA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::ZA, Vop::IA),
-            (false, Vop::ZA, Vop::YB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::ZA, Vop::IB),
-            (false, Vop::IA, Vop::IB),
-            (true, Vop::IA, Vop::YC),
-            (true, Vop::IA, Vop::ZC),
-            (true, Vop::IA, Vop::IC),
-            (true, Vop::IA, Vop::XC),
-        ],
-        [
-            (false, Vop::YB, Vop::ZA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::XB),
-            (false, Vop::IB, Vop::IB),
-            (true, Vop::IA, Vop::IF),
-            (true, Vop::IA, Vop::XF),
-            (true, Vop::IA, Vop::YF),
-            (true, Vop::IA, Vop::ZF),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::YB),
-            (true, Vop::IA, Vop::XC),
-            (true, Vop::IA, Vop::IC),
-            (true, Vop::IA, Vop::ZC),
-            (true, Vop::IA, Vop::YC),
-        ],
-        [
-            (true, Vop::YF, Vop::YB),
-            (true, Vop::IF, Vop::XB),
-            (true, Vop::IF, Vop::YB),
-            (true, Vop::YF, Vop::XB),
-            (true, Vop::IF, Vop::ZA),
-            (true, Vop::YF, Vop::IA),
-            (true, Vop::YF, Vop::ZA),
-            (true, Vop::IF, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-        ],
-        [
-            (true, Vop::YF, Vop::XB),
-            (true, Vop::IF, Vop::YB),
-            (true, Vop::IF, Vop::XB),
-            (true, Vop::YF, Vop::YB),
-            (true, Vop::IF, Vop::IA),
-            (true, Vop::YF, Vop::ZA),
-            (true, Vop::YF, Vop::IA),
-            (true, Vop::IF, Vop::ZA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-        ],
-        [
-            (true, Vop::IF, Vop::YB),
-            (true, Vop::IF, Vop::ZB),
-            (true, Vop::IF, Vop::IB),
-            (true, Vop::IF, Vop::XB),
-            (true, Vop::IF, Vop::XA),
-            (true, Vop::IF, Vop::IA),
-            (true, Vop::IF, Vop::ZA),
-            (true, Vop::IF, Vop::YA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-        ],
-        [
-            (true, Vop::IF, Vop::XB),
-            (true, Vop::IF, Vop::IB),
-            (true, Vop::IF, Vop::ZB),
-            (true, Vop::IF, Vop::YB),
-            (true, Vop::IF, Vop::YA),
-            (true, Vop::IF, Vop::ZA),
-            (true, Vop::IF, Vop::IA),
-            (true, Vop::IF, Vop::XA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-        ],
-        [
-            (false, Vop::YA, Vop::XB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::ZA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (true, Vop::IE, Vop::YE),
-            (true, Vop::IE, Vop::ZE),
-            (true, Vop::IE, Vop::IE),
-            (true, Vop::IE, Vop::XE),
-            (true, Vop::IE, Vop::ID),
-            (true, Vop::IE, Vop::XD),
-            (true, Vop::IE, Vop::YD),
-            (true, Vop::IE, Vop::ZD),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-        ],
-        [
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::XB),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YA, Vop::ZA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (true, Vop::IE, Vop::XE),
-            (true, Vop::IE, Vop::IE),
-            (true, Vop::IE, Vop::ZE),
-            (true, Vop::IE, Vop::YE),
-            (true, Vop::IE, Vop::ZD),
-            (true, Vop::IE, Vop::YD),
-            (true, Vop::IE, Vop::XD),
-            (true, Vop::IE, Vop::ID),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-        ],
-        [
-            (false, Vop::IA, Vop::XB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::ZA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (true, Vop::IE, Vop::IE),
-            (true, Vop::IE, Vop::XE),
-            (true, Vop::IE, Vop::YE),
-            (true, Vop::IE, Vop::ZE),
-            (true, Vop::IE, Vop::YD),
-            (true, Vop::IE, Vop::ZD),
-            (true, Vop::IE, Vop::ID),
-            (true, Vop::IE, Vop::XD),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-        ],
-        [
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::XB),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::ZA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (true, Vop::IE, Vop::ZE),
-            (true, Vop::IE, Vop::YE),
-            (true, Vop::IE, Vop::XE),
-            (true, Vop::IE, Vop::IE),
-            (true, Vop::IE, Vop::XD),
-            (true, Vop::IE, Vop::ID),
-            (true, Vop::IE, Vop::ZD),
-            (true, Vop::IE, Vop::YD),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-        ],
-        [
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::XB),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::ZA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (true, Vop::ID, Vop::IE),
-            (true, Vop::ID, Vop::XE),
-            (true, Vop::ID, Vop::YE),
-            (true, Vop::ID, Vop::ZE),
-            (true, Vop::ID, Vop::YD),
-            (true, Vop::ID, Vop::ZD),
-            (true, Vop::ID, Vop::ID),
-            (true, Vop::ID, Vop::XD),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-        ],
-        [
-            (false, Vop::YB, Vop::XB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::ZA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (true, Vop::ID, Vop::ZE),
-            (true, Vop::ID, Vop::YE),
-            (true, Vop::ID, Vop::XE),
-            (true, Vop::ID, Vop::IE),
-            (true, Vop::ID, Vop::XD),
-            (true, Vop::ID, Vop::ID),
-            (true, Vop::ID, Vop::ZD),
-            (true, Vop::ID, Vop::YD),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-        ],
-        [
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::XB),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::IB, Vop::ZA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (true, Vop::ID, Vop::YE),
-            (true, Vop::ID, Vop::ZE),
-            (true, Vop::ID, Vop::IE),
-            (true, Vop::ID, Vop::XE),
-            (true, Vop::ID, Vop::ID),
-            (true, Vop::ID, Vop::XD),
-            (true, Vop::ID, Vop::YD),
-            (true, Vop::ID, Vop::ZD),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-        ],
-        [
-            (false, Vop::IB, Vop::XB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::ZA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (true, Vop::ID, Vop::XE),
-            (true, Vop::ID, Vop::IE),
-            (true, Vop::ID, Vop::ZE),
-            (true, Vop::ID, Vop::YE),
-            (true, Vop::ID, Vop::ZD),
-            (true, Vop::ID, Vop::YD),
-            (true, Vop::ID, Vop::XD),
-            (true, Vop::ID, Vop::ID),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-        ],
-        [
-            (true, Vop::YC, Vop::XB),
-            (true, Vop::IC, Vop::YB),
-            (true, Vop::IC, Vop::XB),
-            (true, Vop::YC, Vop::YB),
-            (true, Vop::IC, Vop::IA),
-            (true, Vop::YC, Vop::ZA),
-            (true, Vop::YC, Vop::IA),
-            (true, Vop::IC, Vop::ZA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-        ],
-        [
-            (true, Vop::YC, Vop::YB),
-            (true, Vop::IC, Vop::XB),
-            (true, Vop::IC, Vop::YB),
-            (true, Vop::YC, Vop::XB),
-            (true, Vop::IC, Vop::ZA),
-            (true, Vop::YC, Vop::IA),
-            (true, Vop::YC, Vop::ZA),
-            (true, Vop::IC, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-        ],
-        [
-            (true, Vop::IC, Vop::XB),
-            (true, Vop::IC, Vop::IB),
-            (true, Vop::IC, Vop::ZB),
-            (true, Vop::IC, Vop::YB),
-            (true, Vop::IC, Vop::YA),
-            (true, Vop::IC, Vop::ZA),
-            (true, Vop::IC, Vop::IA),
-            (true, Vop::IC, Vop::XA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IB, Vop::IB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::YB, Vop::IB),
-        ],
-        [
-            (true, Vop::IC, Vop::YB),
-            (true, Vop::IC, Vop::ZB),
-            (true, Vop::IC, Vop::IB),
-            (true, Vop::IC, Vop::XB),
-            (true, Vop::IC, Vop::XA),
-            (true, Vop::IC, Vop::IA),
-            (true, Vop::IC, Vop::ZA),
-            (true, Vop::IC, Vop::YA),
-            (false, Vop::IB, Vop::IA),
-            (false, Vop::IB, Vop::YA),
-            (false, Vop::YB, Vop::YA),
-            (false, Vop::YB, Vop::IA),
-            (false, Vop::IA, Vop::YB),
-            (false, Vop::YA, Vop::IB),
-            (false, Vop::IA, Vop::IB),
-            (false, Vop::YA, Vop::YB),
-            (false, Vop::YA, Vop::YA),
-            (false, Vop::IA, Vop::IA),
-            (false, Vop::YA, Vop::IA),
-            (false, Vop::IA, Vop::YA),
-            (false, Vop::YB, Vop::IB),
-            (false, Vop::YB, Vop::YB),
-            (false, Vop::IB, Vop::YB),
-            (false, Vop::IB, Vop::IB),
-        ],
-    ],
-];
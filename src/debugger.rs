@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{GraphSim, MeasurementResult, NodeIdx},
+};
+
+/// Names of every `Op`, as accepted by `Debugger.break_on_op`.
+const OP_NAMES: [&str; 16] = [
+    "x", "y", "z", "h", "s", "sdag", "sx", "sxdag", "sy", "sydag", "cz", "cx", "cy", "swap",
+    "measure", "reset",
+];
+
+/// Number of operations between the snapshots `Debugger.seek` restarts from.
+const SNAPSHOT_INTERVAL: usize = 256;
+
+/// Step-by-step execution of a `Circuit` with breakpoints.
+///
+/// Execution stops before any operation acting on a qubit given to `break_on_qubit` or
+/// with a name given to `break_on_op`, except when that operation is the first one run
+/// by the call, so `resume` always makes progress. `inspect` shows the state between
+/// steps.
+///
+/// Every measurement outcome is kept on a tape and the state is snapshotted every 256
+/// operations, so `seek` can go back to any earlier operation. Running over operations
+/// that ran before replays the taped outcomes, reproducing the same history.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Debugger {
+    circuit: Circuit,
+    sim: GraphSim,
+    position: usize,
+    /// Outcomes of every measurement that ever ran, in circuit order.
+    tape: Vec<MeasurementResult>,
+    /// Number of taped outcomes belonging to the operations before `position`.
+    measured: usize,
+    /// `(position, state, measured)` at every multiple of `SNAPSHOT_INTERVAL` reached.
+    snapshots: Vec<(usize, GraphSim, usize)>,
+    qubit_breakpoints: BitSet,
+    op_breakpoints: HashSet<&'static str>,
+}
+
+impl Debugger {
+    fn is_breakpoint(&self, op: &Op) -> bool {
+        self.op_breakpoints.contains(op.name())
+            || op
+                .qubits()
+                .iter()
+                .any(|&qubit| self.qubit_breakpoints.contains(qubit))
+    }
+
+    /// Run operations while `keep_going` allows it, stopping at breakpoints after the
+    /// first one. Returns the position afterwards.
+    fn run_while(&mut self, keep_going: impl Fn(usize) -> bool) -> usize {
+        let mut first = true;
+        while let Some(&op) = self.circuit.ops_slice().get(self.position) {
+            if !keep_going(self.position) || (!first && self.is_breakpoint(&op)) {
+                break;
+            }
+            first = false;
+            self.step();
+        }
+        self.position
+    }
+}
+
+#[pymethods]
+impl Debugger {
+    /// Prepare to run `circuit` on `sim`, or on a fresh simulator in |0…0⟩.
+    ///
+    /// Raises `ValueError` with the problems `validate` finds, so no step can fail.
+    #[new]
+    #[pyo3(signature = (circuit, sim = None))]
+    pub fn new(circuit: Circuit, sim: Option<GraphSim>) -> PyResult<Debugger> {
+        let sim = sim.unwrap_or_else(|| GraphSim::new(circuit.qubit_amount()));
+        if sim.adjacency().len() < circuit.qubit_amount() {
+            return Err(PyValueError::new_err(format!(
+                "The circuit needs {} qubits, but the simulator has {}",
+                circuit.qubit_amount(),
+                sim.adjacency().len()
+            )));
+        }
+        crate::validate::check(&sim, &circuit)?;
+        Ok(Debugger {
+            circuit,
+            snapshots: vec![(0, sim.clone(), 0)],
+            sim,
+            position: 0,
+            tape: Vec::new(),
+            measured: 0,
+            qubit_breakpoints: BitSet::new(),
+            op_breakpoints: HashSet::new(),
+        })
+    }
+
+    /// Index of the next operation to run.
+    #[getter]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether every operation has run.
+    #[getter]
+    pub fn done(&self) -> bool {
+        self.position >= self.circuit.__len__()
+    }
+
+    /// The next operation to run, `None` when done.
+    #[getter]
+    pub fn next_op(&self) -> Option<Op> {
+        self.circuit.ops_slice().get(self.position).copied()
+    }
+
+    /// Outcomes of the measurements run so far.
+    #[getter]
+    pub fn outcomes(&self) -> Vec<MeasurementResult> {
+        self.tape[..self.measured].to_vec()
+    }
+
+    /// Run the next operation, returning it, or `None` when done.
+    pub fn step(&mut self) -> Option<Op> {
+        let op = self.next_op()?;
+        match (op, self.tape.get(self.measured)) {
+            (Op::Measure { qubit, axis }, Some(&taped)) => {
+                if self.sim.deterministic_outcome(qubit, axis).is_none() {
+                    self.sim.project(qubit, axis, taped);
+                }
+                self.measured += 1;
+            }
+            _ => {
+                if let Some(outcome) = op.apply(&mut self.sim) {
+                    self.tape.push(outcome);
+                    self.measured += 1;
+                }
+            }
+        }
+        self.position += 1;
+
+        let last_snapshot = self.snapshots.last().map_or(0, |&(pos, _, _)| pos);
+        if self.position.is_multiple_of(SNAPSHOT_INTERVAL) && self.position > last_snapshot {
+            self.snapshots
+                .push((self.position, self.sim.clone(), self.measured));
+        }
+        Some(op)
+    }
+
+    /// Move to `position` `op_index`, i.e. the state right after operation
+    /// `op_index - 1`, ignoring breakpoints. Returns the new position.
+    ///
+    /// Operations that ran before replay their taped outcomes from the nearest
+    /// snapshot, so this gives exactly the state seen back then. Listeners registered
+    /// on the simulator see the replayed operations again, except for measurements.
+    /// Resets forget their outcome, so replaying over a reset of an entangled qubit can
+    /// leave its former partners in another state.
+    pub fn seek(&mut self, op_index: usize) -> PyResult<usize> {
+        if op_index > self.circuit.__len__() {
+            return Err(PyValueError::new_err(format!(
+                "Operation {op_index} is past the end of the circuit with {} operations",
+                self.circuit.__len__()
+            )));
+        }
+        if op_index < self.position {
+            let (position, sim, measured) = self
+                .snapshots
+                .iter()
+                .rev()
+                .find(|&&(pos, _, _)| pos <= op_index)
+                .expect("The initial state is a snapshot")
+                .clone();
+            (self.position, self.sim, self.measured) = (position, sim, measured);
+        }
+        while self.position < op_index {
+            self.step();
+        }
+        Ok(self.position)
+    }
+
+    /// Run until operation `op_index` is next, a breakpoint is hit or the circuit ends.
+    ///
+    /// Returns the position afterwards.
+    pub fn run_until(&mut self, op_index: usize) -> usize {
+        self.run_while(|position| position < op_index)
+    }
+
+    /// Run until a breakpoint is hit or the circuit ends, returning the position.
+    pub fn resume(&mut self) -> usize {
+        self.run_while(|_| true)
+    }
+
+    /// A copy of the current state.
+    pub fn inspect(&self) -> GraphSim {
+        self.sim.clone()
+    }
+
+    /// Stop before operations acting on `qubit`.
+    pub fn break_on_qubit(&mut self, qubit: NodeIdx) {
+        self.qubit_breakpoints.insert(qubit);
+    }
+
+    /// Stop before operations called `name`, one of the `Op.name` values like `"cz"`
+    /// or `"measure"`.
+    pub fn break_on_op(&mut self, name: &str) -> PyResult<()> {
+        let name = OP_NAMES
+            .into_iter()
+            .find(|&known| known == name)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown operation {name:?}")))?;
+        self.op_breakpoints.insert(name);
+        Ok(())
+    }
+
+    /// Remove every breakpoint.
+    pub fn clear_breakpoints(&mut self) {
+        self.qubit_breakpoints.clear();
+        self.op_breakpoints.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::Axis;
+
+    #[test]
+    fn test_breakpoints() {
+        let circuit =
+            Circuit::from_text("new_qubit 3\nh 0\ncx 0 1\nh 2\ncx 1 2\nmeasure_z 0\nmeasure_z 2")
+                .unwrap();
+        let mut debugger = Debugger::new(circuit, None).unwrap();
+        debugger.break_on_qubit(2);
+        debugger.break_on_op("measure").unwrap();
+        assert!(debugger.break_on_op("t").is_err());
+
+        assert_eq!(debugger.resume(), 2);
+        assert_eq!(debugger.next_op(), Some(Op::H { qubit: 2 }));
+        assert_eq!(debugger.resume(), 3);
+        assert_eq!(debugger.resume(), 4);
+        assert!(debugger.inspect().adjacency()[1].contains(0));
+        debugger.clear_breakpoints();
+        assert_eq!(debugger.run_until(5), 5);
+        assert_eq!(debugger.outcomes().len(), 1);
+        assert_eq!(debugger.resume(), 6);
+        assert!(debugger.done() && debugger.step().is_none());
+    }
+
+    #[test]
+    fn test_seek_replays_history() {
+        let mut circuit = Circuit::new(2);
+        for _ in 0..300 {
+            circuit.h(0);
+            circuit.measure(0, Axis::Z);
+            circuit.cx(0, 1);
+        }
+        let mut debugger = Debugger::new(circuit, None).unwrap();
+        let states: Vec<GraphSim> = (0..900)
+            .map(|_| {
+                debugger.step();
+                debugger.inspect()
+            })
+            .collect();
+        let outcomes = debugger.outcomes();
+
+        for target in [1, 899, 256, 257, 3, 600] {
+            assert_eq!(debugger.seek(target).unwrap(), target);
+            assert_eq!(debugger.inspect(), states[target - 1]);
+            assert_eq!(debugger.outcomes(), outcomes[..(target - 1).div_ceil(3)]);
+        }
+        assert_eq!(debugger.resume(), 900);
+        assert_eq!(debugger.outcomes(), outcomes);
+        assert!(debugger.seek(901).is_err());
+    }
+
+    #[test]
+    fn test_invalid_circuits_are_rejected() {
+        let mut circuit = Circuit::new(2);
+        circuit.append(Op::Cz {
+            control: 0,
+            target: 0,
+        });
+        assert!(Debugger::new(circuit, None).is_err());
+
+        let mut sim = GraphSim::new(2);
+        sim.trace_out(vec![1]);
+        let mut circuit = Circuit::new(2);
+        circuit.h(1);
+        assert!(Debugger::new(circuit.clone(), Some(sim)).is_err());
+        assert!(Debugger::new(circuit, None).is_ok());
+    }
+}
@@ -0,0 +1,363 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+
+use crate::{
+    circuit::{Circuit, Op},
+    frame::PauliFrame,
+    noise::{Fault, NoiseModel},
+};
+
+/// Independent fault source flipping a fixed set of detectors and observables.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorMechanism {
+    /// Probability that the mechanism occurs.
+    probability: f64,
+    /// Detectors flipped by the mechanism.
+    detectors: Vec<usize>,
+    /// Observables flipped by the mechanism.
+    observables: Vec<usize>,
+}
+
+/// Which detectors and observables flip for every possible noise mechanism of a circuit.
+///
+/// Column `j` of `check_matrix()` and `observables_matrix()` describes mechanism `j`,
+/// which occurs with probability `priors()[j]`. These can be passed straight to
+/// `pymatching.Matching.from_check_matrix(check, weights, faults_matrix=observables)`,
+/// and `str()` gives the same model in the stim `error(p) D0 L0` text format.
+/// Mechanisms are treated as independent, as matching decoders assume.
+#[pyclass(frozen, str)]
+#[derive(Clone, Debug)]
+pub struct DetectorErrorModel {
+    num_detectors: usize,
+    num_observables: usize,
+    mechanisms: Vec<ErrorMechanism>,
+    /// Pauli frame left at the end of the circuit by each mechanism.
+    frames: Vec<PauliFrame>,
+}
+
+/// The detectors (or observables) in `sets` whose parity is odd over `flipped`.
+pub(crate) fn odd_parities(flipped: &BitSet, sets: &[Vec<usize>]) -> Vec<bool> {
+    sets.iter()
+        .map(|set| set.iter().filter(|&&meas| flipped.contains(meas)).count() % 2 == 1)
+        .collect()
+}
+
+fn true_indices(bits: &[bool]) -> Vec<usize> {
+    bits.iter()
+        .enumerate()
+        .filter_map(|(idx, &bit)| bit.then_some(idx))
+        .collect()
+}
+
+/// Measurement index of every operation that is a measurement.
+pub(crate) fn measurement_indices(circuit: &Circuit) -> Vec<Option<usize>> {
+    let mut count = 0;
+    circuit
+        .iter()
+        .map(|op| match op {
+            Op::Measure { .. } => {
+                count += 1;
+                Some(count - 1)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Propagate `faults`, which happen right after op `op_index`, to the end of `circuit`.
+///
+/// Returns the flipped measurements and the frame remaining at the end.
+pub(crate) fn propagate_faults(
+    circuit: &Circuit,
+    meas_index: &[Option<usize>],
+    op_index: usize,
+    faults: &[Fault],
+) -> (BitSet, PauliFrame) {
+    let mut frame = PauliFrame::new();
+    let mut flipped = BitSet::new();
+    for fault in faults {
+        match fault.kind.pauli() {
+            Some(axis) => frame.mul_pauli(fault.qubit, axis),
+            None => {
+                flipped.insert(meas_index[op_index].expect("Flips only follow measurements"));
+            }
+        }
+    }
+
+    for (idx, op) in circuit.iter().enumerate().skip(op_index + 1) {
+        if frame.propagate(op) == Some(true) {
+            flipped.insert(meas_index[idx].expect("Only measurements report flips"));
+        }
+    }
+    (flipped, frame)
+}
+
+/// Sample `shots` rows of detection events and observable flips with `rng`.
+///
+/// Faults are tracked as a Pauli frame relative to a noiseless execution, so the
+/// detectors and observables of `circuit` must be deterministic without noise.
+pub(crate) fn sample_shots<R: Rng + ?Sized>(
+    circuit: &Circuit,
+    noise_model: &NoiseModel,
+    shots: usize,
+    rng: &mut R,
+) -> (Vec<Vec<bool>>, Vec<Vec<bool>>) {
+    let meas_index = measurement_indices(circuit);
+    let mut faults = Vec::new();
+    let mut events = Vec::with_capacity(shots);
+    let mut observable_flips = Vec::with_capacity(shots);
+
+    for _ in 0..shots {
+        let mut frame = PauliFrame::new();
+        let mut flipped = BitSet::new();
+        for (idx, op) in circuit.iter().enumerate() {
+            if frame.propagate(op) == Some(true) {
+                flipped.insert(meas_index[idx].expect("Only measurements report flips"));
+            }
+            faults.clear();
+            noise_model.sample_faults(idx, op, rng, &mut faults);
+            for fault in &faults {
+                match fault.kind.pauli() {
+                    Some(axis) => frame.mul_pauli(fault.qubit, axis),
+                    None => {
+                        let meas = meas_index[idx].expect("Flips only follow measurements");
+                        if !flipped.insert(meas) {
+                            flipped.remove(meas);
+                        }
+                    }
+                }
+            }
+        }
+        events.push(odd_parities(&flipped, &circuit.detectors));
+        observable_flips.push(odd_parities(&flipped, &circuit.observables));
+    }
+
+    (events, observable_flips)
+}
+
+/// Sample detection events and observable flips of `circuit` under `noise_model`.
+///
+/// Faults are tracked as a Pauli frame relative to a noiseless execution, so the
+/// detectors and observables of the circuit must be deterministic without noise.
+/// Returns `(detection_events, observable_flips)`, each a list with one row per shot.
+#[pyfunction]
+pub fn sample_detectors(
+    circuit: &Circuit,
+    noise_model: &NoiseModel,
+    shots: usize,
+) -> (Vec<Vec<bool>>, Vec<Vec<bool>>) {
+    sample_shots(circuit, noise_model, shots, &mut crate::rng::generator())
+}
+
+/// Build the detector error model of `circuit` under `noise_model`.
+///
+/// Mechanisms flipping the same detectors and observables are merged, and ones that
+/// flip nothing are dropped.
+#[pyfunction]
+pub fn detector_error_model(circuit: &Circuit, noise_model: &NoiseModel) -> DetectorErrorModel {
+    let meas_index = measurement_indices(circuit);
+    let mut merged: HashMap<(Vec<usize>, Vec<usize>), usize> = HashMap::new();
+    let mut mechanisms: Vec<ErrorMechanism> = Vec::new();
+    let mut frames = Vec::new();
+
+    for (op_index, op) in circuit.iter().enumerate() {
+        for (probability, faults) in noise_model.mechanisms(op_index, op) {
+            let (flipped, frame) = propagate_faults(circuit, &meas_index, op_index, &faults);
+            let detectors = true_indices(&odd_parities(&flipped, &circuit.detectors));
+            let observables = true_indices(&odd_parities(&flipped, &circuit.observables));
+            if detectors.is_empty() && observables.is_empty() {
+                continue;
+            }
+
+            match merged.get(&(detectors.clone(), observables.clone())) {
+                Some(&idx) => {
+                    let prev = mechanisms[idx].probability;
+                    mechanisms[idx].probability = prev + probability - 2.0 * prev * probability;
+                }
+                None => {
+                    merged.insert((detectors.clone(), observables.clone()), mechanisms.len());
+                    mechanisms.push(ErrorMechanism {
+                        probability,
+                        detectors,
+                        observables,
+                    });
+                    frames.push(frame);
+                }
+            }
+        }
+    }
+
+    DetectorErrorModel {
+        num_detectors: circuit.detectors.len(),
+        num_observables: circuit.observables.len(),
+        mechanisms,
+        frames,
+    }
+}
+
+impl DetectorErrorModel {
+    fn check_correction(&self, correction: &[bool]) -> PyResult<()> {
+        if correction.len() == self.mechanisms.len() {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "Correction has length {}, but the model has {} mechanisms",
+                correction.len(),
+                self.mechanisms.len()
+            )))
+        }
+    }
+}
+
+impl Display for DetectorErrorModel {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for mechanism in &self.mechanisms {
+            write!(fmt, "error({})", mechanism.probability)?;
+            for det in &mechanism.detectors {
+                write!(fmt, " D{det}")?;
+            }
+            for obs in &mechanism.observables {
+                write!(fmt, " L{obs}")?;
+            }
+            writeln!(fmt)?;
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl DetectorErrorModel {
+    #[getter]
+    pub fn num_detectors(&self) -> usize {
+        self.num_detectors
+    }
+
+    #[getter]
+    pub fn num_observables(&self) -> usize {
+        self.num_observables
+    }
+
+    /// Return the error mechanisms of the model.
+    pub fn mechanisms(&self) -> Vec<ErrorMechanism> {
+        self.mechanisms.clone()
+    }
+
+    /// Probability of every mechanism.
+    pub fn priors(&self) -> Vec<f64> {
+        self.mechanisms.iter().map(|m| m.probability).collect()
+    }
+
+    /// Detector-by-mechanism parity-check matrix as rows of 0/1 entries.
+    pub fn check_matrix(&self) -> Vec<Vec<u8>> {
+        let mut matrix = vec![vec![0; self.mechanisms.len()]; self.num_detectors];
+        for (col, mechanism) in self.mechanisms.iter().enumerate() {
+            for &det in &mechanism.detectors {
+                matrix[det][col] = 1;
+            }
+        }
+        matrix
+    }
+
+    /// Observable-by-mechanism matrix as rows of 0/1 entries.
+    pub fn observables_matrix(&self) -> Vec<Vec<u8>> {
+        let mut matrix = vec![vec![0; self.mechanisms.len()]; self.num_observables];
+        for (col, mechanism) in self.mechanisms.iter().enumerate() {
+            for &obs in &mechanism.observables {
+                matrix[obs][col] = 1;
+            }
+        }
+        matrix
+    }
+
+    /// Observables flipped by the mechanisms selected in `correction`.
+    ///
+    /// `correction` has one entry per mechanism, as returned by a decoder.
+    pub fn observable_flips(&self, correction: Vec<bool>) -> PyResult<Vec<bool>> {
+        self.check_correction(&correction)?;
+        let mut flips = vec![false; self.num_observables];
+        for (mechanism, _) in self.mechanisms.iter().zip(correction).filter(|(_, c)| *c) {
+            for &obs in &mechanism.observables {
+                flips[obs] = !flips[obs];
+            }
+        }
+        Ok(flips)
+    }
+
+    /// Pauli frame that undoes the mechanisms selected in `correction` at the end of the
+    /// circuit, to be applied with `GraphSim.apply_frame`.
+    pub fn correction_frame(&self, correction: Vec<bool>) -> PyResult<PauliFrame> {
+        self.check_correction(&correction)?;
+        let mut frame = PauliFrame::new();
+        for (mechanism_frame, _) in self.frames.iter().zip(correction).filter(|(_, c)| *c) {
+            frame.mul_frame(mechanism_frame);
+        }
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::Axis;
+
+    fn parity_circuit() -> Circuit {
+        let mut circuit = Circuit::new(2);
+        circuit.z(0);
+        circuit.cx(0, 1);
+        circuit.measure(1, Axis::Z);
+        circuit.measure(0, Axis::Z);
+        circuit.detector(vec![0]).unwrap();
+        circuit.observable(vec![1]).unwrap();
+        circuit
+    }
+
+    #[test]
+    fn test_single_qubit_mechanisms_merge() {
+        let noise = NoiseModel::new(0.3, 0.0, 0.0, 0.0, None).unwrap();
+        let dem = detector_error_model(&parity_circuit(), &noise);
+
+        let mechanisms = dem.mechanisms();
+        assert_eq!(mechanisms.len(), 1);
+        assert_eq!(mechanisms[0].detectors, vec![0]);
+        assert_eq!(mechanisms[0].observables, vec![0]);
+        assert!((mechanisms[0].probability - 0.18).abs() < 1e-12);
+        assert_eq!(dem.check_matrix(), vec![vec![1]]);
+        assert_eq!(
+            dem.to_string(),
+            format!("error({}) D0 L0\n", mechanisms[0].probability)
+        );
+
+        let frame = dem.correction_frame(vec![true]).unwrap();
+        assert_eq!(frame.get(0), Some(Axis::X));
+        assert_eq!(frame.get(1), Some(Axis::X));
+        assert_eq!(dem.observable_flips(vec![true]).unwrap(), vec![true]);
+    }
+
+    #[test]
+    fn test_sample_detectors() {
+        let noiseless = NoiseModel::default();
+        let (events, flips) = sample_detectors(&parity_circuit(), &noiseless, 20);
+        assert!(
+            events
+                .iter()
+                .chain(flips.iter())
+                .all(|row| row == &vec![false])
+        );
+
+        let readout = NoiseModel::new(0.0, 0.0, 1.0, 0.0, None).unwrap();
+        let (events, flips) = sample_detectors(&parity_circuit(), &readout, 20);
+        assert!(
+            events
+                .iter()
+                .chain(flips.iter())
+                .all(|row| row == &vec![true])
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::{Display, Formatter};
+
+use pyo3::prelude::*;
+
+use crate::{
+    graphsim::{GraphSim, NodeIdx, Vop},
+    stabilizer::canonical_stabilizers,
+};
+
+/// Differences between two simulators, from `GraphSim.diff`.
+///
+/// `str()` lists them line by line, and the diff is falsy when there are none.
+#[pyclass(frozen, get_all, str)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateDiff {
+    /// Number of qubits of both simulators.
+    qubit_amounts: (usize, usize),
+    /// Edges only the first simulator has.
+    removed_edges: Vec<(NodeIdx, NodeIdx)>,
+    /// Edges only the second simulator has.
+    added_edges: Vec<(NodeIdx, NodeIdx)>,
+    /// `(qubit, first, second)` for every qubit whose VOP differs, as the gates of the VOPs.
+    vops: Vec<(NodeIdx, String, String)>,
+    /// Qubits traced out in only one of the simulators.
+    traced: Vec<NodeIdx>,
+    /// Whether both describe the same state, even if through different graphs or VOPs.
+    same_state: bool,
+}
+
+impl StateDiff {
+    /// Lowest qubit involved in any difference, where two runs started to diverge.
+    fn first_qubit(&self) -> Option<NodeIdx> {
+        let edges = self.removed_edges.iter().chain(&self.added_edges);
+        let qubits = edges.map(|&(a, _)| a);
+        let qubits = qubits.chain(self.vops.iter().map(|&(qubit, ..)| qubit));
+        let qubits = qubits.chain(self.traced.iter().copied());
+        let extra = (self.qubit_amounts.0 != self.qubit_amounts.1)
+            .then(|| self.qubit_amounts.0.min(self.qubit_amounts.1));
+        qubits.chain(extra).min()
+    }
+}
+
+/// The gates of `vop`, or `I` for the identity.
+fn vop_name(vop: Vop) -> String {
+    match vop.gate_names().as_slice() {
+        [] => "I".to_string(),
+        gates => gates.join(" "),
+    }
+}
+
+/// Sorted edges of `sim`, each with the lower qubit first.
+fn sorted_edges(sim: &GraphSim) -> Vec<(NodeIdx, NodeIdx)> {
+    let mut edges: Vec<(NodeIdx, NodeIdx)> =
+        sim.edges().map(|(a, b)| (a.min(b), a.max(b))).collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// Compare `first` and `second`, after standardizing the VOPs of both if `canonicalize`.
+pub(crate) fn diff(first: &GraphSim, second: &GraphSim, canonicalize: bool) -> StateDiff {
+    let (mut first, mut second) = (first.clone(), second.clone());
+    if canonicalize {
+        first.standardize_vops();
+        second.standardize_vops();
+    }
+    let (edges_a, edges_b) = (sorted_edges(&first), sorted_edges(&second));
+    let shared = first.vops().len().min(second.vops().len());
+    let traced = (0..first.vops().len().max(second.vops().len()))
+        .filter(|&qubit| first.is_traced(qubit) != second.is_traced(qubit))
+        .collect();
+    StateDiff {
+        qubit_amounts: (first.vops().len(), second.vops().len()),
+        removed_edges: edges_a
+            .iter()
+            .filter(|edge| edges_b.binary_search(edge).is_err())
+            .copied()
+            .collect(),
+        added_edges: edges_b
+            .iter()
+            .filter(|edge| edges_a.binary_search(edge).is_err())
+            .copied()
+            .collect(),
+        vops: (0..shared)
+            .filter(|&qubit| first.vops()[qubit] != second.vops()[qubit])
+            .map(|qubit| {
+                let (a, b) = (first.vops()[qubit], second.vops()[qubit]);
+                (qubit, vop_name(a), vop_name(b))
+            })
+            .collect(),
+        traced,
+        same_state: first.vops().len() == second.vops().len()
+            && canonical_stabilizers(&first) == canonical_stabilizers(&second),
+    }
+}
+
+/// One line per difference, starting with the qubit where they begin, e.g.
+/// `first difference at qubit 1` followed by `- edge (1, 2)` for an edge only the first
+/// simulator has, `+ edge (0, 2)` for one only the second has and `vop 2: h -> s`.
+impl Display for StateDiff {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let Some(first) = self.first_qubit() else {
+            return write!(fmt, "no differences");
+        };
+        write!(fmt, "first difference at qubit {first}")?;
+        let (len_a, len_b) = self.qubit_amounts;
+        if len_a != len_b {
+            write!(fmt, "\nqubits: {len_a} -> {len_b}")?;
+        }
+        for (a, b) in &self.removed_edges {
+            write!(fmt, "\n- edge ({a}, {b})")?;
+        }
+        for (a, b) in &self.added_edges {
+            write!(fmt, "\n+ edge ({a}, {b})")?;
+        }
+        for (qubit, a, b) in &self.vops {
+            write!(fmt, "\nvop {qubit}: {a} -> {b}")?;
+        }
+        for qubit in &self.traced {
+            write!(fmt, "\ntraced out in only one: {qubit}")?;
+        }
+        if self.same_state {
+            write!(fmt, "\nboth describe the same state")?;
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl StateDiff {
+    /// Whether the simulators differ at all.
+    pub fn __bool__(&self) -> bool {
+        self.first_qubit().is_some()
+    }
+
+    /// Lowest qubit involved in any difference, or `None` if there is none.
+    #[getter]
+    pub fn first_difference(&self) -> Option<NodeIdx> {
+        self.first_qubit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lists_first_divergence() {
+        let mut first = GraphSim::new(3);
+        first.h(0);
+        first.h(1);
+        first.cz(0, 1);
+        let mut second = first.clone();
+        assert!(!diff(&first, &second, false).__bool__());
+
+        second.cz(0, 1);
+        second.h(2);
+        second.cz(1, 2);
+        let changes = diff(&first, &second, false);
+        assert_eq!(changes.removed_edges, vec![(0, 1)]);
+        assert_eq!(changes.added_edges, vec![(1, 2)]);
+        assert_eq!(changes.first_difference(), Some(0));
+        assert!(!changes.same_state);
+        assert_eq!(
+            changes.to_string(),
+            "first difference at qubit 0\n- edge (0, 1)\n+ edge (1, 2)\nvop 2: h -> I"
+        );
+
+        // a local complementation changes the graph but not the state
+        let mut complemented = GraphSim::new(3);
+        complemented.h(0);
+        complemented.h(1);
+        complemented.h(2);
+        complemented.cz(0, 1);
+        complemented.cz(1, 2);
+        let mut other = complemented.clone();
+        other.local_comp(1);
+        let changes = diff(&complemented, &other, false);
+        assert!(changes.same_state);
+        assert_eq!(changes.added_edges, vec![(0, 2)]);
+    }
+}
@@ -0,0 +1,124 @@
+use bit_set::BitSet;
+use pyo3::prelude::*;
+
+use crate::{cow::CowVec, graphsim::NodeIdx};
+
+/// Iterator over the edges of a `GraphSim`, from `GraphSim.iter_edges`.
+///
+/// Yields every edge once as `(a, b)` with `a < b`, ordered by `a` and then `b`. It
+/// iterates over a snapshot of the graph taken when it was created, which shares its
+/// storage with the simulator until one of them changes.
+#[pyclass]
+pub struct EdgeIterator {
+    adjacent: CowVec<BitSet>,
+    node: NodeIdx,
+    next_node: NodeIdx,
+    /// Neighbours above `node` that are still to be yielded, in reverse order.
+    pending: Vec<NodeIdx>,
+}
+
+impl EdgeIterator {
+    pub(crate) fn new(adjacent: CowVec<BitSet>) -> EdgeIterator {
+        EdgeIterator {
+            adjacent,
+            node: 0,
+            next_node: 0,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Iterator for EdgeIterator {
+    type Item = (NodeIdx, NodeIdx);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(nb) = self.pending.pop() {
+                return Some((self.node, nb));
+            }
+            if self.next_node >= self.adjacent.len() {
+                return None;
+            }
+            self.node = self.next_node;
+            self.next_node += 1;
+            let node = self.node;
+            self.pending = self.adjacent[node].iter().filter(|&nb| nb > node).collect();
+            self.pending.reverse();
+        }
+    }
+}
+
+#[pymethods]
+impl EdgeIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<(NodeIdx, NodeIdx)> {
+        self.next()
+    }
+}
+
+/// Set-like view of the edges of a `GraphSim`, from `GraphSim.edges_view`.
+///
+/// Supports `len`, iteration and `(a, b) in view` in either order, all without building
+/// a list of the edges. Like `EdgeIterator` it describes the graph at the time it was
+/// created.
+#[pyclass(frozen)]
+pub struct EdgesView {
+    adjacent: CowVec<BitSet>,
+}
+
+impl EdgesView {
+    pub(crate) fn new(adjacent: CowVec<BitSet>) -> EdgesView {
+        EdgesView { adjacent }
+    }
+
+    fn contains(&self, a: NodeIdx, b: NodeIdx) -> bool {
+        a < self.adjacent.len() && self.adjacent[a].contains(b)
+    }
+}
+
+#[pymethods]
+impl EdgesView {
+    fn __len__(&self) -> usize {
+        self.adjacent.iter().map(|adj| adj.len()).sum::<usize>() / 2
+    }
+
+    fn __contains__(&self, edge: &Bound<'_, PyAny>) -> bool {
+        edge.extract::<(NodeIdx, NodeIdx)>()
+            .is_ok_and(|(a, b)| self.contains(a, b))
+    }
+
+    fn __iter__(&self) -> EdgeIterator {
+        EdgeIterator::new(self.adjacent.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::GraphSim;
+
+    #[test]
+    fn test_iterate_star_edges() {
+        let mut sim = GraphSim::new(5);
+        for qubit in [0, 1, 3, 4] {
+            sim.h(qubit);
+        }
+        for leaf in [0, 1, 4] {
+            sim.cz(3, leaf);
+        }
+
+        let edges: Vec<_> = EdgeIterator::new(sim.adjacency().clone()).collect();
+        assert_eq!(edges, vec![(0, 3), (1, 3), (3, 4)]);
+        let view = EdgesView::new(sim.adjacency().clone());
+        assert_eq!(view.__len__(), 3);
+        assert!(view.contains(4, 3) && view.contains(1, 3));
+        assert!(!view.contains(0, 1) && !view.contains(7, 0));
+        assert_eq!(
+            EdgeIterator::new(GraphSim::new(3).adjacency().clone()).next(),
+            None
+        );
+    }
+}
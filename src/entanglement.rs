@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::graphsim::{GraphSim, NodeIdx};
+
+/// Rank over GF(2) of the matrix with `rows` as its rows.
+///
+/// Each reduced row that is not zero becomes a basis row pivoting on its lowest column,
+/// and later rows are reduced against the basis in insertion order. Every step is a
+/// word-wise XOR, so this runs in O(r^2 c / 64) for r rows and c columns.
+pub(crate) fn gf2_rank(rows: impl IntoIterator<Item = BitSet>) -> usize {
+    let mut basis: Vec<(usize, BitSet)> = Vec::new();
+    for mut row in rows {
+        for (pivot, basis_row) in &basis {
+            if row.contains(*pivot) {
+                row.symmetric_difference_with(basis_row);
+            }
+        }
+        if let Some(pivot) = row.iter().next() {
+            basis.push((pivot, row));
+        }
+    }
+    basis.len()
+}
+
+/// Rank over GF(2) of the adjacency submatrix with rows `rows` and columns `cols`.
+///
+/// Rows and columns may overlap, in which case the diagonal entries are 0.
+pub(crate) fn adjacency_rank(
+    sim: &GraphSim,
+    rows: &[NodeIdx],
+    cols: &[NodeIdx],
+) -> PyResult<usize> {
+    let adjacent = sim.adjacency();
+    if let Some(&qubit) = rows.iter().chain(cols).find(|&&q| q >= adjacent.len()) {
+        return Err(PyValueError::new_err(format!(
+            "Qubit {qubit} is out of range"
+        )));
+    }
+    let cols: BitSet = cols.iter().copied().collect();
+    Ok(gf2_rank(
+        rows.iter()
+            .map(|&row| adjacent[row].intersection(&cols).collect()),
+    ))
+}
+
+/// Check that `qubits` are distinct and in range, returning them as a set.
+pub(crate) fn qubit_set(sim: &GraphSim, qubits: &[NodeIdx]) -> PyResult<BitSet> {
+    if let Some(&qubit) = qubits.iter().find(|&&q| q >= sim.adjacency().len()) {
+        return Err(PyValueError::new_err(format!(
+            "Qubit {qubit} is out of range"
+        )));
+    }
+    if qubits.iter().collect::<HashSet<_>>().len() != qubits.len() {
+        return Err(PyValueError::new_err("Qubits must be distinct"));
+    }
+    Ok(qubits.iter().copied().collect())
+}
+
+/// Rank over GF(2) of the adjacency between `subset` and the rest of the graph.
+///
+/// This is the entanglement entropy of `subset` in bits, as local Cliffords leave it
+/// unchanged.
+pub(crate) fn cut_rank(sim: &GraphSim, subset: &BitSet) -> usize {
+    let adjacent = sim.adjacency();
+    gf2_rank(
+        subset
+            .iter()
+            .map(|node| adjacent[node].difference(subset).collect()),
+    )
+}
+
+/// Number of independent stabilizers supported entirely on `qubits`.
+pub(crate) fn subsystem_rank(sim: &GraphSim, qubits: &[NodeIdx]) -> PyResult<usize> {
+    let subset = qubit_set(sim, qubits)?;
+    Ok(subset.len() - cut_rank(sim, &subset))
+}
+
+/// Mutual information S(A) + S(B) − S(AB) in bits between two disjoint sets of qubits.
+pub(crate) fn mutual_information(
+    sim: &GraphSim,
+    set_a: &[NodeIdx],
+    set_b: &[NodeIdx],
+) -> PyResult<usize> {
+    let (a, b) = (qubit_set(sim, set_a)?, qubit_set(sim, set_b)?);
+    if !a.is_disjoint(&b) {
+        return Err(PyValueError::new_err("Qubit sets must be disjoint"));
+    }
+    Ok(cut_rank(sim, &a) + cut_rank(sim, &b) - cut_rank(sim, &a.union(&b).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghz_subsystem_ranks() {
+        let mut sim = GraphSim::new(4);
+        sim.h(0);
+        for qubit in 1..4 {
+            sim.cx(0, qubit);
+        }
+
+        // only the Z_a Z_b of the GHZ state fit inside a proper subset
+        assert_eq!(subsystem_rank(&sim, &[0]).unwrap(), 0);
+        assert_eq!(subsystem_rank(&sim, &[1, 3]).unwrap(), 1);
+        assert_eq!(subsystem_rank(&sim, &[0, 1, 2]).unwrap(), 2);
+        assert_eq!(subsystem_rank(&sim, &[0, 1, 2, 3]).unwrap(), 4);
+        assert_eq!(subsystem_rank(&sim, &[]).unwrap(), 0);
+        assert!(subsystem_rank(&sim, &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_mutual_information() {
+        let mut sim = GraphSim::new(5);
+        sim.h(0);
+        sim.cx(0, 1);
+        sim.h(2);
+        sim.cx(2, 3);
+        sim.cx(2, 4);
+
+        assert_eq!(mutual_information(&sim, &[0], &[1]).unwrap(), 2);
+        assert_eq!(mutual_information(&sim, &[0], &[2]).unwrap(), 0);
+        // GHZ correlations are classical between two of its three qubits
+        assert_eq!(mutual_information(&sim, &[2], &[3]).unwrap(), 1);
+        assert_eq!(mutual_information(&sim, &[2], &[3, 4]).unwrap(), 2);
+        assert!(mutual_information(&sim, &[0, 1], &[1]).is_err());
+    }
+
+    #[test]
+    fn test_adjacency_rank() {
+        // 0 - 1 - 2 - 3 - 4 - 5, a path
+        let mut sim = GraphSim::new(6);
+        for qubit in 0..6 {
+            sim.h(qubit);
+        }
+        for qubit in 0..5 {
+            sim.cz(qubit, qubit + 1);
+        }
+
+        assert_eq!(
+            adjacency_rank(&sim, &[0, 1, 2, 3, 4, 5], &[0, 1, 2, 3, 4, 5]).unwrap(),
+            6
+        );
+        assert_eq!(adjacency_rank(&sim, &[0, 2, 4], &[1, 3, 5]).unwrap(), 3);
+        assert_eq!(adjacency_rank(&sim, &[1, 3], &[0, 2, 4]).unwrap(), 2);
+        assert_eq!(adjacency_rank(&sim, &[0, 4], &[2]).unwrap(), 0);
+        assert_eq!(adjacency_rank(&sim, &[1, 1], &[0]).unwrap(), 1);
+        assert!(adjacency_rank(&sim, &[6], &[0]).is_err());
+    }
+}
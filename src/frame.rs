@@ -0,0 +1,230 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use bit_set::BitSet;
+use pyo3::prelude::*;
+
+use crate::{
+    circuit::Op,
+    graphsim::{Axis, NodeIdx},
+};
+
+/// A Pauli operator on many qubits, ignoring its sign.
+///
+/// Used to track errors and corrections through a circuit without touching the
+/// simulated state. Apply one to a state with `GraphSim.apply_frame`.
+#[pyclass(eq, str)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PauliFrame {
+    x: BitSet,
+    z: BitSet,
+}
+
+fn flip(set: &mut BitSet, idx: usize) {
+    if !set.insert(idx) {
+        set.remove(idx);
+    }
+}
+
+impl PauliFrame {
+    /// Multiply the frame by the Pauli along `axis` on `qubit`.
+    pub fn mul_pauli(&mut self, qubit: NodeIdx, axis: Axis) {
+        match axis {
+            Axis::X => flip(&mut self.x, qubit),
+            Axis::Y => {
+                flip(&mut self.x, qubit);
+                flip(&mut self.z, qubit);
+            }
+            Axis::Z => flip(&mut self.z, qubit),
+        }
+    }
+
+    /// Multiply the frame by another frame.
+    pub fn mul_frame(&mut self, other: &PauliFrame) {
+        self.x.symmetric_difference_with(&other.x);
+        self.z.symmetric_difference_with(&other.z);
+    }
+
+    fn swap_xz(&mut self, qubit: NodeIdx) {
+        let has_x = self.x.contains(qubit);
+        if has_x != self.z.contains(qubit) {
+            flip(&mut self.x, qubit);
+            flip(&mut self.z, qubit);
+        }
+    }
+
+    fn phase(&mut self, qubit: NodeIdx) {
+        if self.x.contains(qubit) {
+            flip(&mut self.z, qubit);
+        }
+    }
+
+    fn root_x(&mut self, qubit: NodeIdx) {
+        if self.z.contains(qubit) {
+            flip(&mut self.x, qubit);
+        }
+    }
+
+    fn cx(&mut self, control: NodeIdx, target: NodeIdx) {
+        if self.x.contains(control) {
+            flip(&mut self.x, target);
+        }
+        if self.z.contains(target) {
+            flip(&mut self.z, control);
+        }
+    }
+
+    fn swap(&mut self, first: NodeIdx, second: NodeIdx) {
+        for part in [&mut self.x, &mut self.z] {
+            if part.contains(first) != part.contains(second) {
+                flip(part, first);
+                flip(part, second);
+            }
+        }
+    }
+
+    /// Propagate the frame through `op`.
+    ///
+    /// For a measurement, returns whether the frame flips its outcome. Afterwards the
+    /// part of the frame that acts trivially on the measured eigenstate is dropped.
+    pub fn propagate(&mut self, op: &Op) -> Option<bool> {
+        match *op {
+            Op::X { .. } | Op::Y { .. } | Op::Z { .. } => {}
+            Op::H { qubit } | Op::Sy { qubit } | Op::Sydag { qubit } => self.swap_xz(qubit),
+            Op::S { qubit } | Op::Sdag { qubit } => self.phase(qubit),
+            Op::Sx { qubit } | Op::Sxdag { qubit } => self.root_x(qubit),
+            Op::Cz { control, target } => {
+                if self.x.contains(control) {
+                    flip(&mut self.z, target);
+                }
+                if self.x.contains(target) {
+                    flip(&mut self.z, control);
+                }
+            }
+            Op::Cx { control, target } => self.cx(control, target),
+            Op::Swap { first, second } => self.swap(first, second),
+            Op::Reset { qubit } => {
+                self.x.remove(qubit);
+                self.z.remove(qubit);
+            }
+            Op::Cy { control, target } => {
+                self.phase(target);
+                self.cx(control, target);
+                self.phase(target);
+            }
+            Op::Measure { qubit, axis } => {
+                let (has_x, has_z) = (self.x.contains(qubit), self.z.contains(qubit));
+                let flipped = match axis {
+                    Axis::X => has_z,
+                    Axis::Y => has_x != has_z,
+                    Axis::Z => has_x,
+                };
+                self.x.remove(qubit);
+                self.z.remove(qubit);
+                if flipped {
+                    match axis {
+                        Axis::X => self.z.insert(qubit),
+                        Axis::Y | Axis::Z => self.x.insert(qubit),
+                    };
+                }
+                return Some(flipped);
+            }
+        }
+        None
+    }
+
+    /// Iterate over the qubits with a non-identity Pauli and that Pauli.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeIdx, Axis)> + '_ {
+        self.x
+            .union(&self.z)
+            .map(|qubit| (qubit, self.get(qubit).expect("Qubit is in the support")))
+    }
+}
+
+impl Display for PauliFrame {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let len = self.x.union(&self.z).max().map_or(0, |max| max + 1);
+        for qubit in 0..len {
+            match self.get(qubit) {
+                None => fmt.write_str("_")?,
+                Some(axis) => write!(fmt, "{axis}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl PauliFrame {
+    /// Create an identity frame.
+    #[new]
+    pub fn new() -> PauliFrame {
+        PauliFrame::default()
+    }
+
+    /// Return the Pauli acting on `qubit`, or `None` for the identity.
+    pub fn get(&self, qubit: NodeIdx) -> Option<Axis> {
+        match (self.x.contains(qubit), self.z.contains(qubit)) {
+            (false, false) => None,
+            (true, false) => Some(Axis::X),
+            (true, true) => Some(Axis::Y),
+            (false, true) => Some(Axis::Z),
+        }
+    }
+
+    /// Multiply the Pauli along `axis` on `qubit` into the frame.
+    pub fn add(&mut self, qubit: NodeIdx, axis: Axis) {
+        self.mul_pauli(qubit, axis);
+    }
+
+    /// Return the non-identity Paulis of the frame as a `{qubit: Axis}` dict.
+    pub fn paulis(&self) -> HashMap<NodeIdx, Axis> {
+        self.iter().collect()
+    }
+
+    /// Number of qubits with a non-identity Pauli.
+    pub fn weight(&self) -> usize {
+        self.x.union(&self.z).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cx_spreads_x_and_z() {
+        let mut frame = PauliFrame::new();
+        frame.add(0, Axis::X);
+        frame.add(1, Axis::Z);
+        frame.propagate(&Op::Cx {
+            control: 0,
+            target: 1,
+        });
+
+        assert_eq!(frame.get(0), Some(Axis::Y));
+        assert_eq!(frame.get(1), Some(Axis::Y));
+    }
+
+    #[test]
+    fn test_measure_reports_flip() {
+        let mut frame = PauliFrame::new();
+        frame.add(0, Axis::Y);
+
+        let flipped = frame.propagate(&Op::Measure {
+            qubit: 0,
+            axis: Axis::Z,
+        });
+        assert_eq!(flipped, Some(true));
+        assert_eq!(frame.get(0), Some(Axis::X));
+
+        let flipped = frame.propagate(&Op::Measure {
+            qubit: 0,
+            axis: Axis::X,
+        });
+        assert_eq!(flipped, Some(false));
+        assert_eq!(frame.get(0), None);
+    }
+}
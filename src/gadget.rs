@@ -0,0 +1,156 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::Op,
+    graphsim::{Axis, NodeIdx},
+    stabilizer::PauliString,
+};
+
+/// A Pauli operator to measure: a sign and the Pauli on each qubit it acts on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PauliProduct {
+    pub(crate) negative: bool,
+    pub(crate) paulis: Vec<(NodeIdx, Axis)>,
+}
+
+impl PauliProduct {
+    /// The same operator as a `PauliString`.
+    pub(crate) fn to_pauli_string(&self) -> PauliString {
+        let mut string = PauliString {
+            negative: self.negative,
+            ..PauliString::default()
+        };
+        for &(qubit, axis) in &self.paulis {
+            string.set(qubit, Some(axis));
+        }
+        string
+    }
+}
+
+/// Parse `pauli`, one of `I` (or `_`), `X`, `Y` and `Z` per qubit after an optional sign.
+///
+/// The characters are for `qubits` if given, and otherwise for qubits 0, 1, … of a
+/// register of `qubit_amount` qubits. Raises `ValueError` for the identity, which has
+/// nothing to measure.
+pub(crate) fn parse_pauli(
+    pauli: &str,
+    qubits: Option<Vec<NodeIdx>>,
+    qubit_amount: usize,
+) -> PyResult<PauliProduct> {
+    let product = parse_observable(pauli, qubits, qubit_amount)?;
+    if product.paulis.is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "Pauli string {pauli:?} acts on no qubit"
+        )));
+    }
+    Ok(product)
+}
+
+/// Parse `pauli` like `parse_pauli`, but allowing the identity.
+pub(crate) fn parse_observable(
+    pauli: &str,
+    qubits: Option<Vec<NodeIdx>>,
+    qubit_amount: usize,
+) -> PyResult<PauliProduct> {
+    let (negative, letters) = match pauli.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, pauli.strip_prefix('+').unwrap_or(pauli)),
+    };
+    let letters: Vec<char> = letters.chars().collect();
+    let qubits = qubits.unwrap_or_else(|| (0..letters.len()).collect());
+    if letters.len() != qubits.len() {
+        return Err(PyValueError::new_err(format!(
+            "Pauli string {pauli:?} has {} letters for {} qubits",
+            letters.len(),
+            qubits.len()
+        )));
+    }
+
+    let mut paulis: Vec<(NodeIdx, Axis)> = Vec::new();
+    for (&letter, &qubit) in letters.iter().zip(&qubits) {
+        let axis = match letter {
+            'I' | '_' => continue,
+            'X' => Axis::X,
+            'Y' => Axis::Y,
+            'Z' => Axis::Z,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown Pauli {letter:?} in {pauli:?}, expected I, X, Y or Z"
+                )));
+            }
+        };
+        if qubit >= qubit_amount {
+            return Err(PyValueError::new_err(format!(
+                "Qubit {qubit} is out of range for {qubit_amount} qubits"
+            )));
+        }
+        if paulis.iter().any(|&(other, _)| other == qubit) {
+            return Err(PyValueError::new_err(format!(
+                "Qubit {qubit} appears twice in {pauli:?}"
+            )));
+        }
+        paulis.push((qubit, axis));
+    }
+    Ok(PauliProduct { negative, paulis })
+}
+
+/// Operations measuring `product` through `ancilla`, which has to start in |0⟩.
+///
+/// The ancilla is put in |+⟩, controls a CX, CY or CZ onto every qubit and is rotated
+/// back, so measuring it in Z gives the eigenvalue of the unsigned product. An X before
+/// the measurement flips the outcome for a negative sign.
+pub(crate) fn gadget(product: &PauliProduct, ancilla: NodeIdx) -> Vec<Op> {
+    let mut ops = vec![Op::H { qubit: ancilla }];
+    ops.extend(product.paulis.iter().map(|&(target, axis)| {
+        let control = ancilla;
+        match axis {
+            Axis::X => Op::Cx { control, target },
+            Axis::Y => Op::Cy { control, target },
+            Axis::Z => Op::Cz { control, target },
+        }
+    }));
+    ops.push(Op::H { qubit: ancilla });
+    if product.negative {
+        ops.push(Op::X { qubit: ancilla });
+    }
+    ops.push(Op::Measure {
+        qubit: ancilla,
+        axis: Axis::Z,
+    });
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::Circuit,
+        graphsim::{GraphSim, MeasurementResult},
+    };
+
+    #[test]
+    fn test_bell_pauli_gadgets() {
+        let mut bell = GraphSim::new(2);
+        bell.h(0);
+        bell.cx(0, 1);
+        for (pauli, expected) in [
+            ("XX", MeasurementResult::PlusOne),
+            ("+ZZ", MeasurementResult::PlusOne),
+            ("-YY", MeasurementResult::PlusOne),
+            ("YY", MeasurementResult::MinusOne),
+        ] {
+            assert_eq!(bell.measure_pauli(pauli, None).unwrap(), expected);
+        }
+        assert_eq!(bell.traced_qubits(), vec![2]);
+
+        let mut circuit = Circuit::new(3);
+        assert_eq!(circuit.measure_pauli("Z_Z", None).unwrap(), 0);
+        assert_eq!(circuit.qubit_amount(), 4);
+        assert_eq!(circuit.ops_slice().len(), 5);
+
+        assert!(parse_pauli("XQ", None, 2).is_err());
+        assert!(parse_pauli("XX", Some(vec![1, 1]), 2).is_err());
+        assert!(parse_pauli("__", None, 2).is_err());
+        assert!(parse_pauli("XXX", None, 2).is_err());
+    }
+}
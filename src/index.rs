@@ -0,0 +1,128 @@
+use std::fmt::{Display, Formatter};
+
+use pyo3::{exceptions::PyValueError, prelude::*, types::PySlice};
+
+use crate::graphsim::NodeIdx;
+
+/// Handle of a qubit handed out by `GraphSim.allocate`.
+///
+/// Besides the index of the qubit it holds the generation the qubit had when allocated,
+/// which changes whenever the qubit is released or traced out. Using a handle after that
+/// raises `ValueError`, even once its index is allocated again.
+#[pyclass(frozen, eq, hash, str)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Qubit {
+    /// Index of the qubit in the register.
+    #[pyo3(get)]
+    pub(crate) index: NodeIdx,
+    pub(crate) generation: u32,
+}
+
+impl Display for Qubit {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "Qubit({}, generation {})", self.index, self.generation)
+    }
+}
+
+/// A qubit argument from Python: a `Qubit` handle, or an `int` counting from the end of
+/// the register when negative, like a list index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, FromPyObject)]
+pub enum QubitIndex {
+    Handle(Qubit),
+    Index(isize),
+}
+
+impl QubitIndex {
+    /// The qubit this index refers to in a register of `qubit_amount` qubits.
+    ///
+    /// The generation of handles is checked by `GraphSim`, which knows the current ones.
+    pub(crate) fn resolve(self, qubit_amount: usize) -> PyResult<NodeIdx> {
+        let resolved = match self {
+            QubitIndex::Handle(handle) => Some(handle.index),
+            QubitIndex::Index(idx) if idx < 0 => qubit_amount.checked_sub(idx.unsigned_abs()),
+            QubitIndex::Index(idx) => Some(idx as usize),
+        };
+        resolved
+            .filter(|&qubit| qubit < qubit_amount)
+            .ok_or_else(|| {
+                let qubit = match self {
+                    QubitIndex::Handle(handle) => handle.to_string(),
+                    QubitIndex::Index(idx) => idx.to_string(),
+                };
+                PyValueError::new_err(format!(
+                    "Qubit {qubit} is out of range for {qubit_amount} qubits"
+                ))
+            })
+    }
+}
+
+impl From<NodeIdx> for QubitIndex {
+    fn from(qubit: NodeIdx) -> Self {
+        QubitIndex::Index(qubit as isize)
+    }
+}
+
+/// A qubit list argument from Python: a `slice` of the register, or any iterable of
+/// qubit indices or handles such as a `list` or a `range`, which may be negative.
+#[derive(Debug)]
+pub enum QubitList {
+    Slice(Py<PySlice>),
+    Indices(Vec<QubitIndex>),
+}
+
+impl<'py> FromPyObject<'_, 'py> for QubitList {
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'_, 'py, PyAny>) -> PyResult<Self> {
+        if let Ok(slice) = obj.cast::<PySlice>() {
+            return Ok(QubitList::Slice(slice.to_owned().unbind()));
+        }
+        let indices = obj
+            .try_iter()?
+            .map(|item| item?.extract())
+            .collect::<PyResult<Vec<QubitIndex>>>()?;
+        Ok(QubitList::Indices(indices))
+    }
+}
+
+impl QubitList {
+    /// The qubits of a register of `qubit_amount` qubits this list refers to, in order.
+    pub(crate) fn resolve(&self, qubit_amount: usize) -> PyResult<Vec<NodeIdx>> {
+        match self {
+            QubitList::Slice(slice) => Python::attach(|py| {
+                let len = isize::try_from(qubit_amount).expect("Registers fit in memory");
+                let indices = slice.bind(py).indices(len)?;
+                Ok((0..indices.slicelength)
+                    .map(|step| (indices.start + step as isize * indices.step) as usize)
+                    .collect())
+            }),
+            QubitList::Indices(indices) => indices
+                .iter()
+                .map(|idx| idx.resolve(qubit_amount))
+                .collect(),
+        }
+    }
+}
+
+impl From<Vec<NodeIdx>> for QubitList {
+    fn from(qubits: Vec<NodeIdx>) -> Self {
+        QubitList::Indices(qubits.into_iter().map(QubitIndex::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_indices() {
+        assert_eq!(QubitIndex::Index(2).resolve(5).unwrap(), 2);
+        assert_eq!(QubitIndex::Index(-1).resolve(5).unwrap(), 4);
+        assert_eq!(QubitIndex::Index(-5).resolve(5).unwrap(), 0);
+        let list = QubitList::Indices(vec![QubitIndex::Index(-2), QubitIndex::Index(0)]);
+        assert_eq!(list.resolve(3).unwrap(), vec![1, 0]);
+        for idx in [-6, 5] {
+            assert!(QubitIndex::Index(idx).resolve(5).is_err());
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::graphsim::{GraphSim, NodeIdx, Vop};
+
+/// A complex number as `(re, im)`.
+type Complex = (f64, f64);
+
+/// A single-qubit operator as rows of complex entries.
+pub(crate) type Matrix = [[Complex; 2]; 2];
+
+const TOLERANCE: f64 = 1e-9;
+
+fn cmul((ar, ai): Complex, (br, bi): Complex) -> Complex {
+    (ar * br - ai * bi, ar * bi + ai * br)
+}
+
+/// Matrix of the gate called `name`, as used by `Vop::gate_names`.
+fn gate_matrix(name: &str) -> Matrix {
+    let r = std::f64::consts::FRAC_1_SQRT_2;
+    let (zero, one) = ((0.0, 0.0), (1.0, 0.0));
+    match name {
+        "h" => [[(r, 0.0), (r, 0.0)], [(r, 0.0), (-r, 0.0)]],
+        "s" => [[one, zero], [zero, (0.0, 1.0)]],
+        "sdag" => [[one, zero], [zero, (0.0, -1.0)]],
+        "x" => [[zero, one], [one, zero]],
+        "y" => [[zero, (0.0, -1.0)], [(0.0, 1.0), zero]],
+        "z" => [[one, zero], [zero, (-1.0, 0.0)]],
+        _ => unreachable!("VOPs decompose into h, s, sdag and Paulis"),
+    }
+}
+
+/// Matrix of `vop`, up to a global phase.
+pub(crate) fn vop_matrix(vop: Vop) -> Matrix {
+    let (zero, one) = ((0.0, 0.0), (1.0, 0.0));
+    vop.gate_names()
+        .iter()
+        .fold([[one, zero], [zero, one]], |product, gate| {
+            let gate = gate_matrix(gate);
+            let entry = |row: usize, col: usize| {
+                let (a, b) = (
+                    cmul(gate[row][0], product[0][col]),
+                    cmul(gate[row][1], product[1][col]),
+                );
+                (a.0 + b.0, a.1 + b.1)
+            };
+            [[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]]
+        })
+}
+
+/// The local Clifford equal to `matrix` up to a global phase, if it is one.
+pub(crate) fn identify_clifford(matrix: &Matrix) -> Option<Vop> {
+    (0..crate::SYMMETRIES).map(Vop::from_index).find(|&vop| {
+        let candidate = vop_matrix(vop);
+        // the phase follows from the entry of the candidate that is furthest from zero
+        let entries = || (0..4).map(|idx| (idx / 2, idx % 2));
+        let (row, col) = entries()
+            .max_by(|&(ar, ac), &(br, bc)| {
+                let norm = |(re, im): Complex| re.hypot(im);
+                norm(candidate[ar][ac]).total_cmp(&norm(candidate[br][bc]))
+            })
+            .expect("Matrices have entries");
+        let ((cr, ci), (mr, mi)) = (candidate[row][col], matrix[row][col]);
+        let scale = cr * cr + ci * ci;
+        let phase = ((mr * cr + mi * ci) / scale, (mi * cr - mr * ci) / scale);
+        (phase.0.hypot(phase.1) - 1.0).abs() < TOLERANCE
+            && entries().all(|(row, col)| {
+                let expected = cmul(phase, candidate[row][col]);
+                let (re, im) = matrix[row][col];
+                (re - expected.0).abs() < TOLERANCE && (im - expected.1).abs() < TOLERANCE
+            })
+    })
+}
+
+/// Read a 2×2 matrix of numbers from Python, e.g. nested lists or a numpy array.
+pub(crate) fn extract_matrix(matrix: &Bound<'_, PyAny>) -> PyResult<Matrix> {
+    let complex = matrix.py().get_type::<pyo3::types::PyComplex>();
+    let rows: Vec<Vec<Complex>> = matrix
+        .try_iter()?
+        .map(|row| {
+            row?.try_iter()?
+                .map(|entry| {
+                    let value = complex.call1((entry?,))?;
+                    Ok((
+                        value.getattr("real")?.extract()?,
+                        value.getattr("imag")?.extract()?,
+                    ))
+                })
+                .collect()
+        })
+        .collect::<PyResult<_>>()?;
+    match rows.as_slice() {
+        [first, second] if first.len() == 2 && second.len() == 2 => {
+            Ok([[first[0], first[1]], [second[0], second[1]]])
+        }
+        _ => Err(PyValueError::new_err("Expected a 2×2 matrix")),
+    }
+}
+
+/// State vector of the connected `component`, with its first qubit the most significant
+/// bit of the basis state index.
+fn amplitudes(sim: &GraphSim, component: &[NodeIdx]) -> Vec<Complex> {
+    let size = component.len();
+    let bit = |pos: usize| 1 << (size - 1 - pos);
+    let norm = 0.5f64.powf(size as f64 / 2.0);
+    let mut state: Vec<Complex> = (0..1usize << size)
+        .map(|basis| {
+            let mut negative = false;
+            for (pos, &qubit) in component.iter().enumerate() {
+                for (other, &nb) in component.iter().enumerate().skip(pos + 1) {
+                    negative ^= basis & bit(pos) != 0
+                        && basis & bit(other) != 0
+                        && sim.adjacency()[qubit].contains(nb);
+                }
+            }
+            (if negative { -norm } else { norm }, 0.0)
+        })
+        .collect();
+
+    for (pos, &qubit) in component.iter().enumerate() {
+        for gate in sim.vops()[qubit].gate_names() {
+            let matrix = gate_matrix(gate);
+            for low in (0..state.len()).filter(|basis| basis & bit(pos) == 0) {
+                let (a, b) = (state[low], state[low | bit(pos)]);
+                let row = |row: [Complex; 2]| {
+                    let (c, d) = (cmul(row[0], a), cmul(row[1], b));
+                    (c.0 + d.0, c.1 + d.1)
+                };
+                state[low] = row(matrix[0]);
+                state[low | bit(pos)] = row(matrix[1]);
+            }
+        }
+    }
+    state
+}
+
+/// The state of `component` as a sum of basis kets, with the global phase chosen to make
+/// the first amplitude positive.
+fn ket(sim: &GraphSim, component: &[NodeIdx]) -> String {
+    let state = amplitudes(sim, component);
+    let terms: Vec<(usize, Complex)> = state
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (re, im))| re.abs() > TOLERANCE || im.abs() > TOLERANCE)
+        .collect();
+    // divide by the phase of the first amplitude, all of them have the same magnitude
+    let (re, im) = terms[0].1;
+    let magnitude = re.hypot(im);
+    let phase = (re / magnitude, -im / magnitude);
+
+    let mut out = String::new();
+    for (idx, &(basis, amplitude)) in terms.iter().enumerate() {
+        let (re, im) = cmul(amplitude, phase);
+        let sign = match (re > TOLERANCE, re < -TOLERANCE, im > TOLERANCE) {
+            (true, _, _) => "+ ",
+            (_, true, _) => "- ",
+            (_, _, true) => "+ i",
+            _ => "- i",
+        };
+        if idx > 0 {
+            out.push(' ');
+            out.push_str(sign);
+        }
+        out.push('|');
+        for pos in 0..component.len() {
+            let set = basis & (1 << (component.len() - 1 - pos)) != 0;
+            out.push(if set { '1' } else { '0' });
+        }
+        out.push('⟩');
+    }
+
+    let exponent = terms.len().trailing_zeros();
+    match exponent {
+        0 => out,
+        _ if exponent.is_multiple_of(2) => format!("({out})/{}", 1 << (exponent / 2)),
+        _ => format!("({out})/√{}", terms.len()),
+    }
+}
+
+/// Human readable summary of the state of `sim` as a tensor product of its components.
+///
+/// Unentangled qubits are shown by their state label, entangled components of up to
+/// `max_size` qubits as a sum of basis kets and larger ones by their size. Every factor
+/// is followed by the qubits it describes, in the order of the bits of its kets.
+pub(crate) fn to_ket_string(sim: &GraphSim, max_size: usize) -> String {
+    let mut seen = BitSet::new();
+    let mut factors = Vec::new();
+    for qubit in sim.nodes() {
+        if seen.contains(qubit) {
+            continue;
+        }
+        let mut component: Vec<NodeIdx> = sim.get_entangled_group(qubit).into_iter().collect();
+        component.sort_unstable();
+        seen.extend(component.iter().copied());
+
+        let qubits = component
+            .iter()
+            .map(NodeIdx::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        factors.push(match component.len() {
+            1 => format!("|{}⟩[{qubits}]", sim.vops()[qubit].get_state_str()),
+            size if size <= max_size => format!("{}[{qubits}]", ket(sim, &component)),
+            size => format!("<{size}-qubit entangled state>[{qubits}]"),
+        });
+    }
+    factors.join(" ⊗ ")
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::graphsim::{Axis, MeasurementResult};
+
+    /// State vector of the whole register of `sim`.
+    fn state(sim: &GraphSim) -> Vec<Complex> {
+        let qubits: Vec<NodeIdx> = (0..sim.adjacency().len()).collect();
+        amplitudes(sim, &qubits)
+    }
+
+    /// Whether `a` and `b` are the same state up to a global phase, given that `b` is
+    /// normalized.
+    fn same_state(a: &[Complex], b: &[Complex]) -> bool {
+        let norm: f64 = a.iter().map(|(re, im)| re * re + im * im).sum();
+        let overlap = a.iter().zip(b).fold((0.0, 0.0), |sum, (&a, &(re, im))| {
+            let term = cmul((a.0, -a.1), (re, im));
+            (sum.0 + term.0, sum.1 + term.1)
+        });
+        (overlap.0.hypot(overlap.1) - norm.sqrt()).abs() < TOLERANCE
+    }
+
+    /// A random entangled state of `qubit_amount` qubits.
+    fn random_state(rng: &mut impl Rng, qubit_amount: usize) -> GraphSim {
+        let mut sim = GraphSim::new(qubit_amount);
+        for _ in 0..6 * qubit_amount {
+            let qubit = rng.random_range(0..qubit_amount);
+            match rng.random_range(0..3) {
+                0 => sim.h(qubit),
+                1 => sim.s(qubit),
+                _ => sim.cz(
+                    qubit,
+                    (qubit + rng.random_range(1..qubit_amount)) % qubit_amount,
+                ),
+            }
+        }
+        sim
+    }
+
+    #[test]
+    fn test_cz_matches_state_vector() {
+        // removing the VOP of the target can give the control other neighbours, so
+        // whether to reduce the control again has to be checked afterwards
+        let mut rng = StdRng::seed_from_u64(7);
+        let qubit_amount = 5;
+        for _ in 0..500 {
+            let mut sim = random_state(&mut rng, qubit_amount);
+            let before = state(&sim);
+            let control = rng.random_range(0..qubit_amount);
+            let target = (control + rng.random_range(1..qubit_amount)) % qubit_amount;
+            sim.cz(control, target);
+            let both = (1 << (qubit_amount - 1 - control)) | (1 << (qubit_amount - 1 - target));
+            let expected: Vec<Complex> = before
+                .iter()
+                .enumerate()
+                .map(|(basis, &(re, im))| match basis & both == both {
+                    true => (-re, -im),
+                    false => (re, im),
+                })
+                .collect();
+            assert!(same_state(&expected, &state(&sim)));
+        }
+    }
+
+    #[test]
+    fn test_x_projection_matches_state_vector() {
+        // the −1 outcome of an X measurement on a qubit with neighbours used to correct
+        // the neighbourhood of the wrong qubit
+        let mut rng = StdRng::seed_from_u64(7);
+        let qubit_amount = 5;
+        for _ in 0..200 {
+            let sim = random_state(&mut rng, qubit_amount);
+            let before = state(&sim);
+            let qubit = rng.random_range(0..qubit_amount);
+            if sim.deterministic_outcome(qubit, Axis::X).is_some() {
+                continue;
+            }
+            for result in [MeasurementResult::PlusOne, MeasurementResult::MinusOne] {
+                let mut projected = sim.clone();
+                projected.project(qubit, Axis::X, result);
+                // (1 ± X) / 2 applied to the state vector
+                let bit = 1 << (qubit_amount - 1 - qubit);
+                let sign = match result {
+                    MeasurementResult::PlusOne => 1.0,
+                    MeasurementResult::MinusOne => -1.0,
+                };
+                let expected: Vec<Complex> = (0..before.len())
+                    .map(|basis| {
+                        let (a, b) = (before[basis], before[basis ^ bit]);
+                        ((a.0 + sign * b.0) / 2.0, (a.1 + sign * b.1) / 2.0)
+                    })
+                    .collect();
+                assert!(same_state(&expected, &state(&projected)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ket_string() {
+        let mut sim = GraphSim::new(9);
+        sim.x(0);
+        sim.h(1);
+        sim.s(1);
+        sim.h(2);
+        sim.cx(2, 3);
+        sim.h(4);
+        sim.s(4);
+        sim.cx(4, 5);
+        sim.z(5);
+        for qubit in 6..9 {
+            sim.h(qubit);
+        }
+        sim.cz(6, 7);
+        sim.cz(7, 8);
+        sim.h(6);
+
+        assert_eq!(
+            to_ket_string(&sim, 2),
+            "|1⟩[0] ⊗ |+i⟩[1] ⊗ (|00⟩ + |11⟩)/√2[2, 3] ⊗ (|00⟩ - i|11⟩)/√2[4, 5] ⊗ \
+             <3-qubit entangled state>[6, 7, 8]"
+        );
+        assert!(to_ket_string(&sim, 3).ends_with("(|000⟩ + |001⟩ + |110⟩ - |111⟩)/2[6, 7, 8]"));
+    }
+
+    #[test]
+    fn test_identify_clifford() {
+        let (c, s) = (0.3f64.cos(), 0.3f64.sin());
+        for idx in 0..crate::SYMMETRIES {
+            let vop = Vop::from_index(idx);
+            let rotated = vop_matrix(vop).map(|row| row.map(|entry| cmul(entry, (c, s))));
+            assert_eq!(identify_clifford(&rotated), Some(vop));
+        }
+
+        let r = std::f64::consts::FRAC_1_SQRT_2;
+        let t_gate = [[(1.0, 0.0), (0.0, 0.0)], [(0.0, 0.0), (r, r)]];
+        assert_eq!(identify_clifford(&t_gate), None);
+        let scaled_h = [[(1.0, 0.0), (1.0, 0.0)], [(1.0, 0.0), (-1.0, 0.0)]];
+        assert_eq!(identify_clifford(&scaled_h), None);
+    }
+}
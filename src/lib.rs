@@ -1,32 +1,162 @@
 use pyo3::prelude::*;
 
-mod cphase_table;
-mod small_tables;
-mod vop_table;
+mod analysis;
+mod batch;
+mod budget;
+mod cat;
+mod circuit;
+mod convert;
+mod cow;
+mod debugger;
+mod decoder;
+mod diff;
+mod edges;
+mod entanglement;
+mod frame;
+mod gadget;
+mod index;
+mod ket;
+mod mbqc;
+mod mixed;
+mod network;
+mod noise;
+mod observer;
+mod orbit;
+mod printer;
+mod profile;
+mod random_circuit;
+mod rng;
+mod routing;
+mod sampling;
+mod schedule;
+#[cfg(feature = "server")]
+pub mod server;
+mod shadow;
+mod shard;
+mod snapshot;
+mod stabilizer;
+mod surface_code;
+mod sweep;
+mod tableau;
+mod tables;
+mod text;
+mod tomography;
+mod validate;
+mod verify;
+mod witness;
+/// Run `code` in an embedded interpreter that can import the `graphsim` module, to test
+/// what Python callers see. Every call gets its own globals.
+#[cfg(test)]
+pub(crate) fn run_python(code: &std::ffi::CStr) -> PyResult<()> {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        pyo3::append_to_inittab!(graphsim);
+        Python::initialize();
+    });
+    Python::attach(|py| py.run(code, Some(&pyo3::types::PyDict::new(py)), None))
+}
+
 const SYMMETRIES: usize = 24;
 const MEAS_AXES: usize = 3;
 
 /// Graph-state based quantum circuit simulator exposed as the `graphsim` Python module.
-#[pymodule]
+///
+/// The module supports free-threaded Python. No class keeps state outside of its
+/// instances, the generator set by `seed` aside, and every class is `Send + Sync`.
+/// Mutating methods borrow their instance exclusively, so calling one while another
+/// thread uses the same object raises `RuntimeError` instead of racing. Separate instances can be used from separate
+/// threads freely.
+#[pymodule(gil_used = false)]
 pub mod graphsim {
     use bit_set::BitSet;
-    use pyo3::prelude::*;
+    use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
     use std::{
-        collections::{HashMap, HashSet, VecDeque},
+        collections::{BTreeMap, HashMap, HashSet, VecDeque},
         fmt::{Debug, Display, Formatter},
-        iter::{once, repeat_n},
-        ops::Mul,
+        iter::once,
+        ops::{Mul, Not},
+        sync::Arc,
     };
 
     use rand::{
-        Rng,
+        Rng, RngCore, SeedableRng,
         distr::{Distribution, StandardUniform},
+        rngs::StdRng,
+    };
+
+    #[pymodule_export]
+    use crate::analysis::CircuitAnalysis;
+    #[pymodule_export]
+    use crate::batch::SimBatch;
+    #[pymodule_export]
+    use crate::budget::{ErrorBudget, GateBudget, error_budget};
+    #[pymodule_export]
+    use crate::cat::{CatStateResult, verified_cat_state};
+    #[pymodule_export]
+    use crate::circuit::{Circuit, Op};
+    #[pymodule_export]
+    use crate::convert::{outcome_dict_to_arrays, outcomes_to_array};
+    #[pymodule_export]
+    use crate::debugger::Debugger;
+    #[pymodule_export]
+    use crate::decoder::{
+        DetectorErrorModel, ErrorMechanism, detector_error_model, sample_detectors,
     };
+    #[pymodule_export]
+    use crate::diff::StateDiff;
+    #[pymodule_export]
+    use crate::edges::{EdgeIterator, EdgesView};
+    #[pymodule_export]
+    use crate::frame::PauliFrame;
+    #[pymodule_export]
+    use crate::index::Qubit;
+    #[pymodule_export]
+    use crate::mbqc::{Byproduct, Pattern, PatternResult};
+    #[pymodule_export]
+    use crate::mixed::MixedGraphSim;
+    #[pymodule_export]
+    use crate::network::Network;
+    #[pymodule_export]
+    use crate::noise::{Fault, FaultKind, NoiseModel, TrajectoryResult, run_trajectories};
+    #[pymodule_export]
+    use crate::observer::{GraphDelta, MeasurementEvent, MeasurementRecord};
+    #[pymodule_export]
+    use crate::profile::ResourceProfile;
+    #[pymodule_export]
+    use crate::random_circuit::random_circuit;
+    #[pymodule_export]
+    use crate::rng::seed;
+    #[pymodule_export]
+    use crate::routing::RoutingResult;
+    #[pymodule_export]
+    use crate::shadow::ClassicalShadow;
+    #[pymodule_export]
+    use crate::shard::ShardedGraphSim;
+    #[pymodule_export]
+    use crate::stabilizer::StabilizerIterator;
+    #[pymodule_export]
+    use crate::surface_code::{MemoryResult, surface_code_circuit, surface_code_memory};
+    #[pymodule_export]
+    use crate::sweep::{
+        LogicalErrorEstimate, SweepPoint, ThresholdPoint, estimate_logical_error_rate, sweep,
+        threshold,
+    };
+    #[pymodule_export]
+    use crate::text::run_text;
+    #[pymodule_export]
+    use crate::tomography::{TomographyResult, stabilizer_tomography};
+    #[pymodule_export]
+    use crate::validate::Problem;
+    #[pymodule_export]
+    use crate::verify::verify_tables;
+    #[pymodule_export]
+    use crate::witness::{FidelityWitness, estimate_fidelity};
 
     use crate::{
-        cphase_table::CPHASE_TABLE,
-        small_tables::{ADJ_TABLE, CONJ_TABLE, DETM_TABLE},
-        vop_table::VOP_TABLE,
+        cow::CowVec,
+        index::{QubitIndex, QubitList},
+        observer::Changes,
+        tables::{ADJ_TABLE, CONJ_TABLE, CPHASE_TABLE, DETM_TABLE, VOP_TABLE},
     };
 
     /// Index of a node / qubit in the graph.
@@ -53,22 +183,39 @@ pub mod graphsim {
         }
     }
 
+    impl Not for MeasurementResult {
+        type Output = MeasurementResult;
+
+        fn not(self) -> Self::Output {
+            match self {
+                MeasurementResult::PlusOne => MeasurementResult::MinusOne,
+                MeasurementResult::MinusOne => MeasurementResult::PlusOne,
+            }
+        }
+    }
+
     /// Measurement outcome and the axis that was measured.
     ///
-    /// Returned in the values of `peek_measure_set`.
+    /// Returned by `peek_measure` and in the values of `peek_measure_set`.
     #[pyclass(frozen, get_all, str)]
     pub struct Outcome {
         result: MeasurementResult,
         axis: Axis,
     }
 
+    impl Outcome {
+        pub(crate) fn result(&self) -> MeasurementResult {
+            self.result
+        }
+    }
+
     impl Display for Outcome {
         fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
             write!(fmt, "({}, {})", self.axis, self.result)
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub(crate) enum Vop {
         IA,
         XA,
@@ -97,21 +244,25 @@ pub mod graphsim {
     }
 
     impl Vop {
+        /// Label of the single-qubit state this VOP turns |+⟩ into.
         pub fn get_state_str(&self) -> &'static str {
             match self {
                 Vop::IA | Vop::XA | Vop::YD | Vop::ZD => "+",
                 Vop::YA | Vop::ZA | Vop::ID | Vop::XD => "-",
-                Vop::IB | Vop::XB | Vop::YE | Vop::ZE => "+i",
-                Vop::YB | Vop::ZB | Vop::IE | Vop::XE => "-i",
+                Vop::YB | Vop::ZB | Vop::IE | Vop::XE => "+i",
+                Vop::IB | Vop::XB | Vop::YE | Vop::ZE => "-i",
                 Vop::IC | Vop::XC | Vop::YF | Vop::ZF => "1",
                 Vop::YC | Vop::ZC | Vop::IF | Vop::XF => "0",
             }
         }
     }
 
+    /// Measurement axis of a single qubit.
+    ///
+    /// Exposed to Python as `graphsim.Axis`.
     #[pyclass(eq, eq_int)]
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-    pub(crate) enum Axis {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum Axis {
         X,
         Y,
         Z,
@@ -144,6 +295,15 @@ pub mod graphsim {
         Two,
     }
 
+    impl Zeta {
+        fn apply(&self, res: MeasurementResult) -> MeasurementResult {
+            match self {
+                Zeta::Zero => res,
+                Zeta::Two => !res,
+            }
+        }
+    }
+
     impl Distribution<MeasurementResult> for StandardUniform {
         fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> MeasurementResult {
             match rng.random() {
@@ -159,6 +319,10 @@ pub mod graphsim {
     const H_GATE: Vop = Vop::YC;
     const S_GATE: Vop = Vop::YB;
     const SDAG_GATE: Vop = Vop::XB;
+    const SX_GATE: Vop = Vop::ZD;
+    const SXDAG_GATE: Vop = Vop::YD;
+    const SY_GATE: Vop = Vop::XC;
+    const SYDAG_GATE: Vop = Vop::ZC;
 
     impl Mul for Vop {
         type Output = Vop;
@@ -168,6 +332,7 @@ pub mod graphsim {
         }
     }
 
+    #[derive(PartialEq, Eq)]
     enum DecompUnit {
         U,
         V,
@@ -178,11 +343,45 @@ pub mod graphsim {
             ADJ_TABLE[self as usize]
         }
 
-        fn is_in_z(self) -> bool {
-            match self {
-                Vop::IA | Vop::ZA | Vop::YB | Vop::XB => true,
-                _ => false,
+        /// The VOP with `index` in the order of the `Vop` variants.
+        pub(crate) fn from_index(index: usize) -> Vop {
+            VOP_TABLE[Vop::IA as usize][index]
+        }
+
+        pub(crate) fn is_in_z(self) -> bool {
+            matches!(self, Vop::IA | Vop::ZA | Vop::YB | Vop::XB)
+        }
+
+        /// Names of a shortest gate sequence equal to this VOP, in the order the gates
+        /// are applied.
+        pub(crate) fn gate_names(self) -> Vec<&'static str> {
+            let gates = [
+                ("h", H_GATE),
+                ("s", S_GATE),
+                ("sdag", SDAG_GATE),
+                ("x", X_GATE),
+                ("y", Y_GATE),
+                ("z", Z_GATE),
+            ];
+            let mut paths: HashMap<Vop, Vec<&'static str>> = HashMap::from([(Vop::IA, vec![])]);
+            let mut queue = VecDeque::from([Vop::IA]);
+            while let Some(vop) = queue.pop_front() {
+                if vop == self {
+                    break;
+                }
+                for (name, gate) in gates {
+                    let next = gate * vop;
+                    if !paths.contains_key(&next) {
+                        let mut path = paths[&vop].clone();
+                        path.push(name);
+                        paths.insert(next, path);
+                        queue.push_back(next);
+                    }
+                }
             }
+            paths
+                .remove(&self)
+                .expect("The gates generate all local Cliffords")
         }
 
         fn decomp(self) -> &'static [DecompUnit] {
@@ -235,12 +434,129 @@ pub mod graphsim {
 
     /// Simulator for graph states over a fixed number of qubits.
     ///
-    /// Use this class from Python to apply gates and perform measurements.
-    #[derive(Clone, Debug)]
+    /// Use this class from Python to apply gates and perform measurements. Copies share
+    /// the storage of VOPs and neighbour sets until they are changed, so cloning takes
+    /// O(n / 64) and a copy only pays for the parts of the graph it touches.
+    ///
+    /// From Python, qubit arguments count from the end of the register when negative,
+    /// and qubit lists can also be a `slice` or a `range`. Indices out of range raise
+    /// `ValueError`, as do `Qubit` handles from `allocate` once their qubit was released.
+    #[derive(Clone)]
     #[pyclass]
     pub struct GraphSim {
-        vop: Vec<Vop>,
-        adjacent: Vec<BitSet>,
+        vop: CowVec<Vop>,
+        adjacent: CowVec<BitSet>,
+        /// Incrementally updated `state_hash` of the graph and VOPs.
+        hash: u64,
+        /// Qubits removed by `trace_out` or `free_ancilla`, which no operation may touch
+        /// until `alloc_ancilla` hands them out again. They are all isolated in |0⟩.
+        traced: BitSet,
+        /// Qubits handed out by `alloc_ancilla` and not freed or traced out since, the
+        /// only ones `free_ancilla` takes back.
+        ancillas: BitSet,
+        /// How often every qubit was traced out, to tell stale `Qubit` handles apart.
+        generations: CowVec<u32>,
+        /// Operations applied since `start_recording`, while recording.
+        recording: Option<CowVec<Op>>,
+        /// Measurements logged while recording, by qubit.
+        history: HashMap<NodeIdx, Vec<MeasurementEvent>>,
+        /// Classical register with the outcome of every measurement, shared between copies
+        /// until they measure.
+        register: CowVec<MeasurementRecord>,
+        /// Python callables registered with `on_measure`, shared between copies.
+        measure_callbacks: Vec<Arc<Py<PyAny>>>,
+        /// Python callables registered with `on_operation`, shared between copies.
+        op_listeners: Vec<Arc<Py<PyAny>>>,
+        /// Changes made by the operation being observed, while one runs.
+        changes: Option<Changes>,
+        /// Qubits passed to `defer_measurement`, with their outcome once it is resolved and
+        /// until it is read.
+        deferred: BTreeMap<NodeIdx, Option<MeasurementResult>>,
+        /// Generator seeded through `GraphSim(qubits, seed=...)`, shared between copies.
+        rng: Option<crate::rng::SharedRng>,
+    }
+
+    /// Simulators are equal when they have the same graph, VOPs and traced qubits,
+    /// whatever they recorded.
+    impl PartialEq for GraphSim {
+        fn eq(&self, other: &Self) -> bool {
+            self.hash == other.hash
+                && self.vop == other.vop
+                && self.adjacent == other.adjacent
+                && self.traced == other.traced
+        }
+    }
+
+    impl Eq for GraphSim {}
+
+    impl std::hash::Hash for GraphSim {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.hash.hash(state);
+            self.traced.hash(state);
+        }
+    }
+
+    /// Most nodes, and neighbours per node, the `Debug` output of a `GraphSim` lists.
+    const DEBUG_LIMIT: usize = 16;
+
+    /// Debug list of the first `DEBUG_LIMIT` items, ending in `..` if there are more.
+    struct Limited<I>(I);
+
+    impl<I: Clone + Iterator<Item: Debug>> Debug for Limited<I> {
+        fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+            let mut list = fmt.debug_list();
+            list.entries(self.0.clone().take(DEBUG_LIMIT));
+            match self.0.clone().nth(DEBUG_LIMIT) {
+                Some(_) => list.finish_non_exhaustive(),
+                None => list.finish(),
+            }
+        }
+    }
+
+    /// A node with its VOP and neighbours, as listed by the `Debug` output.
+    struct DebugNode<'a>(&'a GraphSim, NodeIdx);
+
+    impl Debug for DebugNode<'_> {
+        fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+            let DebugNode(sim, node) = *self;
+            fmt.debug_struct("Node")
+                .field("idx", &node)
+                .field("vop", &sim.vop[node])
+                .field("neighbours", &Limited(sim.adjacent[node].iter()))
+                .finish()
+        }
+    }
+
+    /// Lists the first nodes with their VOPs and neighbours, and the traced out qubits.
+    impl Debug for GraphSim {
+        fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+            fmt.debug_struct("GraphSim")
+                .field("qubits", &self.adjacent.len())
+                .field("edges", &self.edge_count())
+                .field(
+                    "nodes",
+                    &Limited(self.nodes().map(|node| DebugNode(self, node))),
+                )
+                .field("traced", &Limited(self.traced.iter()))
+                .field("recording", &self.recording.is_some())
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// Summarizes the size of the graph, e.g. `GraphSim(5 qubits, 4 edges)`.
+    impl Display for GraphSim {
+        fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+            write!(
+                fmt,
+                "GraphSim({} qubits, {} edges",
+                self.adjacent.len(),
+                self.edge_count()
+            )?;
+            match self.traced.len() {
+                0 => fmt.write_str(")"),
+                traced => write!(fmt, ", {traced} traced out)"),
+            }
+        }
     }
 
     impl GraphSim {
@@ -254,35 +570,445 @@ pub mod graphsim {
                 }
             }
         }
-        // Measurement
-        fn measure(&mut self, node: NodeIdx, axis: Axis) -> (MeasurementResult, bool) {
-            let zeta = find_zeta(self.vop[node].adj(), axis);
-            let basis = &CONJ_TABLE[axis as usize][self.vop[node].adj() as usize];
-
-            let (mut res, deterministic) = match basis {
-                Axis::X => self.int_measure_x(node),
-                Axis::Y => (self.int_measure_y(node), false),
-                Axis::Z => (self.int_measure_z(node), false),
+        /// Neighbours of every node in the graph.
+        pub(crate) fn adjacency(&self) -> &CowVec<BitSet> {
+            &self.adjacent
+        }
+
+        /// Number of edges in the graph.
+        pub fn edge_count(&self) -> usize {
+            self.adjacent.iter().map(|adj| adj.len()).sum::<usize>() / 2
+        }
+
+        /// Iterate over the qubits that can be used, skipping traced out ones.
+        pub fn nodes(&self) -> impl Iterator<Item = NodeIdx> + Clone + '_ {
+            (0..self.adjacent.len()).filter(|&node| !self.traced.contains(node))
+        }
+
+        /// Iterate over the edges of the graph as `(a, b)` with `a < b`, ordered by `a`
+        /// and then `b`.
+        pub fn edges(&self) -> impl Iterator<Item = (NodeIdx, NodeIdx)> + Clone + '_ {
+            self.adjacent.iter().enumerate().flat_map(|(node, adj)| {
+                adj.iter()
+                    .filter(move |&nb| nb > node)
+                    .map(move |nb| (node, nb))
+            })
+        }
+
+        /// Iterate over the neighbours of `qubit` in increasing order.
+        ///
+        /// Panics if `qubit` is out of range.
+        pub fn neighbors(&self, qubit: NodeIdx) -> impl Iterator<Item = NodeIdx> + Clone + '_ {
+            self.adjacent[qubit].iter()
+        }
+
+        /// Set the VOP of `node`, keeping the state hash up to date.
+        fn set_vop(&mut self, node: NodeIdx, vop: Vop) {
+            self.hash ^= vop_hash(node, self.vop[node]) ^ vop_hash(node, vop);
+            if let Some(changes) = &mut self.changes {
+                changes.vops.push((node, self.vop[node], vop));
+            }
+            self.vop[node] = vop;
+        }
+
+        /// Account for toggling the edge between `na` and `nb` in the state hash.
+        fn edge_toggled(&mut self, na: NodeIdx, nb: NodeIdx) {
+            self.hash ^= edge_hash(na, nb);
+            if let Some(changes) = &mut self.changes {
+                changes.edges.push((na.min(nb), na.max(nb)));
+            }
+        }
+
+        /// Compute the state hash from scratch.
+        ///
+        /// Scales as O(n + E)
+        fn full_hash(&self) -> u64 {
+            let vops = self
+                .vop
+                .iter()
+                .enumerate()
+                .fold(0, |hash, (node, &vop)| hash ^ vop_hash(node, vop));
+            self.adjacent
+                .iter()
+                .enumerate()
+                .fold(vops, |hash, (node, adj)| {
+                    adj.iter()
+                        .filter(|&nb| nb > node)
+                        .fold(hash, |hash, nb| hash ^ edge_hash(node, nb))
+                })
+        }
+
+        /// Local Clifford of every node.
+        pub(crate) fn vops(&self) -> &CowVec<Vop> {
+            &self.vop
+        }
+
+        /// Create a new simulator with `nodes` qubits, all initialized in the |0⟩ state.
+        pub fn new(qubit_amount: usize) -> GraphSim {
+            let mut sim = GraphSim {
+                vop: CowVec::from_elem(Vop::YC, qubit_amount),
+                adjacent: CowVec::from_elem(BitSet::with_capacity(qubit_amount), qubit_amount),
+                hash: 0,
+                traced: BitSet::new(),
+                ancillas: BitSet::new(),
+                generations: CowVec::from_elem(0, qubit_amount),
+                recording: None,
+                history: HashMap::new(),
+                register: CowVec::default(),
+                measure_callbacks: Vec::new(),
+                op_listeners: Vec::new(),
+                changes: None,
+                deferred: BTreeMap::new(),
+                rng: crate::rng::inherited(),
+            };
+            sim.hash = sim.full_hash();
+            sim
+        }
+
+        /// Run `f` with the generator random outcomes come from, the thread-local one
+        /// unless the simulator was seeded.
+        ///
+        /// A seeded generator is locked while `f` runs, so `f` can't measure copies.
+        pub(crate) fn with_rng<T>(&self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+            match &self.rng {
+                Some(rng) => f(&mut *rng.lock().expect("Generator isn't poisoned")),
+                None => f(&mut rand::rng()),
+            }
+        }
+
+        /// Draw random outcomes from a generator of this simulator seeded from `rng`, so
+        /// shots sampled with a seeded `rng` repeat.
+        pub(crate) fn reseed(&mut self, rng: &mut dyn RngCore) {
+            self.rng = Some(crate::rng::seeded(rng.next_u64()));
+        }
+
+        /// Simulator with the given VOPs, symmetric neighbour sets and traced qubits.
+        pub(crate) fn from_parts(
+            vops: Vec<Vop>,
+            adjacent: Vec<BitSet>,
+            traced: BitSet,
+        ) -> GraphSim {
+            let mut sim = GraphSim {
+                generations: CowVec::from_elem(0, vops.len()),
+                vop: vops.into_iter().collect(),
+                adjacent: adjacent.into_iter().collect(),
+                hash: 0,
+                traced,
+                ancillas: BitSet::new(),
+                recording: None,
+                history: HashMap::new(),
+                register: CowVec::default(),
+                measure_callbacks: Vec::new(),
+                op_listeners: Vec::new(),
+                changes: None,
+                deferred: BTreeMap::new(),
+                rng: None,
             };
+            sim.hash = sim.full_hash();
+            sim
+        }
 
-            match zeta {
-                Zeta::Two => {
-                    res = match res {
-                        MeasurementResult::PlusOne => MeasurementResult::MinusOne,
-                        MeasurementResult::MinusOne => MeasurementResult::PlusOne,
+        /// Absorb the graph-state stabilizer of `node` into the VOPs.
+        ///
+        /// The stabilizer is X on `node` and Z on its neighbours, so the state is unchanged.
+        pub(crate) fn absorb_stabilizer(&mut self, node: NodeIdx) {
+            self.set_vop(node, self.vop[node] * X_GATE);
+            let neighbours: Vec<NodeIdx> = self.adjacent[node].iter().collect();
+            for nb in neighbours {
+                self.set_vop(nb, self.vop[nb] * Z_GATE);
+            }
+        }
+
+        /// Whether `qubit` was traced out and not handed out again.
+        pub(crate) fn is_traced(&self, qubit: NodeIdx) -> bool {
+            self.traced.contains(qubit)
+        }
+
+        /// Panic if `qubit` was traced out, like any other invalid use of a qubit.
+        ///
+        /// Python methods reject such qubits in `resolve` already, so this only guards
+        /// the invariant for callers within the crate.
+        fn assert_usable(&self, qubit: NodeIdx) {
+            assert!(
+                !self.traced.contains(qubit),
+                "Qubit {qubit} was traced out and can't be used"
+            );
+        }
+
+        /// Forget `qubit` after measuring it in Z, leaving it isolated.
+        ///
+        /// The qubit is projected onto `result` if the outcome is random, otherwise
+        /// `result` is ignored.
+        pub(crate) fn discard(&mut self, qubit: NodeIdx, result: MeasurementResult) {
+            if self.deterministic_outcome(qubit, Axis::Z).is_none() {
+                self.project(qubit, Axis::Z, result);
+            }
+            self.set_vop(qubit, Vop::YC);
+            self.traced.insert(qubit);
+            self.ancillas.remove(qubit);
+            self.generations[qubit] = self.generations[qubit].wrapping_add(1);
+        }
+
+        /// Run `apply`, then log `op` if recording and pass it to the `on_operation`
+        /// listeners. Gates `apply` uses itself are neither logged nor observed.
+        fn recorded<T>(&mut self, op: Op, apply: impl FnOnce(&mut Self) -> T) -> T {
+            if !self.deferred.is_empty() {
+                for qubit in op.qubits() {
+                    self.settle(qubit);
+                }
+            }
+            let log = self.recording.take();
+            let observed = !self.op_listeners.is_empty() && self.changes.is_none();
+            if observed {
+                self.changes = Some(Changes::default());
+            }
+            let res = apply(self);
+            if let Some(mut log) = log {
+                log.push(op);
+                self.recording = Some(log);
+            }
+            if observed {
+                let changes = self
+                    .changes
+                    .take()
+                    .expect("Observed operations collect changes");
+                self.notify_operation(op, changes);
+            }
+            res
+        }
+
+        /// Carry out the deferred measurement of `qubit`, if it has one that is pending.
+        fn settle(&mut self, qubit: NodeIdx) {
+            if self.deferred.get(&qubit) == Some(&None) {
+                // taken out first, as measuring is an operation on the qubit itself
+                self.deferred.remove(&qubit);
+                let result = self.measure_op(qubit, Axis::Z);
+                self.deferred.insert(qubit, Some(result));
+            }
+        }
+
+        fn notify_operation(&self, op: Op, changes: Changes) {
+            let delta = changes.into_delta();
+            Python::attach(|py| {
+                for listener in &self.op_listeners {
+                    if let Err(err) = listener.call1(py, (op, delta.clone())) {
+                        err.write_unraisable(py, Some(listener.bind(py)));
                     }
                 }
-                _ => {}
+            });
+        }
+
+        /// Measure `qubit` along `axis` as the operation a user asked for, logging it.
+        pub(crate) fn measure_op(&mut self, qubit: NodeIdx, axis: Axis) -> MeasurementResult {
+            self.logged_measurement(qubit, axis, None)
+        }
+
+        /// Reset `qubit` to |0⟩ as `Op::Reset`, logging it.
+        ///
+        /// The qubit is measured in Z and the outcome forgotten, so it is neither in the
+        /// register nor passed to `on_measure` callbacks.
+        pub(crate) fn reset_op(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Reset { qubit }, |sim| {
+                if sim.deterministic_outcome(qubit, Axis::Z).is_none() {
+                    let result = sim.with_rng(|rng| rng.random());
+                    sim.project(qubit, Axis::Z, result);
+                }
+                sim.set_vop(qubit, Vop::YC);
+            });
+        }
+
+        /// Measure `qubit` along `axis` like `measure_op`, but project onto `result`.
+        ///
+        /// Raises `ValueError` without changing the state if `result` has probability 0.
+        pub(crate) fn measure_forced(
+            &mut self,
+            qubit: NodeIdx,
+            axis: Axis,
+            result: MeasurementResult,
+        ) -> PyResult<MeasurementResult> {
+            self.settle(qubit);
+            match self.deterministic_outcome(qubit, axis) {
+                Some(certain) if certain != result => Err(PyValueError::new_err(format!(
+                    "Qubit {qubit} can't give {result} along {axis}, the outcome has probability 0"
+                ))),
+                _ => Ok(self.logged_measurement(qubit, axis, Some(result))),
+            }
+        }
+
+        /// Measure `qubit` along `axis`, projecting onto `forced` if given and the outcome
+        /// is random, then log the measurement and pass it to the callbacks.
+        fn logged_measurement(
+            &mut self,
+            qubit: NodeIdx,
+            axis: Axis,
+            forced: Option<MeasurementResult>,
+        ) -> MeasurementResult {
+            let outcome = self.recorded(Op::Measure { qubit, axis }, |sim| {
+                let (result, deterministic) = match forced {
+                    Some(result) if sim.deterministic_outcome(qubit, axis).is_none() => {
+                        sim.project(qubit, axis, result);
+                        (result, false)
+                    }
+                    _ => sim.measure(qubit, axis),
+                };
+                sim.notify_measurement(qubit, axis, result, deterministic)
+            });
+            self.register.push(MeasurementRecord {
+                index: self.register.len(),
+                qubit,
+                axis,
+                outcome,
+            });
+            if let Some(log) = &self.recording {
+                self.history
+                    .entry(qubit)
+                    .or_default()
+                    .push(MeasurementEvent {
+                        op_index: log.len() - 1,
+                        qubit,
+                        axis,
+                        outcome,
+                    });
+            }
+            outcome
+        }
+
+        /// Pass a measurement to the `on_measure` callbacks, returning the outcome after
+        /// any of them replaced it.
+        fn notify_measurement(
+            &self,
+            qubit: NodeIdx,
+            axis: Axis,
+            result: MeasurementResult,
+            deterministic: bool,
+        ) -> MeasurementResult {
+            if self.measure_callbacks.is_empty() {
+                return result;
+            }
+            Python::attach(|py| {
+                self.measure_callbacks
+                    .iter()
+                    .fold(result, |result, callback| {
+                        let replaced = callback
+                            .call1(py, (qubit, axis, result, deterministic))
+                            .map(|ret| ret.extract::<MeasurementResult>(py).ok());
+                        match replaced {
+                            Ok(replaced) => replaced.unwrap_or(result),
+                            Err(err) => {
+                                err.write_unraisable(py, Some(callback.bind(py)));
+                                result
+                            }
+                        }
+                    })
+            })
+        }
+
+        /// Left-multiply the VOP of `qubit` by the single-qubit gate `gate`.
+        fn apply_local(&mut self, qubit: NodeIdx, gate: Vop) {
+            self.assert_usable(qubit);
+            self.set_vop(qubit, gate * self.vop[qubit]);
+        }
+
+        fn apply_cz(&mut self, control: NodeIdx, target: NodeIdx) {
+            self.apply_cz_with(control, target, false);
+        }
+
+        /// `apply_cz`, where with `keep_diagonal` a VOP of `control` that is already in
+        /// Z isn't reduced first, as the CPHASE table only needs it to commute with CZ.
+        fn apply_cz_with(&mut self, control: NodeIdx, target: NodeIdx, keep_diagonal: bool) {
+            // println!(
+            //     "performing cnot between {control} and {target}, with adjacent {:#?} and {:#?} respectively",
+            //     self.adjacent[control], self.adjacent[target]
+            // );
+            assert_ne!(control, target, "Same control and target not allowed");
+            self.assert_usable(control);
+            self.assert_usable(target);
+            if self.has_other_neighbours(control, target)
+                && !(keep_diagonal && self.vop[control].is_in_z())
+            {
+                self.remove_vop(control, target);
+            }
+            if self.has_other_neighbours(target, control) {
+                self.remove_vop(target, control);
+            }
+            if self.has_other_neighbours(control, target) && !self.vop[control].is_in_z() {
+                self.remove_vop(control, target);
+            }
+
+            let cv = self.vop[control];
+            let tv = self.vop[target];
+            let had_edge = match self.adjacent[control].contains(target) {
+                true => 1,
+                false => 0,
             };
+            let val = CPHASE_TABLE[had_edge][cv as usize][tv as usize];
+
+            if val.0 != (had_edge == 1) {
+                self.edge_toggled(control, target);
+            }
+            if val.0 {
+                self.adjacent[control].insert(target);
+                self.adjacent[target].insert(control);
+            } else {
+                self.adjacent[control].remove(target);
+                self.adjacent[target].remove(control);
+            }
+            self.set_vop(control, val.1);
+            self.set_vop(target, val.2);
+        }
 
-            (res, deterministic)
+        /// Apply the Pauli operator along `axis` to `qubit`.
+        pub(crate) fn apply_pauli(&mut self, qubit: NodeIdx, axis: Axis) {
+            match axis {
+                Axis::X => self.x(qubit),
+                Axis::Y => self.y(qubit),
+                Axis::Z => self.z(qubit),
+            }
         }
-        fn int_measure_x(&mut self, node: NodeIdx) -> (MeasurementResult, bool) {
-            if self.adjacent[node].is_empty() {
-                return (MeasurementResult::PlusOne, true);
+        // Measurement
+        pub(crate) fn measure(&mut self, node: NodeIdx, axis: Axis) -> (MeasurementResult, bool) {
+            self.assert_usable(node);
+            match self.deterministic_outcome(node, axis) {
+                Some(res) => (res, true),
+                None => {
+                    let res = self.with_rng(|rng| rng.random());
+                    self.project(node, axis, res);
+                    (res, false)
+                }
+            }
+        }
+
+        /// Outcome of measuring `node` along `axis` if it is certain, `None` if both
+        /// outcomes have probability 1/2.
+        pub(crate) fn deterministic_outcome(
+            &self,
+            node: NodeIdx,
+            axis: Axis,
+        ) -> Option<MeasurementResult> {
+            let basis = CONJ_TABLE[axis as usize][self.vop[node].adj() as usize];
+            if basis == Axis::X && self.adjacent[node].is_empty() {
+                Some(find_zeta(self.vop[node].adj(), axis).apply(MeasurementResult::PlusOne))
+            } else {
+                None
             }
+        }
 
-            let res: MeasurementResult = rand::rng().random();
+        /// Project `node` onto the `result` eigenstate of `axis`.
+        ///
+        /// The measurement must not be deterministic, so both outcomes are valid.
+        pub(crate) fn project(&mut self, node: NodeIdx, axis: Axis, result: MeasurementResult) {
+            debug_assert!(
+                self.deterministic_outcome(node, axis).is_none(),
+                "Can't project a deterministic measurement"
+            );
+            let res = find_zeta(self.vop[node].adj(), axis).apply(result);
+            match CONJ_TABLE[axis as usize][self.vop[node].adj() as usize] {
+                Axis::X => self.int_measure_x(node, res),
+                Axis::Y => self.int_measure_y(node, res),
+                Axis::Z => self.int_measure_z(node, res),
+            }
+        }
+        fn int_measure_x(&mut self, node: NodeIdx, res: MeasurementResult) {
             let other: NodeIdx = self.adjacent[node]
                 .iter()
                 .take(1)
@@ -293,20 +1019,20 @@ pub mod graphsim {
 
             match res {
                 MeasurementResult::PlusOne => {
-                    self.vop[other] = self.vop[other] * Vop::ZC;
+                    self.set_vop(other, self.vop[other] * Vop::ZC);
                     for third in unsafe { (&mut *rself).adjacent[node].iter() } {
                         if third != other && !self.adjacent[other].contains(third) {
-                            self.vop[third] = self.vop[third] * Z_GATE;
+                            self.set_vop(third, self.vop[third] * Z_GATE);
                         }
                     }
                 }
                 MeasurementResult::MinusOne => {
-                    self.vop[other] = self.vop[other] * Vop::XC;
-                    self.vop[node] = self.vop[node] * Vop::ZA;
+                    self.set_vop(other, self.vop[other] * Vop::XC);
+                    self.set_vop(node, self.vop[node] * Vop::ZA);
 
                     for third in unsafe { (&mut *rself).adjacent[other].iter() } {
-                        if third != other && !self.adjacent[other].contains(third) {
-                            self.vop[third] = self.vop[third] * Z_GATE;
+                        if third != node && !self.adjacent[node].contains(third) {
+                            self.set_vop(third, self.vop[third] * Z_GATE);
                         }
                     }
                 }
@@ -344,18 +1070,14 @@ pub mod graphsim {
                     self.toggle_edge(other, nval);
                 }
             }
-
-            (res, false)
         }
-        fn int_measure_y(&mut self, node: NodeIdx) -> MeasurementResult {
-            let res = rand::rng().random();
-
+        fn int_measure_y(&mut self, node: NodeIdx, res: MeasurementResult) {
             let adj = self.adjacent[node].clone();
 
             for other in adj.iter() {
                 match res {
-                    MeasurementResult::PlusOne => self.vop[other] = self.vop[other] * S_GATE,
-                    MeasurementResult::MinusOne => self.vop[other] = self.vop[other] * SDAG_GATE,
+                    MeasurementResult::PlusOne => self.set_vop(other, self.vop[other] * S_GATE),
+                    MeasurementResult::MinusOne => self.set_vop(other, self.vop[other] * SDAG_GATE),
                 }
             }
 
@@ -366,34 +1088,28 @@ pub mod graphsim {
             }
 
             match res {
-                MeasurementResult::PlusOne => self.vop[node] = self.vop[node] * S_GATE,
-                MeasurementResult::MinusOne => self.vop[node] = self.vop[node] * SDAG_GATE,
+                MeasurementResult::PlusOne => self.set_vop(node, self.vop[node] * S_GATE),
+                MeasurementResult::MinusOne => self.set_vop(node, self.vop[node] * SDAG_GATE),
             }
-
-            res
         }
-        fn int_measure_z(&mut self, node: NodeIdx) -> MeasurementResult {
-            let res = rand::rng().random();
-
+        fn int_measure_z(&mut self, node: NodeIdx, res: MeasurementResult) {
             for other in self.adjacent[node].clone().iter() {
                 self.delete_edge(node, other);
                 if res == MeasurementResult::MinusOne {
-                    self.vop[other] = self.vop[other] * Z_GATE;
+                    self.set_vop(other, self.vop[other] * Z_GATE);
                 }
             }
 
             match res {
-                MeasurementResult::PlusOne => self.vop[node] = self.vop[node] * H_GATE,
-                MeasurementResult::MinusOne => self.vop[node] = self.vop[node] * X_GATE * H_GATE,
+                MeasurementResult::PlusOne => self.set_vop(node, self.vop[node] * H_GATE),
+                MeasurementResult::MinusOne => self.set_vop(node, self.vop[node] * X_GATE * H_GATE),
             }
-
-            res
         }
         // Helper functions
         /// remove the local operators non-Z stabilisation by swapping with its neighbours
         ///
         /// Scales as O(?)
-        fn remove_vop(&mut self, first: NodeIdx, avoid: NodeIdx) {
+        pub(crate) fn remove_vop(&mut self, first: NodeIdx, avoid: NodeIdx) {
             let mut second: NodeIdx = avoid;
             for attempt in &self.adjacent[first] {
                 if attempt != avoid {
@@ -402,30 +1118,91 @@ pub mod graphsim {
                 }
             }
 
-            for d in self.vop[first].decomp() {
-                match d {
-                    DecompUnit::U => self.local_comp(first),
-                    DecompUnit::V => self.local_comp(second),
+            // runs of complementations of one node are fused, so e.g. IA toggles no edges
+            for run in self.vop[first].decomp().chunk_by(|a, b| a == b) {
+                match run[0] {
+                    DecompUnit::U => self.local_comps(first, run.len()),
+                    DecompUnit::V => self.local_comps(second, run.len()),
                 }
             }
         }
 
         /// do a local complementation of a qubit with its surroundings
         ///
-        /// Scales as O(d^2 * O(toggle_edge))
-        fn local_comp(&mut self, node: NodeIdx) {
-            let rself: *mut Self = self as *mut Self;
+        /// Scales as O(d^2)
+        pub(crate) fn local_comp(&mut self, node: NodeIdx) {
+            self.local_comps(node, 1);
+        }
 
-            for (idx, i) in unsafe { (&mut *rself).adjacent[node].iter().enumerate() } {
-                for j in unsafe { (&mut *rself).adjacent[node].iter().skip(idx + 1) } {
-                    self.toggle_edge(i, j);
+        /// do `times` local complementations of a qubit with its surroundings at once
+        ///
+        /// The neighbourhood of `node` doesn't change, so the edges among its neighbours
+        /// are only toggled for an odd number of complementations, and only the VOPs are
+        /// updated otherwise. For dense neighbourhoods the toggles of every neighbour are
+        /// applied as one symmetric difference of neighbour sets.
+        ///
+        /// Scales as O(d^2)
+        pub(crate) fn local_comps(&mut self, node: NodeIdx, times: usize) {
+            let neighbours = self.adjacent[node].clone();
+            if times % 2 == 1 {
+                let listed: Vec<NodeIdx> = neighbours.iter().collect();
+                for (idx, &i) in listed.iter().enumerate() {
+                    for &j in &listed[idx + 1..] {
+                        self.edge_toggled(i, j);
+                    }
+                }
+                // a symmetric difference walks the whole set, pairwise toggles only d bits
+                if listed.len() * 32 >= neighbours.capacity() {
+                    for &i in &listed {
+                        self.adjacent[i].symmetric_difference_with(&neighbours);
+                        self.adjacent[i].remove(i);
+                    }
+                } else {
+                    for (idx, &i) in listed.iter().enumerate() {
+                        for &j in &listed[idx + 1..] {
+                            self.flip_edge(i, j);
+                        }
+                    }
                 }
-                self.vop[i] = self.vop[i] * S_GATE;
             }
-            self.vop[node] = self.vop[node] * Vop::YD;
+
+            let (mut nb_gate, mut node_gate) = (Vop::IA, Vop::IA);
+            for _ in 0..times % 4 {
+                nb_gate = nb_gate * S_GATE;
+                node_gate = node_gate * Vop::YD;
+            }
+            for i in neighbours.iter() {
+                self.set_vop(i, self.vop[i] * nb_gate);
+            }
+            self.set_vop(node, self.vop[node] * node_gate);
+        }
+
+        /// Exchange the nodes `na` and `nb` of the graph, along with their VOPs.
+        fn relabel(&mut self, na: NodeIdx, nb: NodeIdx) {
+            self.assert_usable(na);
+            self.assert_usable(nb);
+            assert_ne!(na, nb, "Same qubit can't be swapped with itself");
+            let mut moved = self.adjacent[na].clone();
+            moved.symmetric_difference_with(&self.adjacent[nb]);
+            moved.remove(na);
+            moved.remove(nb);
+            for other in moved.iter() {
+                self.toggle_edge(na, other);
+                self.toggle_edge(nb, other);
+            }
+            let (vop_a, vop_b) = (self.vop[na], self.vop[nb]);
+            self.set_vop(na, vop_b);
+            self.set_vop(nb, vop_a);
         }
 
         fn toggle_edge(&mut self, na: NodeIdx, nb: NodeIdx) -> bool {
+            self.edge_toggled(na, nb);
+            self.flip_edge(na, nb)
+        }
+
+        /// Toggle the edge between `na` and `nb` in the neighbour sets only, returning
+        /// whether it was there.
+        fn flip_edge(&mut self, na: NodeIdx, nb: NodeIdx) -> bool {
             debug_assert_ne!(na, nb, "Can't toggle edge between qubit and itself");
             let a_has_b = self.adjacent[na].remove(nb);
             let b_has_a = self.adjacent[nb].remove(na);
@@ -434,19 +1211,28 @@ pub mod graphsim {
                 "A has B needs to be the same as B having A"
             );
 
-            if a_has_b {
-                true
-            } else {
+            if !a_has_b {
                 self.adjacent[na].insert(nb);
                 self.adjacent[nb].insert(na);
-                false
             }
+            a_has_b
         }
 
         fn delete_edge(&mut self, na: NodeIdx, nb: NodeIdx) {
             debug_assert_ne!(na, nb, "Can't delete edge between qubit and itself");
-            self.adjacent[na].remove(nb);
-            self.adjacent[nb].remove(na);
+            if self.adjacent[na].remove(nb) {
+                self.adjacent[nb].remove(na);
+                self.edge_toggled(na, nb);
+            }
+        }
+
+        /// Whether `node` has any neighbour besides `other`.
+        fn has_other_neighbours(&self, node: NodeIdx, other: NodeIdx) -> bool {
+            self.adjacent[node].len()
+                >= match self.adjacent[node].contains(other) {
+                    true => 2,
+                    false => 1,
+                }
         }
 
         fn find_deterministic(&self, node: NodeIdx) -> Option<Axis> {
@@ -456,113 +1242,272 @@ pub mod graphsim {
                 None
             }
         }
-    }
-
-    #[pymethods]
-    impl GraphSim {
-        /// Create a new simulator with `nodes` qubits, all initialized in the |0⟩ state.
-        #[new]
-        pub fn new(qubit_amount: usize) -> GraphSim {
-            GraphSim {
-                vop: repeat_n(Vop::YC, qubit_amount).collect(),
-                adjacent: repeat_n(BitSet::with_capacity(qubit_amount), qubit_amount).collect(),
-            }
-        }
 
-        /// Apply an X (Pauli-X) gate to the given qubit.
-        ///
-        /// `node` is the index of the qubit.
-        pub fn x(&mut self, qubit: NodeIdx) {
-            self.vop[qubit] = X_GATE * self.vop[qubit];
-        }
+        /// `peek_measure_set` with the axis of every qubit, `None` picking one.
+        pub(crate) fn peek_measure_axes(
+            &self,
+            axes: &BTreeMap<NodeIdx, Option<Axis>>,
+        ) -> BTreeMap<NodeIdx, Outcome> {
+            let mut changeset = self.clone();
+            axes.iter()
+                .map(|(&idx, &axis)| {
+                    let axis = axis
+                        .or_else(|| changeset.find_deterministic(idx))
+                        .unwrap_or_else(|| changeset.with_rng(|rng| rng.random()));
 
-        /// Apply a Y (Pauli-Y) gate to the given qubit.
-        ///
-        /// `node` is the index of the qubit.
-        pub fn y(&mut self, qubit: NodeIdx) {
-            self.vop[qubit] = Y_GATE * self.vop[qubit];
-        }
+                    let (result, _) = changeset.measure(idx, axis);
 
-        /// Apply a Z (Pauli-Z) gate to the given qubit.
-        ///
-        /// `node` is the index of the qubit.
-        pub fn z(&mut self, qubit: NodeIdx) {
-            self.vop[qubit] = Z_GATE * self.vop[qubit];
+                    (idx, Outcome { result, axis })
+                })
+                .collect()
         }
 
-        /// Apply an H (Hadamard) gate to the given qubit.
-        ///
-        /// `node` is the index of the qubit.
-        pub fn h(&mut self, qubit: NodeIdx) {
-            self.vop[qubit] = H_GATE * self.vop[qubit];
+        /// Trace out `qubits`, measuring each in Z and forgetting the outcome.
+        pub fn trace_out(&mut self, qubits: Vec<NodeIdx>) {
+            for qubit in qubits {
+                self.assert_usable(qubit);
+                self.settle(qubit);
+                self.discard(qubit, self.with_rng(|rng| rng.random()));
+            }
         }
 
-        /// Apply an S (phase) gate to the given qubit.
-        ///
-        /// `node` is the index of the qubit.
-        pub fn s(&mut self, qubit: NodeIdx) {
-            self.vop[qubit] = S_GATE * self.vop[qubit];
+        /// Return the set of qubits that are entangled with `qubit`.
+        pub fn get_entangled_group(&self, qubit: NodeIdx) -> HashSet<NodeIdx> {
+            let mut queue = VecDeque::new();
+            let mut part = HashSet::new();
+            queue.push_back(qubit);
+            part.insert(qubit);
+            while let Some(val) = queue.pop_front() {
+                for adj in &self.adjacent[val] {
+                    if !part.contains(&adj) {
+                        queue.push_back(adj);
+                        part.insert(adj);
+                    }
+                }
+            }
+
+            part
         }
 
-        /// Apply an S† (inverse phase) gate to the given qubit.
+        /// The qubit `qubit` refers to, which may count from the end.
         ///
-        /// `node` is the index of the qubit.
-        pub fn sdag(&mut self, qubit: NodeIdx) {
-            self.vop[qubit] = SDAG_GATE * self.vop[qubit];
+        /// Handles of qubits that were released or traced out since they were allocated
+        /// are rejected, and so are qubits that are traced out.
+        pub(crate) fn resolve(&self, qubit: QubitIndex) -> PyResult<NodeIdx> {
+            let idx = self.resolve_any(qubit)?;
+            self.check_usable(idx)?;
+            Ok(idx)
         }
 
-        /// Apply a controlled-Z (CZ) gate with `control` and `target` qubits.
-        pub fn cz(&mut self, control: NodeIdx, target: NodeIdx) {
-            // println!(
-            //     "performing cnot between {control} and {target}, with adjacent {:#?} and {:#?} respectively",
-            //     self.adjacent[control], self.adjacent[target]
-            // );
-            assert_ne!(control, target, "Same control and target not allowed");
-            let c_has_non_t = self.adjacent[control].len()
-                >= match self.adjacent[control].contains(target) {
-                    true => 2,
-                    false => 1,
-                };
-            let t_has_non_c = self.adjacent[target].len()
-                >= match self.adjacent[target].contains(control) {
-                    true => 2,
-                    false => 1,
-                };
+        /// `resolve`, but accepting qubits that are traced out, for queries about them.
+        fn resolve_any(&self, qubit: QubitIndex) -> PyResult<NodeIdx> {
+            let idx = qubit.resolve(self.vop.len())?;
+            match qubit {
+                QubitIndex::Handle(handle) if handle.generation != self.generations[idx] => Err(
+                    PyValueError::new_err(format!("{handle} was released and is no longer valid")),
+                ),
+                _ => Ok(idx),
+            }
+        }
 
-            if c_has_non_t {
-                self.remove_vop(control, target);
+        /// The two distinct qubits of a two-qubit gate, resolved like `resolve`.
+        pub(crate) fn resolve_pair(
+            &self,
+            first: QubitIndex,
+            second: QubitIndex,
+        ) -> PyResult<(NodeIdx, NodeIdx)> {
+            let (first, second) = (self.resolve(first)?, self.resolve(second)?);
+            match first == second {
+                true => Err(PyValueError::new_err(format!(
+                    "A two-qubit gate needs two different qubits, got {first} twice"
+                ))),
+                false => Ok((first, second)),
             }
-            if t_has_non_c {
-                self.remove_vop(target, control);
+        }
+
+        /// The qubits `qubits` refers to, checking them like `resolve`.
+        pub(crate) fn resolve_list(&self, qubits: &QubitList) -> PyResult<Vec<NodeIdx>> {
+            match qubits {
+                QubitList::Indices(indices) => {
+                    indices.iter().map(|&idx| self.resolve(idx)).collect()
+                }
+                QubitList::Slice(_) => {
+                    let qubits = qubits.resolve(self.vop.len())?;
+                    for &qubit in &qubits {
+                        self.check_usable(qubit)?;
+                    }
+                    Ok(qubits)
+                }
             }
-            if c_has_non_t && !self.vop[control].is_in_z() {
-                self.remove_vop(control, target);
+        }
+
+        /// Raise `ValueError` if `qubit` was traced out, where `assert_usable` would panic.
+        fn check_usable(&self, qubit: NodeIdx) -> PyResult<()> {
+            match self.traced.contains(qubit) {
+                true => Err(PyValueError::new_err(format!(
+                    "Qubit {qubit} was traced out and can't be used"
+                ))),
+                false => Ok(()),
             }
+        }
 
-            let cv = self.vop[control];
-            let tv = self.vop[target];
-            let had_edge = match self.adjacent[control].contains(target) {
-                true => 1,
-                false => 0,
+        /// Whether `condition` holds, i.e. an odd number of its records gave −1.
+        fn condition_holds(&self, condition: &Condition) -> PyResult<bool> {
+            let indices = match condition {
+                Condition::Record(index) => std::slice::from_ref(index),
+                Condition::Parity(indices) => indices.as_slice(),
             };
-            let val = CPHASE_TABLE[had_edge][cv as usize][tv as usize];
+            let mut parity = false;
+            for &index in indices {
+                let resolved = match index < 0 {
+                    true => self.register.len().checked_sub(index.unsigned_abs()),
+                    false => Some(index as usize),
+                };
+                let record = resolved
+                    .filter(|&idx| idx < self.register.len())
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Record {index} is out of range for {} records",
+                            self.register.len()
+                        ))
+                    })?;
+                parity ^= self.register[record].outcome == MeasurementResult::MinusOne;
+            }
+            Ok(parity)
+        }
 
-            if val.0 {
-                self.adjacent[control].insert(target);
-                self.adjacent[target].insert(control);
-            } else {
-                self.adjacent[control].remove(target);
-                self.adjacent[target].remove(control);
+        /// Apply an X (Pauli-X) gate to the given qubit.
+        pub fn x(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::X { qubit }, |sim| sim.apply_local(qubit, X_GATE));
+        }
+
+        /// Apply a Y (Pauli-Y) gate to the given qubit.
+        pub fn y(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Y { qubit }, |sim| sim.apply_local(qubit, Y_GATE));
+        }
+
+        /// Apply a Z (Pauli-Z) gate to the given qubit.
+        pub fn z(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Z { qubit }, |sim| sim.apply_local(qubit, Z_GATE));
+        }
+
+        /// Apply an H (Hadamard) gate to the given qubit.
+        pub fn h(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::H { qubit }, |sim| sim.apply_local(qubit, H_GATE));
+        }
+
+        /// Apply an S (phase) gate to the given qubit.
+        pub fn s(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::S { qubit }, |sim| sim.apply_local(qubit, S_GATE));
+        }
+
+        /// Apply an S† (inverse phase) gate to the given qubit.
+        pub fn sdag(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Sdag { qubit }, |sim| sim.apply_local(qubit, SDAG_GATE));
+        }
+
+        /// Apply a √X gate to the given qubit.
+        pub fn sx(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Sx { qubit }, |sim| sim.apply_local(qubit, SX_GATE));
+        }
+
+        /// Apply a √X† gate to the given qubit.
+        pub fn sxdag(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Sxdag { qubit }, |sim| {
+                sim.apply_local(qubit, SXDAG_GATE)
+            });
+        }
+
+        /// Apply a √Y gate to the given qubit.
+        pub fn sy(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Sy { qubit }, |sim| sim.apply_local(qubit, SY_GATE));
+        }
+
+        /// Apply a √Y† gate to the given qubit.
+        pub fn sydag(&mut self, qubit: NodeIdx) {
+            self.recorded(Op::Sydag { qubit }, |sim| {
+                sim.apply_local(qubit, SYDAG_GATE)
+            });
+        }
+
+        /// Apply the local Clifford `vop` to `qubit`, as its shortest gate sequence so
+        /// the gates are recorded.
+        pub(crate) fn apply_vop(&mut self, qubit: NodeIdx, vop: Vop) {
+            for name in vop.gate_names() {
+                crate::text::parse_op(name, &[qubit])
+                    .expect("VOPs decompose into named gates")
+                    .apply(self);
+            }
+        }
+
+        /// Rotate `qubit` about `axis` by `quarter_turns` times π/2.
+        ///
+        /// Up to a global phase this is the square root of the Pauli along `axis`, the
+        /// Pauli itself or the inverse square root for one, two and three quarter turns.
+        pub fn rotate(&mut self, qubit: NodeIdx, axis: Axis, quarter_turns: i64) {
+            match (axis, quarter_turns.rem_euclid(4)) {
+                (_, 0) => {}
+                (Axis::X, 1) => self.sx(qubit),
+                (Axis::X, 2) => self.x(qubit),
+                (Axis::X, _) => self.sxdag(qubit),
+                (Axis::Y, 1) => self.sy(qubit),
+                (Axis::Y, 2) => self.y(qubit),
+                (Axis::Y, _) => self.sydag(qubit),
+                (Axis::Z, 1) => self.s(qubit),
+                (Axis::Z, 2) => self.z(qubit),
+                (Axis::Z, _) => self.sdag(qubit),
+            }
+        }
+
+        /// Apply a controlled-Z (CZ) gate with `control` and `target` qubits.
+        pub fn cz(&mut self, control: NodeIdx, target: NodeIdx) {
+            self.recorded(Op::Cz { control, target }, |sim| {
+                sim.apply_cz(control, target)
+            });
+        }
+
+        /// Apply a CZ gate on every pair of qubits in `edges`, in order.
+        ///
+        /// When the VOPs of both qubits are in Z the CZ commutes with them and only
+        /// toggles their edge, so building a graph state from |+⟩ qubits skips the
+        /// CPHASE table entirely. Other pairs take the path of `cz`. Every CZ is recorded.
+        pub fn cz_many(&mut self, edges: &[(NodeIdx, NodeIdx)]) {
+            for &(control, target) in edges {
+                self.recorded(Op::Cz { control, target }, |sim| {
+                    if sim.vop[control].is_in_z() && sim.vop[target].is_in_z() {
+                        assert_ne!(control, target, "Same control and target not allowed");
+                        sim.assert_usable(control);
+                        sim.assert_usable(target);
+                        sim.toggle_edge(control, target);
+                    } else {
+                        sim.apply_cz(control, target);
+                    }
+                });
             }
-            self.vop[control] = val.1;
-            self.vop[target] = val.2;
         }
 
         /// Apply a controlled-X (CX) / CNOT gate with `control` and `target`.
         pub fn cx(&mut self, control: NodeIdx, target: NodeIdx) {
-            self.h(target);
-            self.cz(control, target);
-            self.h(target);
+            self.recorded(Op::Cx { control, target }, |sim| {
+                sim.h(target);
+                sim.cz(control, target);
+                sim.h(target);
+            });
+        }
+
+        /// Apply a CX from `control` to every qubit of `targets`, in order.
+        ///
+        /// Equal to a `cx` per target, but the VOP of `control` is only reduced while it
+        /// is outside Z, so the normalization of the control is mostly shared by all
+        /// targets instead of being redone by every gate. Every CX is recorded.
+        pub fn cx_fanout(&mut self, control: NodeIdx, targets: &[NodeIdx]) {
+            for &target in targets {
+                self.recorded(Op::Cx { control, target }, |sim| {
+                    sim.h(target);
+                    sim.apply_cz_with(control, target, true);
+                    sim.h(target);
+                });
+            }
         }
 
         /// Apply an X-controlled X gate (CX in the X basis).
@@ -591,9 +1536,20 @@ pub mod graphsim {
 
         /// Apply a controlled-Y (CY) gate with `control` and `target`.
         pub fn cy(&mut self, control: NodeIdx, target: NodeIdx) {
-            self.sdag(target);
-            self.cx(control, target);
-            self.s(target);
+            self.recorded(Op::Cy { control, target }, |sim| {
+                sim.sdag(target);
+                sim.cx(control, target);
+                sim.s(target);
+            });
+        }
+
+        /// Apply the controlled Pauli along `axis`, i.e. a CX, CY or CZ.
+        pub fn cpauli(&mut self, control: NodeIdx, target: NodeIdx, axis: Axis) {
+            match axis {
+                Axis::X => self.cx(control, target),
+                Axis::Y => self.cy(control, target),
+                Axis::Z => self.cz(control, target),
+            }
         }
 
         /// Apply an X-controlled Y gate (control in X basis).
@@ -608,225 +1564,2843 @@ pub mod graphsim {
             self.s(target);
         }
 
+        /// Apply a SWAP gate exchanging the states of `first` and `second`.
+        ///
+        /// The two nodes are relabelled instead of running three CXs: their VOPs are
+        /// exchanged and only the edges to neighbours of exactly one of them are moved,
+        /// so the cost is linear in their degrees and no VOP has to be reduced.
+        pub fn swap(&mut self, first: NodeIdx, second: NodeIdx) {
+            self.recorded(Op::Swap { first, second }, |sim| sim.relabel(first, second));
+        }
+
         /// Perform a projective measurement of `qubit` in the X basis.
         ///
         /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
         pub fn measure_x(&mut self, qubit: NodeIdx) -> MeasurementResult {
-            let (res, _) = self.measure(qubit, Axis::X);
-            res
+            self.measure_op(qubit, Axis::X)
         }
 
         /// Perform a projective measurement of `qubit` in the Y basis.
         ///
         /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
         pub fn measure_y(&mut self, qubit: NodeIdx) -> MeasurementResult {
-            let (res, _) = self.measure(qubit, Axis::Y);
-            res
+            self.measure_op(qubit, Axis::Y)
         }
 
         /// Perform a projective measurement of `qubit` in the Z basis.
         ///
         /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
         pub fn measure_z(&mut self, qubit: NodeIdx) -> MeasurementResult {
-            let (res, _) = self.measure(qubit, Axis::Z);
-            res
+            self.measure_op(qubit, Axis::Z)
         }
+    }
 
-        /// Return the set of qubits that are entangled with `qubit`.
+    /// An outcome to postselect a qubit on from Python: a `MeasurementResult` of a Z
+    /// measurement, or an `(axis, result)` tuple.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, FromPyObject)]
+    pub enum Postselection {
+        Along(Axis, MeasurementResult),
+        Z(MeasurementResult),
+    }
+
+    /// A condition on the classical register from Python: the index of a record, or a list
+    /// of them to take the parity of. Negative indices count from the latest record.
+    #[derive(Clone, Debug, PartialEq, Eq, FromPyObject)]
+    pub enum Condition {
+        Record(isize),
+        Parity(Vec<isize>),
+    }
+
+    /// One of the 24 local Cliffords from Python: its index, its canonical label like
+    /// `"YC"` or a product of gates like `"HS"`.
+    #[derive(Clone, Debug, PartialEq, Eq, FromPyObject)]
+    pub enum LocalClifford {
+        Index(usize),
+        Name(String),
+    }
+
+    impl LocalClifford {
+        /// The VOP this refers to.
         ///
-        /// This follows adjacency in the underlying graph.
-        pub fn get_entangled_group(&self, qubit: NodeIdx) -> HashSet<NodeIdx> {
-            let mut queue = VecDeque::new();
-            let mut part = HashSet::new();
-            queue.push_back(qubit);
-            part.insert(qubit);
-            while let Some(val) = queue.pop_front() {
-                for adj in &self.adjacent[val] {
-                    if !part.contains(&adj) {
-                        queue.push_back(adj);
-                        part.insert(adj);
-                    }
+        /// A product of gates is read as a matrix product, so the rightmost gate acts
+        /// first. Its gates are `I`, `X`, `Y`, `Z`, `H` and `S`, where `S†` or `S'` is the
+        /// inverse of S.
+        fn vop(&self) -> PyResult<Vop> {
+            let name = match self {
+                LocalClifford::Index(idx) if *idx < crate::SYMMETRIES => {
+                    return Ok(Vop::from_index(*idx));
+                }
+                LocalClifford::Index(idx) => {
+                    return Err(PyValueError::new_err(format!(
+                        "Local Clifford {idx} is out of range for {} Cliffords",
+                        crate::SYMMETRIES
+                    )));
                 }
+                LocalClifford::Name(name) => name,
+            };
+            if let Some(vop) = (0..crate::SYMMETRIES)
+                .map(Vop::from_index)
+                .find(|vop| format!("{vop:?}") == *name)
+            {
+                return Ok(vop);
+            }
+            let mut product = Vop::IA;
+            let mut chars = name.chars().peekable();
+            while let Some(gate) = chars.next() {
+                let inverse = chars.next_if(|&c| c == '†' || c == '\'').is_some();
+                let vop = match (gate.to_ascii_uppercase(), inverse) {
+                    ('I', _) => Vop::IA,
+                    ('X', _) => X_GATE,
+                    ('Y', _) => Y_GATE,
+                    ('Z', _) => Z_GATE,
+                    ('H', _) => H_GATE,
+                    ('S', false) => S_GATE,
+                    ('S', true) => SDAG_GATE,
+                    _ => {
+                        return Err(PyValueError::new_err(format!(
+                            "`{name}` is not a local Clifford label or a product of I, X, Y, \
+                             Z, H and S gates"
+                        )));
+                    }
+                };
+                product = product * vop;
             }
+            Ok(product)
+        }
+    }
 
-            part
+    /// Number of quarter turns in `angle`, which has to be a multiple of π/2.
+    fn quarter_turns(angle: f64) -> PyResult<i64> {
+        let turns = angle / std::f64::consts::FRAC_PI_2;
+        match (turns - turns.round()).abs() < 1e-9 {
+            true => Ok(turns.round() as i64),
+            false => Err(PyValueError::new_err(format!(
+                "Angle {angle} is not a multiple of π/2, so the rotation isn't Clifford"
+            ))),
         }
+    }
 
-        /// Simulate measurements on a set of `qubits` without modifying the real state.
+    #[pymethods]
+    impl GraphSim {
+        /// Create a new simulator with `nodes` qubits, all initialized in the |0⟩ state.
         ///
-        /// Returns a map from qubit index to `Outcome` (result and axis used).
-        pub fn peek_measure_set(&self, qubits: HashSet<NodeIdx>) -> HashMap<NodeIdx, Outcome> {
-            let mut changeset = self.clone();
-            qubits
-                .iter()
-                .map(|&idx| {
-                    let axis = if let Some(deterministic) = changeset.find_deterministic(idx) {
-                        deterministic
-                    } else {
-                        rand::rng().random()
-                    };
+        /// Random measurement outcomes come from the thread-local generator, or with a
+        /// `seed` from a generator of this simulator seeded with it, so runs repeat. After
+        /// the module-level `seed`, that generator is seeded from there by default. Copies
+        /// draw from the generator of the simulator they were copied from.
+        #[new]
+        #[pyo3(signature = (qubit_amount, seed = None))]
+        pub fn py_new(qubit_amount: usize, seed: Option<u64>) -> GraphSim {
+            let mut sim = GraphSim::new(qubit_amount);
+            if let Some(seed) = seed {
+                sim.rng = Some(crate::rng::seeded(seed));
+            }
+            sim
+        }
 
-                    let (result, _) = changeset.measure(idx, axis);
+        /// Return an independent copy of the simulator.
+        ///
+        /// The graph storage is shared until either of them changes it, so copying is
+        /// cheap. Like any copy, it draws from the same seeded generator and calls the
+        /// same callbacks.
+        pub fn copy(&self) -> GraphSim {
+            self.clone()
+        }
 
-                    (idx, Outcome { result, axis })
-                })
-                .collect()
+        /// Support for `copy.copy`, the same as `copy`.
+        pub fn __copy__(&self) -> GraphSim {
+            self.clone()
         }
-    }
 
-    fn find_zeta(vop: Vop, axis: Axis) -> Zeta {
-        let rvop = (vop as usize) & 0b11;
+        /// Support for `copy.deepcopy`, the same as `copy` as no state is shared.
+        pub fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> GraphSim {
+            self.clone()
+        }
 
-        match (
-            (rvop == 0 || rvop == (axis as usize + 1)),
-            vop >= Vop::IB && vop < Vop::IE,
-        ) {
-            (true, true) | (false, false) => Zeta::Two,
-            (true, false) | (false, true) => Zeta::Zero,
+        /// Create the state stabilized by the signed Pauli strings `stabilizers`, like
+        /// `["+XZZ", "+ZXI", "+ZIX"]`, with a letter per qubit.
+        ///
+        /// Raises `ValueError` unless there is a generator per qubit and they pairwise
+        /// commute and are independent, so they fix a single state.
+        #[staticmethod]
+        pub fn from_stabilizers(stabilizers: Vec<String>) -> PyResult<GraphSim> {
+            let rows = crate::stabilizer::parse_stabilizers(&stabilizers)?;
+            Ok(crate::stabilizer::from_stabilizers(&rows))
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        #[test]
-        fn test_single_qubit_gates() {
-            let mut qec = GraphSim::new(1);
-            assert_eq!(qec.vop[0], Vop::YC);
-            qec.h(0);
-            assert_eq!(qec.vop[0], Vop::IA);
-            qec.s(0);
-            assert_eq!(qec.vop[0], Vop::YB);
-            qec.s(0);
-            assert_eq!(qec.vop[0], Vop::ZA);
-            qec.z(0);
-            assert_eq!(qec.vop[0], Vop::IA);
-            qec.sdag(0);
-            assert_eq!(qec.vop[0], Vop::XB);
-            qec.z(0);
-            assert_eq!(qec.vop[0], Vop::YB);
+        /// Apply an X (Pauli-X) gate to the given qubit.
+        ///
+        /// `node` is the index of the qubit.
+        #[pyo3(name = "x")]
+        pub fn py_x(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.x(qubit);
+            Ok(())
         }
 
-        #[test]
-        fn test_measure_single_z_det() {
-            let mut qec = GraphSim::new(1);
+        /// Apply a Y (Pauli-Y) gate to the given qubit.
+        ///
+        /// `node` is the index of the qubit.
+        #[pyo3(name = "y")]
+        pub fn py_y(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.y(qubit);
+            Ok(())
+        }
 
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::PlusOne);
-            assert_eq!(det, true);
+        /// Apply a Z (Pauli-Z) gate to the given qubit.
+        ///
+        /// `node` is the index of the qubit.
+        #[pyo3(name = "z")]
+        pub fn py_z(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.z(qubit);
+            Ok(())
+        }
 
-            qec.s(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::PlusOne);
-            assert_eq!(det, true);
+        /// Apply an H (Hadamard) gate to the given qubit.
+        ///
+        /// `node` is the index of the qubit.
+        #[pyo3(name = "h")]
+        pub fn py_h(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.h(qubit);
+            Ok(())
+        }
 
-            qec.s(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::PlusOne);
-            assert_eq!(det, true);
+        /// Apply an S (phase) gate to the given qubit.
+        ///
+        /// `node` is the index of the qubit.
+        #[pyo3(name = "s")]
+        pub fn py_s(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.s(qubit);
+            Ok(())
+        }
 
-            qec.s(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::PlusOne);
-            assert_eq!(det, true);
+        /// Apply an S† (inverse phase) gate to the given qubit.
+        ///
+        /// `node` is the index of the qubit.
+        #[pyo3(name = "sdag")]
+        pub fn py_sdag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.sdag(qubit);
+            Ok(())
+        }
 
-            qec.x(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::MinusOne);
-            assert_eq!(det, true);
+        /// Apply a √X gate to the given qubit.
+        #[pyo3(name = "sx")]
+        pub fn py_sx(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.sx(qubit);
+            Ok(())
+        }
 
-            qec.s(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::MinusOne);
-            assert_eq!(det, true);
+        /// Apply a √X† gate to the given qubit.
+        #[pyo3(name = "sxdag")]
+        pub fn py_sxdag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.sxdag(qubit);
+            Ok(())
+        }
 
-            qec.s(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::MinusOne);
-            assert_eq!(det, true);
+        /// Apply a √Y gate to the given qubit.
+        #[pyo3(name = "sy")]
+        pub fn py_sy(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.sy(qubit);
+            Ok(())
+        }
 
-            qec.s(0);
-            let (outcome, det) = qec.measure(0, Axis::Z);
-            assert_eq!(outcome, MeasurementResult::MinusOne);
-            assert_eq!(det, true);
+        /// Apply a √Y† gate to the given qubit.
+        #[pyo3(name = "sydag")]
+        pub fn py_sydag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            self.sydag(qubit);
+            Ok(())
         }
 
-        #[test]
-        fn test_cnot_measure_x() {
-            let mut qec = GraphSim::new(2);
+        /// Apply any of the 24 local Cliffords to `qubit`.
+        ///
+        /// `clifford` is the index of the Clifford, its canonical label from `IA` to `ZF`,
+        /// or a product of the gates `I`, `X`, `Y`, `Z`, `H`, `S` and `S†` read as matrices,
+        /// so `"HS"` applies S and then H. Raises `ValueError` for anything else.
+        pub fn apply_local_clifford(
+            &mut self,
+            qubit: QubitIndex,
+            clifford: LocalClifford,
+        ) -> PyResult<()> {
+            let (qubit, vop) = (self.resolve(qubit)?, clifford.vop()?);
+            self.apply_vop(qubit, vop);
+            Ok(())
+        }
+
+        /// Apply the Clifford with tableau `tableau` to `qubits`.
+        ///
+        /// For n qubits `tableau` has 2n rows, the images of X on every qubit and then of
+        /// Z, each with n X bits followed by n Z bits, e.g. a boolean numpy array. `signs`
+        /// says which images are negative. The Clifford is decomposed into H, S, S†, CX
+        /// and Pauli gates, which are applied and recorded. Raises `ValueError` unless
+        /// the rows commute like the Paulis they are the images of.
+        #[pyo3(signature = (tableau, qubits, signs = None))]
+        pub fn apply_clifford(
+            &mut self,
+            tableau: Vec<Vec<bool>>,
+            qubits: QubitList,
+            signs: Option<Vec<bool>>,
+        ) -> PyResult<()> {
+            let qubits = self.resolve_list(&qubits)?;
+            let rows = crate::tableau::parse_tableau(&tableau, signs.as_deref())?;
+            if rows.len() != 2 * qubits.len() {
+                return Err(PyValueError::new_err(format!(
+                    "A tableau of {} rows doesn't act on {} qubits",
+                    rows.len(),
+                    qubits.len()
+                )));
+            }
+            if qubits.iter().collect::<HashSet<_>>().len() != qubits.len() {
+                return Err(PyValueError::new_err("Qubits must be distinct"));
+            }
+            for op in crate::tableau::synthesize(&rows) {
+                let local = op.qubits();
+                let second = local.get(1).map_or(0, |&qubit| qubits[qubit]);
+                Op::from_code(op.code(), qubits[local[0]], second)
+                    .expect("Codes of existing operations decode")
+                    .apply(self);
+            }
+            Ok(())
+        }
+
+        /// Apply the single-qubit gate with unitary `matrix` to `qubit`.
+        ///
+        /// `matrix` is a 2×2 matrix of numbers, e.g. a complex numpy array, which has to be
+        /// one of the 24 local Cliffords up to a global phase. Raises `ValueError` if it
+        /// isn't, as the gate could not be simulated.
+        pub fn apply_unitary(
+            &mut self,
+            qubit: QubitIndex,
+            matrix: &Bound<'_, PyAny>,
+        ) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            let matrix = crate::ket::extract_matrix(matrix)?;
+            let vop = crate::ket::identify_clifford(&matrix).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Matrix {matrix:?} is not a Clifford up to a global phase"
+                ))
+            })?;
+            self.apply_vop(qubit, vop);
+            Ok(())
+        }
+
+        /// Apply an X rotation by `angle`, which has to be a multiple of π/2.
+        pub fn rx(&mut self, qubit: QubitIndex, angle: f64) -> PyResult<()> {
+            let (qubit, turns) = (self.resolve(qubit)?, quarter_turns(angle)?);
+            self.rotate(qubit, Axis::X, turns);
+            Ok(())
+        }
+
+        /// Apply a Y rotation by `angle`, which has to be a multiple of π/2.
+        pub fn ry(&mut self, qubit: QubitIndex, angle: f64) -> PyResult<()> {
+            let (qubit, turns) = (self.resolve(qubit)?, quarter_turns(angle)?);
+            self.rotate(qubit, Axis::Y, turns);
+            Ok(())
+        }
+
+        /// Apply a Z rotation by `angle`, which has to be a multiple of π/2.
+        ///
+        /// Angles of π/2, π and −π/2 give S, Z and S† up to a global phase.
+        pub fn rz(&mut self, qubit: QubitIndex, angle: f64) -> PyResult<()> {
+            let (qubit, turns) = (self.resolve(qubit)?, quarter_turns(angle)?);
+            self.rotate(qubit, Axis::Z, turns);
+            Ok(())
+        }
+
+        /// Apply a controlled-Z (CZ) gate with `control` and `target` qubits.
+        #[pyo3(name = "cz")]
+        pub fn py_cz(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.cz(control, target);
+            Ok(())
+        }
+
+        /// Apply a CZ gate on every `(control, target)` pair of `edges`, e.g. to build a
+        /// graph state from |+⟩ qubits in one call.
+        #[pyo3(name = "cz_many")]
+        pub fn py_cz_many(&mut self, edges: Vec<(QubitIndex, QubitIndex)>) -> PyResult<()> {
+            let edges = edges
+                .into_iter()
+                .map(|(control, target)| self.resolve_pair(control, target))
+                .collect::<PyResult<Vec<_>>>()?;
+            self.cz_many(&edges);
+            Ok(())
+        }
+
+        /// Apply a controlled-X (CX) / CNOT gate with `control` and `target`.
+        #[pyo3(name = "cx")]
+        pub fn py_cx(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.cx(control, target);
+            Ok(())
+        }
+
+        /// Apply a CX from `control` to every qubit of `targets`, sharing the
+        /// normalization of the control between them.
+        #[pyo3(name = "cx_fanout")]
+        pub fn py_cx_fanout(&mut self, control: QubitIndex, targets: QubitList) -> PyResult<()> {
+            let control = self.resolve(control)?;
+            let targets = self.resolve_list(&targets)?;
+            if targets.contains(&control) {
+                return Err(PyValueError::new_err(format!(
+                    "Control {control} can't also be a target"
+                )));
+            }
+            self.cx_fanout(control, &targets);
+            Ok(())
+        }
+
+        /// Apply an X-controlled X gate (CX in the X basis).
+        #[pyo3(name = "xcx")]
+        pub fn py_xcx(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.xcx(control, target);
+            Ok(())
+        }
+
+        /// Apply a Y-controlled X gate (control qubit in the Y basis).
+        #[pyo3(name = "ycx")]
+        pub fn py_ycx(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.ycx(control, target);
+            Ok(())
+        }
+
+        /// Apply an X-controlled Z gate (target in X basis).
+        #[pyo3(name = "xcz")]
+        pub fn py_xcz(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.xcz(control, target);
+            Ok(())
+        }
+
+        /// Apply a Y-controlled Z gate (target in Y basis).
+        #[pyo3(name = "ycz")]
+        pub fn py_ycz(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.ycz(control, target);
+            Ok(())
+        }
+
+        /// Apply a controlled-Y (CY) gate with `control` and `target`.
+        #[pyo3(name = "cy")]
+        pub fn py_cy(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.cy(control, target);
+            Ok(())
+        }
+
+        /// Apply the controlled Pauli along `axis` from `control` to `target`, so a CX,
+        /// CY or CZ can be picked by data instead of by method name.
+        #[pyo3(name = "cpauli")]
+        pub fn py_cpauli(
+            &mut self,
+            control: QubitIndex,
+            target: QubitIndex,
+            axis: Axis,
+        ) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.cpauli(control, target, axis);
+            Ok(())
+        }
+
+        /// Apply an X-controlled Y gate (control in X basis).
+        #[pyo3(name = "xcy")]
+        pub fn py_xcy(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.xcy(control, target);
+            Ok(())
+        }
+
+        /// Apply a Y-controlled Y gate (both in Y basis).
+        #[pyo3(name = "ycy")]
+        pub fn py_ycy(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+            let (control, target) = self.resolve_pair(control, target)?;
+            self.ycy(control, target);
+            Ok(())
+        }
+
+        /// Apply a SWAP gate exchanging the states of `first` and `second`.
+        #[pyo3(name = "swap")]
+        pub fn py_swap(&mut self, first: QubitIndex, second: QubitIndex) -> PyResult<()> {
+            let (first, second) = self.resolve_pair(first, second)?;
+            self.swap(first, second);
+            Ok(())
+        }
+
+        /// Perform a projective measurement of `qubit` in the X basis.
+        ///
+        /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
+        #[pyo3(name = "measure_x")]
+        pub fn py_measure_x(&mut self, qubit: QubitIndex) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            Ok(self.measure_x(qubit))
+        }
+
+        /// Perform a projective measurement of `qubit` in the Y basis.
+        ///
+        /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
+        #[pyo3(name = "measure_y")]
+        pub fn py_measure_y(&mut self, qubit: QubitIndex) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            Ok(self.measure_y(qubit))
+        }
+
+        /// Perform a projective measurement of `qubit` in the Z basis.
+        ///
+        /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
+        #[pyo3(name = "measure_z")]
+        pub fn py_measure_z(&mut self, qubit: QubitIndex) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            Ok(self.measure_z(qubit))
+        }
+
+        /// Perform a projective measurement of `qubit` along `axis`, an `Axis`.
+        ///
+        /// The same as `measure_x`, `measure_y` or `measure_z` for that axis.
+        #[pyo3(name = "measure")]
+        pub fn py_measure(&mut self, qubit: QubitIndex, axis: Axis) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            Ok(self.measure_op(qubit, axis))
+        }
+
+        /// Measure every qubit of `qubits` along `axis`, returning `{qubit: result}`.
+        ///
+        /// This is the same as measuring them one by one, but in a single call and in an
+        /// order that saves work: qubits with fewer neighbours go first, so their edges are
+        /// gone before the busier qubits are measured, whose neighbourhoods X and Y
+        /// measurements complement. Ties go to the lower qubit.
+        pub fn measure_set(
+            &mut self,
+            qubits: QubitList,
+            axis: Axis,
+        ) -> PyResult<BTreeMap<NodeIdx, MeasurementResult>> {
+            let mut qubits = self.resolve_list(&qubits)?;
+            qubits.sort_unstable();
+            qubits.dedup();
+            qubits.sort_by_key(|&qubit| self.adjacent[qubit].len());
+            Ok(qubits
+                .into_iter()
+                .map(|qubit| (qubit, self.measure_op(qubit, axis)))
+                .collect())
+        }
+
+        /// Measure `qubit` in the X basis, projecting onto the outcome `result`.
+        ///
+        /// For postselection and for replaying recorded shots. Raises `ValueError` without
+        /// changing the state if `result` has probability 0.
+        pub fn measure_x_forced(
+            &mut self,
+            qubit: QubitIndex,
+            result: MeasurementResult,
+        ) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            self.measure_forced(qubit, Axis::X, result)
+        }
+
+        /// Measure `qubit` in the Y basis, projecting onto the outcome `result`.
+        ///
+        /// Raises `ValueError` without changing the state if `result` has probability 0.
+        pub fn measure_y_forced(
+            &mut self,
+            qubit: QubitIndex,
+            result: MeasurementResult,
+        ) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            self.measure_forced(qubit, Axis::Y, result)
+        }
+
+        /// Measure `qubit` in the Z basis, projecting onto the outcome `result`.
+        ///
+        /// Raises `ValueError` without changing the state if `result` has probability 0.
+        pub fn measure_z_forced(
+            &mut self,
+            qubit: QubitIndex,
+            result: MeasurementResult,
+        ) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            self.measure_forced(qubit, Axis::Z, result)
+        }
+
+        /// Reset `qubit` to the single-qubit state `state`, labelled like for `local_state`.
+        ///
+        /// The qubit is measured in Z and the outcome forgotten, like for `trace_out`, so
+        /// the other qubits are left in a state sampled from their reduced state and keep
+        /// no correlations with it. Then it is prepared in `state`, `"0"` by default, and
+        /// unlike after `trace_out` it stays usable. An ancilla stays allocated, so it
+        /// still has to be returned with `free_ancilla`. Raises `ValueError` for an
+        /// unknown label.
+        ///
+        /// Recordings and listeners see a `reset` operation followed by the gates
+        /// preparing `state`.
+        #[pyo3(signature = (qubit, state = "0"))]
+        pub fn reset(&mut self, qubit: QubitIndex, state: &str) -> PyResult<()> {
+            let gates: &[fn(&mut GraphSim, NodeIdx)] = match state {
+                "0" => &[],
+                "1" => &[GraphSim::x],
+                "+" => &[GraphSim::h],
+                "-" => &[GraphSim::x, GraphSim::h],
+                "+i" => &[GraphSim::h, GraphSim::s],
+                "-i" => &[GraphSim::h, GraphSim::sdag],
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown state {state:?}, expected 0, 1, +, -, +i or -i"
+                    )));
+                }
+            };
+            let qubit = self.resolve(qubit)?;
+            self.reset_op(qubit);
+            for gate in gates {
+                gate(self, qubit);
+            }
+            Ok(())
+        }
+
+        /// Measure `qubit` in the Z basis and return it to |0⟩, to reuse it in place.
+        ///
+        /// Returns the outcome. The measurement disentangles the qubit, and an X is applied
+        /// when it came out in |1⟩, which recordings and listeners see like any gate. This
+        /// keeps the register from growing over long syndrome extraction loops.
+        pub fn measure_reset(&mut self, qubit: QubitIndex) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve(qubit)?;
+            let outcome = self.measure_z(qubit);
+            if self.deterministic_outcome(qubit, Axis::Z) == Some(MeasurementResult::MinusOne) {
+                self.x(qubit);
+            }
+            Ok(outcome)
+        }
+
+        /// Measure the Pauli product `pauli` through an ancilla, like a device would.
+        ///
+        /// `pauli` is given like for `Circuit.measure_pauli`, which describes the gates.
+        /// The ancilla comes from `alloc_ancilla` and is returned with `free_ancilla`
+        /// afterwards, which resets it. Returns the eigenvalue of `pauli` that was found.
+        #[pyo3(signature = (pauli, qubits = None))]
+        pub fn measure_pauli(
+            &mut self,
+            pauli: &str,
+            qubits: Option<QubitList>,
+        ) -> PyResult<MeasurementResult> {
+            let qubits = qubits
+                .map(|qubits| self.resolve_list(&qubits))
+                .transpose()?;
+            let product = crate::gadget::parse_pauli(pauli, qubits, self.vop.len())?;
+            for &(qubit, _) in &product.paulis {
+                self.check_usable(qubit)?;
+            }
+            let ancilla = self.alloc_ancilla();
+            let mut outcome = None;
+            for op in crate::gadget::gadget(&product, ancilla) {
+                outcome = op.apply(self);
+            }
+            self.free_ancilla(ancilla.into())?;
+            Ok(outcome.expect("The gadget ends in a measurement"))
+        }
+
+        /// Call `callback(qubit, axis, outcome, deterministic)` after every measurement.
+        ///
+        /// Covers `measure_x`/`measure_y`/`measure_z` and the measurements of circuits
+        /// and op arrays, but not the scratch measurements of peeking and sampling.
+        /// Returning a `MeasurementResult` replaces the outcome passed on to the caller
+        /// and later callbacks, e.g. to model readout errors or adaptive decisions, while
+        /// the state keeps the measured outcome; any other return keeps it. Callbacks run
+        /// in registration order, and exceptions they raise are reported as unraisable
+        /// instead of aborting the measurement. Copies of the simulator share callbacks.
+        pub fn on_measure(&mut self, callback: Py<PyAny>) {
+            self.measure_callbacks.push(Arc::new(callback));
+        }
+
+        /// Remove every callback registered with `on_measure`.
+        pub fn clear_measure_callbacks(&mut self) {
+            self.measure_callbacks.clear();
+        }
+
+        /// Call `listener(op, delta)` after every operation, with the `Op` applied and the
+        /// `GraphDelta` of edges and VOPs it changed.
+        ///
+        /// Operations are observed like `start_recording` logs them, so gates without an
+        /// `Op` show up as the operations they are built from. Exceptions raised by a
+        /// listener are reported as unraisable. Copies of the simulator share listeners.
+        pub fn on_operation(&mut self, listener: Py<PyAny>) {
+            self.op_listeners.push(Arc::new(listener));
+        }
+
+        /// Remove every listener registered with `on_operation`.
+        pub fn clear_operation_listeners(&mut self) {
+            self.op_listeners.clear();
+        }
+
+        /// Start logging the operations applied to this simulator, clearing any earlier log.
+        ///
+        /// Every gate and measurement that has an `Op` is logged as the call that was
+        /// made, also when applied through `run` or `apply_ops`. Other gates, like `xcx`,
+        /// are logged as the operations they are built from.
+        pub fn start_recording(&mut self) {
+            self.recording = Some(CowVec::default());
+            self.history.clear();
+        }
+
+        /// Stop logging operations, returning the ones logged as a `Circuit`.
+        ///
+        /// The measurement history is cleared as well.
+        pub fn stop_recording(&mut self) -> Option<Circuit> {
+            let circuit = self.recorded_circuit();
+            self.recording = None;
+            self.history.clear();
+            circuit
+        }
+
+        /// Return the measurements of `qubit` logged since `start_recording`, in order.
+        ///
+        /// Every `MeasurementEvent` has the index of the measurement in
+        /// `recorded_circuit`, its axis and the outcome. Raises `ValueError` when not
+        /// recording.
+        pub fn history(&self, qubit: QubitIndex) -> PyResult<Vec<MeasurementEvent>> {
+            let qubit = self.resolve_any(qubit)?;
+            if self.recording.is_none() {
+                return Err(PyValueError::new_err(
+                    "Measurements aren't recorded, call start_recording first",
+                ));
+            }
+            Ok(self.history.get(&qubit).cloned().unwrap_or_default())
+        }
+
+        /// Return the outcome of the last measurement of `qubit` logged since
+        /// `start_recording`, or `None` if it wasn't measured. Raises `ValueError` when
+        /// not recording.
+        pub fn last_outcome(&self, qubit: QubitIndex) -> PyResult<Option<MeasurementResult>> {
+            Ok(self.history(qubit)?.last().map(|event| event.outcome))
+        }
+
+        /// Return the classical register, with a `MeasurementRecord` for every measurement
+        /// in order.
+        ///
+        /// Every `measure_*` call, and every measurement of a `run` circuit, appends its
+        /// qubit, axis and outcome, with its index in the register. Copies start with the
+        /// register of the original. Peeks and samples don't measure the real state, so
+        /// they aren't in it.
+        pub fn records(&self) -> Vec<MeasurementRecord> {
+            self.register.to_vec()
+        }
+
+        /// Apply the gate or measurement `name` of the text format to `qubits` if
+        /// `condition` holds, returning whether it did.
+        ///
+        /// `condition` is the index of a record in the classical register, or a list of
+        /// them, and holds when an odd number of those outcomes was −1, so feed-forward
+        /// corrections like the ones of teleportation stay on the Rust side. Negative
+        /// indices count back from the latest record.
+        pub fn apply_if(
+            &mut self,
+            condition: Condition,
+            name: &str,
+            qubits: Vec<QubitIndex>,
+        ) -> PyResult<bool> {
+            let qubits = qubits
+                .into_iter()
+                .map(|qubit| self.resolve(qubit))
+                .collect::<PyResult<Vec<NodeIdx>>>()?;
+            if let [first, second] = qubits[..]
+                && first == second
+            {
+                return Err(PyValueError::new_err(format!(
+                    "A two-qubit gate needs two different qubits, got {first} twice"
+                )));
+            }
+            let op =
+                crate::text::parse_op(&name.to_ascii_lowercase(), &qubits).ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Unknown operation `{name}` on {} qubits",
+                        qubits.len()
+                    ))
+                })?;
+            let holds = self.condition_holds(&condition)?;
+            if holds {
+                op.apply(self);
+            }
+            Ok(holds)
+        }
+
+        /// Apply a CX from `control` to `target` if `condition` holds, like `apply_if`.
+        pub fn cx_if(
+            &mut self,
+            condition: Condition,
+            control: QubitIndex,
+            target: QubitIndex,
+        ) -> PyResult<bool> {
+            self.apply_if(condition, "cx", vec![control, target])
+        }
+
+        /// Apply the single-qubit gate `name` to `qubit` if the XOR of the outcomes of
+        /// `records` is −1, returning whether it did.
+        ///
+        /// This is `apply_if` with a parity condition, meant for the byproduct corrections
+        /// of measurement-based computation, e.g. `apply_if_parity([0, 3, 5], "Z", qubit)`.
+        pub fn apply_if_parity(
+            &mut self,
+            records: Vec<isize>,
+            name: &str,
+            qubit: QubitIndex,
+        ) -> PyResult<bool> {
+            self.apply_if(Condition::Parity(records), name, vec![qubit])
+        }
+
+        /// Empty the classical register, so indices start from 0 again.
+        pub fn clear_records(&mut self) {
+            self.register = CowVec::default();
+        }
+
+        /// The operations logged since `start_recording` as a `Circuit`, or `None` when
+        /// not recording.
+        pub fn recorded_circuit(&self) -> Option<Circuit> {
+            let log = self.recording.as_ref()?;
+            let mut circuit = Circuit::new(self.vop.len());
+            for &op in log.iter() {
+                circuit.append(op);
+            }
+            Some(circuit)
+        }
+
+        /// List the operations logged since `start_recording`, one aligned line each.
+        ///
+        /// With `diagram` an ASCII diagram is returned instead, with one row per
+        /// operation and one column per qubit. Raises `ValueError` when not recording.
+        #[pyo3(signature = (diagram = false))]
+        pub fn format_circuit(&self, diagram: bool) -> PyResult<String> {
+            let Some(log) = self.recording.as_ref() else {
+                return Err(PyValueError::new_err(
+                    "Operations aren't recorded, call start_recording first",
+                ));
+            };
+            let ops = log.to_vec();
+            Ok(match diagram {
+                true => crate::printer::diagram(&ops, self.vop.len()),
+                false => crate::printer::listing(&ops),
+            })
+        }
+
+        /// Check `circuit` against this simulator without running it.
+        ///
+        /// Returns a `Problem` for every operation on a qubit that doesn't exist or was
+        /// traced out, every two-qubit gate on a single qubit, and every detector or
+        /// observable using a measurement the circuit doesn't make. Running a circuit
+        /// without problems can't fail.
+        pub fn validate(&self, circuit: &Circuit) -> Vec<crate::validate::Problem> {
+            crate::validate::validate(self, circuit)
+        }
+
+        /// Execute every operation of `circuit` on this simulator.
+        ///
+        /// Returns the measurement outcomes in the order they occur in the circuit. Raises
+        /// `ValueError` with the problems `validate` finds, before running anything.
+        pub fn run(&mut self, circuit: &Circuit) -> PyResult<Vec<MeasurementResult>> {
+            crate::validate::check(self, circuit)?;
+            Ok(circuit.execute(self))
+        }
+
+        /// Execute `circuit` like `run`, also tracking how large the graph gets.
+        ///
+        /// The edge count, maximum degree and number of connected components are sampled
+        /// before the first operation, after every `interval` operations and after the
+        /// last one. Returns the measurement outcomes and the `ResourceProfile`. Like `run`
+        /// it raises `ValueError` with the problems `validate` finds, before running
+        /// anything.
+        #[pyo3(signature = (circuit, interval = 1))]
+        pub fn run_profiled(
+            &mut self,
+            py: Python<'_>,
+            circuit: &Circuit,
+            interval: usize,
+        ) -> PyResult<(Vec<MeasurementResult>, crate::profile::ResourceProfile)> {
+            py.detach(|| crate::profile::run_profiled(self, circuit, interval))
+        }
+
+        /// Execute the `Op`s yielded by the Python iterable `ops`, pulling `chunk_size` of
+        /// them at a time.
+        ///
+        /// Only one chunk is held in memory, and it is executed with the GIL released,
+        /// so arbitrarily long generated circuits can be streamed through. Returns the
+        /// measurement outcomes in the order they occur. Every chunk is checked like
+        /// `validate` before it runs, raising `ValueError` for the first chunk with a
+        /// problem, after the chunks before it were applied.
+        #[pyo3(signature = (ops, chunk_size = 4096))]
+        pub fn run_stream(
+            &mut self,
+            py: Python<'_>,
+            ops: &Bound<'_, PyAny>,
+            chunk_size: usize,
+        ) -> PyResult<Vec<MeasurementResult>> {
+            let mut iter = ops.try_iter()?;
+            let mut chunk: Vec<Op> = Vec::with_capacity(chunk_size.max(1));
+            let mut outcomes = Vec::new();
+            let mut offset = 0;
+            loop {
+                chunk.clear();
+                for item in iter.by_ref().take(chunk_size.max(1)) {
+                    chunk.push(item?.extract()?);
+                }
+                if chunk.is_empty() {
+                    return Ok(outcomes);
+                }
+                crate::validate::check_ops(self, &chunk, offset)?;
+                offset += chunk.len();
+                py.detach(|| outcomes.extend(chunk.iter().filter_map(|op| op.apply(self))));
+            }
+        }
+
+        /// Execute operations encoded as an integer array of shape `(n, 3)`.
+        ///
+        /// Each row is `(opcode, qubit, second_qubit)`, with the codes given by `Op.opcode`:
+        /// X 0, Y 1, Z 2, H 3, S 4, S† 5, CZ 6, CX 7, CY 8, measurements along X, Y
+        /// and Z 9, 10 and 11, √X 12, √X† 13, √Y 14, √Y† 15 and SWAP 16. The second
+        /// qubit is ignored by single-qubit operations.
+        /// Any C-contiguous or strided buffer of 32 or 64 bit integers works, e.g. a
+        /// numpy array, and it is decoded without creating Python objects. Returns the
+        /// measurement outcomes in order. Unknown opcodes and the problems `validate`
+        /// looks for raise `ValueError` before any operation is applied.
+        pub fn apply_ops(
+            &mut self,
+            py: Python<'_>,
+            ops: &Bound<'_, PyAny>,
+        ) -> PyResult<Vec<MeasurementResult>> {
+            let ops = crate::circuit::decode_ops(ops)?;
+            crate::validate::check_ops(self, &ops, 0)?;
+            Ok(py.detach(|| ops.iter().filter_map(|op| op.apply(self)).collect()))
+        }
+
+        /// Apply every Pauli of `frame` to the state, e.g. a decoder correction.
+        pub fn apply_frame(&mut self, frame: &PauliFrame) {
+            for (qubit, axis) in frame.iter() {
+                self.apply_pauli(qubit, axis);
+            }
+        }
+
+        /// Iterate lazily over the edges of the graph, as `(a, b)` pairs with `a < b`.
+        ///
+        /// The iterator sees the graph as it is now: later gates on the simulator don't
+        /// change what it yields.
+        pub fn iter_edges(&self) -> crate::edges::EdgeIterator {
+            crate::edges::EdgeIterator::new(self.adjacent.clone())
+        }
+
+        /// Iterate lazily over the elements of the stabilizer group, as Pauli strings.
+        ///
+        /// All 2^n elements are produced one by one from the generators, like `"+XZ_"`
+        /// with a letter per qubit, starting with the identity, and stopping after
+        /// `limit` of them if given. Like `iter_edges`, it sees the state as it is now.
+        #[pyo3(signature = (limit = None))]
+        pub fn iter_stabilizer_group(
+            &self,
+            limit: Option<usize>,
+        ) -> crate::stabilizer::StabilizerIterator {
+            crate::stabilizer::StabilizerIterator::new(self, limit)
+        }
+
+        /// Set-like view of the edges of the graph as it is now, supporting `len`,
+        /// iteration and `(a, b) in view` without building a list of them.
+        pub fn edges_view(&self) -> crate::edges::EdgesView {
+            crate::edges::EdgesView::new(self.adjacent.clone())
+        }
+
+        /// Label of the state of `qubit` if it isn't entangled with any other qubit.
+        ///
+        /// Returns `"0"`, `"1"`, `"+"`, `"-"`, `"+i"` or `"-i"` for the eigenstates of Z,
+        /// X and Y, and `"entangled"` if the qubit has neighbours in the graph. Traced out
+        /// qubits are in |0⟩.
+        pub fn local_state(&self, qubit: QubitIndex) -> PyResult<&'static str> {
+            let qubit = self.resolve_any(qubit)?;
+            Ok(match self.adjacent[qubit].is_empty() {
+                true => self.vop[qubit].get_state_str(),
+                false => "entangled",
+            })
+        }
+
+        /// Human readable summary of the state as a tensor product of its components.
+        ///
+        /// Unentangled qubits are shown by their `local_state` label and entangled
+        /// components of up to `max_size` qubits as a sum of basis kets, with larger ones
+        /// only giving their size. Each factor is followed by its qubits, in the order of
+        /// the bits of its kets, e.g. `|1⟩[0] ⊗ (|00⟩ + |11⟩)/√2[1, 2]`.
+        #[pyo3(signature = (max_size = 4))]
+        pub fn to_ket_string(&self, max_size: usize) -> String {
+            crate::ket::to_ket_string(self, max_size)
+        }
+
+        /// Return the set of qubits that are entangled with `qubit`.
+        ///
+        /// This follows adjacency in the underlying graph.
+        #[pyo3(name = "get_entangled_group")]
+        pub fn py_get_entangled_group(&self, qubit: QubitIndex) -> PyResult<HashSet<NodeIdx>> {
+            Ok(self.get_entangled_group(self.resolve_any(qubit)?))
+        }
+
+        /// Sample `shots` computational-basis bitstrings without changing the state, with
+        /// the GIL released.
+        ///
+        /// Returns a numpy `uint8` array of shape `(shots, ceil(n / 8))`, where qubit `q`
+        /// is bit `q % 8` of byte `q / 8` and set for a −1 (|1⟩) outcome. Unpack with
+        /// `np.unpackbits(a, axis=1, bitorder="little")[:, :n]`.
+        pub fn sample_bitstrings<'py>(
+            &self,
+            py: Python<'py>,
+            shots: usize,
+        ) -> PyResult<Bound<'py, PyAny>> {
+            let data = py.detach(|| crate::sampling::sample_bitstrings(self, shots));
+            let shape = [shots, self.vop.len().div_ceil(8)];
+            crate::convert::numpy_array(py, &data, "uint8", &shape)
+        }
+
+        /// Sample `shots` Z measurements of `qubits` without changing the state, with the
+        /// GIL released. By default all qubits that aren't traced out are measured.
+        ///
+        /// Returns a numpy `int8` array with a row per shot and a column per qubit, encoded
+        /// like `outcomes_to_array`: +1 and −1, or with `binary` 0 and 1.
+        #[pyo3(signature = (shots, qubits = None, binary = false))]
+        pub fn sample<'py>(
+            &self,
+            py: Python<'py>,
+            shots: usize,
+            qubits: Option<QubitList>,
+            binary: bool,
+        ) -> PyResult<Bound<'py, PyAny>> {
+            let qubits = match qubits {
+                Some(qubits) => self.resolve_list(&qubits)?,
+                None => (0..self.vop.len())
+                    .filter(|&qubit| !self.is_traced(qubit))
+                    .collect(),
+            };
+            let data = py.detach(|| crate::sampling::sample(self, shots, &qubits, binary));
+            crate::convert::numpy_array(py, &data, "int8", &[shots, qubits.len()])
+        }
+
+        /// Bell-sample this state and `other`, a second copy, for `shots` shots.
+        ///
+        /// Every shot measures the pairs of qubit `i` of both simulators in the Bell basis
+        /// and returns the Pauli string labelling the outcome, like `"IXZY"`, where a pair
+        /// in (|00⟩ + |11⟩)/√2 gives `I`, and `X`, `Y` and `Z` the Bell states that Pauli
+        /// makes of it on the second copy. For two copies of a real stabilizer state, the
+        /// samples are uniform over its stabilizers, which diagnoses stabilizerness and
+        /// overlaps. Neither simulator is changed.
+        pub fn bell_sample(&self, other: &GraphSim, shots: usize) -> PyResult<Vec<String>> {
+            crate::sampling::bell_sample(self, other, shots)
+        }
+
+        /// Collect a classical shadow of the state from `shots` random Pauli measurements.
+        ///
+        /// Every shot measures all qubits of a copy of the state, each in a uniformly
+        /// random basis X, Y or Z, which is the same as a random single-qubit Clifford
+        /// followed by a Z measurement. The state itself is not changed.
+        pub fn classical_shadow(&self, shots: usize) -> crate::shadow::ClassicalShadow {
+            // the bases come from a generator of their own, as scratch copies draw
+            // their outcomes from the one of this simulator
+            let mut rng = self.with_rng(|rng| StdRng::seed_from_u64(rng.random()));
+            crate::shadow::shadow(self, shots, &mut rng)
+        }
+
+        /// Condition the state on measurements of `outcomes` giving the requested results.
+        ///
+        /// `outcomes` maps qubits to the `MeasurementResult` of a Z measurement or to an
+        /// `(axis, result)` tuple to postselect on. Returns the probability of that
+        /// combination, a power of 1/2, or 0 without changing the state if it can't occur.
+        /// The measurements are recorded like those of `measure`, in increasing qubit
+        /// order, and raise `ValueError` if two keys refer to the same qubit.
+        pub fn postselect(
+            &mut self,
+            outcomes: HashMap<QubitIndex, Postselection>,
+        ) -> PyResult<f64> {
+            let mut resolved = BTreeMap::new();
+            for (qubit, outcome) in outcomes {
+                let outcome = match outcome {
+                    Postselection::Along(axis, result) => (axis, result),
+                    Postselection::Z(result) => (Axis::Z, result),
+                };
+                let qubit = self.resolve(qubit)?;
+                if resolved.insert(qubit, outcome).is_some() {
+                    return Err(PyValueError::new_err(format!(
+                        "Qubit {qubit} is postselected more than once"
+                    )));
+                }
+            }
+            for &qubit in resolved.keys() {
+                self.settle(qubit);
+            }
+
+            let mut conditioned = self.clone();
+            let mut probability = 1.0;
+            for (&qubit, &(axis, result)) in &resolved {
+                match conditioned.deterministic_outcome(qubit, axis) {
+                    Some(certain) if certain != result => return Ok(0.0),
+                    Some(_) => {}
+                    None => {
+                        conditioned.project(qubit, axis, result);
+                        probability /= 2.0;
+                    }
+                }
+            }
+
+            for (qubit, (axis, result)) in resolved {
+                self.measure_forced(qubit, axis, result)?;
+            }
+            Ok(probability)
+        }
+
+        /// Trace out `qubits`, after which they can't be used anymore.
+        ///
+        /// Each qubit is measured in Z and its outcome forgotten, so the remaining qubits
+        /// are left in a state sampled from the mixture their reduced density matrix
+        /// describes, and correlations with the traced qubits become classical. Use
+        /// `MixedGraphSim.trace_out` to keep the whole mixture instead of a sample.
+        #[pyo3(name = "trace_out")]
+        pub fn py_trace_out(&mut self, qubits: QubitList) -> PyResult<()> {
+            let mut qubits = self.resolve_list(&qubits)?;
+            qubits.sort_unstable();
+            qubits.dedup();
+            self.trace_out(qubits);
+            Ok(())
+        }
+
+        /// Copy the state into a new POSIX shared memory segment.
+        ///
+        /// Returns the `multiprocessing.shared_memory.SharedMemory` holding it, whose
+        /// `name` other processes pass to `GraphSim.from_shared_memory`. The caller owns
+        /// the segment and has to `close` and `unlink` it once every worker has attached.
+        /// Callbacks and recordings aren't part of the copy.
+        pub fn to_shared_memory<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+            crate::snapshot::to_shared_memory(py, self)
+        }
+
+        /// Load a state written by `to_shared_memory` from the segment called `name`.
+        ///
+        /// The segment is only read and closed again, it stays around for other workers.
+        #[staticmethod]
+        pub fn from_shared_memory(py: Python<'_>, name: &str) -> PyResult<GraphSim> {
+            crate::snapshot::from_shared_memory(py, name)
+        }
+
+        /// Hash of the graph and VOPs, equal for simulators with identical internals.
+        ///
+        /// It is updated with every change to the graph or a VOP rather than recomputed,
+        /// so this takes O(1). The hash is deterministic across runs and platforms, but
+        /// LC-equivalent representations of the same state hash differently.
+        pub fn state_hash(&self) -> u64 {
+            self.hash
+        }
+
+        /// Hash of the state itself, equal for simulators describing the same state.
+        ///
+        /// Unlike `state_hash`, LC-equivalent graphs and VOPs describing one state (up to
+        /// a global phase) give the same hash, as it hashes the row-reduced stabilizer
+        /// generators. Different states only collide like any 64 bit hash. Scales as O(n^3).
+        pub fn canonical_hash(&self) -> u64 {
+            crate::stabilizer::canonical_hash(self)
+        }
+
+        /// Return the edges and VOPs that differ between this simulator and `other`.
+        ///
+        /// With `canonicalize`, copies of both have their VOPs standardized first, like
+        /// `standardize_vops`, so VOPs that only differ in how gates were absorbed are
+        /// less likely to show up. The result also tells whether both describe the same
+        /// state anyway, which costs O(n^3).
+        #[pyo3(signature = (other, canonicalize = false))]
+        pub fn diff(&self, other: &GraphSim, canonicalize: bool) -> crate::diff::StateDiff {
+            crate::diff::diff(self, other, canonicalize)
+        }
+
+        /// Release excess memory of the graph, e.g. after measuring many qubits.
+        ///
+        /// Every neighbour set is truncated to its largest neighbour, so isolated qubits
+        /// hold no storage at all. Storage still shared with a copy of the simulator is
+        /// left alone, as shrinking it would copy it. Returns the number of bytes of
+        /// capacity released.
+        pub fn shrink_to_fit(&mut self) -> usize {
+            let mut released = 0;
+            for adj in self.adjacent.unshared_mut() {
+                let before = adj.capacity();
+                adj.shrink_to_fit();
+                released += (before - adj.capacity()) / 8;
+            }
+            let before = self.traced.capacity();
+            self.traced.shrink_to_fit();
+            released += (before - self.traced.capacity()) / 8;
+            released + self.adjacent.shrink_to_fit() + self.vop.shrink_to_fit()
+        }
+
+        /// Return a qubit in the |0⟩ state that isn't entangled with anything.
+        ///
+        /// Qubits returned by `free_ancilla` or removed by `trace_out` are reused first,
+        /// and only when none are left the register grows by one qubit.
+        pub fn alloc_ancilla(&mut self) -> NodeIdx {
+            let qubit = match self.traced.iter().next() {
+                Some(qubit) => {
+                    self.traced.remove(qubit);
+                    qubit
+                }
+                None => {
+                    self.vop.push(Vop::YC);
+                    self.adjacent.push(BitSet::new());
+                    self.generations.push(0);
+                    self.hash ^= vop_hash(self.vop.len() - 1, Vop::YC);
+                    self.vop.len() - 1
+                }
+            };
+            self.ancillas.insert(qubit);
+            qubit
+        }
+
+        /// Reset `qubit` and return it to the pool used by `alloc_ancilla`.
+        ///
+        /// The qubit is measured in Z and the outcome forgotten, like `trace_out`, so it
+        /// can't be used until it is allocated again. Raises `ValueError` unless `qubit`
+        /// came from `alloc_ancilla` and wasn't freed or traced out since, so a qubit of
+        /// the register can't end up in the pool by mistake.
+        pub fn free_ancilla(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            if !self.ancillas.contains(qubit) {
+                return Err(PyValueError::new_err(format!(
+                    "Qubit {qubit} isn't an ancilla from alloc_ancilla"
+                )));
+            }
+            self.settle(qubit);
+            self.discard(qubit, self.with_rng(|rng| rng.random()));
+            Ok(())
+        }
+
+        /// Return a handle to a qubit in the |0⟩ state, like `alloc_ancilla`.
+        ///
+        /// Unlike the index, the handle can't be used by mistake after `release` (or
+        /// `trace_out`), which raises `ValueError` even once the qubit is allocated again.
+        pub fn allocate(&mut self) -> Qubit {
+            let index = self.alloc_ancilla();
+            Qubit {
+                index,
+                generation: self.generations[index],
+            }
+        }
+
+        /// Reset the qubit behind `qubit` and return it to the pool, like `free_ancilla`.
+        pub fn release(&mut self, qubit: Qubit) -> PyResult<()> {
+            self.free_ancilla(QubitIndex::Handle(qubit))
+        }
+
+        /// Return the qubits that were traced out or freed, in increasing order.
+        pub fn traced_qubits(&self) -> Vec<NodeIdx> {
+            self.traced.iter().collect()
+        }
+
+        /// Measure `qubit` in Z, but only once it is used again or its outcome is needed.
+        ///
+        /// Operations on other qubits commute with the measurement, so it is put off until
+        /// `deferred_outcome` or `resolve_deferred` asks for the outcome, which saves the
+        /// graph update on qubits that are measured once and never touched again, as in
+        /// photonic streaming. An operation on `qubit` resolves it first, keeping the
+        /// outcome for later. Queries such as `local_state` or `edges` don't, so they see
+        /// the qubit as it was before the measurement.
+        pub fn defer_measurement(&mut self, qubit: QubitIndex) -> PyResult<()> {
+            let qubit = self.resolve(qubit)?;
+            if self.deferred.contains_key(&qubit) {
+                return Err(PyValueError::new_err(format!(
+                    "Qubit {qubit} already has a deferred measurement"
+                )));
+            }
+            self.deferred.insert(qubit, None);
+            Ok(())
+        }
+
+        /// Return the outcome of the deferred measurement of `qubit`, resolving it if needed.
+        ///
+        /// The outcome is only returned once, after which the qubit can be deferred again.
+        pub fn deferred_outcome(&mut self, qubit: QubitIndex) -> PyResult<MeasurementResult> {
+            let qubit = self.resolve_any(qubit)?;
+            self.settle(qubit);
+            self.deferred.remove(&qubit).flatten().ok_or_else(|| {
+                PyValueError::new_err(format!("Qubit {qubit} has no deferred measurement"))
+            })
+        }
+
+        /// Resolve all deferred measurements, returning the outcomes not read yet by qubit.
+        pub fn resolve_deferred(&mut self) -> BTreeMap<NodeIdx, MeasurementResult> {
+            let pending: Vec<NodeIdx> = self.deferred.keys().copied().collect();
+            for qubit in pending {
+                self.settle(qubit);
+            }
+            std::mem::take(&mut self.deferred)
+                .into_iter()
+                .map(|(qubit, result)| (qubit, result.expect("Deferred measurements are resolved")))
+                .collect()
+        }
+
+        /// Return both outcomes of measuring `qubit` along `axis` without sampling.
+        ///
+        /// The result is `[(p_plus, state_plus), (p_minus, state_minus)]` for the +1 and
+        /// −1 outcome, where the state of an outcome with probability 0 is `None`. This
+        /// simulator itself is not changed.
+        pub fn branch_measure(
+            &self,
+            qubit: QubitIndex,
+            axis: Axis,
+        ) -> PyResult<[(f64, Option<GraphSim>); 2]> {
+            let qubit = self.resolve(qubit)?;
+            Ok(crate::sampling::branch(self.clone(), qubit, axis))
+        }
+
+        /// Walk every outcome of measuring `measurement_plan`, a list of `(qubit, axis)`,
+        /// in order.
+        ///
+        /// Returns one `(results, probability, state)` per possible combination of
+        /// results, with `state` the simulator after those measurements. The plan can
+        /// have at most 16 measurements and this simulator is not changed.
+        pub fn enumerate_outcomes(
+            &self,
+            measurement_plan: Vec<(QubitIndex, Axis)>,
+        ) -> PyResult<Vec<crate::sampling::OutcomeLeaf>> {
+            let measurement_plan = measurement_plan
+                .into_iter()
+                .map(|(qubit, axis)| Ok((self.resolve(qubit)?, axis)))
+                .collect::<PyResult<Vec<_>>>()?;
+            crate::sampling::enumerate_outcomes(self, &measurement_plan)
+        }
+
+        /// Table of stabilizer generators with signs, one per line, e.g. `+XX\n+ZZ`.
+        ///
+        /// Every line has a sign followed by the Pauli on each qubit, with `_` for the
+        /// identity, so the columns line up. By default there is one generator per qubit,
+        /// the graph-state stabilizer of that qubit conjugated by the VOPs, or with
+        /// `canonical` the reduced generators that only depend on the state. Given
+        /// `qubits`, only those columns are shown and the lines are the canonical
+        /// generators of the stabilizers supported on them, which may be none.
+        #[pyo3(signature = (qubits = None, canonical = false))]
+        pub fn format_stabilizers(
+            &self,
+            qubits: Option<QubitList>,
+            canonical: bool,
+        ) -> PyResult<String> {
+            use crate::stabilizer::{
+                canonical_stabilizers, format_rows, generators, subsystem_stabilizers,
+            };
+            Ok(match qubits {
+                Some(qubits) => {
+                    let qubits = self.resolve_list(&qubits)?;
+                    let subset = crate::entanglement::qubit_set(self, &qubits)?;
+                    format_rows(&subsystem_stabilizers(self, &subset), &qubits)
+                }
+                None => {
+                    let rows = match canonical {
+                        true => canonical_stabilizers(self),
+                        false => generators(self),
+                    };
+                    format_rows(&rows, &(0..self.vop.len()).collect::<Vec<_>>())
+                }
+            })
+        }
+
+        /// Number of independent stabilizers of the state supported only on `qubits`.
+        ///
+        /// For a subset A this is |A| minus the entanglement entropy of A in bits, so it
+        /// is |A| for a product state and 0 for A maximally entangled with the rest.
+        /// Computed from the GF(2) rank of the graph's cut between A and the rest.
+        pub fn subsystem_rank(&self, qubits: QubitList) -> PyResult<usize> {
+            crate::entanglement::subsystem_rank(self, &self.resolve_list(&qubits)?)
+        }
+
+        /// Mutual information in bits between the disjoint qubit sets `set_a` and `set_b`.
+        ///
+        /// Equal to S(A) + S(B) − S(AB) with S the entanglement entropy, which is always
+        /// a whole number of bits for a stabilizer state.
+        pub fn mutual_information(&self, set_a: QubitList, set_b: QubitList) -> PyResult<usize> {
+            let (set_a, set_b) = (self.resolve_list(&set_a)?, self.resolve_list(&set_b)?);
+            crate::entanglement::mutual_information(self, &set_a, &set_b)
+        }
+
+        /// Rank over GF(2) of the adjacency matrix restricted to `rows` and `cols`.
+        ///
+        /// With `rows` a subset A and `cols` its complement this is the cut rank, the
+        /// entanglement entropy of A in bits. Elimination works on bitsets, so it costs
+        /// O(r^2 n / 64) for `r` rows.
+        pub fn adjacency_rank(&self, rows: QubitList, cols: QubitList) -> PyResult<usize> {
+            let (rows, cols) = (self.resolve_list(&rows)?, self.resolve_list(&cols)?);
+            crate::entanglement::adjacency_rank(self, &rows, &cols)
+        }
+
+        /// Exact probability distribution of the Z outcomes of up to 20 `qubits`.
+        ///
+        /// Entry `i` is the probability that `qubits[k]` gives −1 exactly for the bits
+        /// `k` set in `i`. The state is not changed.
+        pub fn marginal_probabilities(&self, qubits: QubitList) -> PyResult<Vec<f64>> {
+            crate::sampling::marginal_probabilities(self, &self.resolve_list(&qubits)?)
+        }
+
+        /// Exact expectation value, −1.0, 0.0 or +1.0, of the Pauli `pauli_string`.
+        ///
+        /// The string is like for `expectations`, or has a letter for each of `qubits` if
+        /// given, so `expectation("ZZ", [0, 3])` is ⟨Z₀Z₃⟩. The state is not changed.
+        #[pyo3(signature = (pauli_string, qubits = None))]
+        pub fn expectation(&self, pauli_string: &str, qubits: Option<QubitList>) -> PyResult<f64> {
+            let qubits = qubits
+                .map(|qubits| self.resolve_list(&qubits))
+                .transpose()?;
+            let product = crate::gadget::parse_observable(pauli_string, qubits, self.vop.len())?;
+            let group = crate::stabilizer::StabilizerGroup::new(self);
+            Ok(f64::from(group.expectation(&product.to_pauli_string())))
+        }
+
+        /// Exact expectation values, −1.0, 0.0 or +1.0, of every Pauli in `pauli_strings`.
+        ///
+        /// Each string has a letter `I` (or `_`), `X`, `Y` or `Z` for qubits 0, 1, … after
+        /// an optional sign, like `"-XZ_Y"`. The stabilizer group is reduced once for all
+        /// of them in O(n^3), after which every string takes O(n^2). The state is not
+        /// changed. Raises `ValueError` for strings that don't parse.
+        pub fn expectations(&self, pauli_strings: Vec<String>) -> PyResult<Vec<f64>> {
+            let paulis = pauli_strings
+                .iter()
+                .map(|pauli| {
+                    let product = crate::gadget::parse_observable(pauli, None, self.vop.len())?;
+                    Ok(product.to_pauli_string())
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            let group = crate::stabilizer::StabilizerGroup::new(self);
+            Ok(paulis
+                .iter()
+                .map(|pauli| f64::from(group.expectation(pauli)))
+                .collect())
+        }
+
+        /// Enumerate the graphs reachable from this one by local complementations.
+        ///
+        /// Returns at most `max_states` simulators describing the same state, one for
+        /// every graph up to isomorphism, starting with an unchanged copy of this one.
+        pub fn lc_orbit(&self, max_states: usize) -> Vec<GraphSim> {
+            crate::orbit::lc_orbit(self, max_states)
+        }
+
+        /// Generators of the symmetries of the graph, as permutations of the qubits.
+        ///
+        /// Permutation `p` maps qubit `q` to `p[q]`, keeping every edge an edge and every
+        /// VOP in place, so relabelling the qubits by it leaves the state unchanged. Every
+        /// symmetry is a product of the generators, and a graph without any gives an empty
+        /// list. Like `lc_orbit` this is exponential for some highly symmetric graphs.
+        pub fn automorphisms(&self) -> Vec<Vec<NodeIdx>> {
+            crate::orbit::automorphisms(self)
+        }
+
+        /// Classes of qubits that `automorphisms` map onto each other.
+        ///
+        /// Qubits of a class play the same role in the state, such as the leaves of a star
+        /// graph. Classes are sorted and ordered by their first qubit, and qubits without a
+        /// symmetric partner form a class of their own.
+        pub fn symmetry_classes(&self) -> Vec<Vec<NodeIdx>> {
+            crate::orbit::orbits(self.vop.len(), &crate::orbit::automorphisms(self))
+        }
+
+        /// Replace the graph by an LC-equivalent one with as few edges as can be found.
+        ///
+        /// The state is unchanged, but fewer edges make later gates and measurements
+        /// faster. Graphs up to 8 qubits are searched exhaustively, larger ones greedily.
+        /// Returns the number of edges afterwards.
+        pub fn minimize_edges(&mut self) -> usize {
+            crate::orbit::minimize_edges(self)
+        }
+
+        /// Push as many VOPs as possible to the identity without changing the state.
+        ///
+        /// Local complementations and graph-state stabilizers are applied greedily, first
+        /// to bring VOPs into the diagonal {I, Z, S, S†} group and then to remove them.
+        /// Returns the residual local Cliffords as `{qubit: gates}`, where applying the
+        /// gates in order to the plain graph state on the current edges gives the state.
+        pub fn standardize_vops(&mut self) -> HashMap<NodeIdx, Vec<&'static str>> {
+            crate::orbit::standardize_vops(self);
+            self.vop
+                .iter()
+                .enumerate()
+                .filter(|&(_, &vop)| vop != Vop::IA)
+                .map(|(qubit, vop)| (qubit, vop.gate_names()))
+                .collect()
+        }
+
+        /// Probability that measuring `qubit` along `axis` gives `result`: 0, 1/2 or 1.
+        ///
+        /// Only looks at the VOP and neighbours of `qubit`, so unlike `peek_measure_set`
+        /// this neither copies nor changes the state and takes O(1).
+        pub fn probability(
+            &self,
+            qubit: QubitIndex,
+            axis: Axis,
+            result: MeasurementResult,
+        ) -> PyResult<f64> {
+            let qubit = self.resolve(qubit)?;
+            Ok(match self.deterministic_outcome(qubit, axis) {
+                Some(certain) if certain == result => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            })
+        }
+
+        /// Simulate a measurement of `qubit` without modifying the real state.
+        ///
+        /// Measures along `axis`, or with `None` along the axis the outcome is deterministic
+        /// in if there is one, and along a random axis otherwise. Like `probability` this
+        /// only looks at the VOP and neighbours of `qubit` instead of copying the state.
+        /// Returns the `Outcome` with the result and axis used.
+        #[pyo3(signature = (qubit, axis = None))]
+        pub fn peek_measure(&self, qubit: QubitIndex, axis: Option<Axis>) -> PyResult<Outcome> {
+            let qubit = self.resolve(qubit)?;
+            let axis = axis
+                .or_else(|| self.find_deterministic(qubit))
+                .unwrap_or_else(|| self.with_rng(|rng| rng.random()));
+            let result = self
+                .deterministic_outcome(qubit, axis)
+                .unwrap_or_else(|| self.with_rng(|rng| rng.random()));
+            Ok(Outcome { result, axis })
+        }
+
+        /// Simulate measurements on `qubits` without modifying the real state.
+        ///
+        /// `qubits` is a dict `{qubit: axis}` or a set of qubits. Qubits with an axis of
+        /// `None`, and all qubits of a set, are measured along the axis their outcome is
+        /// deterministic in if there is one, and along a random axis otherwise.
+        ///
+        /// The qubits are measured in increasing order, so the random numbers drawn don't
+        /// depend on the iteration order of the set. Returns a map from qubit index to
+        /// `Outcome` (result and axis used), with its keys in that order.
+        pub fn peek_measure_set(
+            &self,
+            qubits: &Bound<'_, PyAny>,
+        ) -> PyResult<BTreeMap<NodeIdx, Outcome>> {
+            let axes: HashMap<QubitIndex, Option<Axis>> = if qubits.is_instance_of::<PyDict>() {
+                qubits.extract()?
+            } else {
+                let qubits: HashSet<QubitIndex> = qubits.extract()?;
+                qubits.into_iter().map(|qubit| (qubit, None)).collect()
+            };
+            let axes = axes
+                .into_iter()
+                .map(|(qubit, axis)| Ok((self.resolve(qubit)?, axis)))
+                .collect::<PyResult<BTreeMap<NodeIdx, Option<Axis>>>>()?;
+            Ok(self.peek_measure_axes(&axes))
+        }
+    }
+
+    /// Deterministic 64 bit mixer (the splitmix64 finalizer) behind the state hash.
+    pub(crate) fn mix(mut x: u64) -> u64 {
+        x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        x ^ (x >> 31)
+    }
+
+    fn vop_hash(node: NodeIdx, vop: Vop) -> u64 {
+        mix(node as u64 * crate::SYMMETRIES as u64 + vop as u64)
+    }
+
+    fn edge_hash(na: NodeIdx, nb: NodeIdx) -> u64 {
+        let (low, high) = (na.min(nb) as u64, na.max(nb) as u64);
+        !mix(high << 32 | low)
+    }
+
+    fn find_zeta(vop: Vop, axis: Axis) -> Zeta {
+        let rvop = (vop as usize) & 0b11;
+
+        match (
+            (rvop == 0 || rvop == (axis as usize + 1)),
+            vop >= Vop::IB && vop < Vop::IE,
+        ) {
+            (true, true) | (false, false) => Zeta::Two,
+            (true, false) | (false, true) => Zeta::Zero,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        #[test]
+        fn test_single_qubit_gates() {
+            let mut qec = GraphSim::new(1);
+            assert_eq!(qec.vop[0], Vop::YC);
+            qec.h(0);
+            assert_eq!(qec.vop[0], Vop::IA);
+            qec.s(0);
+            assert_eq!(qec.vop[0], Vop::YB);
+            qec.s(0);
+            assert_eq!(qec.vop[0], Vop::ZA);
+            qec.z(0);
+            assert_eq!(qec.vop[0], Vop::IA);
+            qec.sdag(0);
+            assert_eq!(qec.vop[0], Vop::XB);
+            qec.z(0);
+            assert_eq!(qec.vop[0], Vop::YB);
+        }
+
+        #[test]
+        fn test_sqrt_x_gates() {
+            let mut qec = GraphSim::new(2);
+            qec.sx(0);
+            assert_eq!(qec.local_state(0.into()).unwrap(), "-i");
+            qec.sx(0);
+            assert_eq!(qec.local_state(0.into()).unwrap(), "1");
+            qec.sxdag(1);
+            assert_eq!(qec.local_state(1.into()).unwrap(), "+i");
+            qec.sx(1);
+            assert_eq!(qec.local_state(1.into()).unwrap(), "0");
+        }
+
+        #[test]
+        fn test_sqrt_y_gates() {
+            let mut qec = GraphSim::new(2);
+            qec.sy(0);
+            assert_eq!(qec.local_state(0.into()).unwrap(), "+");
+            qec.sy(0);
+            assert_eq!(qec.local_state(0.into()).unwrap(), "1");
+            qec.sydag(1);
+            assert_eq!(qec.local_state(1.into()).unwrap(), "-");
+            qec.sy(1);
+            assert_eq!(qec.local_state(1.into()).unwrap(), "0");
+        }
+
+        #[test]
+        fn test_measure_single_z_det() {
+            let mut qec = GraphSim::new(1);
+
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::PlusOne);
+            assert!(det);
+
+            qec.s(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::PlusOne);
+            assert!(det);
+
+            qec.s(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::PlusOne);
+            assert!(det);
+
+            qec.s(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::PlusOne);
+            assert!(det);
+
+            qec.x(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::MinusOne);
+            assert!(det);
+
+            qec.s(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::MinusOne);
+            assert!(det);
+
+            qec.s(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::MinusOne);
+            assert!(det);
+
+            qec.s(0);
+            let (outcome, det) = qec.measure(0, Axis::Z);
+            assert_eq!(outcome, MeasurementResult::MinusOne);
+            assert!(det);
+        }
+
+        #[test]
+        fn test_cnot_measure_x() {
+            let mut qec = GraphSim::new(2);
+
+            qec.h(0);
+            qec.cx(0, 1);
+
+            println!("state is {:#?} before meas", qec);
+            let (outcome_1, det_1) = qec.measure(0, Axis::X);
+            println!(
+                "state is {:#?} between meas, outcome was {:?}",
+                qec, outcome_1
+            );
+            let (outcome_2, det_2) = qec.measure(1, Axis::X);
+            println!(
+                "state is {:#?} after meas, outcome was {:?}",
+                qec, outcome_2
+            );
+
+            assert!(!det_1);
+            assert!(det_2);
+
+            assert_eq!(qec.vop[0].get_state_str(), qec.vop[1].get_state_str());
+            assert_eq!(outcome_1, outcome_2);
+        }
+
+        #[test]
+        fn test_cnot_measure_y() {
+            let mut qec = GraphSim::new(2);
+
+            qec.h(0);
+            qec.cx(0, 1);
+
+            println!("state is {:#?} before meas", qec);
+            let (outcome_1, det_1) = qec.measure(0, Axis::Y);
+            println!(
+                "state is {:#?} between meas, outcome was {:?}",
+                qec, outcome_1
+            );
+            let (outcome_2, det_2) = qec.measure(1, Axis::Y);
+            println!(
+                "state is {:#?} after meas, outcome was {:?}",
+                qec, outcome_2
+            );
+
+            assert!(!det_1);
+            assert!(det_2);
+
+            assert_ne!(qec.vop[0].get_state_str(), qec.vop[1].get_state_str());
+            assert_ne!(outcome_1, outcome_2);
+        }
+
+        #[test]
+        fn test_cnot_measure_z() {
+            let mut qec = GraphSim::new(2);
+
+            qec.h(0);
+            qec.cx(0, 1);
+
+            println!("state is {:#?} before meas", qec);
+            let (outcome_1, det_1) = qec.measure(0, Axis::Z);
+            println!(
+                "state is {:#?} between meas, outcome was {:?}",
+                qec, outcome_1
+            );
+            let (outcome_2, det_2) = qec.measure(1, Axis::Z);
+            println!(
+                "state is {:#?} after meas, outcome was {:?}",
+                qec, outcome_2
+            );
+
+            assert!(!det_1);
+            assert!(det_2);
+
+            assert_eq!(qec.vop[0].get_state_str(), qec.vop[1].get_state_str());
+            assert_eq!(outcome_1, outcome_2);
+        }
+
+        #[test]
+        fn test_cz_is_undone_by_inverse() {
+            // removing the VOP of the target can give the control other neighbours, so
+            // whether to reduce the control again has to be checked afterwards
+            use rand::{SeedableRng, rngs::StdRng};
+
+            let mut rng = StdRng::seed_from_u64(7);
+            for _ in 0..200 {
+                let mut qec = GraphSim::new(5);
+                let gates: Vec<(u8, NodeIdx, NodeIdx)> = (0..30)
+                    .map(|_| {
+                        let qubit = rng.random_range(0..5);
+                        let other = (qubit + rng.random_range(1..5)) % 5;
+                        (rng.random_range(0..3), qubit, other)
+                    })
+                    .collect();
+                for &(gate, qubit, other) in &gates {
+                    match gate {
+                        0 => qec.h(qubit),
+                        1 => qec.s(qubit),
+                        _ => qec.cz(qubit, other),
+                    }
+                }
+                for &(gate, qubit, other) in gates.iter().rev() {
+                    match gate {
+                        0 => qec.h(qubit),
+                        1 => qec.sdag(qubit),
+                        _ => qec.cz(qubit, other),
+                    }
+                }
+                for qubit in 0..5 {
+                    assert_eq!(
+                        qec.deterministic_outcome(qubit, Axis::Z),
+                        Some(MeasurementResult::PlusOne)
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_cluster_project_x() {
+            // the -1 outcome has to correct the neighbourhood of the measured qubit
+            for result in [MeasurementResult::PlusOne, MeasurementResult::MinusOne] {
+                let mut qec = GraphSim::new(3);
+                for qubit in 0..3 {
+                    qec.h(qubit);
+                }
+                qec.cz(0, 1);
+                qec.cz(1, 2);
+
+                qec.project(0, Axis::X, result);
+
+                assert_eq!(qec.deterministic_outcome(0, Axis::X), Some(result));
+                assert_eq!(qec.deterministic_outcome(1, Axis::Z), Some(result));
+                let (outcome, det) = qec.measure(2, Axis::X);
+                assert!(det);
+                assert_eq!(outcome, result);
+            }
+        }
+
+        #[test]
+        fn test_postselect_bell() {
+            let mut qec = GraphSim::new(2);
+            qec.h(0);
+            qec.cx(0, 1);
+
+            let impossible = HashMap::from([
+                (0.into(), Postselection::Z(MeasurementResult::PlusOne)),
+                (1.into(), Postselection::Z(MeasurementResult::MinusOne)),
+            ]);
+            let before = qec.clone();
+            assert_eq!(qec.postselect(impossible).unwrap(), 0.0);
+            assert_eq!(qec, before);
+
+            // XX is a stabilizer, so the parity of X outcomes is fixed as well
+            let plus = MeasurementResult::PlusOne;
+            assert_eq!(qec.probability(0.into(), Axis::Z, plus).unwrap(), 0.5);
+
+            let mut copy = qec.clone();
+            let along_x = HashMap::from([
+                (
+                    0.into(),
+                    Postselection::Along(Axis::X, MeasurementResult::MinusOne),
+                ),
+                (
+                    1.into(),
+                    Postselection::Along(Axis::X, MeasurementResult::PlusOne),
+                ),
+            ]);
+            assert_eq!(copy.postselect(along_x).unwrap(), 0.0);
+
+            let prob = qec.postselect(HashMap::from([(
+                0.into(),
+                Postselection::Z(MeasurementResult::MinusOne),
+            )]));
+            assert_eq!(prob.unwrap(), 0.5);
+            assert_eq!(
+                qec.deterministic_outcome(1, Axis::Z),
+                Some(MeasurementResult::MinusOne)
+            );
+            assert_eq!(qec.probability(1.into(), Axis::Z, plus).unwrap(), 0.0);
+            assert_eq!(
+                qec.probability(QubitIndex::Index(-1), Axis::Z, !plus)
+                    .unwrap(),
+                1.0
+            );
+        }
+
+        #[test]
+        fn test_postselect_checks_and_records() {
+            let minus = MeasurementResult::MinusOne;
+            let mut qec = GraphSim::new(3);
+            qec.h(0);
+            qec.cx(0, 1);
+
+            // -1 and 1 are the same qubit
+            let twice = HashMap::from([
+                (QubitIndex::Index(-2), Postselection::Z(!minus)),
+                (1.into(), Postselection::Z(minus)),
+            ]);
+            assert!(qec.postselect(twice).is_err());
+            qec.py_trace_out(vec![2].into()).unwrap();
+            let traced = HashMap::from([(2.into(), Postselection::Along(Axis::X, minus))]);
+            assert!(qec.postselect(traced).is_err());
+            assert!(qec.is_traced(2));
+
+            // a deferred measurement is carried out before postselecting
+            qec.start_recording();
+            qec.defer_measurement(1.into()).unwrap();
+            let prob = qec.postselect(HashMap::from([(1.into(), Postselection::Z(minus))]));
+            let deferred = qec.deferred_outcome(1.into()).unwrap();
+            assert_eq!(prob.unwrap(), if deferred == minus { 1.0 } else { 0.0 });
+            if deferred == minus {
+                assert_eq!(qec.register.len(), 2);
+                assert_eq!(qec.history(1.into()).unwrap()[0].outcome, minus);
+                let recorded = qec.recorded_circuit().unwrap();
+                assert_eq!(
+                    recorded.iter().last(),
+                    Some(&Op::Measure {
+                        qubit: 1,
+                        axis: Axis::Z
+                    })
+                );
+            } else {
+                assert_eq!(qec.register.len(), 1);
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "traced out")]
+        fn test_traced_qubit_unusable() {
+            let mut qec = GraphSim::new(2);
+            qec.h(0);
+            qec.cx(0, 1);
+            qec.trace_out(vec![0]);
+
+            assert_eq!(qec.traced_qubits(), vec![0]);
+            assert!(qec.adjacent[1].is_empty());
+            qec.h(0);
+        }
+
+        #[test]
+        fn test_copies_are_independent() {
+            let mut qec = GraphSim::new(200);
+            for qubit in 0..199 {
+                qec.h(qubit);
+                qec.cx(qubit, qubit + 1);
+            }
+            // owned snapshots, as a clone would share the storage a leak ends up in
+            let snapshot = |sim: &GraphSim| {
+                let edges: Vec<Vec<NodeIdx>> = sim
+                    .adjacency()
+                    .iter()
+                    .map(|adj| adj.iter().collect())
+                    .collect();
+                let vops: Vec<Vop> = sim.vops().iter().copied().collect();
+                (edges, vops)
+            };
+            let before = snapshot(&qec);
+            for mut copy in [qec.copy(), qec.__copy__()] {
+                copy.cz(0, 150);
+                copy.measure_op(100, Axis::X);
+                copy.x(199);
+                assert_eq!(snapshot(&qec), before);
+                assert_eq!(qec.state_hash(), qec.full_hash());
+                assert_ne!(snapshot(&copy), before);
+            }
+        }
 
+        #[test]
+        fn test_traced_qubits_raise_value_error() {
+            let mut qec = GraphSim::new(3);
             qec.h(0);
             qec.cx(0, 1);
+            qec.py_trace_out(vec![0].into()).unwrap();
 
-            println!("state is {:#?} before meas", qec);
-            let (outcome_1, det_1) = qec.measure(0, Axis::X);
-            println!(
-                "state is {:#?} between meas, outcome was {:?}",
-                qec, outcome_1
+            assert!(qec.py_x(0.into()).is_err());
+            assert!(qec.py_measure(0.into(), Axis::Z).is_err());
+            assert!(qec.reset(QubitIndex::Index(-3), "0").is_err());
+            assert!(qec.peek_measure(0.into(), None).is_err());
+            assert!(
+                qec.probability(0.into(), Axis::Z, MeasurementResult::PlusOne)
+                    .is_err()
             );
-            let (outcome_2, det_2) = qec.measure(1, Axis::X);
-            println!(
-                "state is {:#?} after meas, outcome was {:?}",
-                qec, outcome_2
+            assert!(qec.measure_set(vec![1, 0].into(), Axis::Z).is_err());
+            assert!(qec.py_trace_out(vec![0].into()).is_err());
+            assert_eq!(qec.local_state(0.into()).unwrap(), "0");
+            // nothing was applied to the other qubits either
+            assert_eq!(qec.history.len(), 0);
+            assert_eq!(qec.register.len(), 0);
+
+            qec.py_trace_out(vec![2, 2].into()).unwrap();
+            assert_eq!(qec.traced_qubits(), vec![0, 2]);
+        }
+
+        #[test]
+        fn test_ancilla_reuse() {
+            let mut qec = GraphSim::new(1);
+            qec.h(0);
+            let anc = qec.alloc_ancilla();
+            assert_eq!(anc, 1);
+            qec.cx(0, anc);
+
+            qec.free_ancilla(anc.into()).unwrap();
+            assert!(qec.adjacent[0].is_empty());
+            assert_eq!(qec.alloc_ancilla(), anc);
+            assert_eq!(
+                qec.deterministic_outcome(anc, Axis::Z),
+                Some(MeasurementResult::PlusOne)
             );
+            assert_eq!(qec.alloc_ancilla(), 2);
+        }
+
+        #[test]
+        fn test_free_ancilla_checks_qubit() {
+            let mut qec = GraphSim::new(2);
+            // qubits of the register aren't ancillas
+            assert!(qec.free_ancilla(0.into()).is_err());
 
-            assert_eq!(det_1, false);
-            assert_eq!(det_2, true);
+            let anc = qec.alloc_ancilla();
+            qec.cx(0, anc);
+            qec.free_ancilla(anc.into()).unwrap();
+            assert!(qec.free_ancilla(anc.into()).is_err());
+            assert!(qec.reset(anc.into(), "0").is_err());
 
-            assert_eq!(qec.vop[0].get_state_str(), qec.vop[1].get_state_str());
-            assert_eq!(outcome_1, outcome_2);
+            // a reset ancilla is still allocated
+            let anc = qec.alloc_ancilla();
+            qec.h(anc);
+            qec.reset(anc.into(), "1").unwrap();
+            qec.free_ancilla(anc.into()).unwrap();
+            assert_eq!(qec.traced_qubits(), vec![anc]);
+
+            // tracing out an ancilla returns it to the pool as well
+            let anc = qec.alloc_ancilla();
+            qec.trace_out(vec![anc]);
+            assert!(qec.free_ancilla(anc.into()).is_err());
+            assert_eq!(qec.alloc_ancilla(), anc);
+
+            // a qubit traced out of the register is only an ancilla once allocated
+            qec.trace_out(vec![1]);
+            assert_eq!(qec.alloc_ancilla(), 1);
+            qec.free_ancilla(1.into()).unwrap();
         }
 
         #[test]
-        fn test_cnot_measure_y() {
+        fn test_deferred_measurements() {
+            let mut qec = GraphSim::new(3);
+            qec.h(0);
+            qec.cx(0, 1);
+            qec.cx(0, 2);
+            qec.defer_measurement(0.into()).unwrap();
+            assert!(qec.defer_measurement(0.into()).is_err());
+            assert_eq!(qec.edge_count(), 2);
+
+            // touching the qubit resolves the measurement before the gate
+            qec.x(0);
+            let outcome = qec.deferred_outcome(0.into()).unwrap();
+            assert!(qec.deferred_outcome(0.into()).is_err());
+            assert_eq!(qec.measure_op(0, Axis::Z), !outcome);
+
+            qec.defer_measurement(1.into()).unwrap();
+            let outcomes = qec.resolve_deferred();
+            assert_eq!(outcomes[&1], qec.measure_op(2, Axis::Z));
+            assert!(qec.resolve_deferred().is_empty());
+        }
+
+        #[test]
+        fn test_stale_handles() {
+            let mut qec = GraphSim::new(1);
+            let first = qec.allocate();
+            qec.release(first).unwrap();
+            let second = qec.allocate();
+            assert_eq!(first.index, second.index);
+            assert!(qec.release(first).is_err());
+            assert!(qec.resolve(QubitIndex::Handle(second)).is_ok());
+
+            qec.trace_out(vec![second.index]);
+            assert!(qec.resolve(QubitIndex::Handle(second)).is_err());
+        }
+
+        #[test]
+        fn test_seeded_measurements_repeat() {
+            let run = |seed| {
+                let mut qec = GraphSim::py_new(8, Some(seed));
+                for qubit in 0..8 {
+                    qec.h(qubit);
+                }
+                let samples = crate::sampling::sample_bitstrings(&qec, 4);
+                let outcomes: Vec<MeasurementResult> =
+                    (0..8).map(|qubit| qec.measure(qubit, Axis::Z).0).collect();
+                (samples, outcomes)
+            };
+            let (samples, outcomes) = run(1234);
+            assert_eq!(run(1234), (samples.clone(), outcomes));
+            assert_ne!(run(4321).0, samples);
+            // scratch copies continue the stream instead of repeating it
+            assert!(samples.windows(2).any(|pair| pair[0] != pair[1]));
+        }
+
+        #[test]
+        fn test_forced_measurements() {
             let mut qec = GraphSim::new(2);
+            qec.h(0);
+            qec.cx(0, 1);
+            let minus = MeasurementResult::MinusOne;
+            assert_eq!(qec.measure_forced(0, Axis::Z, minus).unwrap(), minus);
+            let before = qec.clone();
+            assert!(qec.measure_forced(1, Axis::Z, !minus).is_err());
+            assert_eq!(qec, before);
+            assert_eq!(qec.measure_forced(1, Axis::Z, minus).unwrap(), minus);
+            assert_eq!(qec.measure_forced(1, Axis::X, !minus).unwrap(), !minus);
+        }
+
+        #[test]
+        fn test_measure_along_axis() {
+            let mut qec = GraphSim::new(3);
+            qec.h(0);
+            qec.x(1);
+            qec.h(2);
+            qec.s(2);
+            let plus = MeasurementResult::PlusOne;
+            assert_eq!(qec.py_measure(0.into(), Axis::X).unwrap(), plus);
+            assert_eq!(qec.py_measure(1.into(), Axis::Z).unwrap(), !plus);
+            assert_eq!(
+                qec.py_measure(QubitIndex::Index(-1), Axis::Y).unwrap(),
+                plus
+            );
+            assert!(qec.py_measure(3.into(), Axis::Z).is_err());
+        }
 
+        #[test]
+        fn test_measure_reset_recycles_ancilla() {
+            let mut qec = GraphSim::new(3);
             qec.h(0);
             qec.cx(0, 1);
+            for _ in 0..8 {
+                // the ancilla measures the parity of the pair, always even
+                qec.cx(0, 2);
+                qec.cx(1, 2);
+                let parity = qec.measure_reset(2.into()).unwrap();
+                assert_eq!(parity, MeasurementResult::PlusOne);
+                assert!(qec.adjacency()[2].is_empty());
+                assert_eq!(qec.vops()[2], Vop::YC);
+            }
+            let outcome = qec.measure_reset(0.into()).unwrap();
+            assert_eq!(
+                qec.deterministic_outcome(0, Axis::Z),
+                Some(MeasurementResult::PlusOne)
+            );
+            assert_eq!(qec.deterministic_outcome(1, Axis::Z), Some(outcome));
+        }
 
-            println!("state is {:#?} before meas", qec);
-            let (outcome_1, det_1) = qec.measure(0, Axis::Y);
-            println!(
-                "state is {:#?} between meas, outcome was {:?}",
-                qec, outcome_1
+        #[test]
+        fn test_reset_is_recorded() {
+            for state in ["0", "1", "+", "-", "+i", "-i"] {
+                let mut qec = GraphSim::new(2);
+                qec.x(0);
+                qec.start_recording();
+                qec.h(1);
+                qec.reset(0.into(), state).unwrap();
+                qec.cz(0, 1);
+                let circuit = qec.stop_recording().unwrap();
+                assert_eq!(circuit.ops()[1], Op::Reset { qubit: 0 });
+
+                let mut replay = GraphSim::new(2);
+                replay.x(0);
+                replay.run(&circuit).unwrap();
+                assert_eq!(replay, qec);
+                let text = Circuit::from_text(&circuit.to_text()).unwrap();
+                assert_eq!(text.ops(), circuit.ops());
+            }
+
+            crate::run_python(
+                cr#"
+from graphsim import GraphSim, Op
+
+sim = GraphSim(1)
+ops = []
+sim.on_operation(lambda op, delta: ops.append(op))
+sim.reset(0, "-")
+assert ops == [Op.Reset(qubit=0), Op.X(qubit=0), Op.H(qubit=0)], ops
+"#,
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_reset_forgets_correlations() {
+            for state in ["0", "1", "+", "-", "+i", "-i"] {
+                let mut qec = GraphSim::new(2);
+                qec.h(0);
+                qec.cx(0, 1);
+                qec.reset(0.into(), state).unwrap();
+                assert_eq!(qec.local_state(0.into()).unwrap(), state);
+                assert!(matches!(qec.local_state(1.into()).unwrap(), "0" | "1"));
+            }
+            assert!(GraphSim::new(1).reset(0.into(), "2").is_err());
+        }
+
+        #[test]
+        fn test_measure_set_of_ghz() {
+            let mut qec = GraphSim::new(5);
+            qec.h(0);
+            for qubit in 1..5 {
+                qec.cx(0, qubit);
+            }
+            let mut copy = qec.clone();
+            let results = qec
+                .measure_set(
+                    QubitList::Indices(vec![4.into(), 0.into(), 2.into(), 4.into()]),
+                    Axis::Z,
+                )
+                .unwrap();
+            assert_eq!(results.keys().copied().collect::<Vec<_>>(), vec![0, 2, 4]);
+            assert!(results.values().all(|&result| result == results[&0]));
+
+            // the X parity of a GHZ state is even
+            let results = copy
+                .measure_set(
+                    QubitList::Indices((0..5).map(QubitIndex::from).collect()),
+                    Axis::X,
+                )
+                .unwrap();
+            let odd = results
+                .values()
+                .filter(|&&result| result == MeasurementResult::MinusOne);
+            assert_eq!(odd.count() % 2, 0);
+        }
+
+        #[test]
+        fn test_expectation_on_qubits() {
+            let mut qec = GraphSim::new(4);
+            qec.h(0);
+            qec.cx(0, 3);
+            qec.x(3);
+            let on = |qubits: [usize; 2]| {
+                Some(QubitList::Indices(qubits.map(QubitIndex::from).to_vec()))
+            };
+            assert_eq!(qec.expectation("ZZ", on([0, 3])).unwrap(), -1.0);
+            assert_eq!(qec.expectation("XX", on([3, 0])).unwrap(), 1.0);
+            assert!(qec.expectation("Z", on([0, 1])).is_err());
+            assert_eq!(qec.expectation("_Z__", None).unwrap(), 1.0);
+            assert_eq!(qec.expectation("Z___", None).unwrap(), 0.0);
+        }
+
+        #[test]
+        fn test_classical_register() {
+            let mut qec = GraphSim::new(3);
+            qec.x(1);
+            qec.measure_z(1);
+            qec.measure_x(0);
+            let mut circuit = Circuit::new(3);
+            circuit.measure(2, Axis::Y);
+            qec.run(&circuit).unwrap();
+            qec.peek_measure(0.into(), None).unwrap();
+            crate::sampling::sample_bitstrings(&qec, 2);
+
+            let records = qec.records();
+            let summary: Vec<(usize, NodeIdx, Axis)> = records
+                .iter()
+                .map(|record| (record.index, record.qubit, record.axis))
+                .collect();
+            assert_eq!(
+                summary,
+                vec![(0, 1, Axis::Z), (1, 0, Axis::X), (2, 2, Axis::Y)]
             );
-            let (outcome_2, det_2) = qec.measure(1, Axis::Y);
-            println!(
-                "state is {:#?} after meas, outcome was {:?}",
-                qec, outcome_2
+            assert_eq!(records[0].outcome, MeasurementResult::MinusOne);
+
+            let mut copy = qec.clone();
+            copy.clear_records();
+            copy.measure_z(0);
+            assert_eq!(copy.records()[0].index, 0);
+            assert_eq!(qec.records().len(), 3);
+        }
+
+        #[test]
+        fn test_teleportation_with_feed_forward() {
+            for _ in 0..16 {
+                // teleport |−i⟩ from qubit 0 to qubit 2
+                let mut qec = GraphSim::new(3);
+                qec.h(0);
+                qec.sdag(0);
+                qec.h(1);
+                qec.cx(1, 2);
+                qec.cx(0, 1);
+                qec.h(0);
+                qec.measure_z(0);
+                qec.measure_z(1);
+                qec.cx_if(Condition::Record(-1), 1.into(), 2.into())
+                    .unwrap();
+                qec.apply_if(Condition::Record(0), "z", vec![2.into()])
+                    .unwrap();
+                assert_eq!(qec.local_state(2.into()).unwrap(), "-i");
+            }
+
+            let mut qec = GraphSim::new(2);
+            qec.x(0);
+            qec.measure_z(0);
+            qec.measure_z(1);
+            assert!(
+                qec.apply_if(Condition::Parity(vec![0, 1]), "x", vec![1.into()])
+                    .unwrap()
+            );
+            assert!(
+                !qec.apply_if(Condition::Parity(vec![0, 0]), "x", vec![1.into()])
+                    .unwrap()
+            );
+            assert!(
+                qec.apply_if(Condition::Record(2), "x", vec![1.into()])
+                    .is_err()
+            );
+            assert!(
+                qec.apply_if(Condition::Record(-3), "x", vec![1.into()])
+                    .is_err()
+            );
+            assert!(
+                qec.apply_if(Condition::Record(0), "cx", vec![1.into()])
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn test_apply_if_parity() {
+            let mut qec = GraphSim::new(4);
+            qec.x(0);
+            qec.x(2);
+            qec.measure_z(0);
+            qec.measure_z(1);
+            qec.measure_z(2);
+            assert!(!qec.apply_if_parity(vec![0, 2], "Z", 3.into()).unwrap());
+            assert!(qec.apply_if_parity(vec![0, 1], "X", 3.into()).unwrap());
+            assert_eq!(qec.local_state(3.into()).unwrap(), "1");
+            assert!(
+                !qec.apply_if_parity(vec![-3, -2, -1], "h", 3.into())
+                    .unwrap()
             );
+            assert!(!qec.apply_if_parity(vec![], "x", 3.into()).unwrap());
+            assert!(qec.apply_if_parity(vec![3], "x", 3.into()).is_err());
+        }
 
-            assert_eq!(det_1, false);
-            assert_eq!(det_2, true);
+        #[test]
+        fn test_incremental_hash() {
+            let mut rng = rand::rng();
+            let mut qec = GraphSim::new(6);
+            for _ in 0..500 {
+                let qubit = rng.random_range(0..6);
+                match rng.random_range(0..4) {
+                    0 => qec.h(qubit),
+                    1 => qec.s(qubit),
+                    2 => qec.cz(qubit, (qubit + rng.random_range(1..6)) % 6),
+                    _ => {
+                        qec.measure(qubit, rng.random());
+                    }
+                }
+                assert_eq!(qec.state_hash(), qec.full_hash());
+            }
 
-            assert_ne!(qec.vop[0].get_state_str(), qec.vop[1].get_state_str());
-            assert_ne!(outcome_1, outcome_2);
+            let mut other = GraphSim::new(2);
+            other.x(0);
+            other.x(0);
+            assert_eq!(other.state_hash(), GraphSim::new(2).state_hash());
         }
 
         #[test]
-        fn test_cnot_measure_z() {
+        fn test_swap_relabels() {
+            let mut rng = rand::rng();
+            for _ in 0..20 {
+                let mut qec = GraphSim::new(6);
+                for _ in 0..30 {
+                    let qubit = rng.random_range(0..6);
+                    match rng.random_range(0..3) {
+                        0 => qec.h(qubit),
+                        1 => qec.s(qubit),
+                        _ => qec.cz(qubit, (qubit + rng.random_range(1..6)) % 6),
+                    }
+                }
+                let (first, second) = (rng.random_range(0..3), rng.random_range(3..6));
+                let mut swapped = qec.clone();
+                swapped.swap(first, second);
+                assert_eq!(swapped.state_hash(), swapped.full_hash());
+                for (control, target) in [(first, second), (second, first), (first, second)] {
+                    qec.cx(control, target);
+                }
+                assert_eq!(
+                    crate::stabilizer::canonical_stabilizers(&swapped),
+                    crate::stabilizer::canonical_stabilizers(&qec)
+                );
+            }
+        }
+
+        #[test]
+        fn test_two_qubit_gates_need_two_qubits() {
+            let mut qec = GraphSim::new(2);
+            qec.h(0);
+            let before = qec.clone();
+            assert!(qec.py_cz(0.into(), 0.into()).is_err());
+            assert!(qec.py_cx(1.into(), QubitIndex::Index(-1)).is_err());
+            assert!(qec.py_swap(1.into(), 1.into()).is_err());
+            assert!(qec.py_cpauli(0.into(), 0.into(), Axis::Y).is_err());
+            assert!(
+                qec.py_cz_many(vec![(0.into(), 1.into()), (1.into(), 1.into())])
+                    .is_err()
+            );
+            let condition = Condition::Parity(Vec::new());
+            assert!(qec.cx_if(condition, 0.into(), 0.into()).is_err());
+            assert_eq!(qec, before);
+        }
+
+        #[test]
+        fn test_cx_fanout_matches_cx() {
+            let mut rng = rand::rng();
+            for _ in 0..20 {
+                let mut qec = GraphSim::new(8);
+                for _ in 0..40 {
+                    let qubit = rng.random_range(0..8);
+                    match rng.random_range(0..3) {
+                        0 => qec.h(qubit),
+                        1 => qec.s(qubit),
+                        _ => qec.cz(qubit, (qubit + rng.random_range(1..8)) % 8),
+                    }
+                }
+                let control = rng.random_range(0..8);
+                let targets: Vec<NodeIdx> = (1..8)
+                    .map(|offset| (control + offset) % 8)
+                    .filter(|_| rng.random())
+                    .collect();
+                let mut fanned = qec.clone();
+                fanned.cx_fanout(control, &targets);
+                assert_eq!(fanned.state_hash(), fanned.full_hash());
+                for &target in &targets {
+                    qec.cx(control, target);
+                }
+                assert_eq!(
+                    crate::stabilizer::canonical_stabilizers(&fanned),
+                    crate::stabilizer::canonical_stabilizers(&qec)
+                );
+            }
+        }
+
+        #[test]
+        fn test_cz_many_matches_cz() {
+            let mut rng = rand::rng();
+            let mut ring = GraphSim::new(5);
+            for qubit in 0..5 {
+                ring.h(qubit);
+            }
+            ring.cz_many(&[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+            assert_eq!(ring.adjacent[0], BitSet::from_iter([1, 4]));
+            assert_eq!(ring.vop[0], Vop::IA);
+
+            for _ in 0..20 {
+                let mut qec = GraphSim::new(6);
+                for _ in 0..20 {
+                    let qubit = rng.random_range(0..6);
+                    match rng.random_range(0..3) {
+                        0 => qec.h(qubit),
+                        1 => qec.s(qubit),
+                        _ => qec.cz(qubit, (qubit + rng.random_range(1..6)) % 6),
+                    }
+                }
+                let edges: Vec<(NodeIdx, NodeIdx)> = (0..8)
+                    .map(|_| {
+                        let control = rng.random_range(0..6);
+                        (control, (control + rng.random_range(1..6)) % 6)
+                    })
+                    .collect();
+                let mut batched = qec.clone();
+                batched.cz_many(&edges);
+                assert_eq!(batched.state_hash(), batched.full_hash());
+                for &(control, target) in &edges {
+                    qec.cz(control, target);
+                }
+                assert_eq!(
+                    crate::stabilizer::canonical_stabilizers(&batched),
+                    crate::stabilizer::canonical_stabilizers(&qec)
+                );
+            }
+        }
+
+        #[test]
+        fn test_clifford_rotations() {
+            use std::f64::consts::{FRAC_PI_2, PI};
+            let mut qec = GraphSim::new(3);
+            qec.rx(0.into(), FRAC_PI_2).unwrap();
+            assert_eq!(qec.local_state(0.into()).unwrap(), "-i");
+            qec.rx(0.into(), -FRAC_PI_2).unwrap();
+            assert_eq!(qec.local_state(0.into()).unwrap(), "0");
+            qec.ry(1.into(), 3.0 * PI).unwrap();
+            assert_eq!(qec.local_state(1.into()).unwrap(), "1");
+            qec.h(2);
+            qec.rz(2.into(), FRAC_PI_2).unwrap();
+            assert_eq!(qec.local_state(2.into()).unwrap(), "+i");
+            qec.rz(2.into(), 2.0 * PI).unwrap();
+            assert_eq!(qec.local_state(2.into()).unwrap(), "+i");
+            assert!(qec.rz(2.into(), PI / 4.0).is_err());
+            assert_eq!(qec.local_state(2.into()).unwrap(), "+i");
+        }
+
+        #[test]
+        fn test_apply_local_clifford() {
+            for idx in 0..crate::SYMMETRIES {
+                let mut qec = GraphSim::new(1);
+                qec.h(0);
+                qec.apply_local_clifford(0.into(), LocalClifford::Index(idx))
+                    .unwrap();
+                assert_eq!(qec.vop[0], Vop::from_index(idx));
+                let label = format!("{:?}", Vop::from_index(idx));
+                qec.apply_local_clifford(0.into(), LocalClifford::Name(label))
+                    .unwrap();
+                assert_eq!(qec.vop[0], Vop::from_index(idx) * Vop::from_index(idx));
+            }
+
+            let mut qec = GraphSim::new(1);
+            qec.apply_local_clifford(0.into(), LocalClifford::Name("HS".into()))
+                .unwrap();
+            assert_eq!(qec.local_state(0.into()).unwrap(), "+");
+            qec.apply_local_clifford(0.into(), LocalClifford::Name("HS".into()))
+                .unwrap();
+            assert_eq!(qec.local_state(0.into()).unwrap(), "-i");
+            assert_eq!(LocalClifford::Name("S†".into()).vop().unwrap(), SDAG_GATE);
+            assert_eq!(LocalClifford::Name("S'S".into()).vop().unwrap(), Vop::IA);
+            assert!(
+                qec.apply_local_clifford(0.into(), LocalClifford::Index(24))
+                    .is_err()
+            );
+            assert!(
+                qec.apply_local_clifford(0.into(), LocalClifford::Name("HT".into()))
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn test_apply_clifford_tableau() {
+            // CX from the first to the second qubit followed by X on both, negating Z₀
+            let cx = vec![
+                vec![true, true, false, false],
+                vec![false, true, false, false],
+                vec![false, false, true, false],
+                vec![false, false, true, true],
+            ];
+            let signs = vec![false, false, true, false];
+            let mut qec = GraphSim::new(3);
+            qec.h(2);
+            qec.s(2);
+            qec.apply_clifford(cx.clone(), vec![2, 0].into(), Some(signs))
+                .unwrap();
+            let mut expected = GraphSim::new(3);
+            expected.h(2);
+            expected.s(2);
+            expected.cx(2, 0);
+            expected.x(2);
+            expected.x(0);
+            assert_eq!(
+                crate::stabilizer::canonical_stabilizers(&qec),
+                crate::stabilizer::canonical_stabilizers(&expected)
+            );
+            assert!(
+                qec.apply_clifford(cx.clone(), vec![0, 1, 2].into(), None)
+                    .is_err()
+            );
+            assert!(qec.apply_clifford(cx, vec![1, 1].into(), None).is_err());
+        }
+
+        #[test]
+        fn test_cpauli_dispatches_on_axis() {
+            for (axis, name) in [(Axis::X, "cx"), (Axis::Y, "cy"), (Axis::Z, "cz")] {
+                let mut qec = GraphSim::new(2);
+                qec.h(0);
+                qec.start_recording();
+                qec.cpauli(0, 1, axis);
+                let mut expected = GraphSim::new(2);
+                expected.h(0);
+                crate::text::parse_op(name, &[0, 1])
+                    .unwrap()
+                    .apply(&mut expected);
+                assert_eq!(qec, expected);
+                assert_eq!(qec.recorded_circuit().unwrap().iter().count(), 1);
+            }
+        }
+
+        #[test]
+        fn test_fused_local_complementations() {
+            let mut rng = rand::rng();
+            // the last qubits are far apart, so their neighbour sets are toggled pairwise
+            for qubits in [6, 200] {
+                let mut qec = GraphSim::new(qubits);
+                for qubit in 0..qubits {
+                    qec.h(qubit);
+                }
+                let hub = qubits - 6;
+                for _ in 0..12 {
+                    let a = hub + rng.random_range(0..6);
+                    let b = hub + rng.random_range(0..6);
+                    if a != b {
+                        qec.cz(a, b);
+                    }
+                }
+                let state = qec.canonical_hash();
+                for times in 0..6 {
+                    let node = hub + rng.random_range(0..6);
+                    let mut single = qec.clone();
+                    for _ in 0..times {
+                        single.local_comp(node);
+                    }
+                    qec.local_comps(node, times);
+                    assert_eq!(qec, single);
+                    assert_eq!(qec.state_hash(), qec.full_hash());
+                    assert_eq!(qec.canonical_hash(), state);
+                }
+            }
+        }
+
+        #[test]
+        fn test_peek_measure_set_is_ordered() {
+            let mut qec = GraphSim::new(4);
+            qec.x(1);
+            qec.h(3);
+
+            let outcomes =
+                qec.peek_measure_axes(&BTreeMap::from([(3, None), (1, None), (0, None)]));
+            let outcomes: Vec<_> = outcomes
+                .iter()
+                .map(|(&qubit, outcome)| (qubit, outcome.axis, outcome.result))
+                .collect();
+            assert_eq!(
+                outcomes,
+                vec![
+                    (0, Axis::Z, MeasurementResult::PlusOne),
+                    (1, Axis::Z, MeasurementResult::MinusOne),
+                    (3, Axis::X, MeasurementResult::PlusOne),
+                ]
+            );
+            assert_eq!(qec, {
+                let mut fresh = GraphSim::new(4);
+                fresh.x(1);
+                fresh.h(3);
+                fresh
+            });
+
+            let outcomes = qec.peek_measure_axes(&BTreeMap::from([(1, Some(Axis::X))]));
+            assert_eq!(outcomes[&1].axis, Axis::X);
+        }
+
+        #[test]
+        fn test_peek_measure_set_takes_axes() {
+            crate::run_python(
+                cr#"
+from graphsim import Axis, GraphSim, MeasurementResult
+
+sim = GraphSim(3)
+sim.x(1)
+sim.h(2)
+
+outcomes = sim.peek_measure_set({2: Axis.Z, 0: Axis.X, 1: None})
+assert list(outcomes) == [0, 1, 2], outcomes
+assert outcomes[0].axis == Axis.X
+assert outcomes[1].axis == Axis.Z
+assert outcomes[1].result == MeasurementResult.MinusOne
+assert outcomes[2].axis == Axis.Z
+
+outcomes = sim.peek_measure_set({1: None, 2: None})
+assert outcomes[1].axis == Axis.Z
+assert outcomes[2].axis == Axis.X
+assert outcomes[2].result == MeasurementResult.PlusOne
+assert sim.peek_measure_set({2})[2].axis == Axis.X
+"#,
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_peek_measure_single_qubit() {
+            let mut qec = GraphSim::new(3);
+            qec.x(1);
+            qec.h(2);
+            qec.cx(2, 0);
+            let before = qec.clone();
+
+            let outcome = qec.peek_measure(1.into(), None).unwrap();
+            assert_eq!(
+                (outcome.axis, outcome.result),
+                (Axis::Z, MeasurementResult::MinusOne)
+            );
+            assert_eq!(
+                qec.peek_measure(1.into(), Some(Axis::X)).unwrap().axis,
+                Axis::X
+            );
+            // qubits 0 and 2 form a Bell pair, so no axis gives a certain outcome
+            assert!(!qec.adjacency()[0].is_empty());
+            let outcome = qec.peek_measure(0.into(), None).unwrap();
+            assert_eq!(
+                qec.probability(0.into(), outcome.axis, outcome.result)
+                    .unwrap(),
+                0.5
+            );
+            assert_eq!(qec, before);
+        }
+
+        #[test]
+        fn test_local_state() {
+            let mut qec = GraphSim::new(3);
+            qec.h(1);
+            qec.s(1);
+            qec.x(2);
+            assert_eq!(qec.local_state(0.into()).unwrap(), "0");
+            assert_eq!(qec.local_state(1.into()).unwrap(), "+i");
+            assert_eq!(
+                qec.deterministic_outcome(1, Axis::Y),
+                Some(MeasurementResult::PlusOne)
+            );
+            assert_eq!(qec.local_state(2.into()).unwrap(), "1");
+
+            qec.h(0);
+            qec.cx(0, 2);
+            assert_eq!(qec.local_state(2.into()).unwrap(), "entangled");
+            qec.cx(0, 2);
+            qec.h(0);
+            assert_eq!(qec.local_state(0.into()).unwrap(), "0");
+            assert!(qec.local_state(3.into()).is_err());
+        }
+
+        #[test]
+        fn test_branch_measure_bell() {
             let mut qec = GraphSim::new(2);
+            qec.h(0);
+            qec.cx(0, 1);
+
+            let [(p_plus, plus), (p_minus, minus)] = qec.branch_measure(0.into(), Axis::Z).unwrap();
+            assert_eq!((p_plus, p_minus), (0.5, 0.5));
+            let (plus, minus) = (plus.unwrap(), minus.unwrap());
+            assert_eq!(
+                plus.deterministic_outcome(1, Axis::Z),
+                Some(MeasurementResult::PlusOne)
+            );
+            assert_eq!(
+                minus.deterministic_outcome(1, Axis::Z),
+                Some(MeasurementResult::MinusOne)
+            );
+
+            let [(p_plus, _), (p_minus, none)] = plus.branch_measure(1.into(), Axis::Z).unwrap();
+            assert_eq!((p_plus, p_minus), (1.0, 0.0));
+            assert!(none.is_none());
+        }
 
+        #[test]
+        fn test_recording_logs_calls() {
+            let mut qec = GraphSim::new(3);
             qec.h(0);
+            qec.start_recording();
             qec.cx(0, 1);
+            qec.xcz(2, 1);
+            qec.measure_y(2);
+            qec.run(&Circuit::from_text("new_qubit 3\nmeasure_x 0").unwrap())
+                .unwrap();
+            let unrecorded = {
+                let mut sim = GraphSim::new(3);
+                sim.h(0);
+                sim.cx(0, 1);
+                sim.xcz(2, 1);
+                sim.measure_y(2);
+                sim.measure_x(0);
+                sim
+            };
 
-            println!("state is {:#?} before meas", qec);
-            let (outcome_1, det_1) = qec.measure(0, Axis::Z);
-            println!(
-                "state is {:#?} between meas, outcome was {:?}",
-                qec, outcome_1
+            let circuit = qec.stop_recording().unwrap();
+            let names: Vec<String> = circuit.iter().map(|op| op.to_string()).collect();
+            assert_eq!(
+                names,
+                vec!["cx 0 1", "cx 1 2", "measure_Y 2", "measure_X 0"]
             );
-            let (outcome_2, det_2) = qec.measure(1, Axis::Z);
-            println!(
-                "state is {:#?} after meas, outcome was {:?}",
-                qec, outcome_2
+            assert!(qec.recorded_circuit().is_none());
+            assert_eq!(qec.adjacent, unrecorded.adjacent);
+        }
+
+        #[test]
+        fn test_measurement_history() {
+            let mut sim = GraphSim::new(2);
+            sim.measure_z(0);
+            sim.start_recording();
+            sim.x(0);
+            assert_eq!(sim.measure_z(0), MeasurementResult::MinusOne);
+            sim.h(0);
+            let second = sim.measure_x(0);
+            sim.measure_z(1);
+
+            let history = sim.history(QubitIndex::Index(0)).unwrap();
+            let events: Vec<(usize, Axis)> = history.iter().map(|e| (e.op_index, e.axis)).collect();
+            assert_eq!(events, vec![(1, Axis::Z), (3, Axis::X)]);
+            assert_eq!(history[0].outcome, MeasurementResult::MinusOne);
+            assert_eq!(
+                sim.last_outcome(QubitIndex::Index(0)).unwrap(),
+                Some(second)
             );
+            assert_eq!(sim.history(QubitIndex::Index(1)).unwrap().len(), 1);
 
-            assert_eq!(det_1, false);
-            assert_eq!(det_2, true);
+            sim.stop_recording();
+            assert!(sim.last_outcome(QubitIndex::Index(0)).is_err());
+        }
 
-            assert_eq!(qec.vop[0].get_state_str(), qec.vop[1].get_state_str());
-            assert_eq!(outcome_1, outcome_2);
+        #[test]
+        fn test_node_and_edge_iterators() {
+            let mut qec = GraphSim::new(4);
+            for qubit in 0..4 {
+                qec.h(qubit);
+            }
+            qec.cz(2, 0);
+            qec.cz(2, 1);
+            qec.trace_out(vec![3]);
+
+            assert_eq!(qec.nodes().collect::<Vec<_>>(), vec![0, 1, 2]);
+            assert_eq!(qec.edges().collect::<Vec<_>>(), vec![(0, 2), (1, 2)]);
+            assert_eq!(qec.neighbors(2).collect::<Vec<_>>(), vec![0, 1]);
+            assert_eq!(qec.neighbors(3).count(), 0);
+        }
+
+        #[test]
+        fn test_classes_are_thread_safe() {
+            fn assert_thread_safe<T: Send + Sync>() {}
+            assert_thread_safe::<GraphSim>();
+            assert_thread_safe::<Circuit>();
+            assert_thread_safe::<Debugger>();
+            assert_thread_safe::<EdgeIterator>();
+            assert_thread_safe::<MixedGraphSim>();
+            assert_thread_safe::<NoiseModel>();
+            assert_thread_safe::<PauliFrame>();
+            assert_thread_safe::<Pattern>();
+        }
+
+        #[test]
+        fn test_display_and_debug() {
+            let mut qec = GraphSim::new(40);
+            for qubit in 1..40 {
+                qec.h(0);
+                qec.h(qubit);
+                qec.cz(0, qubit);
+                qec.h(0);
+            }
+            qec.discard(39, MeasurementResult::PlusOne);
+            assert_eq!(
+                qec.to_string(),
+                "GraphSim(40 qubits, 38 edges, 1 traced out)"
+            );
+
+            let debug = format!("{qec:?}");
+            assert!(debug.starts_with(
+                "GraphSim { qubits: 40, edges: 38, nodes: [Node { idx: 0, vop: YC, neighbours: \
+                 [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, ..] }"
+            ));
+            assert!(debug.ends_with(
+                "Node { idx: 15, vop: IA, neighbours: [0] }, ..], traced: [39], \
+                 recording: false, .. }"
+            ));
+        }
+
+        #[test]
+        fn test_shrink_after_measurements() {
+            let mut qec = GraphSim::new(1000);
+            for qubit in 0..1000 {
+                qec.h(qubit);
+            }
+            for qubit in 0..999 {
+                qec.cz(qubit, qubit + 1);
+            }
+            for qubit in 0..990 {
+                qec.measure(qubit, Axis::Z);
+            }
+            let edges: Vec<_> = qec.edges().collect();
+            let vops = qec.vop.to_vec();
+
+            assert!(qec.shrink_to_fit() > 0);
+            assert_eq!(qec.shrink_to_fit(), 0);
+            assert_eq!(qec.edges().collect::<Vec<_>>(), edges);
+            assert_eq!(qec.vop.to_vec(), vops);
+            assert!(qec.adjacent.iter().take(990).all(|adj| adj.capacity() == 0));
+        }
+
+        #[test]
+        fn test_shrink_keeps_copies_shared() {
+            let mut sim = GraphSim::new(200);
+            for qubit in 0..199 {
+                sim.h(qubit);
+                sim.cz(qubit, qubit + 1);
+            }
+            for qubit in 0..199 {
+                sim.measure(qubit, Axis::Z);
+            }
+            let copy = sim.clone();
+            let shared = sim.adjacent.shared_chunks();
+
+            // nothing is owned by `sim` alone, so nothing is copied or released
+            assert_eq!(sim.shrink_to_fit(), 0);
+            assert_eq!(sim.adjacent.shared_chunks(), shared);
+            assert_eq!(sim, copy);
+
+            drop(copy);
+            assert!(sim.shrink_to_fit() > 0);
         }
     }
 }
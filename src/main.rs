@@ -0,0 +1,48 @@
+//! Command line interface, built with the `server` feature.
+//!
+//! `quantum-graphsim serve [ADDR [MAX_QUBITS]]` answers newline-delimited JSON requests on
+//! `ADDR`, `127.0.0.1:7878` by default, and creates simulators of at most `MAX_QUBITS`
+//! qubits, `graphsim::server::DEFAULT_MAX_QUBITS` by default. See `graphsim::server` for
+//! the protocol.
+
+use std::{env, process::ExitCode};
+
+const USAGE: &str = "Usage: quantum-graphsim serve [ADDR [MAX_QUBITS]]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["serve", rest @ ..] if rest.len() <= 2 => {
+            let addr = rest.first().copied().unwrap_or("127.0.0.1:7878");
+            let max_qubits = match rest.get(1).map(|max| max.parse()) {
+                None => graphsim::server::DEFAULT_MAX_QUBITS,
+                Some(Ok(max)) => max,
+                Some(Err(_)) => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            eprintln!("Listening on {addr}");
+            match graphsim::server::serve(addr, max_qubits) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("Can't serve on {addr}: {err}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        ["help" | "--help" | "-h"] => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
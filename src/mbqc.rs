@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    frame::PauliFrame,
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Command {
+    Entangle(NodeIdx, NodeIdx),
+    Measure(NodeIdx, Axis),
+    CorrectX(NodeIdx, Vec<usize>),
+    CorrectZ(NodeIdx, Vec<usize>),
+}
+
+/// A measurement pattern for measurement-based quantum computing.
+///
+/// All qubits start in |+⟩. Commands are entangling CZs, Pauli measurements producing
+/// signals (numbered in order of the measurements) and X/Z corrections conditioned on
+/// the parity of a domain of earlier signals. Corrections are not applied to the state
+/// but tracked symbolically as the Pauli byproduct of every qubit, see `run`.
+#[pyclass]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pattern {
+    qubit_amount: usize,
+    commands: Vec<Command>,
+    signals: usize,
+}
+
+/// Symbolic Pauli byproduct X^(x_domain) Z^(z_domain) on a single qubit.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Byproduct {
+    /// Signals whose parity gives the exponent of X.
+    x_domain: Vec<usize>,
+    /// Signals whose parity gives the exponent of Z.
+    z_domain: Vec<usize>,
+}
+
+/// Outcome of running a `Pattern`.
+#[pyclass(frozen)]
+#[derive(Clone, Debug)]
+pub struct PatternResult {
+    signals: Vec<MeasurementResult>,
+    x_domains: Vec<BitSet>,
+    z_domains: Vec<BitSet>,
+    measured: BitSet,
+    state: GraphSim,
+}
+
+fn parity(domain: &BitSet, signals: &[MeasurementResult]) -> bool {
+    domain
+        .iter()
+        .filter(|&sig| signals[sig] == MeasurementResult::MinusOne)
+        .count()
+        % 2
+        == 1
+}
+
+impl Pattern {
+    fn check_qubit(&self, qubit: NodeIdx) -> PyResult<()> {
+        match qubit < self.qubit_amount {
+            true => Ok(()),
+            false => Err(PyValueError::new_err(format!(
+                "Qubit {qubit} is out of range for a pattern of {} qubits",
+                self.qubit_amount
+            ))),
+        }
+    }
+
+    fn check_domain(&self, domain: &[usize]) -> PyResult<()> {
+        match domain.iter().find(|&&sig| sig >= self.signals) {
+            Some(sig) => Err(PyValueError::new_err(format!(
+                "Signal {sig} is not measured yet, only {} signals exist",
+                self.signals
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[pymethods]
+impl Pattern {
+    /// Create an empty pattern over `qubit_amount` qubits.
+    #[new]
+    pub fn new(qubit_amount: usize) -> Pattern {
+        Pattern {
+            qubit_amount,
+            ..Default::default()
+        }
+    }
+
+    /// Entangle qubits `a` and `b` with a CZ.
+    pub fn entangle(&mut self, a: NodeIdx, b: NodeIdx) -> PyResult<()> {
+        self.check_qubit(a)?;
+        self.check_qubit(b)?;
+        if a == b {
+            return Err(PyValueError::new_err(format!(
+                "Can't entangle qubit {a} with itself"
+            )));
+        }
+        self.commands.push(Command::Entangle(a, b));
+        Ok(())
+    }
+
+    /// Measure `qubit` along `axis`, returning the index of the resulting signal.
+    ///
+    /// The signal is the outcome relative to the byproduct at that point, i.e. what
+    /// the measurement would give had all earlier corrections been applied.
+    pub fn measure(&mut self, qubit: NodeIdx, axis: Axis) -> PyResult<usize> {
+        self.check_qubit(qubit)?;
+        self.commands.push(Command::Measure(qubit, axis));
+        self.signals += 1;
+        Ok(self.signals - 1)
+    }
+
+    /// Correct `qubit` with X if the parity of the signals in `domain` is odd.
+    pub fn x(&mut self, qubit: NodeIdx, domain: Vec<usize>) -> PyResult<()> {
+        self.check_qubit(qubit)?;
+        self.check_domain(&domain)?;
+        self.commands.push(Command::CorrectX(qubit, domain));
+        Ok(())
+    }
+
+    /// Correct `qubit` with Z if the parity of the signals in `domain` is odd.
+    pub fn z(&mut self, qubit: NodeIdx, domain: Vec<usize>) -> PyResult<()> {
+        self.check_qubit(qubit)?;
+        self.check_domain(&domain)?;
+        self.commands.push(Command::CorrectZ(qubit, domain));
+        Ok(())
+    }
+
+    /// Execute the pattern on a fresh simulator, tracking byproducts symbolically.
+    ///
+    /// Byproducts move through later CZs like Pauli errors and adjust the signals of
+    /// measurements they anticommute with, so no correction touches the state.
+    pub fn run(&self) -> PatternResult {
+        let mut state = GraphSim::new(self.qubit_amount);
+        for qubit in 0..self.qubit_amount {
+            state.h(qubit);
+        }
+        let mut x_domains = vec![BitSet::new(); self.qubit_amount];
+        let mut z_domains = vec![BitSet::new(); self.qubit_amount];
+        let mut measured = BitSet::with_capacity(self.qubit_amount);
+        let mut signals = Vec::with_capacity(self.signals);
+
+        for command in &self.commands {
+            match command {
+                &Command::Entangle(a, b) => {
+                    state.cz(a, b);
+                    let (x_a, x_b) = (x_domains[a].clone(), x_domains[b].clone());
+                    z_domains[b].symmetric_difference_with(&x_a);
+                    z_domains[a].symmetric_difference_with(&x_b);
+                }
+                &Command::Measure(qubit, axis) => {
+                    let (raw, _) = state.measure(qubit, axis);
+                    let anticommuting = match axis {
+                        Axis::X => z_domains[qubit].clone(),
+                        Axis::Y => x_domains[qubit]
+                            .symmetric_difference(&z_domains[qubit])
+                            .collect(),
+                        Axis::Z => x_domains[qubit].clone(),
+                    };
+                    signals.push(match parity(&anticommuting, &signals) {
+                        true => !raw,
+                        false => raw,
+                    });
+                    x_domains[qubit].clear();
+                    z_domains[qubit].clear();
+                    measured.insert(qubit);
+                }
+                Command::CorrectX(qubit, domain) => {
+                    let domain: BitSet = domain.iter().copied().collect();
+                    x_domains[*qubit].symmetric_difference_with(&domain);
+                }
+                Command::CorrectZ(qubit, domain) => {
+                    let domain: BitSet = domain.iter().copied().collect();
+                    z_domains[*qubit].symmetric_difference_with(&domain);
+                }
+            }
+        }
+
+        PatternResult {
+            signals,
+            x_domains,
+            z_domains,
+            measured,
+            state,
+        }
+    }
+}
+
+#[pymethods]
+impl PatternResult {
+    /// Signals of the measurements, in the order they were measured.
+    #[getter]
+    pub fn signals(&self) -> Vec<MeasurementResult> {
+        self.signals.clone()
+    }
+
+    /// The simulated state, without any byproduct corrections applied.
+    #[getter]
+    pub fn state(&self) -> GraphSim {
+        self.state.clone()
+    }
+
+    /// Symbolic byproducts of the unmeasured qubits that carry one.
+    pub fn byproducts(&self) -> HashMap<NodeIdx, Byproduct> {
+        (0..self.x_domains.len())
+            .filter(|&qubit| !self.measured.contains(qubit))
+            .filter(|&qubit| !self.x_domains[qubit].is_empty() || !self.z_domains[qubit].is_empty())
+            .map(|qubit| {
+                let byproduct = Byproduct {
+                    x_domain: self.x_domains[qubit].iter().collect(),
+                    z_domain: self.z_domains[qubit].iter().collect(),
+                };
+                (qubit, byproduct)
+            })
+            .collect()
+    }
+
+    /// The byproducts evaluated with the actual signals.
+    pub fn byproduct_frame(&self) -> PauliFrame {
+        let mut frame = PauliFrame::new();
+        for qubit in (0..self.x_domains.len()).filter(|&qubit| !self.measured.contains(qubit)) {
+            if parity(&self.x_domains[qubit], &self.signals) {
+                frame.mul_pauli(qubit, Axis::X);
+            }
+            if parity(&self.z_domains[qubit], &self.signals) {
+                frame.mul_pauli(qubit, Axis::Z);
+            }
+        }
+        frame
+    }
+
+    /// The state with all byproducts corrected, as if the pattern applied them eagerly.
+    pub fn corrected_state(&self) -> GraphSim {
+        let mut state = self.state.clone();
+        state.apply_frame(&self.byproduct_frame());
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_teleport_byproduct() {
+        let mut pattern = Pattern::new(2);
+        pattern.entangle(0, 1).unwrap();
+        let s0 = pattern.measure(0, Axis::X).unwrap();
+        pattern.x(1, vec![s0]).unwrap();
+
+        let result = pattern.run();
+        let byproducts = result.byproducts();
+        assert_eq!(byproducts[&1].x_domain, vec![s0]);
+        assert!(byproducts[&1].z_domain.is_empty());
+
+        // J(0) maps |+⟩ to H|+⟩ = |0⟩
+        let mut corrected = result.corrected_state();
+        assert_eq!(
+            corrected.measure(1, Axis::Z),
+            (MeasurementResult::PlusOne, true)
+        );
+    }
+
+    #[test]
+    fn test_two_step_wire() {
+        let mut pattern = Pattern::new(3);
+        pattern.entangle(0, 1).unwrap();
+        pattern.entangle(1, 2).unwrap();
+        let s0 = pattern.measure(0, Axis::X).unwrap();
+        pattern.x(1, vec![s0]).unwrap();
+        let s1 = pattern.measure(1, Axis::X).unwrap();
+        pattern.x(2, vec![s1]).unwrap();
+        pattern.z(2, vec![s0]).unwrap();
+
+        for _ in 0..20 {
+            let result = pattern.run();
+            let mut corrected = result.corrected_state();
+            assert_eq!(
+                corrected.measure(2, Axis::X),
+                (MeasurementResult::PlusOne, true)
+            );
+        }
+    }
+
+    #[test]
+    fn test_domain_must_exist() {
+        let mut pattern = Pattern::new(2);
+        assert!(pattern.x(1, vec![0]).is_err());
+    }
+
+    #[test]
+    fn test_qubits_must_exist() {
+        let mut pattern = Pattern::new(2);
+        assert!(pattern.entangle(0, 2).is_err());
+        assert!(pattern.entangle(1, 1).is_err());
+        assert!(pattern.measure(2, Axis::X).is_err());
+        assert!(pattern.x(2, vec![]).is_err());
+        assert!(pattern.z(2, vec![]).is_err());
+        assert_eq!(pattern, Pattern::new(2));
+    }
+}
@@ -0,0 +1,379 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+
+use crate::{
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    index::{QubitIndex, QubitList},
+};
+
+/// Classical mixture of graph states, stored as `(probability, GraphSim)` branches.
+///
+/// Gates act on every branch, Pauli channels split each branch into its error
+/// outcomes and measurements condition the whole mixture on the sampled result.
+/// Branches with an identical graph and VOPs are merged, and branches whose
+/// probability drops below `prune_threshold` are discarded (the remaining weights are
+/// renormalized).
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct MixedGraphSim {
+    branches: Vec<(f64, GraphSim)>,
+    prune_threshold: f64,
+}
+
+impl MixedGraphSim {
+    fn for_each_branch(&mut self, mut op: impl FnMut(&mut GraphSim)) {
+        for (_, sim) in self.branches.iter_mut() {
+            op(sim);
+        }
+    }
+
+    /// A branch, which has the same qubits as every other branch.
+    fn any_branch(&self) -> &GraphSim {
+        &self.branches[0].1
+    }
+
+    /// Apply the single-qubit gate `gate` to `qubit` on every branch.
+    fn gate(&mut self, qubit: QubitIndex, gate: impl Fn(&mut GraphSim, NodeIdx)) -> PyResult<()> {
+        let qubit = self.any_branch().resolve(qubit)?;
+        self.for_each_branch(|sim| gate(sim, qubit));
+        Ok(())
+    }
+
+    /// Apply the two-qubit gate `gate` to `control` and `target` on every branch.
+    fn two_qubit_gate(
+        &mut self,
+        control: QubitIndex,
+        target: QubitIndex,
+        gate: impl Fn(&mut GraphSim, NodeIdx, NodeIdx),
+    ) -> PyResult<()> {
+        let (control, target) = self.any_branch().resolve_pair(control, target)?;
+        self.for_each_branch(|sim| gate(sim, control, target));
+        Ok(())
+    }
+
+    /// Merge branches with identical states and drop the ones below the threshold.
+    ///
+    /// Scales as O(b * (n + E)) for b branches.
+    fn compact(&mut self) {
+        let hasher = RandomState::new();
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::with_capacity(self.branches.len());
+        let mut merged: Vec<(f64, GraphSim)> = Vec::with_capacity(self.branches.len());
+
+        for (prob, sim) in self.branches.drain(..) {
+            let bucket = seen.entry(hasher.hash_one(&sim)).or_default();
+            match bucket.iter().find(|&&idx| merged[idx].1 == sim) {
+                Some(&idx) => merged[idx].0 += prob,
+                None => {
+                    bucket.push(merged.len());
+                    merged.push((prob, sim));
+                }
+            }
+        }
+
+        let threshold = self.prune_threshold;
+        let most_likely = merged.iter().map(|(prob, _)| *prob).fold(0.0, f64::max);
+        merged.retain(|(prob, _)| *prob >= threshold || *prob == most_likely);
+
+        let total: f64 = merged.iter().map(|(prob, _)| prob).sum();
+        for (prob, _) in merged.iter_mut() {
+            *prob /= total;
+        }
+        self.branches = merged;
+    }
+}
+
+/// Probability that measuring `node` along `axis` on a pure state yields `result`.
+fn outcome_probability(
+    sim: &GraphSim,
+    node: NodeIdx,
+    axis: Axis,
+    result: MeasurementResult,
+) -> f64 {
+    match sim.deterministic_outcome(node, axis) {
+        Some(res) if res == result => 1.0,
+        Some(_) => 0.0,
+        None => 0.5,
+    }
+}
+
+#[pymethods]
+impl MixedGraphSim {
+    /// Create a mixture holding a single branch of `qubit_amount` qubits in the |0⟩ state.
+    ///
+    /// Branches with a probability below `prune_threshold` are discarded.
+    #[new]
+    #[pyo3(signature = (qubit_amount, prune_threshold = 1e-9))]
+    pub fn new(qubit_amount: usize, prune_threshold: f64) -> MixedGraphSim {
+        MixedGraphSim {
+            branches: vec![(1.0, GraphSim::new(qubit_amount))],
+            prune_threshold,
+        }
+    }
+
+    /// Create a mixture holding only the pure state `sim`.
+    #[staticmethod]
+    #[pyo3(signature = (sim, prune_threshold = 1e-9))]
+    pub fn from_pure(sim: PyRef<'_, GraphSim>, prune_threshold: f64) -> MixedGraphSim {
+        MixedGraphSim {
+            branches: vec![(1.0, sim.clone())],
+            prune_threshold,
+        }
+    }
+
+    /// Return the `(probability, GraphSim)` branches of the mixture.
+    pub fn branches(&self) -> Vec<(f64, GraphSim)> {
+        self.branches.clone()
+    }
+
+    /// Number of branches currently in the mixture.
+    pub fn __len__(&self) -> usize {
+        self.branches.len()
+    }
+
+    /// Apply an X (Pauli-X) gate to the given qubit on every branch.
+    pub fn x(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        self.gate(qubit, GraphSim::x)
+    }
+
+    /// Apply a Y (Pauli-Y) gate to the given qubit on every branch.
+    pub fn y(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        self.gate(qubit, GraphSim::y)
+    }
+
+    /// Apply a Z (Pauli-Z) gate to the given qubit on every branch.
+    pub fn z(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        self.gate(qubit, GraphSim::z)
+    }
+
+    /// Apply an H (Hadamard) gate to the given qubit on every branch.
+    pub fn h(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        self.gate(qubit, GraphSim::h)
+    }
+
+    /// Apply an S (phase) gate to the given qubit on every branch.
+    pub fn s(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        self.gate(qubit, GraphSim::s)
+    }
+
+    /// Apply an S† (inverse phase) gate to the given qubit on every branch.
+    pub fn sdag(&mut self, qubit: QubitIndex) -> PyResult<()> {
+        self.gate(qubit, GraphSim::sdag)
+    }
+
+    /// Apply a controlled-Z (CZ) gate with `control` and `target` qubits on every branch.
+    pub fn cz(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        self.two_qubit_gate(control, target, GraphSim::cz)
+    }
+
+    /// Apply a controlled-X (CX) / CNOT gate with `control` and `target` on every branch.
+    pub fn cx(&mut self, control: QubitIndex, target: QubitIndex) -> PyResult<()> {
+        self.two_qubit_gate(control, target, GraphSim::cx)
+    }
+
+    /// Apply X, Y and Z errors to `qubit` with probabilities `px`, `py` and `pz`.
+    ///
+    /// Every branch is split into one branch per possible error.
+    pub fn pauli_channel(&mut self, qubit: QubitIndex, px: f64, py: f64, pz: f64) -> PyResult<()> {
+        let qubit = self.any_branch().resolve(qubit)?;
+        if [px, py, pz].iter().any(|p| !(0.0..=1.0).contains(p)) || px + py + pz > 1.0 {
+            return Err(PyValueError::new_err(
+                "Pauli error probabilities must be non-negative and sum to at most 1",
+            ));
+        }
+
+        let mut split = Vec::with_capacity(self.branches.len() * 4);
+        for (prob, sim) in self.branches.drain(..) {
+            for (p_err, err) in [(px, Axis::X), (py, Axis::Y), (pz, Axis::Z)] {
+                if p_err > 0.0 {
+                    let mut branch = sim.clone();
+                    branch.apply_pauli(qubit, err);
+                    split.push((prob * p_err, branch));
+                }
+            }
+            let p_none = 1.0 - px - py - pz;
+            if p_none > 0.0 {
+                split.push((prob * p_none, sim));
+            }
+        }
+        self.branches = split;
+        self.compact();
+        Ok(())
+    }
+
+    /// Apply a single-qubit depolarizing channel with total error probability `p`.
+    pub fn depolarize(&mut self, qubit: QubitIndex, p: f64) -> PyResult<()> {
+        self.pauli_channel(qubit, p / 3.0, p / 3.0, p / 3.0)
+    }
+
+    /// Probability that measuring `qubit` along `axis` yields `result`.
+    pub fn probability(
+        &self,
+        qubit: QubitIndex,
+        axis: Axis,
+        result: MeasurementResult,
+    ) -> PyResult<f64> {
+        let qubit = self.any_branch().resolve(qubit)?;
+        Ok(self
+            .branches
+            .iter()
+            .map(|(prob, sim)| prob * outcome_probability(sim, qubit, axis, result))
+            .sum())
+    }
+
+    /// Measure `qubit` along `axis`, conditioning the mixture on the sampled outcome.
+    ///
+    /// Returns `MeasurementResult.PlusOne` or `MeasurementResult.MinusOne`.
+    pub fn measure(&mut self, qubit: QubitIndex, axis: Axis) -> PyResult<MeasurementResult> {
+        let p_plus = self.probability(qubit, axis, MeasurementResult::PlusOne)?;
+        let qubit = self.any_branch().resolve(qubit)?;
+        let result = if crate::rng::generator().random_bool(p_plus.clamp(0.0, 1.0)) {
+            MeasurementResult::PlusOne
+        } else {
+            MeasurementResult::MinusOne
+        };
+
+        let mut conditioned = Vec::with_capacity(self.branches.len());
+        for (prob, mut sim) in self.branches.drain(..) {
+            match sim.deterministic_outcome(qubit, axis) {
+                Some(res) if res == result => conditioned.push((prob, sim)),
+                Some(_) => {}
+                None => {
+                    sim.project(qubit, axis, result);
+                    conditioned.push((prob * 0.5, sim));
+                }
+            }
+        }
+        self.branches = conditioned;
+        self.compact();
+        Ok(result)
+    }
+
+    /// Trace out `qubits`, after which they can't be used anymore.
+    ///
+    /// Every branch is split over the Z outcomes of each qubit, so the mixture becomes the
+    /// exact reduced state of the remaining qubits.
+    pub fn trace_out(&mut self, qubits: QubitList) -> PyResult<()> {
+        let mut qubits = self.any_branch().resolve_list(&qubits)?;
+        qubits.sort_unstable();
+        qubits.dedup();
+        for qubit in qubits {
+            let mut split = Vec::with_capacity(self.branches.len() * 2);
+            for (prob, mut sim) in self.branches.drain(..) {
+                if sim.deterministic_outcome(qubit, Axis::Z).is_none() {
+                    let mut minus = sim.clone();
+                    minus.discard(qubit, MeasurementResult::MinusOne);
+                    split.push((prob * 0.5, minus));
+                    sim.discard(qubit, MeasurementResult::PlusOne);
+                    split.push((prob * 0.5, sim));
+                } else {
+                    sim.discard(qubit, MeasurementResult::PlusOne);
+                    split.push((prob, sim));
+                }
+            }
+            self.branches = split;
+            self.compact();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_flip_probability() {
+        let mut mixed = MixedGraphSim::new(2, 1e-9);
+        mixed.pauli_channel(0.into(), 0.25, 0.0, 0.0).unwrap();
+
+        assert_eq!(mixed.__len__(), 2);
+        let p_flip = mixed
+            .probability(0.into(), Axis::Z, MeasurementResult::MinusOne)
+            .unwrap();
+        assert!((p_flip - 0.25).abs() < 1e-12);
+        assert_eq!(
+            mixed
+                .probability(1.into(), Axis::Z, MeasurementResult::MinusOne)
+                .unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_identical_branches_merge() {
+        let mut mixed = MixedGraphSim::new(1, 1e-9);
+        mixed.pauli_channel(0.into(), 0.5, 0.0, 0.0).unwrap();
+        mixed.pauli_channel(0.into(), 0.5, 0.0, 0.0).unwrap();
+
+        assert_eq!(mixed.__len__(), 2);
+        for (prob, _) in mixed.branches() {
+            assert!((prob - 0.5).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_measure_conditions_correlated_branches() {
+        let mut mixed = MixedGraphSim::new(2, 1e-9);
+        mixed.h(0.into()).unwrap();
+        mixed.cx(0.into(), 1.into()).unwrap();
+        mixed.pauli_channel(1.into(), 0.1, 0.0, 0.0).unwrap();
+
+        let first = mixed.measure(0.into(), Axis::Z).unwrap();
+        let p_same = mixed.probability(1.into(), Axis::Z, first).unwrap();
+        assert!((p_same - 0.9).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_trace_out_half_of_bell_pair() {
+        let mut mixed = MixedGraphSim::new(2, 1e-9);
+        mixed.h(0.into()).unwrap();
+        mixed.cx(0.into(), 1.into()).unwrap();
+        mixed.trace_out(vec![0].into()).unwrap();
+
+        assert_eq!(mixed.__len__(), 2);
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let p_plus = mixed
+                .probability(1.into(), axis, MeasurementResult::PlusOne)
+                .unwrap();
+            assert!((p_plus - 0.5).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_prune_drops_unlikely_branches() {
+        let mut mixed = MixedGraphSim::new(1, 0.01);
+        mixed.pauli_channel(0.into(), 0.001, 0.0, 0.0).unwrap();
+
+        assert_eq!(mixed.__len__(), 1);
+        assert_eq!(
+            mixed
+                .probability(0.into(), Axis::Z, MeasurementResult::PlusOne)
+                .unwrap(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_qubits_are_resolved() {
+        let mut mixed = MixedGraphSim::new(2, 1e-9);
+        mixed.x(QubitIndex::Index(-1)).unwrap();
+        assert_eq!(
+            mixed
+                .probability(1.into(), Axis::Z, MeasurementResult::MinusOne)
+                .unwrap(),
+            1.0
+        );
+        assert!(mixed.x(5.into()).is_err());
+        assert!(mixed.cz(0.into(), 0.into()).is_err());
+        assert!(mixed.pauli_channel(2.into(), 0.1, 0.0, 0.0).is_err());
+
+        mixed.trace_out(vec![0, 0].into()).unwrap();
+        assert!(mixed.measure(0.into(), Axis::Z).is_err());
+        assert!(mixed.trace_out(vec![0].into()).is_err());
+    }
+}
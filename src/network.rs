@@ -0,0 +1,298 @@
+use std::ops::Range;
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    text::parse_op,
+};
+
+/// A qubit of a network, as the index of its party and its index within the party.
+type Site = (usize, NodeIdx);
+
+/// Parties with a simulator each that share entanglement only through Bell pairs.
+///
+/// Once a Bell pair or a teleported gate links two parties their qubits are entangled,
+/// so the network keeps a single joint state, but every operation it offers is local to
+/// one party apart from declaring Bell pairs, and parties only exchange classical bits.
+/// Qubits are given as `(party, qubit)`, where `qubit` counts the qubits of the party's
+/// simulator first and then the halves of its Bell pairs.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Network {
+    state: GraphSim,
+    /// Index in `state` of every qubit of every party.
+    parties: Vec<Vec<NodeIdx>>,
+    /// Qubits of every party in |0⟩ that held a consumed Bell pair half.
+    spare: Vec<Vec<NodeIdx>>,
+    /// Bell pairs declared and not consumed yet.
+    pairs: Vec<(Site, Site)>,
+    classical_bits: usize,
+}
+
+/// Tensor product of `sims`, with the qubits of each after those of the ones before,
+/// and the range of qubits every simulator ended up at.
+///
+/// Traced out qubits are isolated in |0⟩, so they become ordinary qubits.
+pub(crate) fn tensor_product<'a>(
+    sims: impl IntoIterator<Item = &'a GraphSim>,
+) -> (GraphSim, Vec<Range<NodeIdx>>) {
+    let (mut vops, mut adjacent, mut ranges) = (Vec::new(), Vec::new(), Vec::new());
+    for sim in sims {
+        let offset = vops.len();
+        vops.extend(sim.vops().iter().copied());
+        adjacent.extend(
+            sim.adjacency()
+                .iter()
+                .map(|neighbours| neighbours.iter().map(|nb| nb + offset).collect()),
+        );
+        ranges.push(offset..vops.len());
+    }
+    (GraphSim::from_parts(vops, adjacent, BitSet::new()), ranges)
+}
+
+impl Network {
+    /// Network of a party for every simulator of `parties`, in their current state.
+    ///
+    /// Traced out qubits become ordinary qubits of their party, so every qubit the joint
+    /// state allocates is a new Bell pair half.
+    fn from_sims<'a>(parties: impl IntoIterator<Item = &'a GraphSim>) -> Network {
+        let (state, ranges) = tensor_product(parties);
+        Network {
+            state,
+            spare: vec![Vec::new(); ranges.len()],
+            parties: ranges.into_iter().map(Vec::from_iter).collect(),
+            pairs: Vec::new(),
+            classical_bits: 0,
+        }
+    }
+
+    /// Qubits of `party` in the joint state.
+    fn party(&self, party: usize) -> PyResult<&[NodeIdx]> {
+        self.parties.get(party).map(Vec::as_slice).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Party {party} is out of range for {} parties",
+                self.parties.len()
+            ))
+        })
+    }
+
+    /// Index in the joint state of `site`.
+    fn joint(&self, (party, qubit): Site) -> PyResult<NodeIdx> {
+        let qubits = self.party(party)?;
+        qubits.get(qubit).copied().ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Qubit {qubit} is out of range for the {} qubits of party {party}",
+                qubits.len()
+            ))
+        })
+    }
+
+    /// A qubit of `party` in |0⟩, reusing a spare one if there is any.
+    fn fresh_qubit(&mut self, party: usize) -> Site {
+        if let Some(qubit) = self.spare[party].pop() {
+            return (party, qubit);
+        }
+        let joint = self.state.alloc_ancilla();
+        self.parties[party].push(joint);
+        (party, self.parties[party].len() - 1)
+    }
+
+    /// Take a declared Bell pair between `party_a` and `party_b`, halves in that order.
+    fn take_pair(&mut self, party_a: usize, party_b: usize) -> PyResult<(Site, Site)> {
+        let idx = self
+            .pairs
+            .iter()
+            .position(|&((a, _), (b, _))| {
+                (a, b) == (party_a, party_b) || (b, a) == (party_a, party_b)
+            })
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "No Bell pair is declared between party {party_a} and party {party_b}"
+                ))
+            })?;
+        let (first, second) = self.pairs.swap_remove(idx);
+        Ok(match first.0 == party_a {
+            true => (first, second),
+            false => (second, first),
+        })
+    }
+
+    /// Bring the measured half `site` of a Bell pair back to |0⟩ and keep it for later.
+    fn recycle(&mut self, site: Site, axis: Axis, result: MeasurementResult) {
+        let joint = self.parties[site.0][site.1];
+        if axis == Axis::X {
+            self.state.h(joint);
+        }
+        if result == MeasurementResult::MinusOne {
+            self.state.x(joint);
+        }
+        self.spare[site.0].push(site.1);
+    }
+}
+
+#[pymethods]
+impl Network {
+    /// Create a network with one party for every simulator in `parties`.
+    ///
+    /// The simulators are copied, and deferred measurements of them are resolved first.
+    /// Qubits they traced out are usable again in the network, in |0⟩.
+    #[new]
+    pub fn new(parties: Vec<PyRef<'_, GraphSim>>) -> Network {
+        let sims: Vec<GraphSim> = parties
+            .iter()
+            .map(|party| {
+                let mut sim = GraphSim::clone(party);
+                sim.resolve_deferred();
+                sim
+            })
+            .collect();
+        Network::from_sims(&sims)
+    }
+
+    /// Number of parties in the network.
+    pub fn __len__(&self) -> usize {
+        self.parties.len()
+    }
+
+    /// Number of classical bits the parties sent each other so far.
+    #[getter]
+    pub fn classical_bits(&self) -> usize {
+        self.classical_bits
+    }
+
+    /// Share a new Bell pair (|00⟩ + |11⟩)/√2 between `party_a` and `party_b`.
+    ///
+    /// Returns the qubits holding the two halves, for `teleported_cnot` or any protocol
+    /// of your own built from `apply`.
+    pub fn bell_pair(&mut self, party_a: usize, party_b: usize) -> PyResult<(Site, Site)> {
+        if party_a == party_b {
+            return Err(PyValueError::new_err(
+                "A Bell pair needs two different parties",
+            ));
+        }
+        self.party(party_a)?;
+        self.party(party_b)?;
+        let first = self.fresh_qubit(party_a);
+        let second = self.fresh_qubit(party_b);
+        let (a, b) = (self.joint(first)?, self.joint(second)?);
+        self.state.h(a);
+        self.state.cx(a, b);
+        self.pairs.push((first, second));
+        Ok((first, second))
+    }
+
+    /// Return the Bell pairs that were declared and not used by `teleported_cnot` yet.
+    pub fn bell_pairs(&self) -> Vec<(Site, Site)> {
+        self.pairs.clone()
+    }
+
+    /// Apply the gate or measurement `name` of the text format to qubits of `party`.
+    ///
+    /// Returns the outcome for measurements and `None` for gates. Raises `ValueError`
+    /// for unknown operations or qubits outside the party.
+    pub fn apply(
+        &mut self,
+        party: usize,
+        name: &str,
+        qubits: Vec<NodeIdx>,
+    ) -> PyResult<Option<MeasurementResult>> {
+        let joint = qubits
+            .iter()
+            .map(|&qubit| self.joint((party, qubit)))
+            .collect::<PyResult<Vec<NodeIdx>>>()?;
+        let op = parse_op(name, &joint).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown operation `{name}` on {} qubits",
+                qubits.len()
+            ))
+        })?;
+        Ok(op.apply(&mut self.state))
+    }
+
+    /// Apply a CNOT from `control` to `target` of another party by gate teleportation.
+    ///
+    /// This consumes a declared Bell pair between the two parties and follows the
+    /// protocol of Eisert et al.: the party of `control` applies a CX onto its half and
+    /// measures it in Z, then the party of `target` corrects its half with X on a −1,
+    /// applies a CX from it onto `target` and measures it in X, and finally the party of
+    /// `control` applies Z on a −1. So two classical bits are sent, which are returned.
+    /// The halves are reset afterwards and reused by later Bell pairs. Raises
+    /// `ValueError` if both qubits belong to the same party or no pair is declared.
+    pub fn teleported_cnot(
+        &mut self,
+        control: Site,
+        target: Site,
+    ) -> PyResult<(MeasurementResult, MeasurementResult)> {
+        if control.0 == target.0 {
+            return Err(PyValueError::new_err(
+                "Control and target belong to the same party, apply a CX directly",
+            ));
+        }
+        let (control_joint, target_joint) = (self.joint(control)?, self.joint(target)?);
+        let (near, far) = self.take_pair(control.0, target.0)?;
+        let (near_joint, far_joint) = (self.joint(near)?, self.joint(far)?);
+
+        self.state.cx(control_joint, near_joint);
+        let first = self.state.measure_z(near_joint);
+        self.classical_bits += 1;
+        if first == MeasurementResult::MinusOne {
+            self.state.x(far_joint);
+        }
+        self.state.cx(far_joint, target_joint);
+        let second = self.state.measure_x(far_joint);
+        self.classical_bits += 1;
+        if second == MeasurementResult::MinusOne {
+            self.state.z(control_joint);
+        }
+
+        self.recycle(near, Axis::Z, first);
+        self.recycle(far, Axis::X, second);
+        Ok((first, second))
+    }
+
+    /// Return the index of `qubit` of `party` in the simulator from `state`.
+    pub fn joint_index(&self, party: usize, qubit: NodeIdx) -> PyResult<NodeIdx> {
+        self.joint((party, qubit))
+    }
+
+    /// Return a copy of the joint state of all parties.
+    pub fn state(&self) -> GraphSim {
+        self.state.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_teleported_cnot_entangles_parties() {
+        let mut alice = GraphSim::new(1);
+        alice.h(0);
+        let bob = GraphSim::new(1);
+        for _ in 0..20 {
+            let mut network = Network::from_sims([&alice, &bob]);
+            assert!(network.teleported_cnot((0, 0), (1, 0)).is_err());
+
+            assert_eq!(network.bell_pair(0, 1).unwrap(), ((0, 1), (1, 1)));
+            network.teleported_cnot((0, 0), (1, 0)).unwrap();
+            assert_eq!(network.classical_bits(), 2);
+            assert!(network.bell_pairs().is_empty());
+
+            // |+⟩|0⟩ became a Bell pair, which a CX and an H take back to |00⟩
+            let control = network.joint_index(0, 0).unwrap();
+            let target = network.joint_index(1, 0).unwrap();
+            let mut state = network.state();
+            state.cx(control, target);
+            state.h(control);
+            for qubit in 0..4 {
+                let outcome = state.deterministic_outcome(qubit, Axis::Z);
+                assert_eq!(outcome, Some(MeasurementResult::PlusOne));
+            }
+            // the halves are back in |0⟩ and reused
+            assert_eq!(network.bell_pair(1, 0).unwrap(), ((1, 1), (0, 1)));
+        }
+    }
+}
@@ -0,0 +1,545 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+};
+
+/// Kind of fault sampled by a `NoiseModel`.
+///
+/// Exposed to Python as `graphsim.FaultKind`.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FaultKind {
+    /// Pauli-X error on the qubit.
+    X,
+    /// Pauli-Y error on the qubit.
+    Y,
+    /// Pauli-Z error on the qubit.
+    Z,
+    /// The reported measurement outcome was flipped.
+    Flip,
+}
+
+impl From<Axis> for FaultKind {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::X => FaultKind::X,
+            Axis::Y => FaultKind::Y,
+            Axis::Z => FaultKind::Z,
+        }
+    }
+}
+
+impl FaultKind {
+    /// The Pauli error of this fault, `None` for a flipped outcome.
+    pub fn pauli(&self) -> Option<Axis> {
+        match self {
+            FaultKind::X => Some(Axis::X),
+            FaultKind::Y => Some(Axis::Y),
+            FaultKind::Z => Some(Axis::Z),
+            FaultKind::Flip => None,
+        }
+    }
+}
+
+/// A fault that occurred on `qubit` right after operation `op_index` of a circuit.
+#[pyclass(frozen, get_all, str)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fault {
+    pub op_index: usize,
+    pub qubit: NodeIdx,
+    pub kind: FaultKind,
+}
+
+impl Display for Fault {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "({:?} on {} after op {})",
+            self.kind, self.qubit, self.op_index
+        )
+    }
+}
+
+/// The non-identity parts of two-qubit Pauli `pair`, which encodes the Pauli on
+/// `control` as `pair / 4` and on `target` as `pair % 4`, with 0 being the identity.
+fn pauli_pair(
+    control: NodeIdx,
+    target: NodeIdx,
+    pair: usize,
+) -> impl Iterator<Item = (NodeIdx, Axis)> {
+    [(control, pair / 4), (target, pair % 4)]
+        .into_iter()
+        .filter_map(|(qubit, pauli)| match pauli {
+            1 => Some((qubit, Axis::X)),
+            2 => Some((qubit, Axis::Y)),
+            3 => Some((qubit, Axis::Z)),
+            _ => None,
+        })
+}
+
+/// Independent Pauli noise attached to the operations of a `Circuit`.
+///
+/// Single- and two-qubit gates are followed by a depolarizing channel on the qubits
+/// they act on, and measurement outcomes are flipped with probability `measurement`.
+///
+/// With a `coupling_map` of the device, every gate also causes crosstalk: for each qubit
+/// it acts on and each coupled spectator qubit outside the gate, a correlated ZZ error
+/// hits the pair with probability `crosstalk`.
+#[pyclass]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoiseModel {
+    #[pyo3(get)]
+    single_qubit: f64,
+    #[pyo3(get)]
+    two_qubit: f64,
+    #[pyo3(get)]
+    measurement: f64,
+    #[pyo3(get)]
+    crosstalk: f64,
+    #[pyo3(get)]
+    coupling_map: Vec<(NodeIdx, NodeIdx)>,
+    /// Qubits coupled to each qubit, following `coupling_map`.
+    spectators: HashMap<NodeIdx, Vec<NodeIdx>>,
+}
+
+impl NoiseModel {
+    /// Pairs of a qubit `op` acts on and a coupled qubit it doesn't, which crosstalk
+    /// can hit. Empty for measurements.
+    fn crosstalk_pairs(&self, op: &Op) -> Vec<(NodeIdx, NodeIdx)> {
+        if matches!(op, Op::Measure { .. }) || self.crosstalk == 0.0 {
+            return Vec::new();
+        }
+        let qubits = op.qubits();
+        qubits
+            .iter()
+            .flat_map(|&qubit| {
+                self.spectators
+                    .get(&qubit)
+                    .into_iter()
+                    .flatten()
+                    .filter(|spectator| !qubits.contains(spectator))
+                    .map(move |&spectator| (qubit, spectator))
+            })
+            .collect()
+    }
+
+    /// Sample the faults following operation `op_index`, appending them to `faults`.
+    pub(crate) fn sample_faults<R: Rng + ?Sized>(
+        &self,
+        op_index: usize,
+        op: &Op,
+        rng: &mut R,
+        faults: &mut Vec<Fault>,
+    ) {
+        match (op, op.qubits().as_slice()) {
+            (Op::Measure { .. }, &[qubit]) => {
+                if rng.random_bool(self.measurement) {
+                    faults.push(Fault {
+                        op_index,
+                        qubit,
+                        kind: FaultKind::Flip,
+                    });
+                }
+            }
+            (_, &[qubit]) => {
+                if rng.random_bool(self.single_qubit) {
+                    let axis: Axis = rng.random();
+                    faults.push(Fault {
+                        op_index,
+                        qubit,
+                        kind: axis.into(),
+                    });
+                }
+            }
+            (_, &[control, target]) => {
+                if rng.random_bool(self.two_qubit) {
+                    let pair = rng.random_range(1..16);
+                    faults.extend(
+                        pauli_pair(control, target, pair).map(|(qubit, axis)| Fault {
+                            op_index,
+                            qubit,
+                            kind: axis.into(),
+                        }),
+                    );
+                }
+            }
+            _ => unreachable!("operations act on one or two qubits"),
+        }
+        for (qubit, spectator) in self.crosstalk_pairs(op) {
+            if rng.random_bool(self.crosstalk) {
+                faults.extend([qubit, spectator].map(|qubit| Fault {
+                    op_index,
+                    qubit,
+                    kind: FaultKind::Z,
+                }));
+            }
+        }
+    }
+
+    /// Every fault combination that can follow operation `op_index`, with its probability.
+    pub(crate) fn mechanisms(&self, op_index: usize, op: &Op) -> Vec<(f64, Vec<Fault>)> {
+        let fault = |qubit, kind| Fault {
+            op_index,
+            qubit,
+            kind,
+        };
+        match (op, op.qubits().as_slice()) {
+            (Op::Measure { .. }, &[qubit]) => {
+                vec![(self.measurement, vec![fault(qubit, FaultKind::Flip)])]
+            }
+            (_, &[qubit]) => [Axis::X, Axis::Y, Axis::Z]
+                .into_iter()
+                .map(|axis| (self.single_qubit / 3.0, vec![fault(qubit, axis.into())]))
+                .collect(),
+            (_, &[control, target]) => (1..16)
+                .map(|pair| {
+                    let faults = pauli_pair(control, target, pair)
+                        .map(|(qubit, axis)| fault(qubit, axis.into()))
+                        .collect();
+                    (self.two_qubit / 15.0, faults)
+                })
+                .collect(),
+            _ => unreachable!("operations act on one or two qubits"),
+        }
+        .into_iter()
+        .chain(
+            self.crosstalk_pairs(op)
+                .into_iter()
+                .map(|(qubit, spectator)| {
+                    let faults = vec![fault(qubit, FaultKind::Z), fault(spectator, FaultKind::Z)];
+                    (self.crosstalk, faults)
+                }),
+        )
+        .filter(|(prob, _)| *prob > 0.0)
+        .collect()
+    }
+
+    /// Perform `op` on `sim` followed by its sampled faults, which are appended to
+    /// `faults`. Returns the (possibly flipped) outcome if `op` is a measurement.
+    pub(crate) fn apply_noisy<R: Rng + ?Sized>(
+        &self,
+        op_index: usize,
+        op: &Op,
+        sim: &mut GraphSim,
+        rng: &mut R,
+        faults: &mut Vec<Fault>,
+    ) -> Option<MeasurementResult> {
+        let mut res = op.apply(sim);
+        let first = faults.len();
+        self.sample_faults(op_index, op, rng, faults);
+
+        for fault in &faults[first..] {
+            match fault.kind.pauli() {
+                Some(axis) => sim.apply_pauli(fault.qubit, axis),
+                None => res = res.map(|outcome| !outcome),
+            }
+        }
+
+        res
+    }
+
+    /// Execute `circuit` once on `sim` with sampled faults.
+    pub(crate) fn execute<R: Rng + ?Sized>(
+        &self,
+        circuit: &Circuit,
+        sim: &mut GraphSim,
+        rng: &mut R,
+        faults: &mut Vec<Fault>,
+    ) -> Vec<MeasurementResult> {
+        circuit
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, op)| self.apply_noisy(idx, op, sim, rng, faults))
+            .collect()
+    }
+}
+
+#[pymethods]
+impl NoiseModel {
+    /// Create a noise model from the single-qubit, two-qubit and measurement error rates.
+    ///
+    /// `coupling_map` lists the coupled qubit pairs of the device, between which gates
+    /// cause ZZ crosstalk with probability `crosstalk`.
+    #[new]
+    #[pyo3(signature = (
+        single_qubit = 0.0,
+        two_qubit = 0.0,
+        measurement = 0.0,
+        crosstalk = 0.0,
+        coupling_map = None,
+    ))]
+    pub fn new(
+        single_qubit: f64,
+        two_qubit: f64,
+        measurement: f64,
+        crosstalk: f64,
+        coupling_map: Option<Vec<(NodeIdx, NodeIdx)>>,
+    ) -> PyResult<NoiseModel> {
+        if [single_qubit, two_qubit, measurement, crosstalk]
+            .iter()
+            .any(|p| !(0.0..=1.0).contains(p))
+        {
+            return Err(PyValueError::new_err(
+                "Error probabilities must lie between 0 and 1",
+            ));
+        }
+        let coupling_map = coupling_map.unwrap_or_default();
+        let mut spectators: HashMap<NodeIdx, Vec<NodeIdx>> = HashMap::new();
+        for &(a, b) in &coupling_map {
+            if a == b {
+                return Err(PyValueError::new_err(format!(
+                    "Qubit {a} can't be coupled to itself"
+                )));
+            }
+            for (qubit, other) in [(a, b), (b, a)] {
+                let coupled = spectators.entry(qubit).or_default();
+                if !coupled.contains(&other) {
+                    coupled.push(other);
+                }
+            }
+        }
+        Ok(NoiseModel {
+            single_qubit,
+            two_qubit,
+            measurement,
+            crosstalk,
+            coupling_map,
+            spectators,
+        })
+    }
+}
+
+/// Aggregated outcome statistics of `run_trajectories`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug)]
+pub struct TrajectoryResult {
+    /// Number of shots that were run.
+    shots: usize,
+    /// Occurrences of every outcome string, with character `i` being `0` for a +1 and
+    /// `1` for a −1 outcome of measurement `i`.
+    counts: HashMap<String, usize>,
+    /// Number of −1 outcomes of each measurement.
+    minus_one_counts: Vec<usize>,
+    /// Faults sampled in each shot, only present when requested.
+    faults: Option<Vec<Vec<Fault>>>,
+}
+
+/// Run `circuit` for `shots` noisy trajectories, each starting from |0…0⟩.
+///
+/// Every shot samples its own faults from `noise_model`. With `record_faults` the
+/// faults of every shot are returned as well. The GIL is released while sampling.
+///
+/// Raises `ValueError` listing the problems `validate` finds before running any shot.
+#[pyfunction]
+#[pyo3(signature = (circuit, noise_model, shots, record_faults = false))]
+pub fn run_trajectories(
+    py: Python<'_>,
+    circuit: &Circuit,
+    noise_model: &NoiseModel,
+    shots: usize,
+    record_faults: bool,
+) -> PyResult<TrajectoryResult> {
+    crate::validate::check(&GraphSim::new(circuit.qubit_amount()), circuit)?;
+    Ok(py.detach(|| {
+        trajectories(
+            circuit,
+            noise_model,
+            shots,
+            record_faults,
+            &mut crate::rng::generator(),
+        )
+    }))
+}
+
+/// `run_trajectories` with outcomes and faults drawn from `rng`.
+fn trajectories(
+    circuit: &Circuit,
+    noise_model: &NoiseModel,
+    shots: usize,
+    record_faults: bool,
+    rng: &mut impl Rng,
+) -> TrajectoryResult {
+    let mut counts = HashMap::new();
+    let mut minus_one_counts = vec![0; circuit.num_measurements()];
+    let mut all_faults = record_faults.then(|| Vec::with_capacity(shots));
+    let mut faults = Vec::new();
+    let mut initial = GraphSim::new(circuit.qubit_amount());
+    initial.reseed(rng);
+
+    for _ in 0..shots {
+        let mut sim = initial.clone();
+        faults.clear();
+        let outcomes = noise_model.execute(circuit, &mut sim, rng, &mut faults);
+
+        let mut key = String::with_capacity(outcomes.len());
+        for (count, outcome) in minus_one_counts.iter_mut().zip(outcomes) {
+            match outcome {
+                MeasurementResult::PlusOne => key.push('0'),
+                MeasurementResult::MinusOne => {
+                    key.push('1');
+                    *count += 1;
+                }
+            }
+        }
+        *counts.entry(key).or_insert(0) += 1;
+
+        if let Some(all_faults) = all_faults.as_mut() {
+            all_faults.push(faults.clone());
+        }
+    }
+
+    TrajectoryResult {
+        shots,
+        counts,
+        minus_one_counts,
+        faults: all_faults,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn bell_circuit() -> Circuit {
+        let mut circuit = Circuit::new(2);
+        circuit.h(0);
+        circuit.cx(0, 1);
+        circuit.measure(0, Axis::Z);
+        circuit.measure(1, Axis::Z);
+        circuit
+    }
+
+    #[test]
+    fn test_noiseless_bell_trajectories() {
+        let noise = NoiseModel::default();
+        let result = trajectories(
+            &bell_circuit(),
+            &noise,
+            200,
+            true,
+            &mut StdRng::seed_from_u64(7),
+        );
+
+        assert_eq!(result.shots, 200);
+        assert!(result.counts.keys().all(|key| key == "00" || key == "11"));
+        assert_eq!(result.minus_one_counts[0], result.minus_one_counts[1]);
+        assert!(result.faults.unwrap().iter().all(|shot| shot.is_empty()));
+    }
+
+    #[test]
+    fn test_seeded_trajectories_repeat() {
+        let noise = NoiseModel::new(0.1, 0.1, 0.1, 0.0, None).unwrap();
+        let run = |seed| {
+            let result = trajectories(
+                &bell_circuit(),
+                &noise,
+                200,
+                true,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            (result.counts, result.minus_one_counts, result.faults)
+        };
+        assert_eq!(run(3), run(3));
+        assert_ne!(run(3), run(4));
+    }
+
+    #[test]
+    fn test_measurement_flips_are_recorded() {
+        let noise = NoiseModel::new(0.0, 0.0, 1.0, 0.0, None).unwrap();
+        let mut circuit = Circuit::new(1);
+        circuit.measure(0, Axis::Z);
+
+        let result = trajectories(&circuit, &noise, 10, true, &mut StdRng::seed_from_u64(7));
+
+        assert_eq!(result.counts.get("1"), Some(&10));
+        for shot in result.faults.unwrap() {
+            assert_eq!(
+                shot,
+                vec![Fault {
+                    op_index: 0,
+                    qubit: 0,
+                    kind: FaultKind::Flip
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn test_two_qubit_faults_hit_gate_qubits() {
+        let noise = NoiseModel::new(0.0, 1.0, 0.0, 0.0, None).unwrap();
+        let result = trajectories(
+            &bell_circuit(),
+            &noise,
+            50,
+            true,
+            &mut StdRng::seed_from_u64(7),
+        );
+
+        for shot in result.faults.unwrap() {
+            assert!(!shot.is_empty());
+            assert!(
+                shot.iter()
+                    .all(|fault| fault.op_index == 1 && fault.qubit < 2)
+            );
+        }
+    }
+
+    #[test]
+    fn test_crosstalk_hits_coupled_spectators() {
+        let coupling_map = vec![(0, 1), (1, 2), (2, 3), (1, 0)];
+        let noise = NoiseModel::new(0.0, 0.0, 0.0, 1.0, Some(coupling_map)).unwrap();
+        let mut circuit = Circuit::new(4);
+        circuit.cz(1, 2);
+        circuit.measure(3, Axis::Z);
+
+        let z = |qubit| Fault {
+            op_index: 0,
+            qubit,
+            kind: FaultKind::Z,
+        };
+        let result = trajectories(&circuit, &noise, 5, true, &mut StdRng::seed_from_u64(7));
+        for shot in result.faults.unwrap() {
+            assert_eq!(shot, vec![z(1), z(0), z(2), z(3)]);
+        }
+        let mechanisms = noise.mechanisms(0, &circuit.ops_slice()[0]);
+        assert_eq!(
+            mechanisms,
+            vec![(1.0, vec![z(1), z(0)]), (1.0, vec![z(2), z(3)])]
+        );
+    }
+
+    #[test]
+    fn test_invalid_circuits_raise_value_error() {
+        crate::run_python(
+            cr#"
+from graphsim import Circuit, NoiseModel, Op, run_trajectories
+
+for op in [Op.X(qubit=5), Op.Cz(control=0, target=0)]:
+    circuit = Circuit(2)
+    circuit.append(op)
+    try:
+        run_trajectories(circuit, NoiseModel(), 10)
+    except ValueError:
+        continue
+    raise AssertionError(f"{op} ran")
+"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_invalid_probability() {
+        assert!(NoiseModel::new(1.5, 0.0, 0.0, 0.0, None).is_err());
+        assert!(NoiseModel::new(0.0, 0.0, 0.0, 0.5, Some(vec![(2, 2)])).is_err());
+    }
+}
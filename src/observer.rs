@@ -0,0 +1,96 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use pyo3::prelude::*;
+
+use crate::graphsim::{Axis, MeasurementResult, NodeIdx, Vop};
+
+/// Graph changes collected while a single operation runs.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Changes {
+    /// Every edge toggle, as `(low, high)`.
+    pub(crate) edges: Vec<(NodeIdx, NodeIdx)>,
+    /// Every VOP assignment, as `(node, before, after)`.
+    pub(crate) vops: Vec<(NodeIdx, Vop, Vop)>,
+}
+
+impl Changes {
+    /// The net effect of the collected changes.
+    pub(crate) fn into_delta(self) -> GraphDelta {
+        let mut edges = BTreeSet::new();
+        for edge in self.edges {
+            if !edges.insert(edge) {
+                edges.remove(&edge);
+            }
+        }
+        let mut vops: BTreeMap<NodeIdx, (Vop, Vop)> = BTreeMap::new();
+        for (node, before, after) in self.vops {
+            vops.entry(node).or_insert((before, after)).1 = after;
+        }
+        GraphDelta {
+            edges: edges.into_iter().collect(),
+            vops: vops
+                .into_iter()
+                .filter(|(_, (before, after))| before != after)
+                .map(|(node, (before, after))| (node, before.gate_names(), after.gate_names()))
+                .collect(),
+        }
+    }
+}
+
+/// Net change of the graph by one operation, passed to `GraphSim.on_operation` listeners.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphDelta {
+    /// Edges `(a, b)` with `a < b` that were added or removed, sorted.
+    edges: Vec<(NodeIdx, NodeIdx)>,
+    /// Changed VOPs as `(qubit, before, after)`, sorted by qubit, where the VOPs are
+    /// given as gate names like the ones of `GraphSim.standardize_vops`.
+    vops: Vec<(NodeIdx, Vec<&'static str>, Vec<&'static str>)>,
+}
+
+/// A measurement logged while recording, from `GraphSim.history`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeasurementEvent {
+    /// Index of the measurement among the operations logged since `start_recording`.
+    pub(crate) op_index: usize,
+    pub(crate) qubit: NodeIdx,
+    pub(crate) axis: Axis,
+    /// Outcome the caller got, after any `on_measure` callback replaced it.
+    pub(crate) outcome: MeasurementResult,
+}
+
+/// An outcome in the classical register of a simulator, from `GraphSim.records`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeasurementRecord {
+    /// Position of the outcome in the register, counting measurements from the first.
+    pub(crate) index: usize,
+    pub(crate) qubit: NodeIdx,
+    pub(crate) axis: Axis,
+    /// Outcome the caller got, after any `on_measure` callback replaced it.
+    pub(crate) outcome: MeasurementResult,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_are_netted() {
+        let changes = Changes {
+            edges: vec![(1, 2), (0, 3), (1, 2), (0, 1)],
+            vops: vec![
+                (2, Vop::IA, Vop::XA),
+                (2, Vop::XA, Vop::IA),
+                (0, Vop::YC, Vop::IA),
+                (0, Vop::IA, Vop::ZA),
+            ],
+        };
+        let delta = changes.into_delta();
+        assert_eq!(delta.edges, vec![(0, 1), (0, 3)]);
+        assert_eq!(delta.vops.len(), 1);
+        assert_eq!(delta.vops[0].0, 0);
+        assert_eq!(delta.vops[0].2, vec!["z"]);
+    }
+}
@@ -0,0 +1,482 @@
+use std::collections::{HashSet, VecDeque};
+
+use bit_set::BitSet;
+
+use crate::{
+    cow::CowVec,
+    graphsim::{GraphSim, NodeIdx, Vop},
+};
+
+/// Canonical labelling of a graph: equal for two graphs exactly when they are isomorphic.
+///
+/// The graph is relabelled and returned as sorted neighbour lists per new label.
+pub(crate) type CanonicalForm = Vec<Vec<NodeIdx>>;
+
+/// Refine `colors` until every color class has a uniform multiset of neighbour colors.
+///
+/// Colors are ranks of label-independent signatures, so the result only depends on
+/// the graph and the initial coloring, not on the node indices.
+fn refine(adjacent: &[BitSet], mut colors: Vec<usize>) -> Vec<usize> {
+    let mut classes = colors.iter().collect::<HashSet<_>>().len();
+    loop {
+        let signatures: Vec<(usize, Vec<usize>)> = (0..adjacent.len())
+            .map(|node| {
+                let mut neighbours: Vec<usize> =
+                    adjacent[node].iter().map(|nb| colors[nb]).collect();
+                neighbours.sort_unstable();
+                (colors[node], neighbours)
+            })
+            .collect();
+        let mut ranked = signatures.clone();
+        ranked.sort_unstable();
+        ranked.dedup();
+        colors = signatures
+            .iter()
+            .map(|sig| ranked.binary_search(sig).expect("Signature was ranked"))
+            .collect();
+
+        if ranked.len() == classes {
+            return colors;
+        }
+        classes = ranked.len();
+    }
+}
+
+fn relabel(adjacent: &[BitSet], labels: &[usize]) -> CanonicalForm {
+    let mut form = vec![Vec::new(); adjacent.len()];
+    for (node, adj) in adjacent.iter().enumerate() {
+        form[labels[node]] = adj.iter().map(|nb| labels[nb]).collect();
+        form[labels[node]].sort_unstable();
+    }
+    form
+}
+
+/// Whether swapping `a` and `b` is an automorphism of the graph.
+fn are_twins(adjacent: &[BitSet], a: NodeIdx, b: NodeIdx) -> bool {
+    let mut na = adjacent[a].clone();
+    let mut nb = adjacent[b].clone();
+    na.remove(b);
+    nb.remove(a);
+    na == nb
+}
+
+/// The nodes of the first color class with more than one node, if any.
+fn target_cell(colors: &[usize]) -> Option<Vec<NodeIdx>> {
+    (0..colors.len())
+        .map(|color| {
+            (0..colors.len())
+                .filter(|&node| colors[node] == color)
+                .collect::<Vec<_>>()
+        })
+        .find(|cell| cell.len() > 1)
+}
+
+/// Give `node` a color of its own, ordered before the rest of its class, and refine.
+fn individualize(adjacent: &[BitSet], colors: &[usize], node: NodeIdx) -> Vec<usize> {
+    let individualized = colors
+        .iter()
+        .enumerate()
+        .map(|(other, &color)| 2 * color + usize::from(other != node))
+        .collect();
+    refine(adjacent, individualized)
+}
+
+fn search(adjacent: &[BitSet], colors: Vec<usize>, best: &mut Option<CanonicalForm>) {
+    let Some(cell) = target_cell(&colors) else {
+        let form = relabel(adjacent, &colors);
+        if best.as_ref().is_none_or(|best| form < *best) {
+            *best = Some(form);
+        }
+        return;
+    };
+
+    let mut branched: Vec<NodeIdx> = Vec::new();
+    for node in cell {
+        // twins of an earlier branch give the same leaves
+        if branched
+            .iter()
+            .any(|&other| are_twins(adjacent, node, other))
+        {
+            continue;
+        }
+        branched.push(node);
+        search(adjacent, individualize(adjacent, &colors, node), best);
+    }
+}
+
+/// Compute the canonical form of the graph given by `adjacent`.
+///
+/// Uses individualization and refinement, branching once per class of twin nodes.
+/// This is fast for the small graphs it is meant for, but exponential in the worst
+/// case for highly symmetric graphs.
+pub(crate) fn canonical_form(adjacent: &[BitSet]) -> CanonicalForm {
+    let mut best = None;
+    search(
+        adjacent,
+        refine(adjacent, vec![0; adjacent.len()]),
+        &mut best,
+    );
+    best.unwrap_or_default()
+}
+
+/// A leaf below `colors` with the same relabelled graph as `form`, if there is one.
+fn find_leaf(adjacent: &[BitSet], colors: Vec<usize>, form: &CanonicalForm) -> Option<Vec<usize>> {
+    match target_cell(&colors) {
+        None => (relabel(adjacent, &colors) == *form).then_some(colors),
+        Some(cell) => cell
+            .into_iter()
+            .find_map(|node| find_leaf(adjacent, individualize(adjacent, &colors, node), form)),
+    }
+}
+
+/// Representative of the orbit of `node` in the union-find forest `parent`.
+fn find(parent: &mut [NodeIdx], mut node: NodeIdx) -> NodeIdx {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+/// Generators of the automorphisms of the graph `adjacent` that keep `colors`.
+///
+/// The search follows the first path of individualizations down to a leaf, then from
+/// the deepest level up tries every other node of the cell individualized there, unless
+/// an automorphism found before already maps the path node to it. A leaf below that
+/// node relabelling the graph like the first leaf gives an automorphism, and together
+/// these generate the whole group.
+pub(crate) fn graph_automorphisms(adjacent: &[BitSet], colors: Vec<usize>) -> Vec<Vec<NodeIdx>> {
+    let mut path = vec![refine(adjacent, colors)];
+    let mut cells = Vec::new();
+    while let Some(cell) = target_cell(path.last().expect("The path starts at the root")) {
+        let next = individualize(
+            adjacent,
+            path.last().expect("The path is not empty"),
+            cell[0],
+        );
+        path.push(next);
+        cells.push(cell);
+    }
+    let leaf = path.pop().expect("The path ends in a leaf");
+    let form = relabel(adjacent, &leaf);
+
+    let mut generators = Vec::new();
+    let mut parent: Vec<NodeIdx> = (0..adjacent.len()).collect();
+    for (colors, cell) in path.iter().zip(cells).rev() {
+        for &node in &cell[1..] {
+            if find(&mut parent, node) == find(&mut parent, cell[0]) {
+                continue;
+            }
+            let Some(labels) = find_leaf(adjacent, individualize(adjacent, colors, node), &form)
+            else {
+                continue;
+            };
+            let mut by_label = vec![0; labels.len()];
+            for (qubit, &label) in labels.iter().enumerate() {
+                by_label[label] = qubit;
+            }
+            let permutation: Vec<NodeIdx> = leaf.iter().map(|&label| by_label[label]).collect();
+            for (qubit, &image) in permutation.iter().enumerate() {
+                let (a, b) = (find(&mut parent, qubit), find(&mut parent, image));
+                parent[a] = b;
+            }
+            generators.push(permutation);
+        }
+    }
+    generators
+}
+
+/// Split the nodes `0..len` into the orbits of the permutations `generators`.
+///
+/// Orbits are sorted and ordered by their smallest node.
+pub(crate) fn orbits(len: usize, generators: &[Vec<NodeIdx>]) -> Vec<Vec<NodeIdx>> {
+    let mut parent: Vec<NodeIdx> = (0..len).collect();
+    for permutation in generators {
+        for (node, &image) in permutation.iter().enumerate() {
+            let (a, b) = (find(&mut parent, node), find(&mut parent, image));
+            parent[a] = b;
+        }
+    }
+    let mut orbits: Vec<Vec<NodeIdx>> = Vec::new();
+    let mut index: Vec<Option<usize>> = vec![None; len];
+    for node in 0..len {
+        let root = find(&mut parent, node);
+        match index[root] {
+            Some(idx) => orbits[idx].push(node),
+            None => {
+                index[root] = Some(orbits.len());
+                orbits.push(vec![node]);
+            }
+        }
+    }
+    orbits
+}
+
+/// Generators of the automorphisms of the graph of `sim` that keep every VOP, so
+/// permuting the qubits by them leaves the state unchanged.
+pub(crate) fn automorphisms(sim: &GraphSim) -> Vec<Vec<NodeIdx>> {
+    let colors = sim.vops().iter().map(|&vop| vop as usize).collect();
+    graph_automorphisms(&sim.adjacency().to_vec(), colors)
+}
+
+/// Breadth-first search over the graphs reachable from `sim` by local complementations.
+///
+/// Returns at most `max_states` simulators, one per isomorphism class of the graph and
+/// each describing the same state as `sim`, starting with `sim` itself.
+pub(crate) fn lc_orbit(sim: &GraphSim, max_states: usize) -> Vec<GraphSim> {
+    let mut seen = HashSet::from([canonical_form(&sim.adjacency().to_vec())]);
+    let mut queue = VecDeque::from([0]);
+    let mut orbit = vec![sim.clone()];
+
+    while let Some(idx) = queue.pop_front() {
+        if orbit.len() >= max_states {
+            break;
+        }
+        let current = orbit[idx].clone();
+        let adjacent = current.adjacency();
+        // complementing symmetric nodes gives isomorphic graphs, so one of every orbit
+        // is enough, and a node with fewer than two neighbours leaves the graph as is
+        let listed = adjacent.to_vec();
+        let generators = graph_automorphisms(&listed, vec![0; listed.len()]);
+        let representatives = orbits(listed.len(), &generators)
+            .into_iter()
+            .map(|orbit| orbit[0])
+            .filter(|&node| adjacent[node].len() > 1);
+        for node in representatives {
+            let mut next = current.clone();
+            next.local_comp(node);
+            if seen.insert(canonical_form(&next.adjacency().to_vec())) {
+                queue.push_back(orbit.len());
+                orbit.push(next);
+                if orbit.len() >= max_states {
+                    break;
+                }
+            }
+        }
+    }
+
+    orbit.truncate(max_states);
+    orbit
+}
+
+/// Graphs with at most this many nodes get their whole LC orbit searched.
+const EXHAUSTIVE_NODES: usize = 8;
+/// Budget for the exhaustive search over the LC orbit.
+const EXHAUSTIVE_STATES: usize = 10_000;
+
+fn edge_count(sim: &GraphSim) -> usize {
+    sim.adjacency().iter().map(|adj| adj.len()).sum::<usize>() / 2
+}
+
+/// Change in the number of edges when locally complementing `node`.
+fn lc_edge_delta(adjacent: &CowVec<BitSet>, node: NodeIdx) -> isize {
+    let degree = adjacent[node].len();
+    let internal: usize = adjacent[node]
+        .iter()
+        .map(|nb| adjacent[nb].intersection(&adjacent[node]).count())
+        .sum::<usize>()
+        / 2;
+    (degree * degree.saturating_sub(1) / 2) as isize - 2 * internal as isize
+}
+
+/// Convert `sim` to an LC-equivalent graph with as few edges as found.
+///
+/// Small graphs search their LC orbit exhaustively, after which local complementations
+/// are applied greedily for as long as one of them removes edges. Returns the number
+/// of edges left.
+pub(crate) fn minimize_edges(sim: &mut GraphSim) -> usize {
+    if sim.adjacency().len() <= EXHAUSTIVE_NODES {
+        let orbit = lc_orbit(sim, EXHAUSTIVE_STATES);
+        *sim = orbit
+            .into_iter()
+            .min_by_key(edge_count)
+            .expect("The orbit contains the graph itself");
+    }
+
+    loop {
+        let adjacent = sim.adjacency();
+        let best = (0..adjacent.len())
+            .map(|node| (lc_edge_delta(adjacent, node), node))
+            .min();
+        match best {
+            Some((delta, node)) if delta < 0 => sim.local_comp(node),
+            _ => return edge_count(sim),
+        }
+    }
+}
+
+/// Number of non-diagonal and of non-identity VOPs, the cost `standardize_vops` lowers.
+fn vop_cost(sim: &GraphSim) -> (usize, usize) {
+    let vops = sim.vops();
+    (
+        vops.iter().filter(|vop| !vop.is_in_z()).count(),
+        vops.iter().filter(|&&vop| vop != Vop::IA).count(),
+    )
+}
+
+/// Greedily apply whichever state-preserving move lowers `vop_cost` the most.
+///
+/// Moves are one to three local complementations of a node, absorbing its stabilizer
+/// and removing its VOP through a neighbour. Every accepted move lowers the cost, so
+/// this takes at most 2n rounds of O(n) candidate moves.
+pub(crate) fn standardize_vops(sim: &mut GraphSim) {
+    let mut cost = vop_cost(sim);
+    loop {
+        let mut best: Option<((usize, usize), GraphSim)> = None;
+        for node in 0..sim.adjacency().len() {
+            let mut candidates = Vec::with_capacity(5);
+            let mut complemented = sim.clone();
+            for _ in 0..3 {
+                complemented.local_comp(node);
+                candidates.push(complemented.clone());
+            }
+            let mut absorbed = sim.clone();
+            absorbed.absorb_stabilizer(node);
+            candidates.push(absorbed);
+            if !sim.adjacency()[node].is_empty() {
+                let mut removed = sim.clone();
+                removed.remove_vop(node, node);
+                candidates.push(removed);
+            }
+
+            for candidate in candidates {
+                let candidate_cost = vop_cost(&candidate);
+                if candidate_cost < best.as_ref().map_or(cost, |(best, _)| *best) {
+                    best = Some((candidate_cost, candidate));
+                }
+            }
+        }
+
+        match best {
+            Some((best_cost, best_sim)) => {
+                cost = best_cost;
+                *sim = best_sim;
+            }
+            None => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(n: usize, edges: &[(NodeIdx, NodeIdx)]) -> Vec<BitSet> {
+        let mut adjacent = vec![BitSet::with_capacity(n); n];
+        for &(a, b) in edges {
+            adjacent[a].insert(b);
+            adjacent[b].insert(a);
+        }
+        adjacent
+    }
+
+    #[test]
+    fn test_canonical_form_ignores_labels() {
+        let path = graph(4, &[(0, 1), (1, 2), (2, 3)]);
+        let relabelled = graph(4, &[(2, 0), (0, 3), (3, 1)]);
+        let star = graph(4, &[(0, 1), (0, 2), (0, 3)]);
+
+        assert_eq!(canonical_form(&path), canonical_form(&relabelled));
+        assert_ne!(canonical_form(&path), canonical_form(&star));
+        assert_eq!(canonical_form(&graph(12, &[])).len(), 12);
+    }
+
+    #[test]
+    fn test_automorphisms() {
+        let cycle = graph(
+            5,
+            &(0..5)
+                .map(|node| (node, (node + 1) % 5))
+                .collect::<Vec<_>>(),
+        );
+        let generators = graph_automorphisms(&cycle, vec![0; 5]);
+        for permutation in &generators {
+            for (a, adj) in cycle.iter().enumerate() {
+                assert!(
+                    adj.iter()
+                        .all(|b| cycle[permutation[a]].contains(permutation[b]))
+                );
+            }
+        }
+        assert_eq!(orbits(5, &generators), vec![vec![0, 1, 2, 3, 4]]);
+
+        let mut star = GraphSim::new(4);
+        for qubit in 0..4 {
+            star.h(qubit);
+        }
+        for leaf in 1..4 {
+            star.cz(0, leaf);
+        }
+        assert_eq!(star.symmetry_classes(), vec![vec![0], vec![1, 2, 3]]);
+        // a VOP singles out its qubit
+        star.s(2);
+        assert_eq!(star.symmetry_classes(), vec![vec![0], vec![1, 3], vec![2]]);
+        assert!(graph_automorphisms(&graph(3, &[(0, 1), (1, 2)]), vec![0, 1, 2]).is_empty());
+    }
+
+    #[test]
+    fn test_ghz_orbit() {
+        // star and complete graph are the only graphs of the 4 qubit GHZ state
+        let mut sim = GraphSim::new(4);
+        for qubit in 0..4 {
+            sim.h(qubit);
+        }
+        for leaf in 1..4 {
+            sim.cz(0, leaf);
+        }
+
+        let orbit = lc_orbit(&sim, 100);
+        assert_eq!(orbit.len(), 2);
+        assert_eq!(orbit[0], sim);
+        assert!(orbit[1].adjacency().iter().all(|adj| adj.len() == 3));
+        assert_eq!(lc_orbit(&sim, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_minimize_complete_graph() {
+        let mut sim = GraphSim::new(12);
+        for qubit in 0..12 {
+            sim.h(qubit);
+        }
+        for leaf in 1..12 {
+            sim.cz(0, leaf);
+        }
+        sim.local_comp(0);
+        assert_eq!(edge_count(&sim), 66);
+
+        assert_eq!(minimize_edges(&mut sim), 11);
+
+        let mut small = GraphSim::new(5);
+        for qubit in 0..5 {
+            small.h(qubit);
+        }
+        for leaf in 1..5 {
+            small.cz(0, leaf);
+        }
+        small.local_comp(0);
+        assert_eq!(minimize_edges(&mut small), 4);
+    }
+
+    #[test]
+    fn test_standardize_graph_state() {
+        let mut sim = GraphSim::new(5);
+        for qubit in 0..5 {
+            sim.h(qubit);
+        }
+        for qubit in 0..4 {
+            sim.cz(qubit, qubit + 1);
+        }
+        sim.local_comp(2);
+        sim.local_comp(1);
+        assert!(sim.vops().iter().any(|&vop| vop != Vop::IA));
+        assert!(sim.standardize_vops().is_empty());
+
+        // |00⟩ + |11⟩ has no graph state with the same support, so needs one H
+        let mut bell = GraphSim::new(2);
+        bell.h(0);
+        bell.cx(0, 1);
+        let residual = bell.standardize_vops();
+        assert_eq!(residual.len(), 1);
+        assert!(residual.values().all(|gates| gates.contains(&"h")));
+    }
+}
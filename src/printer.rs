@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use crate::circuit::Op;
+
+/// Width of a qubit column in `diagram`.
+const CELL: usize = 4;
+
+/// Short label of `op` on `qubit`, which it acts on, in `diagram`.
+fn label(op: &Op, qubit: usize) -> String {
+    match *op {
+        Op::X { .. } => "X".into(),
+        Op::Y { .. } => "Y".into(),
+        Op::Z { .. } => "Z".into(),
+        Op::H { .. } => "H".into(),
+        Op::S { .. } => "S".into(),
+        Op::Sdag { .. } => "S'".into(),
+        Op::Sx { .. } => "SX".into(),
+        Op::Sxdag { .. } => "SX'".into(),
+        Op::Sy { .. } => "SY".into(),
+        Op::Sydag { .. } => "SY'".into(),
+        Op::Measure { axis, .. } => format!("M{axis}"),
+        Op::Reset { .. } => "R".into(),
+        Op::Cz { .. } => "@".into(),
+        Op::Cx { control, .. } | Op::Cy { control, .. } if control == qubit => "@".into(),
+        Op::Cx { .. } => "X".into(),
+        Op::Cy { .. } => "Y".into(),
+        Op::Swap { .. } => "SW".into(),
+    }
+}
+
+/// One line per operation with its index, name and qubits in aligned columns.
+pub(crate) fn listing(ops: &[Op]) -> String {
+    let rows: Vec<(String, String)> = ops
+        .iter()
+        .map(|op| {
+            let text = op.to_string();
+            let (name, qubits) = text.split_once(' ').unwrap_or((&text, ""));
+            (name.to_string(), qubits.to_string())
+        })
+        .collect();
+    let index_width = ops.len().saturating_sub(1).to_string().len();
+    let name_width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    let mut text = String::new();
+    for (idx, (name, qubits)) in rows.iter().enumerate() {
+        writeln!(text, "{idx:>index_width$}  {name:<name_width$}  {qubits}")
+            .expect("Writing to a String");
+    }
+    text
+}
+
+/// ASCII diagram with time running down and one column per qubit.
+///
+/// Idle qubits show their wire `|`, and a two-qubit gate joins its qubits with `-`.
+pub(crate) fn diagram(ops: &[Op], qubit_amount: usize) -> String {
+    let index_width = ops.len().saturating_sub(1).to_string().len();
+    let mut text = format!("{:index_width$}  ", "");
+    for qubit in 0..qubit_amount {
+        write!(text, "{:<CELL$}", format!("q{qubit}")).expect("Writing to a String");
+    }
+    text = text.trim_end().to_string();
+    text.push('\n');
+
+    for (idx, op) in ops.iter().enumerate() {
+        let qubits = op.qubits();
+        let low = qubits.iter().copied().min().unwrap_or(0);
+        let high = qubits.iter().copied().max().unwrap_or(0);
+        let mut row = format!("{idx:>index_width$}  ");
+        for qubit in 0..qubit_amount {
+            let cell = match qubits.contains(&qubit) {
+                true => label(op, qubit),
+                false => "|".to_string(),
+            };
+            let fill = match (low..high).contains(&qubit) {
+                true => '-',
+                false => ' ',
+            };
+            row.push_str(&cell);
+            row.extend(std::iter::repeat_n(fill, CELL.saturating_sub(cell.len())));
+        }
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::Axis;
+
+    #[test]
+    fn test_listing_and_diagram() {
+        let ops = [
+            Op::H { qubit: 0 },
+            Op::Cx {
+                control: 0,
+                target: 2,
+            },
+            Op::Measure {
+                qubit: 1,
+                axis: Axis::Z,
+            },
+        ];
+        assert_eq!(
+            listing(&ops),
+            "0  h          0\n1  cx         0 2\n2  measure_Z  1\n"
+        );
+        assert_eq!(
+            diagram(&ops, 3),
+            "   q0  q1  q2\n0  H   |   |\n1  @---|---X\n2  |   MZ  |\n"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::Circuit,
+    graphsim::{GraphSim, MeasurementResult},
+};
+
+/// Graph size over the course of a `GraphSim.run_profiled` call.
+///
+/// Entry `i` of every list describes the graph after the first `ops_done[i]`
+/// operations, so plotting any of them against `ops_done` shows where a circuit makes
+/// the graph dense.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceProfile {
+    /// Number of operations executed when each sample was taken.
+    ops_done: Vec<usize>,
+    /// Number of edges in the graph.
+    edges: Vec<usize>,
+    /// Largest number of neighbours of any node.
+    max_degree: Vec<usize>,
+    /// Number of connected components, counting unentangled qubits but not traced ones.
+    components: Vec<usize>,
+}
+
+impl ResourceProfile {
+    fn sample(&mut self, sim: &GraphSim, ops_done: usize) {
+        let adjacent = sim.adjacency();
+        let mut seen = BitSet::with_capacity(adjacent.len());
+        let mut components = 0;
+        for node in (0..adjacent.len()).filter(|&node| !sim.is_traced(node)) {
+            if !seen.insert(node) {
+                continue;
+            }
+            components += 1;
+            let mut stack = vec![node];
+            while let Some(current) = stack.pop() {
+                stack.extend(adjacent[current].iter().filter(|&nb| seen.insert(nb)));
+            }
+        }
+
+        self.ops_done.push(ops_done);
+        self.edges.push(sim.edge_count());
+        self.max_degree
+            .push(adjacent.iter().map(|adj| adj.len()).max().unwrap_or(0));
+        self.components.push(components);
+    }
+}
+
+/// Run `circuit` on `sim`, sampling the graph before the first operation, after every
+/// `interval` operations and after the last one. Raises `ValueError` with the problems
+/// `validate` finds before running anything.
+pub(crate) fn run_profiled(
+    sim: &mut GraphSim,
+    circuit: &Circuit,
+    interval: usize,
+) -> PyResult<(Vec<MeasurementResult>, ResourceProfile)> {
+    if interval == 0 {
+        return Err(PyValueError::new_err("The interval must be positive"));
+    }
+    crate::validate::check(sim, circuit)?;
+    let mut profile = ResourceProfile::default();
+    let mut outcomes = Vec::new();
+    profile.sample(sim, 0);
+    for (idx, op) in circuit.iter().enumerate() {
+        outcomes.extend(op.apply(sim));
+        let ops_done = idx + 1;
+        if ops_done.is_multiple_of(interval) || ops_done == circuit.ops_slice().len() {
+            profile.sample(sim, ops_done);
+        }
+    }
+    Ok((outcomes, profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::Op;
+
+    #[test]
+    fn test_profile_ghz_and_measure() {
+        let circuit =
+            Circuit::from_text("new_qubit 4\nh 0\ncx 0 1\ncx 0 2\ncx 0 3\nmeasure_z 0").unwrap();
+        let mut sim = GraphSim::new(5);
+        sim.trace_out(vec![4]);
+
+        let (outcomes, profile) = run_profiled(&mut sim, &circuit, 1).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(profile.ops_done, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(profile.edges, vec![0, 0, 1, 2, 3, 0]);
+        assert_eq!(profile.max_degree, vec![0, 0, 1, 2, 3, 0]);
+        assert_eq!(profile.components, vec![4, 4, 3, 2, 1, 4]);
+
+        let (_, sparse) = run_profiled(&mut GraphSim::new(4), &circuit, 2).unwrap();
+        assert_eq!(sparse.ops_done, vec![0, 2, 4, 5]);
+        assert!(run_profiled(&mut GraphSim::new(4), &circuit, 0).is_err());
+    }
+
+    #[test]
+    fn test_invalid_circuits_are_not_profiled() {
+        let mut sim = GraphSim::new(2);
+        sim.h(0);
+        let before = sim.clone();
+        for op in [
+            Op::Cz {
+                control: 0,
+                target: 0,
+            },
+            Op::X { qubit: 5 },
+        ] {
+            let mut circuit = Circuit::new(2);
+            circuit.h(1);
+            circuit.append(op);
+            assert!(run_profiled(&mut sim, &circuit, 1).is_err());
+            assert_eq!(sim, before);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::{
+    Rng, SeedableRng,
+    distr::{Distribution, weighted::WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{Axis, NodeIdx, Vop},
+    text::parse_op,
+};
+
+/// Kinds of operations `random_circuit` picks from, by their key in `gate_distribution`.
+const KINDS: [&str; 4] = ["single", "cz", "cx", "measure"];
+
+/// Weights of `KINDS` when no `gate_distribution` is given.
+const DEFAULT_WEIGHTS: [f64; 4] = [0.5, 0.25, 0.25, 0.0];
+
+/// Circuit of `depth` random layers on `qubit_amount` qubits.
+///
+/// In every layer each qubit not used yet picks a kind of operation by `weights`, and
+/// two-qubit gates take a random partner among the unused qubits, or fall back to a
+/// single-qubit Clifford if there is none.
+fn generate<R: Rng + ?Sized>(
+    qubit_amount: usize,
+    depth: usize,
+    weights: &WeightedIndex<f64>,
+    rng: &mut R,
+) -> Circuit {
+    let cliffords: Vec<Vec<&'static str>> = (0..crate::SYMMETRIES)
+        .map(|idx| Vop::from_index(idx).gate_names())
+        .collect();
+    let mut circuit = Circuit::new(qubit_amount);
+    let mut order: Vec<NodeIdx> = (0..qubit_amount).collect();
+    for _ in 0..depth {
+        order.shuffle(rng);
+        let mut free = order.as_slice();
+        while let Some((&qubit, rest)) = free.split_first() {
+            free = rest;
+            let kind = KINDS[weights.sample(rng)];
+            match (kind, free.split_first()) {
+                ("cz" | "cx", Some((&partner, rest))) => {
+                    free = rest;
+                    let (control, target) = match rng.random() {
+                        true => (qubit, partner),
+                        false => (partner, qubit),
+                    };
+                    circuit.append(match kind {
+                        "cz" => Op::Cz { control, target },
+                        _ => Op::Cx { control, target },
+                    });
+                }
+                ("measure", _) => {
+                    let axis = [Axis::X, Axis::Y, Axis::Z][rng.random_range(0..3)];
+                    circuit.append(Op::Measure { qubit, axis });
+                }
+                _ => {
+                    for gate in &cliffords[rng.random_range(0..cliffords.len())] {
+                        circuit
+                            .append(parse_op(gate, &[qubit]).expect("VOPs decompose into gates"));
+                    }
+                }
+            }
+        }
+    }
+    circuit
+}
+
+/// Generate a random circuit of `depth` layers on `n` qubits.
+///
+/// Every layer uses every qubit once: each picks one of the kinds of `gate_distribution`,
+/// a dict of relative weights for `"single"` (a uniformly random single-qubit Clifford,
+/// written as its gates, so the identity adds none), `"cz"`, `"cx"` and `"measure"` (along
+/// a random axis). Missing kinds have weight 0, and without a distribution half the picks
+/// are single-qubit Cliffords and the rest CZ and CX in equal parts. Two-qubit gates pair
+/// up with another qubit of the layer in random order. The same `seed` always gives the
+/// same circuit. Raises `ValueError` for unknown kinds or weights that are negative or
+/// all zero.
+#[pyfunction]
+#[pyo3(signature = (n, depth, gate_distribution = None, seed = None))]
+pub fn random_circuit(
+    py: Python<'_>,
+    n: usize,
+    depth: usize,
+    gate_distribution: Option<HashMap<String, f64>>,
+    seed: Option<u64>,
+) -> PyResult<Circuit> {
+    let weights = match gate_distribution {
+        None => DEFAULT_WEIGHTS,
+        Some(distribution) => {
+            let mut weights = [0.0; 4];
+            for (kind, weight) in distribution {
+                let idx = KINDS
+                    .iter()
+                    .position(|&known| known == kind)
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Unknown gate kind `{kind}`, expected one of {}",
+                            KINDS.join(", ")
+                        ))
+                    })?;
+                weights[idx] = weight;
+            }
+            weights
+        }
+    };
+    let weights = WeightedIndex::new(weights)
+        .map_err(|err| PyValueError::new_err(format!("Invalid gate distribution: {err}")))?;
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    Ok(py.detach(|| generate(n, depth, &weights, &mut rng)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_circuit_proportions() {
+        let uniform = WeightedIndex::new(DEFAULT_WEIGHTS).unwrap();
+        let first = generate(6, 20, &uniform, &mut StdRng::seed_from_u64(7));
+        assert_eq!(
+            first,
+            generate(6, 20, &uniform, &mut StdRng::seed_from_u64(7))
+        );
+        assert_eq!(first.num_measurements(), 0);
+
+        let measure = WeightedIndex::new([0.0, 0.0, 0.0, 1.0]).unwrap();
+        let circuit = generate(5, 4, &measure, &mut StdRng::seed_from_u64(1));
+        assert_eq!(circuit.num_measurements(), 20);
+
+        let cz = WeightedIndex::new([0.0, 1.0, 0.0, 0.0]).unwrap();
+        let circuit = generate(4, 10, &cz, &mut StdRng::seed_from_u64(2));
+        assert!(
+            circuit
+                .ops_slice()
+                .iter()
+                .all(|op| matches!(op, Op::Cz { .. }))
+        );
+        assert_eq!(circuit.ops_slice().len(), 20);
+    }
+}
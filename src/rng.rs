@@ -0,0 +1,87 @@
+use std::sync::{Arc, Mutex};
+
+use pyo3::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Random generator of a seeded simulator, shared between its copies.
+pub(crate) type SharedRng = Arc<Mutex<StdRng>>;
+
+/// Generator the simulators created after `seed` draw their seeds from.
+static SEEDER: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// A shared generator seeded with `seed`.
+pub(crate) fn seeded(seed: u64) -> SharedRng {
+    Arc::new(Mutex::new(StdRng::seed_from_u64(seed)))
+}
+
+/// A generator seeded from `seeder`, or `None` if it isn't set.
+fn draw(seeder: &Mutex<Option<StdRng>>) -> Option<SharedRng> {
+    let mut seeder = seeder.lock().expect("Seeder isn't poisoned");
+    seeder.as_mut().map(|seeder| seeded(seeder.random()))
+}
+
+/// The generator of a new simulator, from the seed given to `seed` if there is one.
+pub(crate) fn inherited() -> Option<SharedRng> {
+    draw(&SEEDER)
+}
+
+/// A generator for a one-off computation such as a batch of shots, seeded from the
+/// seed given to `seed` if there is one and from the thread-local generator otherwise.
+pub(crate) fn generator() -> StdRng {
+    generator_from(&SEEDER)
+}
+
+/// A generator seeded from `seeder`, or from the thread-local generator if it isn't set.
+fn generator_from(seeder: &Mutex<Option<StdRng>>) -> StdRng {
+    let mut seeder = seeder.lock().expect("Seeder isn't poisoned");
+    match seeder.as_mut() {
+        Some(seeder) => StdRng::seed_from_u64(seeder.random()),
+        None => StdRng::from_rng(&mut rand::rng()),
+    }
+}
+
+/// Seed the randomness of every simulator created afterwards, like `numpy.random.seed`.
+///
+/// Each new `GraphSim` draws the seed of its own generator from one seeded with `seed`,
+/// and so does every call that samples shots, such as `run_trajectories` or a sweep.
+/// A program that creates its simulators and samples in the same order repeats its
+/// outcomes. An explicit `GraphSim(qubits, seed=...)` takes precedence, and `seed(None)`
+/// goes back to the thread-local generator. Simulators that exist already are not affected.
+#[pyfunction]
+#[pyo3(signature = (seed = None))]
+pub fn seed(seed: Option<u64>) {
+    *SEEDER.lock().expect("Seeder isn't poisoned") = seed.map(StdRng::seed_from_u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeder_repeats_generators() {
+        let first = |seed: Option<u64>| {
+            let seeder = Mutex::new(seed.map(StdRng::seed_from_u64));
+            let drawn: Vec<Option<u64>> = (0..3)
+                .map(|_| draw(&seeder).map(|rng| rng.lock().unwrap().random()))
+                .collect();
+            drawn
+        };
+        assert_eq!(first(Some(7)), first(Some(7)));
+        assert_ne!(first(Some(7)), first(Some(8)));
+        assert_eq!(first(None), vec![None; 3]);
+        // every simulator gets a generator of its own
+        let drawn = first(Some(7));
+        assert_ne!(drawn[0], drawn[1]);
+    }
+
+    #[test]
+    fn test_seeder_repeats_one_off_generators() {
+        let first = |seed: Option<u64>| -> Vec<u64> {
+            let seeder = Mutex::new(seed.map(StdRng::seed_from_u64));
+            (0..3).map(|_| generator_from(&seeder).random()).collect()
+        };
+        assert_eq!(first(Some(7)), first(Some(7)));
+        assert_ne!(first(Some(7)), first(Some(8)));
+        assert_ne!(first(None), first(None));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::NodeIdx,
+};
+
+/// A circuit rewritten to only use the couplings of a device, from `Circuit.route`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug)]
+pub struct RoutingResult {
+    /// The routed circuit, acting on physical qubits.
+    circuit: Circuit,
+    /// Indices of the two-qubit gates of the original circuit on uncoupled qubits.
+    illegal_gates: Vec<usize>,
+    /// Number of SWAPs inserted, each made of three CX gates.
+    swaps: usize,
+    /// Physical qubit holding each logical qubit at the end of the circuit.
+    final_layout: Vec<NodeIdx>,
+}
+
+/// Shortest path from `from` to `to` in the coupling graph, both ends included.
+fn shortest_path(coupled: &[Vec<NodeIdx>], from: NodeIdx, to: NodeIdx) -> Option<Vec<NodeIdx>> {
+    let mut previous = vec![None; coupled.len()];
+    previous[from] = Some(from);
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        if node == to {
+            let mut path = vec![to];
+            while let Some(&last) = path.last()
+                && last != from
+            {
+                path.push(previous[last].expect("Visited nodes have a predecessor"));
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &nb in &coupled[node] {
+            if previous[nb].is_none() {
+                previous[nb] = Some(node);
+                queue.push_back(nb);
+            }
+        }
+    }
+    None
+}
+
+/// Route `circuit` onto the device with the undirected couplings `coupling_map`.
+///
+/// Logical qubit `q` starts on physical qubit `q`. Before every two-qubit gate on
+/// uncoupled qubits, the control is swapped along a shortest path until it is next to
+/// the target, and the new positions are kept for the rest of the circuit.
+pub(crate) fn route(
+    circuit: &Circuit,
+    coupling_map: &[(NodeIdx, NodeIdx)],
+) -> PyResult<RoutingResult> {
+    let physical = coupling_map
+        .iter()
+        .map(|&(a, b)| a.max(b) + 1)
+        .chain([circuit.qubit_amount()])
+        .max()
+        .unwrap_or(0);
+    let mut coupled = vec![Vec::new(); physical];
+    for &(a, b) in coupling_map {
+        if a == b {
+            return Err(PyValueError::new_err(format!(
+                "Qubit {a} can't be coupled to itself"
+            )));
+        }
+        coupled[a].push(b);
+        coupled[b].push(a);
+    }
+    if let Some(qubit) = circuit
+        .iter()
+        .flat_map(|op| op.qubits())
+        .find(|&qubit| qubit >= physical)
+    {
+        return Err(PyValueError::new_err(format!(
+            "Qubit {qubit} is out of range"
+        )));
+    }
+
+    let mut layout: Vec<NodeIdx> = (0..physical).collect();
+    // logical qubit on each physical qubit
+    let mut occupant: Vec<NodeIdx> = (0..physical).collect();
+    let mut illegal_gates = Vec::new();
+    let mut swaps = 0;
+    let mut ops = Vec::with_capacity(circuit.ops_slice().len());
+
+    for (idx, op) in circuit.iter().enumerate() {
+        if let [control, target] = op.qubits()[..]
+            && !coupled[layout[control]].contains(&layout[target])
+        {
+            illegal_gates.push(idx);
+            let path =
+                shortest_path(&coupled, layout[control], layout[target]).ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Operation {idx} ({op}) acts on qubits that are not connected"
+                    ))
+                })?;
+            for pair in path[..path.len() - 1].windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                for (control, target) in [(a, b), (b, a), (a, b)] {
+                    ops.push(Op::Cx { control, target });
+                }
+                occupant.swap(a, b);
+                layout[occupant[a]] = a;
+                layout[occupant[b]] = b;
+                swaps += 1;
+            }
+        }
+        let qubits: Vec<NodeIdx> = op.qubits().iter().map(|&qubit| layout[qubit]).collect();
+        ops.push(
+            Op::from_code(op.code(), qubits[0], qubits.get(1).copied().unwrap_or(0))
+                .expect("Codes of existing operations decode"),
+        );
+    }
+
+    let mut routed = circuit.with_ops(ops);
+    routed.add_qubits(physical - circuit.qubit_amount());
+    layout.truncate(circuit.qubit_amount());
+    Ok(RoutingResult {
+        circuit: routed,
+        illegal_gates,
+        swaps,
+        final_layout: layout,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::{Axis, GraphSim, MeasurementResult};
+
+    #[test]
+    fn test_route_on_line() {
+        let circuit =
+            Circuit::from_text("new_qubit 4\nx 0\ncx 0 3\ncz 0 3\nmeasure_z 3\nmeasure_z 0")
+                .unwrap();
+        let line = [(0, 1), (1, 2), (2, 3)];
+        let result = route(&circuit, &line).unwrap();
+
+        assert_eq!(result.illegal_gates, vec![1]);
+        assert_eq!(result.swaps, 2);
+        assert_eq!(result.final_layout, vec![2, 0, 1, 3]);
+        assert_eq!(
+            result.circuit.ops_slice().len(),
+            circuit.ops_slice().len() + 6
+        );
+        assert!(result.circuit.iter().all(|op| match op.qubits()[..] {
+            [a, b] => line.contains(&(a.min(b), a.max(b))),
+            _ => true,
+        }));
+
+        let outcomes = result.circuit.execute(&mut GraphSim::new(4));
+        assert_eq!(outcomes, vec![MeasurementResult::MinusOne; 2]);
+        let mut sim = GraphSim::new(4);
+        circuit.execute(&mut sim);
+        assert_eq!(
+            sim.deterministic_outcome(3, Axis::Z),
+            Some(MeasurementResult::MinusOne)
+        );
+
+        assert!(route(&circuit, &[(0, 1), (2, 3)]).is_err());
+    }
+}
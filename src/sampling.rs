@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    convert::encode,
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    network::tensor_product,
+};
+
+/// Largest subset `marginal_probabilities` computes the full distribution of.
+const MAX_MARGINAL_QUBITS: usize = 20;
+
+/// Longest measurement plan `enumerate_outcomes` walks the outcome tree of.
+const MAX_PLAN_MEASUREMENTS: usize = 16;
+
+/// Number of bytes a single packed bitstring over `qubit_amount` qubits takes.
+fn packed_len(qubit_amount: usize) -> usize {
+    qubit_amount.div_ceil(8)
+}
+
+/// Sample `shots` computational-basis bitstrings of `sim`, packed into bytes.
+///
+/// Each shot measures every qubit in Z, in order, on a scratch copy of the state, so
+/// later qubits are conditioned on the earlier outcomes. Shot `s` occupies bytes
+/// `s * packed_len(n)..(s + 1) * packed_len(n)`, with qubit `q` in bit `q % 8` of its
+/// byte `q / 8` and a −1 outcome (|1⟩) being a set bit.
+pub(crate) fn sample_bitstrings(sim: &GraphSim, shots: usize) -> Vec<u8> {
+    let qubit_amount = sim.adjacency().len();
+    let row = packed_len(qubit_amount);
+    let mut samples = vec![0; shots * row];
+
+    for bits in samples.chunks_mut(row.max(1)) {
+        let mut scratch = sim.clone();
+        for qubit in 0..qubit_amount {
+            let (res, _) = scratch.measure(qubit, Axis::Z);
+            if res == MeasurementResult::MinusOne {
+                bits[qubit / 8] |= 1 << (qubit % 8);
+            }
+        }
+    }
+
+    samples
+}
+
+/// Z outcomes of `qubits` for `shots` shots, each on a scratch copy of `sim`, as bytes of
+/// `int8` like `convert::encode` makes them, shot by shot.
+pub(crate) fn sample(sim: &GraphSim, shots: usize, qubits: &[NodeIdx], binary: bool) -> Vec<u8> {
+    let mut samples = Vec::with_capacity(shots * qubits.len());
+    for _ in 0..shots {
+        let mut scratch = sim.clone();
+        samples.extend(
+            qubits
+                .iter()
+                .map(|&qubit| encode(scratch.measure(qubit, Axis::Z).0, binary)),
+        );
+    }
+    samples
+}
+
+/// Transversal Bell measurements of `first` ⊗ `second`, for `shots` shots.
+///
+/// The CX and H of the Bell basis change act alike on every shot, so they are applied
+/// once to the joint state, after which each shot is a Z sample of it. Every sample is
+/// written as the Pauli string labelling the Bell states of the pairs: the parity of a
+/// pair gives its X part and the outcome of its first qubit after the H its Z part.
+pub(crate) fn bell_sample(
+    first: &GraphSim,
+    second: &GraphSim,
+    shots: usize,
+) -> PyResult<Vec<String>> {
+    let qubit_amount = first.vops().len();
+    if second.vops().len() != qubit_amount {
+        return Err(PyValueError::new_err(format!(
+            "Copies have {qubit_amount} and {} qubits",
+            second.vops().len()
+        )));
+    }
+    let (mut joint, _) = tensor_product([first, second]);
+    for qubit in 0..qubit_amount {
+        joint.cx(qubit, qubit_amount + qubit);
+        joint.h(qubit);
+    }
+    let row = packed_len(2 * qubit_amount);
+    let bit = |bits: &[u8], qubit: usize| bits[qubit / 8] >> (qubit % 8) & 1 == 1;
+    Ok(sample_bitstrings(&joint, shots)
+        .chunks(row.max(1))
+        .take(shots)
+        .map(|bits| {
+            (0..qubit_amount)
+                .map(
+                    |qubit| match (bit(bits, qubit_amount + qubit), bit(bits, qubit)) {
+                        (false, false) => 'I',
+                        (true, false) => 'X',
+                        (true, true) => 'Y',
+                        (false, true) => 'Z',
+                    },
+                )
+                .collect()
+        })
+        .collect())
+}
+
+/// Both outcomes of measuring `qubit` along `axis`, as `(probability, state)` pairs for
+/// the +1 and the −1 outcome. An outcome that can't occur has no state.
+pub(crate) fn branch(sim: GraphSim, qubit: NodeIdx, axis: Axis) -> [(f64, Option<GraphSim>); 2] {
+    match sim.deterministic_outcome(qubit, axis) {
+        Some(MeasurementResult::PlusOne) => [(1.0, Some(sim)), (0.0, None)],
+        Some(MeasurementResult::MinusOne) => [(0.0, None), (1.0, Some(sim))],
+        None => {
+            let mut minus = sim.clone();
+            minus.project(qubit, axis, MeasurementResult::MinusOne);
+            let mut plus = sim;
+            plus.project(qubit, axis, MeasurementResult::PlusOne);
+            [(0.5, Some(plus)), (0.5, Some(minus))]
+        }
+    }
+}
+
+fn marginal_recurse(
+    sim: GraphSim,
+    qubits: &[NodeIdx],
+    depth: usize,
+    index: usize,
+    prob: f64,
+    probs: &mut [f64],
+) {
+    let Some(&qubit) = qubits.get(depth) else {
+        probs[index] = prob;
+        return;
+    };
+    let [plus, minus] = branch(sim, qubit, Axis::Z);
+    for ((branch_prob, child), index) in [(plus, index), (minus, index | 1 << depth)] {
+        if let Some(child) = child {
+            marginal_recurse(child, qubits, depth + 1, index, prob * branch_prob, probs);
+        }
+    }
+}
+
+/// Exact distribution of the Z outcomes of `qubits`.
+///
+/// Entry `i` is the probability of the outcomes where bit `k` of `i` is set exactly
+/// when `qubits[k]` gives −1. The outcome tree is walked with one copy of the state per
+/// random measurement, so this takes O(2^k) copies in the worst case.
+pub(crate) fn marginal_probabilities(sim: &GraphSim, qubits: &[NodeIdx]) -> PyResult<Vec<f64>> {
+    if qubits.len() > MAX_MARGINAL_QUBITS {
+        return Err(PyValueError::new_err(format!(
+            "At most {MAX_MARGINAL_QUBITS} qubits are supported, got {}",
+            qubits.len()
+        )));
+    }
+    if let Some(&qubit) = qubits.iter().find(|&&q| q >= sim.adjacency().len()) {
+        return Err(PyValueError::new_err(format!(
+            "Qubit {qubit} is out of range"
+        )));
+    }
+    if qubits.iter().collect::<HashSet<_>>().len() != qubits.len() {
+        return Err(PyValueError::new_err("Qubits must be distinct"));
+    }
+
+    let mut probs = vec![0.0; 1 << qubits.len()];
+    marginal_recurse(sim.clone(), qubits, 0, 0, 1.0, &mut probs);
+    Ok(probs)
+}
+
+/// A leaf of the outcome tree: the results of every measurement in the plan, how likely
+/// they are and the state they leave behind.
+pub(crate) type OutcomeLeaf = (Vec<MeasurementResult>, f64, GraphSim);
+
+fn outcome_recurse(
+    sim: GraphSim,
+    plan: &[(NodeIdx, Axis)],
+    results: &mut Vec<MeasurementResult>,
+    prob: f64,
+    leaves: &mut Vec<OutcomeLeaf>,
+) {
+    let Some(&(qubit, axis)) = plan.get(results.len()) else {
+        leaves.push((results.clone(), prob, sim));
+        return;
+    };
+    let [plus, minus] = branch(sim, qubit, axis);
+    for ((branch_prob, child), result) in [
+        (plus, MeasurementResult::PlusOne),
+        (minus, MeasurementResult::MinusOne),
+    ] {
+        if let Some(child) = child {
+            results.push(result);
+            outcome_recurse(child, plan, results, prob * branch_prob, leaves);
+            results.pop();
+        }
+    }
+}
+
+/// Every possible combination of results of the measurements in `plan`, in order.
+///
+/// Leaves with probability 0 are left out, and the leaves are ordered with +1 before
+/// −1 for each measurement, earlier measurements varying slowest. A qubit may appear in
+/// the plan more than once.
+pub(crate) fn enumerate_outcomes(
+    sim: &GraphSim,
+    plan: &[(NodeIdx, Axis)],
+) -> PyResult<Vec<OutcomeLeaf>> {
+    if plan.len() > MAX_PLAN_MEASUREMENTS {
+        return Err(PyValueError::new_err(format!(
+            "At most {MAX_PLAN_MEASUREMENTS} measurements are supported, got {}",
+            plan.len()
+        )));
+    }
+    if let Some(&(qubit, _)) = plan.iter().find(|&&(q, _)| q >= sim.adjacency().len()) {
+        return Err(PyValueError::new_err(format!(
+            "Qubit {qubit} is out of range"
+        )));
+    }
+
+    let mut leaves = Vec::new();
+    outcome_recurse(sim.clone(), plan, &mut Vec::new(), 1.0, &mut leaves);
+    Ok(leaves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghz_bitstrings() {
+        let mut sim = GraphSim::new(10);
+        sim.h(0);
+        for qubit in 1..10 {
+            sim.cx(0, qubit);
+        }
+        sim.x(9);
+
+        let samples = sample_bitstrings(&sim, 50);
+        assert_eq!(samples.len(), 50 * 2);
+        for shot in samples.chunks(2) {
+            assert!(shot == [0b0000_0000, 0b10] || shot == [0b1111_1111, 0b01]);
+        }
+
+        let samples = sample(&sim, 50, &[9, 0], true);
+        assert_eq!(samples.len(), 50 * 2);
+        assert!(
+            samples
+                .chunks(2)
+                .all(|shot| shot == [1, 0] || shot == [0, 1])
+        );
+    }
+
+    #[test]
+    fn test_bell_samples_are_stabilizers() {
+        let mut sim = GraphSim::new(4);
+        for qubit in 0..4 {
+            sim.h(qubit);
+        }
+        sim.cz(0, 1);
+        sim.cz(1, 2);
+        sim.cz(1, 3);
+        sim.h(3);
+
+        let group = crate::stabilizer::StabilizerGroup::new(&sim);
+        let samples = bell_sample(&sim, &sim, 100).unwrap();
+        assert_eq!(samples.len(), 100);
+        for sample in &samples {
+            let product = crate::gadget::parse_observable(sample, None, 4).unwrap();
+            assert_ne!(group.expectation(&product.to_pauli_string()), 0, "{sample}");
+        }
+        assert!(samples.iter().any(|sample| sample != "IIII"));
+        assert!(bell_sample(&sim, &GraphSim::new(3), 1).is_err());
+    }
+
+    #[test]
+    fn test_bell_marginals() {
+        let mut sim = GraphSim::new(3);
+        sim.h(0);
+        sim.cx(0, 1);
+        sim.x(2);
+
+        assert_eq!(
+            marginal_probabilities(&sim, &[0, 1]).unwrap(),
+            vec![0.5, 0.0, 0.0, 0.5]
+        );
+        assert_eq!(
+            marginal_probabilities(&sim, &[2, 0]).unwrap(),
+            vec![0.0, 0.5, 0.0, 0.5]
+        );
+        assert!(marginal_probabilities(&sim, &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_enumerate_bell_outcomes() {
+        let mut sim = GraphSim::new(2);
+        sim.h(0);
+        sim.cx(0, 1);
+
+        let leaves = enumerate_outcomes(&sim, &[(0, Axis::X), (1, Axis::X), (0, Axis::Z)]).unwrap();
+        let results: Vec<_> = leaves
+            .iter()
+            .map(|(results, _, _)| results.clone())
+            .collect();
+        use MeasurementResult::{MinusOne, PlusOne};
+        assert_eq!(
+            results,
+            vec![
+                vec![PlusOne, PlusOne, PlusOne],
+                vec![PlusOne, PlusOne, MinusOne],
+                vec![MinusOne, MinusOne, PlusOne],
+                vec![MinusOne, MinusOne, MinusOne],
+            ]
+        );
+        assert!(leaves.iter().all(|&(_, prob, _)| prob == 0.25));
+        assert_eq!(leaves[1].2.deterministic_outcome(1, Axis::Z), None);
+        assert_eq!(
+            leaves[1].2.deterministic_outcome(0, Axis::Z),
+            Some(MinusOne)
+        );
+        assert!(enumerate_outcomes(&sim, &[(2, Axis::Z)]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use crate::circuit::{Circuit, Op};
+
+/// Whether `op` is diagonal in the Z basis, so commutes with every other diagonal op.
+fn is_diagonal(op: &Op) -> bool {
+    matches!(
+        op,
+        Op::Z { .. } | Op::S { .. } | Op::Sdag { .. } | Op::Cz { .. }
+    )
+}
+
+/// For every operation, the earlier operations that have to stay before it.
+///
+/// Two operations on a common qubit are ordered unless both are diagonal, and all
+/// measurements keep their order so outcome indices stay valid.
+fn dependencies(ops: &[Op]) -> Vec<Vec<usize>> {
+    // per qubit, the operations of the last run of diagonal or of other operations,
+    // and the run before it
+    let mut runs: Vec<(bool, Vec<usize>, Vec<usize>)> = Vec::new();
+    let mut last_measurement = None;
+    let mut deps = Vec::with_capacity(ops.len());
+
+    for (idx, op) in ops.iter().enumerate() {
+        let diagonal = is_diagonal(op);
+        let mut op_deps = Vec::new();
+        for qubit in op.qubits() {
+            if runs.len() <= qubit {
+                runs.resize(qubit + 1, (false, Vec::new(), Vec::new()));
+            }
+            let (run_diagonal, run, previous) = &mut runs[qubit];
+            if diagonal && *run_diagonal {
+                op_deps.extend_from_slice(previous);
+                run.push(idx);
+            } else {
+                op_deps.extend_from_slice(run);
+                *previous = std::mem::replace(run, vec![idx]);
+                *run_diagonal = diagonal;
+            }
+        }
+        if let Op::Measure { .. } = op {
+            op_deps.extend(last_measurement.replace(idx));
+        }
+        op_deps.sort_unstable();
+        op_deps.dedup();
+        deps.push(op_deps);
+    }
+    deps
+}
+
+/// Reorder the operations of `circuit` without changing what it does.
+///
+/// Operations only move past operations they commute with: operations on other
+/// qubits, or diagonal operations (Z, S, S†, CZ) past each other. Measurements keep
+/// their order. Among the operations that can go next, one sharing a qubit with the
+/// previous operation is preferred, which groups CZs around common qubits so the VOPs
+/// those qubits need cleared are cleared once instead of once per interleaved gate.
+/// Ties go to the earliest operation in the original order.
+pub(crate) fn schedule(circuit: &Circuit) -> Circuit {
+    let ops = circuit.ops_slice();
+    let deps = dependencies(ops);
+    let qubit_amount = ops
+        .iter()
+        .flat_map(|op| op.qubits())
+        .max()
+        .map_or(0, |max| max + 1);
+
+    let mut dependents = vec![Vec::new(); ops.len()];
+    let mut waiting: Vec<usize> = deps.iter().map(|deps| deps.len()).collect();
+    for (idx, op_deps) in deps.iter().enumerate() {
+        for &dep in op_deps {
+            dependents[dep].push(idx);
+        }
+    }
+
+    // ready operations, all of them and per qubit they act on
+    let mut ready = BTreeSet::new();
+    let mut ready_on = vec![BTreeSet::new(); qubit_amount];
+    let mark_ready = |idx: usize, ready: &mut BTreeSet<_>, ready_on: &mut [BTreeSet<_>]| {
+        ready.insert(idx);
+        for qubit in ops[idx].qubits() {
+            ready_on[qubit].insert(idx);
+        }
+    };
+    for idx in (0..ops.len()).filter(|&idx| waiting[idx] == 0) {
+        mark_ready(idx, &mut ready, &mut ready_on);
+    }
+
+    let mut order = Vec::with_capacity(ops.len());
+    let mut last_qubits = Vec::new();
+    while let Some(&first) = ready.first() {
+        let next = last_qubits
+            .iter()
+            .filter_map(|&qubit: &usize| ready_on[qubit].first().copied())
+            .min()
+            .unwrap_or(first);
+        ready.remove(&next);
+        last_qubits = ops[next].qubits();
+        for &qubit in &last_qubits {
+            ready_on[qubit].remove(&next);
+        }
+        order.push(ops[next]);
+        for &dependent in &dependents[next] {
+            waiting[dependent] -= 1;
+            if waiting[dependent] == 0 {
+                mark_ready(dependent, &mut ready, &mut ready_on);
+            }
+        }
+    }
+    circuit.with_ops(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::GraphSim;
+
+    #[test]
+    fn test_schedule_groups_cz() {
+        let circuit =
+            Circuit::from_text("new_qubit 4\nh 0\nh 1\nh 2\nh 3\ncz 0 1\ncz 2 3\ncz 0 2\ncz 1 3")
+                .unwrap();
+        let scheduled = schedule(&circuit);
+        let order: Vec<usize> = scheduled
+            .iter()
+            .map(|op| circuit.iter().position(|other| other == op).unwrap())
+            .collect();
+        assert_eq!(order, vec![0, 1, 4, 2, 6, 3, 5, 7]);
+
+        let (mut sim, mut other) = (GraphSim::new(4), GraphSim::new(4));
+        circuit.execute(&mut sim);
+        scheduled.execute(&mut other);
+        assert_eq!(sim.canonical_hash(), other.canonical_hash());
+    }
+
+    #[test]
+    fn test_schedule_keeps_dependencies() {
+        let circuit =
+            Circuit::from_text("new_qubit 3\nh 0\ncz 0 1\nh 0\nmeasure_z 2\nh 2\nmeasure_x 1")
+                .unwrap();
+        let deps = dependencies(circuit.ops_slice());
+        assert_eq!(
+            deps,
+            vec![vec![], vec![0], vec![1], vec![], vec![3], vec![1, 3]]
+        );
+        assert_eq!(
+            dependencies(&schedule(&circuit).ops_slice()[..3]),
+            vec![vec![], vec![0], vec![1]]
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Newline-delimited JSON server driving simulators over TCP, behind the `server` feature.
+//!
+//! Every request is one line holding an object `{"id": ..., "method": ..., "params": {...}}`
+//! and is answered by one line `{"id": ..., "result": ...}` or `{"id": ..., "error": "..."}`,
+//! echoing the `id` of the request. The methods are
+//!
+//! - `create` with `qubits`, returning the id of a new simulator as `{"sim": id}`, up to
+//!   the maximum number of qubits the server was started with,
+//! - `gate` with `sim`, `gate` (a name of the text format, e.g. `"cx"`) and `qubits`,
+//! - `measure` with `sim`, `qubit` and an optional `axis` (`"X"`, `"Y"` or `"Z"`, the
+//!   default), returning the outcome as 1 or -1,
+//! - `query` with `sim`, returning `qubits`, `edges`, `vops`, `traced` and `hash`,
+//! - `delete` with `sim`, dropping the simulator.
+//!
+//! Simulators are shared by all connections, so several clients can work on one state.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    text::parse_op,
+    validate::validate,
+};
+
+/// Largest simulator `create` makes unless the server is given another maximum.
+pub const DEFAULT_MAX_QUBITS: usize = 1 << 20;
+
+/// The simulators created by clients, by id.
+pub struct Server {
+    sims: HashMap<u64, GraphSim>,
+    next_id: u64,
+    /// Largest number of qubits `create` accepts, so a client can't exhaust the memory.
+    max_qubits: usize,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server::new(DEFAULT_MAX_QUBITS)
+    }
+}
+
+fn param<'a>(params: &'a Map<String, Value>, name: &str) -> Result<&'a Value, String> {
+    params
+        .get(name)
+        .ok_or_else(|| format!("Missing parameter `{name}`"))
+}
+
+fn index(value: &Value, name: &str) -> Result<usize, String> {
+    value
+        .as_u64()
+        .and_then(|idx| usize::try_from(idx).ok())
+        .ok_or_else(|| format!("Parameter `{name}` has to be a non-negative integer"))
+}
+
+fn outcome(result: MeasurementResult) -> Value {
+    match result {
+        MeasurementResult::PlusOne => json!(1),
+        MeasurementResult::MinusOne => json!(-1),
+    }
+}
+
+impl Server {
+    /// A server without simulators, creating ones of at most `max_qubits` qubits.
+    pub fn new(max_qubits: usize) -> Server {
+        Server {
+            sims: HashMap::new(),
+            next_id: 0,
+            max_qubits,
+        }
+    }
+
+    /// Answer the request on `line`, as the line to send back without its newline.
+    pub fn handle(&mut self, line: &str) -> String {
+        let request = serde_json::from_str::<Value>(line);
+        let id = request
+            .as_ref()
+            .ok()
+            .and_then(|request| request.get("id"))
+            .cloned()
+            .unwrap_or(Value::Null);
+        let response = match request {
+            Ok(request) => match self.dispatch(&request) {
+                Ok(result) => json!({"id": id, "result": result}),
+                Err(message) => json!({"id": id, "error": message}),
+            },
+            Err(err) => json!({"id": id, "error": format!("Invalid JSON: {err}")}),
+        };
+        response.to_string()
+    }
+
+    fn dispatch(&mut self, request: &Value) -> Result<Value, String> {
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .ok_or("Missing method")?;
+        let empty = Map::new();
+        let params = match request.get("params") {
+            None => &empty,
+            Some(Value::Object(params)) => params,
+            Some(_) => return Err("Parameters have to be an object".to_string()),
+        };
+        match method {
+            "create" => {
+                let qubits = index(param(params, "qubits")?, "qubits")?;
+                if qubits > self.max_qubits {
+                    return Err(format!(
+                        "Can't create {qubits} qubits, the maximum is {}",
+                        self.max_qubits
+                    ));
+                }
+                let id = self.next_id;
+                self.next_id += 1;
+                self.sims.insert(id, GraphSim::new(qubits));
+                Ok(json!({"sim": id}))
+            }
+            "gate" => {
+                let name = param(params, "gate")?
+                    .as_str()
+                    .ok_or("Parameter `gate` has to be a string")?;
+                let qubits = param(params, "qubits")?
+                    .as_array()
+                    .ok_or("Parameter `qubits` has to be a list")?
+                    .iter()
+                    .map(|qubit| index(qubit, "qubits"))
+                    .collect::<Result<Vec<NodeIdx>, String>>()?;
+                let op = parse_op(name, &qubits)
+                    .ok_or_else(|| format!("Unknown gate `{name}` on {} qubits", qubits.len()))?;
+                let result = self.apply(params, op)?;
+                Ok(result.map_or(Value::Null, outcome))
+            }
+            "measure" => {
+                let qubit = index(param(params, "qubit")?, "qubit")?;
+                let axis = match params.get("axis").map(|axis| axis.as_str()) {
+                    None | Some(Some("Z" | "z")) => Axis::Z,
+                    Some(Some("X" | "x")) => Axis::X,
+                    Some(Some("Y" | "y")) => Axis::Y,
+                    Some(_) => return Err("Parameter `axis` has to be X, Y or Z".to_string()),
+                };
+                let result = self.apply(params, Op::Measure { qubit, axis })?;
+                Ok(outcome(result.expect("Measurements have an outcome")))
+            }
+            "query" => {
+                let sim = self.sim(params)?;
+                let vops: Vec<String> = sim.vops().iter().map(|vop| format!("{vop:?}")).collect();
+                Ok(json!({
+                    "qubits": vops.len(),
+                    "edges": sim.edges().collect::<Vec<_>>(),
+                    "vops": vops,
+                    "traced": sim.traced_qubits(),
+                    "hash": sim.state_hash(),
+                }))
+            }
+            "delete" => {
+                let id = param(params, "sim")?;
+                self.sims
+                    .remove(&id.as_u64().unwrap_or(u64::MAX))
+                    .ok_or_else(|| format!("No simulator {id}"))?;
+                Ok(Value::Null)
+            }
+            _ => Err(format!("Unknown method `{method}`")),
+        }
+    }
+
+    fn sim(&mut self, params: &Map<String, Value>) -> Result<&mut GraphSim, String> {
+        let id = param(params, "sim")?;
+        id.as_u64()
+            .and_then(|id| self.sims.get_mut(&id))
+            .ok_or_else(|| format!("No simulator {id}"))
+    }
+
+    /// Apply `op` to the simulator in `params`, if it can run on it.
+    fn apply(
+        &mut self,
+        params: &Map<String, Value>,
+        op: Op,
+    ) -> Result<Option<MeasurementResult>, String> {
+        let sim = self.sim(params)?;
+        let mut circuit = Circuit::new(0);
+        circuit.append(op);
+        if let Some(problem) = validate(sim, &circuit).into_iter().next() {
+            return Err(problem.to_string());
+        }
+        Ok(op.apply(sim))
+    }
+}
+
+fn serve_client(server: &Mutex<Server>, stream: TcpStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = server
+            .lock()
+            .expect("Requests don't panic while holding the lock")
+            .handle(&line);
+        writeln!(writer, "{response}")?;
+    }
+    Ok(())
+}
+
+/// Listen on `addr` and answer the requests of every client on its own thread, creating
+/// simulators of at most `max_qubits` qubits.
+pub fn serve(addr: impl ToSocketAddrs, max_qubits: usize) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let server = Arc::new(Mutex::new(Server::new(max_qubits)));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            let peer = stream.peer_addr();
+            if let Err(err) = serve_client(&server, stream) {
+                eprintln!("Connection to {peer:?} failed: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_requests() {
+        let mut server = Server::default();
+        let mut request = |line: &str| -> Value {
+            serde_json::from_str(&server.handle(line)).expect("Responses are JSON")
+        };
+
+        assert_eq!(
+            request(r#"{"id": 1, "method": "create", "params": {"qubits": 3}}"#),
+            json!({"id": 1, "result": {"sim": 0}})
+        );
+        for line in [
+            r#"{"id": 2, "method": "gate", "params": {"sim": 0, "gate": "h", "qubits": [0]}}"#,
+            r#"{"id": 3, "method": "gate", "params": {"sim": 0, "gate": "cx", "qubits": [0, 1]}}"#,
+        ] {
+            assert_eq!(request(line)["result"], Value::Null);
+        }
+        let query = request(r#"{"id": 4, "method": "query", "params": {"sim": 0}}"#);
+        assert_eq!(query["result"]["qubits"], json!(3));
+        assert_eq!(query["result"]["edges"].as_array().unwrap().len(), 1);
+
+        let first = request(r#"{"method": "measure", "params": {"sim": 0, "qubit": 0}}"#);
+        let second = request(
+            r#"{"method": "gate", "params": {"sim": 0, "gate": "measure_z", "qubits": [1]}}"#,
+        );
+        assert_eq!(first["result"], second["result"]);
+
+        for line in [
+            "not json",
+            r#"{"method": "teleport"}"#,
+            r#"{"method": "gate", "params": {"sim": 0, "gate": "cz", "qubits": [0, 0]}}"#,
+            r#"{"method": "gate", "params": {"sim": 0, "gate": "h", "qubits": [3]}}"#,
+            r#"{"method": "measure", "params": {"sim": 1, "qubit": 0}}"#,
+        ] {
+            assert!(request(line)["error"].is_string(), "{line} should fail");
+        }
+        assert_eq!(
+            request(r#"{"id": "end", "method": "delete", "params": {"sim": 0}}"#),
+            json!({"id": "end", "result": null})
+        );
+    }
+
+    #[test]
+    fn test_create_is_limited() {
+        let mut server = Server::new(10);
+        let mut request = |line: &str| -> Value {
+            serde_json::from_str(&server.handle(line)).expect("Responses are JSON")
+        };
+
+        assert_eq!(
+            request(r#"{"id": 1, "method": "create", "params": {"qubits": 10}}"#),
+            json!({"id": 1, "result": {"sim": 0}})
+        );
+        assert_eq!(
+            request(r#"{"id": 2, "method": "create", "params": {"qubits": 11}}"#),
+            json!({"id": 2, "error": "Can't create 11 qubits, the maximum is 10"})
+        );
+        let huge = request(r#"{"method": "create", "params": {"qubits": 18446744073709551615}}"#);
+        assert!(huge["error"].is_string());
+    }
+}
@@ -0,0 +1,191 @@
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+
+use crate::{
+    gadget::{PauliProduct, parse_observable},
+    graphsim::{Axis, GraphSim, MeasurementResult},
+};
+
+/// A classical-shadow dataset: a random Pauli basis per qubit and the outcomes, per shot.
+///
+/// A random single-qubit Clifford followed by a Z measurement is a measurement in a
+/// uniformly random Pauli basis, so only the bases are kept. Build one with
+/// `GraphSim.classical_shadow`.
+#[pyclass(frozen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassicalShadow {
+    qubit_amount: usize,
+    bases: Vec<Vec<Axis>>,
+    /// Qubits that gave −1, per shot.
+    outcomes: Vec<BitSet>,
+}
+
+/// Measure every qubit of copies of `sim` in random Pauli bases, for `shots` shots.
+pub(crate) fn shadow<R: Rng + ?Sized>(
+    sim: &GraphSim,
+    shots: usize,
+    rng: &mut R,
+) -> ClassicalShadow {
+    let qubit_amount = sim.vops().len();
+    let (mut bases, mut outcomes) = (Vec::with_capacity(shots), Vec::with_capacity(shots));
+    for _ in 0..shots {
+        let mut scratch = sim.clone();
+        let basis: Vec<Axis> = (0..qubit_amount).map(|_| rng.random()).collect();
+        let mut minus = BitSet::with_capacity(qubit_amount);
+        for (qubit, &axis) in basis.iter().enumerate() {
+            if scratch.is_traced(qubit) {
+                // a traced out qubit is in |0⟩
+                if axis != Axis::Z && rng.random() {
+                    minus.insert(qubit);
+                }
+                continue;
+            }
+            if scratch.measure(qubit, axis).0 == MeasurementResult::MinusOne {
+                minus.insert(qubit);
+            }
+        }
+        bases.push(basis);
+        outcomes.push(minus);
+    }
+    ClassicalShadow {
+        qubit_amount,
+        bases,
+        outcomes,
+    }
+}
+
+impl ClassicalShadow {
+    /// Single-shot estimates of `product`: 3^k times the product of the outcomes on its
+    /// `k` qubits if every basis matches its Pauli there, and 0 otherwise.
+    fn snapshots(&self, product: &PauliProduct) -> impl Iterator<Item = f64> + '_ {
+        let weight = 3f64.powi(product.paulis.len() as i32);
+        let paulis = product.paulis.clone();
+        let negative = product.negative;
+        self.bases
+            .iter()
+            .zip(&self.outcomes)
+            .map(move |(basis, minus)| {
+                let mut parity = negative;
+                for &(qubit, axis) in &paulis {
+                    if basis[qubit] != axis {
+                        return 0.0;
+                    }
+                    parity ^= minus.contains(qubit);
+                }
+                if parity { -weight } else { weight }
+            })
+    }
+
+    /// Median of the means of `groups` equal parts of the snapshots of `product`.
+    fn median_of_means(&self, product: &PauliProduct, groups: usize) -> f64 {
+        let snapshots: Vec<f64> = self.snapshots(product).collect();
+        let size = snapshots.len() / groups;
+        let mut means: Vec<f64> = snapshots
+            .chunks_exact(size)
+            .take(groups)
+            .map(|chunk| chunk.iter().sum::<f64>() / size as f64)
+            .collect();
+        means.sort_unstable_by(f64::total_cmp);
+        match groups % 2 {
+            1 => means[groups / 2],
+            _ => (means[groups / 2 - 1] + means[groups / 2]) / 2.0,
+        }
+    }
+}
+
+#[pymethods]
+impl ClassicalShadow {
+    /// Number of shots in the dataset.
+    pub fn __len__(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Number of qubits every shot measured.
+    #[getter]
+    pub fn qubit_amount(&self) -> usize {
+        self.qubit_amount
+    }
+
+    /// Measurement basis of every qubit per shot, like `"XZY"`.
+    #[getter]
+    pub fn bases(&self) -> Vec<String> {
+        self.bases
+            .iter()
+            .map(|basis| basis.iter().map(Axis::to_string).collect())
+            .collect()
+    }
+
+    /// Whether every qubit gave −1 per shot.
+    #[getter]
+    pub fn outcomes(&self) -> Vec<Vec<bool>> {
+        self.outcomes
+            .iter()
+            .map(|minus| {
+                (0..self.qubit_amount)
+                    .map(|qubit| minus.contains(qubit))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Estimate the expectation value of the Pauli string `pauli`, like for
+    /// `GraphSim.expectations`, from the shadow.
+    ///
+    /// This is the standard estimator of random Pauli measurements, unbiased with a
+    /// variance of up to 3^k for a Pauli on `k` qubits. With `groups` above 1 the shots
+    /// are split into that many groups and the median of their means is returned, which
+    /// is robust against outliers when estimating many observables.
+    #[pyo3(signature = (pauli, groups = 1))]
+    pub fn estimate(&self, pauli: &str, groups: usize) -> PyResult<f64> {
+        Ok(self.estimate_many(vec![pauli.to_string()], groups)?[0])
+    }
+
+    /// Estimate every Pauli string of `paulis`, like `estimate`.
+    #[pyo3(signature = (paulis, groups = 1))]
+    pub fn estimate_many(&self, paulis: Vec<String>, groups: usize) -> PyResult<Vec<f64>> {
+        if groups == 0 || groups > self.bases.len() {
+            return Err(PyValueError::new_err(format!(
+                "Can't split {} shots into {groups} groups",
+                self.bases.len()
+            )));
+        }
+        paulis
+            .iter()
+            .map(|pauli| {
+                let product = parse_observable(pauli, None, self.qubit_amount)?;
+                Ok(self.median_of_means(&product, groups))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_shadow_estimates_bell_pair() {
+        let mut sim = GraphSim::new(2);
+        sim.h(0);
+        sim.cx(0, 1);
+        let data = shadow(&sim, 3000, &mut StdRng::seed_from_u64(7));
+        assert_eq!(data.__len__(), 3000);
+        assert_eq!(data.bases()[0].len(), 2);
+
+        let estimates = data
+            .estimate_many(
+                ["XX", "-YY", "ZZ", "__", "XZ", "Z_"]
+                    .map(String::from)
+                    .to_vec(),
+                5,
+            )
+            .unwrap();
+        for (estimate, expected) in estimates.iter().zip([1.0, 1.0, 1.0, 1.0, 0.0, 0.0]) {
+            assert!((estimate - expected).abs() < 0.3, "{estimates:?}");
+        }
+        assert!(data.estimate("XX", 0).is_err());
+    }
+}
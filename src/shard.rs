@@ -0,0 +1,336 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{GraphSim, MeasurementResult, NodeIdx},
+    network::tensor_product,
+    text::parse_op,
+};
+
+/// A group of qubits simulated together, with the global index of every local qubit.
+#[derive(Clone, Debug)]
+struct Shard {
+    sim: GraphSim,
+    qubits: Vec<NodeIdx>,
+}
+
+/// A simulation split into shards by connected components, each with its own `GraphSim`.
+///
+/// Qubits start out in a shard of their own. A two-qubit gate between shards migrates
+/// the qubits of the smaller one into the larger, so every shard holds whole components
+/// and `split_components` divides shards again once measurements cut them apart. Since
+/// shards never share entanglement, each one can be simulated on its own, e.g. in
+/// another process through `shards` and `GraphSim.to_shared_memory`, and a shard only
+/// has to fit in the memory of one machine when `max_shard_qubits` says so.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct ShardedGraphSim {
+    /// Shards by their index, `None` for ones that were merged into another.
+    shards: Vec<Option<Shard>>,
+    /// Shard and local index of every qubit.
+    location: Vec<(usize, NodeIdx)>,
+    max_shard_qubits: Option<usize>,
+    migrations: usize,
+}
+
+impl ShardedGraphSim {
+    fn shard(&self, idx: usize) -> &Shard {
+        self.shards[idx]
+            .as_ref()
+            .expect("Qubits are in a live shard")
+    }
+
+    /// Index of an empty slot for a new shard.
+    fn free_slot(&mut self) -> usize {
+        match self.shards.iter().position(Option::is_none) {
+            Some(idx) => idx,
+            None => {
+                self.shards.push(None);
+                self.shards.len() - 1
+            }
+        }
+    }
+
+    /// Put `shard` at `idx` and point its qubits there.
+    fn place(&mut self, idx: usize, shard: Shard) {
+        for (local, &qubit) in shard.qubits.iter().enumerate() {
+            self.location[qubit] = (idx, local);
+        }
+        self.shards[idx] = Some(shard);
+    }
+
+    /// Migrate the qubits of the smaller of the shards `a` and `b` into the larger one,
+    /// returning the index of the merged shard.
+    fn join(&mut self, a: usize, b: usize) -> PyResult<usize> {
+        let (a_len, b_len) = (self.shard(a).qubits.len(), self.shard(b).qubits.len());
+        if let Some(max) = self.max_shard_qubits
+            && a_len + b_len > max
+        {
+            return Err(PyValueError::new_err(format!(
+                "Joining shards of {a_len} and {b_len} qubits exceeds the maximum of {max}"
+            )));
+        }
+        let (keep, moved) = match a_len >= b_len {
+            true => (a, b),
+            false => (b, a),
+        };
+        let moved = self.shards[moved]
+            .take()
+            .expect("Qubits are in a live shard");
+        let mut kept = self.shards[keep]
+            .take()
+            .expect("Qubits are in a live shard");
+        kept.sim = tensor_product([&kept.sim, &moved.sim]).0;
+        kept.qubits.extend(&moved.qubits);
+        self.migrations += moved.qubits.len();
+        self.place(keep, kept);
+        Ok(keep)
+    }
+
+    /// Perform `op` on global qubits, joining the shards it acts on first.
+    fn route(&mut self, op: Op) -> PyResult<Option<MeasurementResult>> {
+        let qubits = op.qubits();
+        if let Some(&qubit) = qubits.iter().find(|&&qubit| qubit >= self.location.len()) {
+            return Err(PyValueError::new_err(format!(
+                "Qubit {qubit} is out of range for {} qubits",
+                self.location.len()
+            )));
+        }
+        let mut idx = self.location[qubits[0]].0;
+        for &qubit in &qubits[1..] {
+            let other = self.location[qubit].0;
+            if other != idx {
+                idx = self.join(idx, other)?;
+            }
+        }
+        let local: Vec<NodeIdx> = qubits.iter().map(|&qubit| self.location[qubit].1).collect();
+        let op = Op::from_code(op.code(), local[0], local.get(1).copied().unwrap_or(0))
+            .expect("Codes of operations decode");
+        let shard = self.shards[idx]
+            .as_mut()
+            .expect("Qubits are in a live shard");
+        Ok(op.apply(&mut shard.sim))
+    }
+
+    /// Divide shard `idx` into one shard per connected component.
+    fn split(&mut self, idx: usize) {
+        let shard = self.shards[idx].take().expect("Split shards are live");
+        let adjacency = shard.sim.adjacency();
+        let mut component = vec![usize::MAX; adjacency.len()];
+        let mut members: Vec<Vec<NodeIdx>> = Vec::new();
+        for root in 0..adjacency.len() {
+            if component[root] != usize::MAX {
+                continue;
+            }
+            let mut stack = vec![root];
+            component[root] = members.len();
+            let mut nodes = Vec::new();
+            while let Some(node) = stack.pop() {
+                nodes.push(node);
+                for nb in adjacency[node].iter() {
+                    if component[nb] == usize::MAX {
+                        component[nb] = members.len();
+                        stack.push(nb);
+                    }
+                }
+            }
+            nodes.sort_unstable();
+            members.push(nodes);
+        }
+        if members.len() == 1 {
+            self.shards[idx] = Some(shard);
+            return;
+        }
+
+        let vops = shard.sim.vops();
+        for (part, nodes) in members.iter().enumerate() {
+            let local = |node: NodeIdx| nodes.binary_search(&node).expect("Components are closed");
+            let sim = GraphSim::from_parts(
+                nodes.iter().map(|&node| vops[node]).collect(),
+                nodes
+                    .iter()
+                    .map(|&node| adjacency[node].iter().map(local).collect())
+                    .collect(),
+                Default::default(),
+            );
+            let qubits = nodes.iter().map(|&node| shard.qubits[node]).collect();
+            let slot = match part {
+                0 => idx,
+                _ => self.free_slot(),
+            };
+            self.place(slot, Shard { sim, qubits });
+        }
+    }
+}
+
+#[pymethods]
+impl ShardedGraphSim {
+    /// Create a sharded simulation of `qubit_amount` qubits in the |0⟩ state.
+    ///
+    /// Raises `ValueError` from any gate that would grow a shard past `max_shard_qubits`.
+    #[new]
+    #[pyo3(signature = (qubit_amount, max_shard_qubits = None))]
+    pub fn new(qubit_amount: usize, max_shard_qubits: Option<usize>) -> ShardedGraphSim {
+        ShardedGraphSim {
+            shards: (0..qubit_amount)
+                .map(|qubit| {
+                    Some(Shard {
+                        sim: GraphSim::new(1),
+                        qubits: vec![qubit],
+                    })
+                })
+                .collect(),
+            location: (0..qubit_amount).map(|qubit| (qubit, 0)).collect(),
+            max_shard_qubits,
+            migrations: 0,
+        }
+    }
+
+    /// Number of qubits of the simulation.
+    pub fn __len__(&self) -> usize {
+        self.location.len()
+    }
+
+    /// Number of qubits that were migrated to another shard so far.
+    #[getter]
+    pub fn migrations(&self) -> usize {
+        self.migrations
+    }
+
+    /// Apply the gate or measurement `name` of the text format to `qubits`.
+    ///
+    /// Returns the outcome for measurements and `None` for gates.
+    pub fn apply(
+        &mut self,
+        name: &str,
+        qubits: Vec<NodeIdx>,
+    ) -> PyResult<Option<MeasurementResult>> {
+        let op = parse_op(name, &qubits).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unknown operation `{name}` on {} qubits",
+                qubits.len()
+            ))
+        })?;
+        self.route(op)
+    }
+
+    /// Run `circuit` on the simulation, returning the measurement outcomes in order.
+    pub fn run(&mut self, circuit: &Circuit) -> PyResult<Vec<MeasurementResult>> {
+        if circuit.qubit_amount() > self.location.len() {
+            return Err(PyValueError::new_err(format!(
+                "Circuit has {} qubits but the simulation {}",
+                circuit.qubit_amount(),
+                self.location.len()
+            )));
+        }
+        let mut outcomes = Vec::with_capacity(circuit.num_measurements());
+        for &op in circuit.iter() {
+            outcomes.extend(self.route(op)?);
+        }
+        Ok(outcomes)
+    }
+
+    /// Divide every shard into its connected components, returning the number of shards.
+    ///
+    /// Shards only grow by themselves, so this is worth calling after measurements
+    /// disentangled parts of a shard, before the next migration.
+    pub fn split_components(&mut self) -> usize {
+        for idx in 0..self.shards.len() {
+            if self.shards[idx].is_some() {
+                self.split(idx);
+            }
+        }
+        self.shard_sizes().len()
+    }
+
+    /// Return the number of qubits of every shard.
+    pub fn shard_sizes(&self) -> Vec<usize> {
+        self.shards
+            .iter()
+            .flatten()
+            .map(|shard| shard.qubits.len())
+            .collect()
+    }
+
+    /// Return `(qubits, sim)` for every shard, where local qubit `i` of `sim` is
+    /// `qubits[i]` of the simulation.
+    pub fn shards(&self) -> Vec<(Vec<NodeIdx>, GraphSim)> {
+        self.shards
+            .iter()
+            .flatten()
+            .map(|shard| (shard.qubits.clone(), shard.sim.clone()))
+            .collect()
+    }
+
+    /// Return the shard holding `qubit` and its index within the shard's simulator, with
+    /// shards numbered like in `shards`.
+    pub fn locate(&self, qubit: NodeIdx) -> PyResult<(usize, NodeIdx)> {
+        let &(idx, local) = self.location.get(qubit).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Qubit {qubit} is out of range for {} qubits",
+                self.location.len()
+            ))
+        })?;
+        let position = self.shards[..idx].iter().flatten().count();
+        Ok((position, local))
+    }
+
+    /// Gather the shards into a single simulator of all qubits.
+    ///
+    /// Shards number their qubits in the order they joined, so the graph can differ from
+    /// running the same gates on one `GraphSim` by local complementations, for the same
+    /// state.
+    pub fn state(&self) -> GraphSim {
+        let shards: Vec<&Shard> = self.shards.iter().flatten().collect();
+        let (joint, ranges) = tensor_product(shards.iter().map(|shard| &shard.sim));
+        let mut vops = Vec::with_capacity(self.location.len());
+        let mut adjacent = Vec::with_capacity(self.location.len());
+        let global = |joint: NodeIdx| {
+            let shard = ranges.partition_point(|range| range.end <= joint);
+            shards[shard].qubits[joint - ranges[shard].start]
+        };
+        let mut order: Vec<NodeIdx> = (0..joint.vops().len()).collect();
+        order.sort_unstable_by_key(|&node| global(node));
+        for node in order {
+            vops.push(joint.vops()[node]);
+            adjacent.push(joint.adjacency()[node].iter().map(global).collect());
+        }
+        GraphSim::from_parts(vops, adjacent, Default::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shards_follow_components() {
+        let mut circuit = Circuit::new(6);
+        circuit.h(0);
+        circuit.cx(0, 1);
+        circuit.h(3);
+        circuit.cz(3, 4);
+        circuit.cx(1, 4);
+        circuit.h(5);
+
+        let mut sharded = ShardedGraphSim::new(6, None);
+        sharded.run(&circuit).unwrap();
+        let mut sizes = sharded.shard_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 4]);
+        assert_eq!(sharded.migrations(), 4);
+
+        let mut whole = GraphSim::new(6);
+        circuit.execute(&mut whole);
+        assert_eq!(sharded.state(), whole);
+
+        // measuring 1 and 4 in Z cuts the component apart again
+        sharded.apply("measure_z", vec![1]).unwrap();
+        sharded.apply("measure_z", vec![4]).unwrap();
+        assert_eq!(sharded.split_components(), 6);
+
+        let mut capped = ShardedGraphSim::new(3, Some(2));
+        capped.apply("cz", vec![0, 1]).unwrap();
+        assert!(capped.apply("cz", vec![1, 2]).is_err());
+    }
+}
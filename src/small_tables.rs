@@ -1,139 +0,0 @@
-use crate::{
-    MEAS_AXES, SYMMETRIES,
-    graphsim::{Axis, Vop},
-};
-
-pub(crate) const ADJ_TABLE: [Vop; SYMMETRIES] = [
-    Vop::IA,
-    Vop::XA,
-    Vop::YA,
-    Vop::ZA,
-    Vop::IB,
-    Vop::YB,
-    Vop::XB,
-    Vop::ZB,
-    Vop::IC,
-    Vop::ZC,
-    Vop::YC,
-    Vop::XC,
-    Vop::ID,
-    Vop::XD,
-    Vop::ZD,
-    Vop::YD,
-    Vop::IF,
-    Vop::YF,
-    Vop::ZF,
-    Vop::XF,
-    Vop::IE,
-    Vop::ZE,
-    Vop::XE,
-    Vop::YE,
-];
-
-pub(crate) const DETM_TABLE: [Axis; SYMMETRIES] = [
-    Axis::X,
-    Axis::X,
-    Axis::X,
-    Axis::X,
-    Axis::Y,
-    Axis::Y,
-    Axis::Y,
-    Axis::Y,
-    Axis::Z,
-    Axis::Z,
-    Axis::Z,
-    Axis::Z,
-    Axis::X,
-    Axis::X,
-    Axis::X,
-    Axis::X,
-    Axis::Z,
-    Axis::Z,
-    Axis::Z,
-    Axis::Z,
-    Axis::Y,
-    Axis::Y,
-    Axis::Y,
-    Axis::Y,
-];
-
-pub(crate) const CONJ_TABLE: [[Axis; SYMMETRIES]; MEAS_AXES] = [
-    [
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-    ],
-    [
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-    ],
-    [
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::Z,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::X,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-        Axis::Y,
-    ],
-];
@@ -0,0 +1,179 @@
+use bit_set::BitSet;
+use pyo3::{buffer::PyBuffer, exceptions::PyValueError, prelude::*, types::PySlice};
+
+use crate::{
+    SYMMETRIES,
+    graphsim::{GraphSim, NodeIdx, Vop},
+};
+
+const MAGIC: &[u8; 4] = b"GSIM";
+const VERSION: u8 = 1;
+
+fn push(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend((value as u64).to_le_bytes());
+}
+
+/// Serialize the graph, VOPs and traced qubits of `sim`.
+///
+/// The layout is the magic `GSIM`, a version byte and then little-endian `u64`s: the
+/// number of qubits, one byte per VOP, the number of traced qubits followed by them,
+/// and the number of edges followed by their endpoints.
+pub(crate) fn encode(sim: &GraphSim) -> Vec<u8> {
+    let edges: Vec<(NodeIdx, NodeIdx)> = sim.edges().collect();
+    let traced: Vec<NodeIdx> = sim.traced_qubits();
+    let qubits = sim.adjacency().len();
+    let mut bytes = Vec::with_capacity(5 + 8 * 3 + qubits + 8 * (traced.len() + 2 * edges.len()));
+
+    bytes.extend(MAGIC);
+    bytes.push(VERSION);
+    push(&mut bytes, qubits);
+    bytes.extend(sim.vops().iter().map(|&vop| vop as u8));
+    push(&mut bytes, traced.len());
+    for qubit in traced {
+        push(&mut bytes, qubit);
+    }
+    push(&mut bytes, edges.len());
+    for (a, b) in edges {
+        push(&mut bytes, a);
+        push(&mut bytes, b);
+    }
+    bytes
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> PyResult<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(PyValueError::new_err("Snapshot is truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn usize(&mut self) -> PyResult<usize> {
+        let bytes = self.take(8)?.try_into().expect("Took 8 bytes");
+        usize::try_from(u64::from_le_bytes(bytes))
+            .map_err(|_| PyValueError::new_err("Snapshot value doesn't fit in memory"))
+    }
+
+    /// A qubit index below `qubits`.
+    fn qubit(&mut self, qubits: usize) -> PyResult<NodeIdx> {
+        match self.usize()? {
+            qubit if qubit < qubits => Ok(qubit),
+            qubit => Err(PyValueError::new_err(format!(
+                "Snapshot refers to qubit {qubit}, but has {qubits} qubits"
+            ))),
+        }
+    }
+}
+
+/// Rebuild a simulator from the output of `encode`, ignoring trailing bytes.
+pub(crate) fn decode(bytes: &[u8]) -> PyResult<GraphSim> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len()).ok() != Some(MAGIC) {
+        return Err(PyValueError::new_err("Not a GraphSim snapshot"));
+    }
+    let version = reader.take(1)?[0];
+    if version != VERSION {
+        return Err(PyValueError::new_err(format!(
+            "Unsupported snapshot version {version}"
+        )));
+    }
+
+    let qubits = reader.usize()?;
+    let vops = reader
+        .take(qubits)?
+        .iter()
+        .map(|&idx| match usize::from(idx) {
+            idx if idx < SYMMETRIES => Ok(Vop::from_index(idx)),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid VOP {idx} in snapshot"
+            ))),
+        })
+        .collect::<PyResult<Vec<Vop>>>()?;
+    let mut traced = BitSet::with_capacity(qubits);
+    for _ in 0..reader.usize()? {
+        traced.insert(reader.qubit(qubits)?);
+    }
+    let mut adjacent = vec![BitSet::new(); qubits];
+    for _ in 0..reader.usize()? {
+        let (a, b) = (reader.qubit(qubits)?, reader.qubit(qubits)?);
+        if a == b {
+            return Err(PyValueError::new_err(format!(
+                "Snapshot has an edge from qubit {a} to itself"
+            )));
+        }
+        adjacent[a].insert(b);
+        adjacent[b].insert(a);
+    }
+    Ok(GraphSim::from_parts(vops, adjacent, traced))
+}
+
+/// Copy the snapshot of `sim` into a new `multiprocessing.shared_memory.SharedMemory`.
+pub(crate) fn to_shared_memory<'py>(
+    py: Python<'py>,
+    sim: &GraphSim,
+) -> PyResult<Bound<'py, PyAny>> {
+    let bytes = encode(sim);
+    let shared_memory = py
+        .import("multiprocessing.shared_memory")?
+        .getattr("SharedMemory")?;
+    let segment = shared_memory.call((py.None(), true, bytes.len().max(1)), None)?;
+    segment.getattr("buf")?.set_item(
+        PySlice::new(py, 0, bytes.len() as isize, 1),
+        bytes.as_slice(),
+    )?;
+    Ok(segment)
+}
+
+/// Attach to the shared memory segment called `name` and decode the snapshot in it.
+pub(crate) fn from_shared_memory(py: Python<'_>, name: &str) -> PyResult<GraphSim> {
+    let shared_memory = py
+        .import("multiprocessing.shared_memory")?
+        .getattr("SharedMemory")?;
+    let segment = shared_memory.call1((name,))?;
+    let decoded = {
+        let view = segment.getattr("buf")?;
+        let buffer = PyBuffer::<u8>::get(&view)?;
+        let bytes = buffer.to_vec(py)?;
+        buffer.release(py);
+        view.call_method0("release")?;
+        py.detach(|| decode(&bytes))
+    };
+    segment.call_method0("close")?;
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut sim = GraphSim::new(6);
+        for qubit in 0..5 {
+            sim.h(qubit);
+            sim.s(qubit);
+        }
+        for qubit in 0..4 {
+            sim.cz(qubit, qubit + 1);
+        }
+        sim.trace_out(vec![5]);
+
+        let bytes = encode(&sim);
+        assert_eq!(bytes.len(), 5 + 8 + 6 + 8 * 2 + 8 + 16 * sim.edge_count());
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded, sim);
+        assert_eq!(decoded.state_hash(), sim.state_hash());
+
+        let mut padded = bytes.clone();
+        padded.extend([0; 100]);
+        assert_eq!(decode(&padded).unwrap(), sim);
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(b"GSIN").is_err());
+    }
+}
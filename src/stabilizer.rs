@@ -0,0 +1,660 @@
+use std::{
+    fmt::{Display, Formatter},
+    iter::once,
+};
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    gadget::parse_observable,
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx, Vop, mix},
+};
+
+/// A Hermitian Pauli operator with a sign, (−1)^`negative` ⊗ σ(x_q, z_q).
+///
+/// σ(1, 0) is X, σ(1, 1) is Y and σ(0, 1) is Z on each qubit.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct PauliString {
+    pub(crate) x: BitSet,
+    pub(crate) z: BitSet,
+    pub(crate) negative: bool,
+}
+
+impl PauliString {
+    /// The Pauli on `qubit`, `None` for the identity.
+    pub(crate) fn get(&self, qubit: NodeIdx) -> Option<Axis> {
+        match (self.x.contains(qubit), self.z.contains(qubit)) {
+            (false, false) => None,
+            (true, false) => Some(Axis::X),
+            (true, true) => Some(Axis::Y),
+            (false, true) => Some(Axis::Z),
+        }
+    }
+
+    /// Set the Pauli on `qubit`, `None` for the identity.
+    pub(crate) fn set(&mut self, qubit: NodeIdx, pauli: Option<Axis>) {
+        let (x, z) = match pauli {
+            None => (false, false),
+            Some(Axis::X) => (true, false),
+            Some(Axis::Y) => (true, true),
+            Some(Axis::Z) => (false, true),
+        };
+        for (set, bit) in [(&mut self.x, x), (&mut self.z, z)] {
+            match bit {
+                true => set.insert(qubit),
+                false => set.remove(qubit),
+            };
+        }
+    }
+
+    /// Whether this operator anticommutes with `other`.
+    pub(crate) fn anticommutes(&self, other: &PauliString) -> bool {
+        let overlap = self.x.intersection(&other.z).count() + self.z.intersection(&other.x).count();
+        overlap % 2 == 1
+    }
+
+    /// Multiply `other` into this operator from the right.
+    ///
+    /// Both operators must commute, so the product is Hermitian again.
+    pub(crate) fn mul_assign(&mut self, other: &PauliString) {
+        // exponent of i picked up by reordering the single-qubit products
+        let mut phase: i64 = 0;
+        for qubit in other.x.union(&other.z) {
+            let (x1, z1) = (self.x.contains(qubit) as i64, self.z.contains(qubit) as i64);
+            let (x2, z2) = (
+                other.x.contains(qubit) as i64,
+                other.z.contains(qubit) as i64,
+            );
+            phase += match (x1, z1) {
+                (0, 0) => 0,
+                (1, 1) => z2 - x2,
+                (1, 0) => z2 * (2 * x2 - 1),
+                _ => x2 * (1 - 2 * z2),
+            };
+        }
+        debug_assert_eq!(phase.rem_euclid(2), 0, "Product of anticommuting Paulis");
+        self.negative ^= other.negative ^ (phase.rem_euclid(4) == 2);
+        self.x.symmetric_difference_with(&other.x);
+        self.z.symmetric_difference_with(&other.z);
+    }
+}
+
+impl Display for PauliString {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        fmt.write_str(if self.negative { "-" } else { "+" })?;
+        let len = self.x.union(&self.z).max().map_or(0, |max| max + 1);
+        for qubit in 0..len {
+            match self.get(qubit) {
+                None => fmt.write_str("_")?,
+                Some(axis) => write!(fmt, "{axis}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Conjugate the single-qubit Pauli `pauli` by the gate called `gate`, G P G†.
+fn conjugate_gate(gate: &str, pauli: Axis) -> (bool, Axis) {
+    match (gate, pauli) {
+        ("h", Axis::X) => (false, Axis::Z),
+        ("h", Axis::Y) => (true, Axis::Y),
+        ("h", Axis::Z) => (false, Axis::X),
+        ("s", Axis::X) => (false, Axis::Y),
+        ("s", Axis::Y) => (true, Axis::X),
+        ("sdag", Axis::X) => (true, Axis::Y),
+        ("sdag", Axis::Y) => (false, Axis::X),
+        ("x", Axis::Y | Axis::Z) | ("y", Axis::X | Axis::Z) | ("z", Axis::X | Axis::Y) => {
+            (true, pauli)
+        }
+        _ => (false, pauli),
+    }
+}
+
+/// Table of V P V† for every VOP V and Pauli P, as `(negative, pauli)`.
+pub(crate) fn conjugation_table() -> Vec<[(bool, Axis); 3]> {
+    (0..crate::SYMMETRIES)
+        .map(|idx| {
+            let gates = Vop::from_index(idx).gate_names();
+            [Axis::X, Axis::Y, Axis::Z].map(|pauli| {
+                gates
+                    .iter()
+                    .fold((false, pauli), |(negative, pauli), gate| {
+                        let (flip, pauli) = conjugate_gate(gate, pauli);
+                        (negative ^ flip, pauli)
+                    })
+            })
+        })
+        .collect()
+}
+
+/// Stabilizer generators of `sim`, one per node.
+///
+/// Generator `v` is the graph-state stabilizer X_v Z_N(v) conjugated by the VOPs.
+pub(crate) fn generators(sim: &GraphSim) -> Vec<PauliString> {
+    let table = conjugation_table();
+    let vops = sim.vops();
+    sim.adjacency()
+        .iter()
+        .enumerate()
+        .map(|(node, adj)| {
+            let mut generator = PauliString::default();
+            for (qubit, pauli) in
+                std::iter::once((node, Axis::X)).chain(adj.iter().map(|nb| (nb, Axis::Z)))
+            {
+                let (negative, pauli) = table[vops[qubit] as usize][pauli as usize];
+                generator.negative ^= negative;
+                generator.set(qubit, Some(pauli));
+            }
+            generator
+        })
+        .collect()
+}
+
+/// Lazy iterator over the elements of a stabilizer group, from
+/// `GraphSim.iter_stabilizer_group`.
+///
+/// Walks the 2^n products of the generators in Gray code order, so every element costs a
+/// single multiplication and the group is never stored. Elements are Pauli strings with
+/// a sign and a letter per qubit, like `"+XZ_"`, starting with the identity.
+#[pyclass]
+pub struct StabilizerIterator {
+    generators: Vec<PauliString>,
+    columns: Vec<NodeIdx>,
+    current: PauliString,
+    /// Number of elements yielded so far.
+    index: u128,
+    limit: Option<usize>,
+}
+
+impl StabilizerIterator {
+    /// Iterator over the group of `sim`, stopping after `limit` elements if given.
+    pub(crate) fn new(sim: &GraphSim, limit: Option<usize>) -> StabilizerIterator {
+        StabilizerIterator {
+            generators: generators(sim),
+            columns: (0..sim.adjacency().len()).collect(),
+            current: PauliString::default(),
+            index: 0,
+            limit,
+        }
+    }
+}
+
+impl Iterator for StabilizerIterator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let size = u32::try_from(self.generators.len())
+            .ok()
+            .and_then(|len| 1u128.checked_shl(len));
+        if self.limit.is_some_and(|limit| self.index >= limit as u128)
+            || size.is_some_and(|size| self.index >= size)
+        {
+            return None;
+        }
+        if self.index > 0 {
+            let flipped = self.index.trailing_zeros() as usize;
+            self.current.mul_assign(&self.generators[flipped]);
+        }
+        self.index += 1;
+        Some(format_rows(
+            std::slice::from_ref(&self.current),
+            &self.columns,
+        ))
+    }
+}
+
+#[pymethods]
+impl StabilizerIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<String> {
+        self.next()
+    }
+}
+
+/// Bring `rows` into reduced row echelon form, returning the rank.
+///
+/// Pivot columns are ordered X then Z per qubit, qubit by qubit. Zero rows end up at
+/// the back. The result only depends on the group generated by `rows`.
+pub(crate) fn row_reduce(rows: &mut [PauliString], qubit_amount: usize) -> usize {
+    let mut rank = 0;
+    for qubit in 0..qubit_amount {
+        for use_x in [true, false] {
+            let has = |row: &PauliString| match use_x {
+                true => row.x.contains(qubit),
+                false => row.z.contains(qubit),
+            };
+            let Some(pivot) = (rank..rows.len()).find(|&idx| has(&rows[idx])) else {
+                continue;
+            };
+            rows.swap(rank, pivot);
+            let pivot_row = rows[rank].clone();
+            for (idx, row) in rows.iter_mut().enumerate() {
+                if idx != rank && has(row) {
+                    row.mul_assign(&pivot_row);
+                }
+            }
+            rank += 1;
+        }
+    }
+    rank
+}
+
+/// Canonical generators of the stabilizer group of `sim`.
+///
+/// Equal for two simulators exactly when they describe the same state up to a global
+/// phase, whatever their graphs and VOPs. Scales as O(n^3).
+pub(crate) fn canonical_stabilizers(sim: &GraphSim) -> Vec<PauliString> {
+    let mut rows = generators(sim);
+    row_reduce(&mut rows, sim.adjacency().len());
+    rows
+}
+
+/// The stabilizer group of a state, kept in reduced form to decompose operators into it.
+pub(crate) struct StabilizerGroup {
+    rows: Vec<PauliString>,
+    /// Pivot column of every row, as its qubit and whether it is the X part.
+    pivots: Vec<(NodeIdx, bool)>,
+}
+
+impl StabilizerGroup {
+    /// Stabilizer group of `sim`. Scales as O(n^3).
+    pub(crate) fn new(sim: &GraphSim) -> StabilizerGroup {
+        let qubit_amount = sim.adjacency().len();
+        let rows = canonical_stabilizers(sim);
+        let pivots = rows
+            .iter()
+            .map(|row| {
+                (0..qubit_amount)
+                    .flat_map(|qubit| [(qubit, true), (qubit, false)])
+                    .find(|&(qubit, x)| match x {
+                        true => row.x.contains(qubit),
+                        false => row.z.contains(qubit),
+                    })
+                    .expect("Generators of a pure state are not the identity")
+            })
+            .collect();
+        StabilizerGroup { rows, pivots }
+    }
+
+    /// Expectation value of `pauli` on the state, −1, 0 or +1.
+    ///
+    /// The group of a pure state has a generator for every qubit, so `pauli` or its
+    /// negative is in it exactly when it commutes with all of them, and otherwise the
+    /// expectation is 0. Scales as O(n^2).
+    pub(crate) fn expectation(&self, pauli: &PauliString) -> i8 {
+        if self.rows.iter().any(|row| row.anticommutes(pauli)) {
+            return 0;
+        }
+        // the pivot columns of the other rows are clear, so clearing them one by one
+        // leaves the sign of the identity
+        let mut residual = pauli.clone();
+        for (row, &(qubit, x)) in self.rows.iter().zip(&self.pivots) {
+            let set = match x {
+                true => residual.x.contains(qubit),
+                false => residual.z.contains(qubit),
+            };
+            if set {
+                residual.mul_assign(row);
+            }
+        }
+        debug_assert!(residual.x.is_empty() && residual.z.is_empty());
+        match residual.negative {
+            true => -1,
+            false => 1,
+        }
+    }
+}
+
+/// Canonical generators of the stabilizers of `sim` supported only on `qubits`.
+///
+/// Rows with support outside `qubits` are eliminated first, pivoting on the other qubits,
+/// and the rows left over generate the subgroup and are brought into reduced form.
+pub(crate) fn subsystem_stabilizers(sim: &GraphSim, qubits: &BitSet) -> Vec<PauliString> {
+    let qubit_amount = sim.adjacency().len();
+    let mut rows = generators(sim);
+    for qubit in (0..qubit_amount).filter(|qubit| !qubits.contains(*qubit)) {
+        for use_x in [true, false] {
+            let has = |row: &PauliString| match use_x {
+                true => row.x.contains(qubit),
+                false => row.z.contains(qubit),
+            };
+            let Some(pivot) = rows.iter().position(has) else {
+                continue;
+            };
+            let pivot_row = rows.swap_remove(pivot);
+            for row in rows.iter_mut().filter(|row| has(row)) {
+                row.mul_assign(&pivot_row);
+            }
+        }
+    }
+    let rank = row_reduce(&mut rows, qubit_amount);
+    rows.truncate(rank);
+    rows
+}
+
+/// One line per generator in `rows`, its sign followed by the Pauli on every qubit of
+/// `columns` with `_` for the identity, e.g. `+XZ_`.
+pub(crate) fn format_rows(rows: &[PauliString], columns: &[NodeIdx]) -> String {
+    rows.iter()
+        .map(|row| {
+            let sign = if row.negative { '-' } else { '+' };
+            once(sign)
+                .chain(columns.iter().map(|&qubit| match row.get(qubit) {
+                    None => '_',
+                    Some(Axis::X) => 'X',
+                    Some(Axis::Y) => 'Y',
+                    Some(Axis::Z) => 'Z',
+                }))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Deterministic hash of `canonical_stabilizers(sim)`.
+pub(crate) fn canonical_hash(sim: &GraphSim) -> u64 {
+    canonical_stabilizers(sim).iter().fold(0, |hash, row| {
+        let hash = (0..sim.adjacency().len())
+            .filter_map(|qubit| row.get(qubit).map(|pauli| (qubit, pauli)))
+            .fold(hash, |hash, (qubit, pauli)| {
+                mix(hash ^ ((qubit as u64) << 2 | (pauli as u64 + 1)))
+            });
+        mix(hash ^ u64::from(row.negative) ^ 0xA5A5_A5A5_0000_0000)
+    })
+}
+
+/// Parse `stabilizers` into the generators of a pure state, like `"+XZZ"`.
+///
+/// Raises `ValueError` unless there are as many as every string has letters, and they
+/// pairwise commute and are independent.
+pub(crate) fn parse_stabilizers(stabilizers: &[String]) -> PyResult<Vec<PauliString>> {
+    let qubit_amount = stabilizers.len();
+    let rows = stabilizers
+        .iter()
+        .map(|stabilizer| {
+            let letters = stabilizer.trim_start_matches(['+', '-']).chars().count();
+            if letters != qubit_amount {
+                return Err(PyValueError::new_err(format!(
+                    "Stabilizer {stabilizer:?} has {letters} letters for {qubit_amount} generators"
+                )));
+            }
+            Ok(parse_observable(stabilizer, None, qubit_amount)?.to_pauli_string())
+        })
+        .collect::<PyResult<Vec<PauliString>>>()?;
+    for (idx, row) in rows.iter().enumerate() {
+        if let Some(other) = rows[..idx].iter().position(|other| other.anticommutes(row)) {
+            return Err(PyValueError::new_err(format!(
+                "Stabilizers {:?} and {:?} anticommute",
+                stabilizers[other], stabilizers[idx]
+            )));
+        }
+    }
+    if row_reduce(&mut rows.clone(), qubit_amount) < qubit_amount {
+        return Err(PyValueError::new_err(
+            "Stabilizers are not independent, so they don't fix a single state",
+        ));
+    }
+    Ok(rows)
+}
+
+/// A Pauli operator that anticommutes with the rows where `flip` is set and commutes with
+/// the others, which exists as the rows are independent.
+fn flipping_pauli(rows: &[PauliString], flip: &[bool], qubit_amount: usize) -> PauliString {
+    // the symplectic product with a row pairs the X part of the operator with the Z
+    // part of the row and the other way around, so solve over the columns of the
+    // swapped rows, X then Z per qubit, with the flips as the right hand side
+    let mut equations: Vec<(BitSet, bool)> = rows
+        .iter()
+        .zip(flip)
+        .map(|(row, &flip)| {
+            let columns = row.z.iter().map(|qubit| 2 * qubit);
+            let columns = columns.chain(row.x.iter().map(|qubit| 2 * qubit + 1));
+            (columns.collect(), flip)
+        })
+        .collect();
+    let mut pivots = Vec::with_capacity(equations.len());
+    for column in 0..2 * qubit_amount {
+        let rank = pivots.len();
+        let Some(pivot) = (rank..equations.len()).find(|&idx| equations[idx].0.contains(column))
+        else {
+            continue;
+        };
+        equations.swap(rank, pivot);
+        let (pivot_row, pivot_rhs) = equations[rank].clone();
+        for (idx, (row, rhs)) in equations.iter_mut().enumerate() {
+            if idx != rank && row.contains(column) {
+                row.symmetric_difference_with(&pivot_row);
+                *rhs ^= pivot_rhs;
+            }
+        }
+        pivots.push(column);
+    }
+    // free columns are left clear, so every pivot column takes its right hand side
+    let mut pauli = PauliString::default();
+    for (&column, (_, rhs)) in pivots.iter().zip(&equations) {
+        if *rhs {
+            match column % 2 {
+                0 => pauli.x.insert(column / 2),
+                _ => pauli.z.insert(column / 2),
+            };
+        }
+    }
+    pauli
+}
+
+/// The state stabilized by `rows`, which have to come from `parse_stabilizers`.
+///
+/// Starting from |0…0⟩, every generator is measured in place by rotating its Paulis to
+/// Z and collecting their parity on one qubit with CXs, projecting onto +1 whenever the
+/// outcome is random. Generators that came out −1 anyway are flipped at the end by a
+/// single Pauli that commutes with all the others. Scales as O(n^3).
+pub(crate) fn from_stabilizers(rows: &[PauliString]) -> GraphSim {
+    let qubit_amount = rows.len();
+    let mut sim = GraphSim::new(qubit_amount);
+    for row in rows {
+        let support: Vec<(NodeIdx, Axis)> = (0..qubit_amount)
+            .filter_map(|qubit| row.get(qubit).map(|pauli| (qubit, pauli)))
+            .collect();
+        for &(qubit, pauli) in &support {
+            match pauli {
+                Axis::X => sim.h(qubit),
+                Axis::Y => {
+                    sim.sdag(qubit);
+                    sim.h(qubit);
+                }
+                Axis::Z => {}
+            }
+        }
+        let root = support[0].0;
+        for &(qubit, _) in &support[1..] {
+            sim.cx(qubit, root);
+        }
+        if sim.deterministic_outcome(root, Axis::Z).is_none() {
+            let outcome = match row.negative {
+                true => MeasurementResult::MinusOne,
+                false => MeasurementResult::PlusOne,
+            };
+            sim.project(root, Axis::Z, outcome);
+        }
+        for &(qubit, _) in &support[1..] {
+            sim.cx(qubit, root);
+        }
+        for &(qubit, pauli) in &support {
+            match pauli {
+                Axis::X => sim.h(qubit),
+                Axis::Y => {
+                    sim.h(qubit);
+                    sim.s(qubit);
+                }
+                Axis::Z => {}
+            }
+        }
+    }
+
+    let group = StabilizerGroup::new(&sim);
+    let flip: Vec<bool> = rows.iter().map(|row| group.expectation(row) < 0).collect();
+    if flip.contains(&true) {
+        let correction = flipping_pauli(rows, &flip, qubit_amount);
+        for qubit in 0..qubit_amount {
+            match correction.get(qubit) {
+                Some(Axis::X) => sim.x(qubit),
+                Some(Axis::Y) => sim.y(qubit),
+                Some(Axis::Z) => sim.z(qubit),
+                None => {}
+            }
+        }
+    }
+    sim
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_stabilizers() {
+        let stabilizers = ["-XZZ", "+ZX_", "+Z_Y"].map(String::from);
+        let sim = from_stabilizers(&parse_stabilizers(&stabilizers).unwrap());
+        let group = StabilizerGroup::new(&sim);
+        for stabilizer in &stabilizers {
+            let row = parse_observable(stabilizer, None, 3)
+                .unwrap()
+                .to_pauli_string();
+            assert_eq!(group.expectation(&row), 1, "{stabilizer}");
+        }
+
+        let mut ghz = GraphSim::new(3);
+        ghz.h(0);
+        ghz.cx(0, 1);
+        ghz.cx(1, 2);
+        ghz.x(2);
+        let rows = canonical_stabilizers(&ghz);
+        assert_eq!(
+            canonical_stabilizers(&from_stabilizers(&rows)),
+            canonical_stabilizers(&ghz)
+        );
+
+        for invalid in [
+            &["+XX", "+ZI"][..],
+            &["+ZZ", "-ZZ"],
+            &["+Z", "+X"],
+            &["+ZZ"],
+        ] {
+            let invalid: Vec<String> = invalid.iter().map(|s| s.to_string()).collect();
+            assert!(parse_stabilizers(&invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_bell_stabilizers() {
+        let mut sim = GraphSim::new(2);
+        sim.h(0);
+        sim.cx(0, 1);
+
+        let strings: Vec<String> = canonical_stabilizers(&sim)
+            .iter()
+            .map(|row| row.to_string())
+            .collect();
+        assert_eq!(strings, vec!["+XX", "+ZZ"]);
+
+        sim.z(0);
+        let strings: Vec<String> = canonical_stabilizers(&sim)
+            .iter()
+            .map(|row| row.to_string())
+            .collect();
+        assert_eq!(strings, vec!["-XX", "+ZZ"]);
+    }
+
+    #[test]
+    fn test_format_subsystem_stabilizers() {
+        let mut sim = GraphSim::new(3);
+        sim.h(0);
+        sim.cx(0, 1);
+        sim.cx(1, 2);
+        sim.x(2);
+
+        assert_eq!(
+            format_rows(&canonical_stabilizers(&sim), &[0, 1, 2]),
+            "+XXX\n-Z_Z\n-_ZZ"
+        );
+        let qubits = BitSet::from_iter([1, 2]);
+        assert_eq!(
+            format_rows(&subsystem_stabilizers(&sim, &qubits), &[1, 2]),
+            "-ZZ"
+        );
+        assert_eq!(subsystem_stabilizers(&sim, &BitSet::from_iter([0])), vec![]);
+    }
+
+    #[test]
+    fn test_ghz_expectations() {
+        let mut sim = GraphSim::new(3);
+        sim.h(0);
+        sim.cx(0, 1);
+        sim.cx(0, 2);
+        sim.z(2);
+        let group = StabilizerGroup::new(&sim);
+        let expectation = |letters: &str, negative: bool| {
+            let mut pauli = PauliString {
+                negative,
+                ..PauliString::default()
+            };
+            for (qubit, letter) in letters.chars().enumerate() {
+                let axis = match letter {
+                    'X' => Axis::X,
+                    'Y' => Axis::Y,
+                    'Z' => Axis::Z,
+                    _ => continue,
+                };
+                pauli.set(qubit, Some(axis));
+            }
+            group.expectation(&pauli)
+        };
+        assert_eq!(expectation("XXX", false), -1);
+        assert_eq!(expectation("XXX", true), 1);
+        assert_eq!(expectation("Z_Z", false), 1);
+        assert_eq!(expectation("YYX", false), 1);
+        assert_eq!(expectation("X__", false), 0);
+        assert_eq!(expectation("ZZ_", false), 1);
+        assert_eq!(expectation("___", false), 1);
+    }
+
+    #[test]
+    fn test_iterate_stabilizer_group() {
+        let mut sim = GraphSim::new(2);
+        sim.h(0);
+        sim.cx(0, 1);
+        let mut elements: Vec<String> = StabilizerIterator::new(&sim, None).collect();
+        elements.sort();
+        assert_eq!(elements, vec!["+XX", "+ZZ", "+__", "-YY"]);
+
+        let big = GraphSim::new(200);
+        let first: Vec<String> = StabilizerIterator::new(&big, Some(3)).collect();
+        assert_eq!(first.len(), 3);
+        assert_eq!(first[2], format!("+ZZ{}", "_".repeat(198)));
+    }
+
+    #[test]
+    fn test_lc_equivalent_graphs_agree() {
+        let mut sim = GraphSim::new(4);
+        for qubit in 0..4 {
+            sim.h(qubit);
+        }
+        for qubit in 0..3 {
+            sim.cz(qubit, qubit + 1);
+        }
+        let mut complemented = sim.clone();
+        complemented.local_comp(1);
+        complemented.local_comp(2);
+
+        assert_ne!(sim, complemented);
+        assert_eq!(canonical_hash(&sim), canonical_hash(&complemented));
+        assert_eq!(
+            canonical_stabilizers(&sim),
+            canonical_stabilizers(&complemented)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::Circuit,
+    decoder::odd_parities,
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    noise::NoiseModel,
+};
+
+/// Supports of the Z- and X-type stabilizers of a rotated surface code patch.
+///
+/// Data qubit `(row, col)` has index `row * distance + col`. Plaquette `(i, j)` covers
+/// the data qubits around the corner shared by rows `i - 1, i` and columns `j - 1, j`.
+/// The weight-2 boundary plaquettes are X-type on the top and bottom edges and Z-type
+/// on the left and right edges, so a row of Z operators is a logical Z.
+fn stabilizers(distance: usize) -> (Vec<Vec<NodeIdx>>, Vec<Vec<NodeIdx>>) {
+    let mut z_stabs = Vec::new();
+    let mut x_stabs = Vec::new();
+    for i in 0..=distance {
+        for j in 0..=distance {
+            let is_x = (i + j) % 2 == 0;
+            let on_row_edge = i == 0 || i == distance;
+            let on_col_edge = j == 0 || j == distance;
+            let keep = match (on_row_edge, on_col_edge) {
+                (false, false) => true,
+                (true, false) => is_x,
+                (false, true) => !is_x,
+                (true, true) => false,
+            };
+            if !keep {
+                continue;
+            }
+
+            let support = [
+                (i.wrapping_sub(1), j.wrapping_sub(1)),
+                (i.wrapping_sub(1), j),
+                (i, j.wrapping_sub(1)),
+                (i, j),
+            ]
+            .into_iter()
+            .filter(|&(row, col)| row < distance && col < distance)
+            .map(|(row, col)| row * distance + col)
+            .collect();
+            match is_x {
+                true => x_stabs.push(support),
+                false => z_stabs.push(support),
+            }
+        }
+    }
+    (z_stabs, x_stabs)
+}
+
+/// Build a Z-basis memory experiment on a rotated surface code patch.
+///
+/// The data qubits start in |0…0⟩ and every round measures all stabilizers with
+/// fresh ancillas, Z-type first. Detectors compare each stabilizer with its previous
+/// round (Z-type ones also with the initial state and the final data measurement), and
+/// observable 0 is the logical Z along the first row of data qubits.
+#[pyfunction]
+pub fn surface_code_circuit(distance: usize, rounds: usize) -> PyResult<Circuit> {
+    if distance < 2 || rounds < 1 {
+        return Err(PyValueError::new_err(
+            "A memory experiment needs a distance of at least 2 and at least one round",
+        ));
+    }
+
+    let data = distance * distance;
+    let (z_stabs, x_stabs) = stabilizers(distance);
+    let ancillas = z_stabs.len() + x_stabs.len();
+    let mut circuit = Circuit::new(data + ancillas * rounds);
+
+    let mut prev_z: Vec<usize> = Vec::new();
+    let mut prev_x: Vec<usize> = Vec::new();
+    for round in 0..rounds {
+        let base = data + round * ancillas;
+
+        let mut cur_z = Vec::with_capacity(z_stabs.len());
+        for (k, stab) in z_stabs.iter().enumerate() {
+            let anc = base + k;
+            for &qubit in stab {
+                circuit.cx(qubit, anc);
+            }
+            cur_z.push(circuit.num_measurements());
+            circuit.measure(anc, Axis::Z);
+            match prev_z.get(k) {
+                Some(&prev) => circuit.detector(vec![prev, cur_z[k]])?,
+                None => circuit.detector(vec![cur_z[k]])?,
+            };
+        }
+
+        let mut cur_x = Vec::with_capacity(x_stabs.len());
+        for (k, stab) in x_stabs.iter().enumerate() {
+            let anc = base + z_stabs.len() + k;
+            circuit.h(anc);
+            for &qubit in stab {
+                circuit.cx(anc, qubit);
+            }
+            cur_x.push(circuit.num_measurements());
+            circuit.measure(anc, Axis::X);
+            if let Some(&prev) = prev_x.get(k) {
+                circuit.detector(vec![prev, cur_x[k]])?;
+            }
+        }
+
+        prev_z = cur_z;
+        prev_x = cur_x;
+    }
+
+    let first_data = circuit.num_measurements();
+    for qubit in 0..data {
+        circuit.measure(qubit, Axis::Z);
+    }
+    for (k, stab) in z_stabs.iter().enumerate() {
+        let mut measurements: Vec<usize> = stab.iter().map(|&qubit| first_data + qubit).collect();
+        measurements.push(prev_z[k]);
+        circuit.detector(measurements)?;
+    }
+    circuit.observable((first_data..first_data + distance).collect())?;
+
+    Ok(circuit)
+}
+
+/// Result of a single `surface_code_memory` run.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug)]
+pub struct MemoryResult {
+    /// The executed circuit, e.g. for building its `DetectorErrorModel`.
+    circuit: Circuit,
+    /// Whether each detector of `circuit` fired.
+    detection_events: Vec<bool>,
+    /// Outcome of the final logical Z measurement.
+    logical: MeasurementResult,
+}
+
+/// Run one noisy Z-basis memory experiment on a rotated surface code patch.
+///
+/// See `surface_code_circuit` for the layout of the circuit and its detectors.
+#[pyfunction]
+pub fn surface_code_memory(
+    distance: usize,
+    rounds: usize,
+    noise: &NoiseModel,
+) -> PyResult<MemoryResult> {
+    let circuit = surface_code_circuit(distance, rounds)?;
+    let mut rng = crate::rng::generator();
+    let mut sim = GraphSim::new(circuit.qubit_amount());
+    sim.reseed(&mut rng);
+    let outcomes = noise.execute(&circuit, &mut sim, &mut rng, &mut Vec::new());
+
+    let minus_one: BitSet = outcomes
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, &res)| (res == MeasurementResult::MinusOne).then_some(idx))
+        .collect();
+    let detection_events = odd_parities(&minus_one, &circuit.detectors);
+    let logical = match odd_parities(&minus_one, &circuit.observables)[0] {
+        false => MeasurementResult::PlusOne,
+        true => MeasurementResult::MinusOne,
+    };
+
+    Ok(MemoryResult {
+        circuit,
+        detection_events,
+        logical,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stabilizers_commute() {
+        for distance in 2..7 {
+            let (z_stabs, x_stabs) = stabilizers(distance);
+            assert_eq!(z_stabs.len() + x_stabs.len(), distance * distance - 1);
+            for z_stab in &z_stabs {
+                for x_stab in &x_stabs {
+                    let overlap = z_stab.iter().filter(|q| x_stab.contains(q)).count();
+                    assert_eq!(overlap % 2, 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_noiseless_memory_is_quiet() {
+        let result = surface_code_memory(3, 3, &NoiseModel::default()).unwrap();
+
+        assert_eq!(result.circuit.qubit_amount(), 9 + 8 * 3);
+        assert_eq!(result.detection_events.len(), 4 * 4 + 4 * 2);
+        assert!(result.detection_events.iter().all(|&event| !event));
+        assert_eq!(result.logical, MeasurementResult::PlusOne);
+
+        for distance in [2, 4, 5] {
+            let result = surface_code_memory(distance, 2, &NoiseModel::default()).unwrap();
+            assert!(result.detection_events.iter().all(|&event| !event));
+            assert_eq!(result.logical, MeasurementResult::PlusOne);
+        }
+    }
+
+    #[test]
+    fn test_readout_noise_fires_detectors() {
+        let noise = NoiseModel::new(0.0, 0.0, 1.0, 0.0, None).unwrap();
+        let result = surface_code_memory(3, 1, &noise).unwrap();
+
+        // every ancilla readout flips, while the even-weight data parities don't
+        assert!(result.detection_events.iter().all(|&event| event));
+    }
+}
@@ -0,0 +1,371 @@
+use std::thread;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    circuit::Circuit,
+    decoder::{detector_error_model, sample_shots},
+    noise::NoiseModel,
+    surface_code::surface_code_circuit,
+};
+
+/// Standard normal quantile of the 95% confidence intervals of logical error rates.
+const Z_95: f64 = 1.959_963_984_540_054;
+
+/// Detection events and observable flips of every shot, one row per shot.
+type Shots = (Vec<Vec<bool>>, Vec<Vec<bool>>);
+
+/// Statistics of the shots of a `sweep` at a single noise level.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepPoint {
+    /// Error rate of every gate and measurement.
+    noise_level: f64,
+    /// Number of shots that were run.
+    shots: usize,
+    /// Fraction of shots in which each detector fired.
+    detector_rates: Vec<f64>,
+    /// Fraction of shots in which each observable flipped.
+    observable_rates: Vec<f64>,
+    /// Number of shots in which any detector fired.
+    nontrivial_shots: usize,
+    /// Number of shots in which any observable flipped.
+    flipped_shots: usize,
+}
+
+/// Gather the statistics of `shots` rows of detection events and observable flips.
+fn summarize(
+    noise_level: f64,
+    shots: usize,
+    events: &[Vec<bool>],
+    flips: &[Vec<bool>],
+) -> SweepPoint {
+    let rates = |rows: &[Vec<bool>], width: usize| -> Vec<f64> {
+        (0..width)
+            .map(|col| rows.iter().filter(|row| row[col]).count() as f64 / shots.max(1) as f64)
+            .collect()
+    };
+    let any = |rows: &[Vec<bool>]| rows.iter().filter(|row| row.contains(&true)).count();
+    SweepPoint {
+        noise_level,
+        shots,
+        detector_rates: rates(events, events.first().map_or(0, Vec::len)),
+        observable_rates: rates(flips, flips.first().map_or(0, Vec::len)),
+        nontrivial_shots: any(events),
+        flipped_shots: any(flips),
+    }
+}
+
+/// Sample `shots` shots of `circuit` under every model of `levels` in parallel.
+///
+/// Each thread gets a generator seeded from `rng`, so the shots only depend on it.
+fn sample_levels(
+    circuit: &Circuit,
+    levels: &[(f64, NoiseModel)],
+    shots: usize,
+    rng: &mut StdRng,
+) -> Vec<Shots> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = levels
+            .iter()
+            .map(|(_, model)| {
+                let mut rng = StdRng::seed_from_u64(rng.random());
+                scope.spawn(move || sample_shots(circuit, model, shots, &mut rng))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Sweep thread panicked"))
+            .collect()
+    })
+}
+
+fn run_levels(
+    circuit: &Circuit,
+    levels: &[(f64, NoiseModel)],
+    shots: usize,
+    rng: &mut StdRng,
+) -> Vec<SweepPoint> {
+    sample_levels(circuit, levels, shots, rng)
+        .iter()
+        .zip(levels)
+        .map(|((events, flips), (level, _))| summarize(*level, shots, events, flips))
+        .collect()
+}
+
+/// Uniform noise models for `noise_levels`, failing every operation with probability `p`.
+fn uniform_levels(noise_levels: &[f64]) -> PyResult<Vec<(f64, NoiseModel)>> {
+    noise_levels
+        .iter()
+        .map(|&p| Ok((p, NoiseModel::new(p, p, p, 0.0, None)?)))
+        .collect()
+}
+
+/// Run `circuit` for `shots` shots at each of `noise_levels`, one thread per level.
+///
+/// Level `p` uses `NoiseModel(p, p, p)`, so every gate and measurement fails with
+/// probability `p`. Shots are sampled like `sample_detectors`, so the detectors and
+/// observables of the circuit must be deterministic without noise. The GIL is released
+/// while sampling and one `SweepPoint` is returned per level, in order.
+#[pyfunction]
+pub fn sweep(
+    py: Python<'_>,
+    circuit: &Circuit,
+    noise_levels: Vec<f64>,
+    shots: usize,
+) -> PyResult<Vec<SweepPoint>> {
+    let levels = uniform_levels(&noise_levels)?;
+    let mut rng = crate::rng::generator();
+    Ok(py.detach(|| run_levels(circuit, &levels, shots, &mut rng)))
+}
+
+/// Logical error rate of a code at one distance and noise level, from `threshold`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThresholdPoint {
+    /// Code distance.
+    distance: usize,
+    /// Error rate of every gate and measurement.
+    noise_level: f64,
+    /// Number of shots that were run.
+    shots: usize,
+    /// Number of shots where the decoder got an observable wrong.
+    errors: usize,
+    /// Fraction of shots with a logical error.
+    logical_error_rate: f64,
+    /// 95% Wilson score interval `(low, high)` of the logical error rate.
+    confidence_interval: (f64, f64),
+}
+
+/// Wilson score interval of a binomial rate with `errors` out of `shots` at quantile `z`.
+fn wilson_interval(errors: usize, shots: usize, z: f64) -> (f64, f64) {
+    if shots == 0 {
+        return (0.0, 1.0);
+    }
+    let n = shots as f64;
+    let rate = errors as f64 / n;
+    let denominator = 1.0 + z * z / n;
+    let center = (rate + z * z / (2.0 * n)) / denominator;
+    let half_width = z * (rate * (1.0 - rate) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+/// Number of shots where `predictions` differ from the actual observable `flips`.
+///
+/// Without predictions every flipped observable counts as a logical error.
+fn count_failures(flips: &[Vec<bool>], predictions: Option<&[Vec<u8>]>) -> PyResult<usize> {
+    let Some(predictions) = predictions else {
+        return Ok(flips.iter().filter(|row| row.contains(&true)).count());
+    };
+    if predictions.len() != flips.len() {
+        return Err(PyValueError::new_err(format!(
+            "Decoder returned {} predictions for {} shots",
+            predictions.len(),
+            flips.len()
+        )));
+    }
+    let mut failures = 0;
+    for (actual, predicted) in flips.iter().zip(predictions) {
+        if predicted.len() != actual.len() {
+            return Err(PyValueError::new_err(format!(
+                "Decoder predicted {} observables, but the circuit has {}",
+                predicted.len(),
+                actual.len()
+            )));
+        }
+        if actual.iter().zip(predicted).any(|(&a, &p)| a != (p != 0)) {
+            failures += 1;
+        }
+    }
+    Ok(failures)
+}
+
+/// Count the logical errors in `shots` after asking `decoder` for its predictions.
+fn logical_errors(
+    circuit: &Circuit,
+    model: &NoiseModel,
+    (events, flips): Shots,
+    decoder: Option<&Bound<'_, PyAny>>,
+) -> PyResult<usize> {
+    let predictions: Option<Vec<Vec<u8>>> = decoder
+        .map(|decoder| {
+            let dem = detector_error_model(circuit, model);
+            decoder.call1((dem, events))?.extract()
+        })
+        .transpose()?;
+    count_failures(&flips, predictions.as_deref())
+}
+
+/// Estimate logical error rates of surface code memory experiments for a threshold plot.
+///
+/// Every combination of `distances` and `noise_levels` runs `shots` shots of
+/// `surface_code_circuit(d, rounds)` under `NoiseModel(p, p, p)`, with `rounds`
+/// defaulting to the distance. The noise levels of a distance are sampled in parallel
+/// without the GIL. `decoder(dem, detection_events)` is then called once per point with
+/// the `DetectorErrorModel` and a list of detection events per shot, and must return the
+/// predicted observable flips per shot, e.g. via pymatching's `decode_batch`. Without a
+/// decoder any observable flip counts as a logical error.
+#[pyfunction]
+#[pyo3(signature = (distances, noise_levels, shots, decoder = None, rounds = None))]
+pub fn threshold(
+    py: Python<'_>,
+    distances: Vec<usize>,
+    noise_levels: Vec<f64>,
+    shots: usize,
+    decoder: Option<&Bound<'_, PyAny>>,
+    rounds: Option<usize>,
+) -> PyResult<Vec<ThresholdPoint>> {
+    let levels = uniform_levels(&noise_levels)?;
+    let mut points = Vec::with_capacity(distances.len() * levels.len());
+    let mut rng = crate::rng::generator();
+    for distance in distances {
+        let circuit = surface_code_circuit(distance, rounds.unwrap_or(distance))?;
+        let sampled = py.detach(|| sample_levels(&circuit, &levels, shots, &mut rng));
+        for (shots_data, (level, model)) in sampled.into_iter().zip(&levels) {
+            let errors = logical_errors(&circuit, model, shots_data, decoder)?;
+            points.push(ThresholdPoint {
+                distance,
+                noise_level: *level,
+                shots,
+                errors,
+                logical_error_rate: errors as f64 / shots.max(1) as f64,
+                confidence_interval: wilson_interval(errors, shots, Z_95),
+            });
+        }
+    }
+    Ok(points)
+}
+
+/// Sample `shots` shots of `circuit` under `model`, split over every available core.
+fn sample_parallel(circuit: &Circuit, model: &NoiseModel, shots: usize, rng: &mut StdRng) -> Shots {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let per_thread = shots.div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..shots)
+            .step_by(per_thread)
+            .map(|start| {
+                let count = per_thread.min(shots - start);
+                let mut rng = StdRng::seed_from_u64(rng.random());
+                scope.spawn(move || sample_shots(circuit, model, count, &mut rng))
+            })
+            .collect();
+        let (mut events, mut flips) = (Vec::with_capacity(shots), Vec::with_capacity(shots));
+        for handle in handles {
+            let (chunk_events, chunk_flips) = handle.join().expect("Sampling thread panicked");
+            events.extend(chunk_events);
+            flips.extend(chunk_flips);
+        }
+        (events, flips)
+    })
+}
+
+/// Result of `estimate_logical_error_rate`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogicalErrorEstimate {
+    /// Number of shots that were run.
+    shots: usize,
+    /// Number of shots where the decoder got an observable wrong.
+    errors: usize,
+    /// Fraction of shots with a logical error.
+    logical_error_rate: f64,
+    /// 95% Wilson score interval `(low, high)` of the logical error rate.
+    confidence_interval: (f64, f64),
+}
+
+/// Monte Carlo estimate of the logical error rate of the code experiment `code`.
+///
+/// `code` is a circuit that encodes, runs its noisy rounds and reads out, with detectors
+/// and observables that are deterministic without noise, such as `surface_code_circuit`.
+/// Shots are sampled under `noise` on all cores without the GIL and then decoded with a
+/// single `decoder(dem, detection_events)` call, following the contract of `threshold`.
+/// Without a decoder any observable flip counts as a logical error.
+#[pyfunction]
+#[pyo3(signature = (code, noise, shots, decoder = None))]
+pub fn estimate_logical_error_rate(
+    py: Python<'_>,
+    code: &Circuit,
+    noise: &NoiseModel,
+    shots: usize,
+    decoder: Option<&Bound<'_, PyAny>>,
+) -> PyResult<LogicalErrorEstimate> {
+    let mut rng = crate::rng::generator();
+    let sampled = py.detach(|| sample_parallel(code, noise, shots, &mut rng));
+    let errors = logical_errors(code, noise, sampled, decoder)?;
+    Ok(LogicalErrorEstimate {
+        shots,
+        errors,
+        logical_error_rate: errors as f64 / shots.max(1) as f64,
+        confidence_interval: wilson_interval(errors, shots, Z_95),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphsim::Axis;
+
+    #[test]
+    fn test_sweep_extremes() {
+        let mut circuit = Circuit::new(2);
+        circuit.cx(0, 1);
+        circuit.measure(0, Axis::Z);
+        circuit.measure(1, Axis::Z);
+        circuit.detector(vec![0, 1]).unwrap();
+        circuit.observable(vec![0]).unwrap();
+
+        let levels = uniform_levels(&[0.0, 0.5]).unwrap();
+        let points = run_levels(&circuit, &levels, 200, &mut StdRng::seed_from_u64(7));
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].noise_level, 0.0);
+        assert_eq!(points[0].detector_rates, vec![0.0]);
+        assert_eq!(points[0].flipped_shots, 0);
+        assert!(points[1].nontrivial_shots > 0 && points[1].flipped_shots > 0);
+        assert!(uniform_levels(&[1.5]).is_err());
+
+        // the same seed gives the same shots at every level
+        let seeded = |seed| run_levels(&circuit, &levels, 200, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(seeded(3), seeded(3));
+        assert_ne!(seeded(3), seeded(4));
+    }
+
+    #[test]
+    fn test_parallel_sampling_keeps_shot_count() {
+        let circuit = surface_code_circuit(3, 2).unwrap();
+        for shots in [0, 1, 17] {
+            let (events, flips) = sample_parallel(
+                &circuit,
+                &NoiseModel::default(),
+                shots,
+                &mut StdRng::seed_from_u64(7),
+            );
+            assert_eq!((events.len(), flips.len()), (shots, shots));
+            assert!(
+                events
+                    .iter()
+                    .flatten()
+                    .chain(flips.iter().flatten())
+                    .all(|&bit| !bit)
+            );
+        }
+    }
+
+    #[test]
+    fn test_logical_failures() {
+        let flips = vec![vec![false], vec![true], vec![true]];
+        assert_eq!(count_failures(&flips, None).unwrap(), 2);
+        assert_eq!(
+            count_failures(&flips, Some(&[vec![0], vec![1], vec![0]])).unwrap(),
+            1
+        );
+        assert!(count_failures(&flips, Some(&[vec![0]])).is_err());
+
+        let (low, high) = wilson_interval(10, 100, Z_95);
+        assert!((low - 0.0552).abs() < 1e-4 && (high - 0.1744).abs() < 1e-4);
+        assert!(wilson_interval(0, 50, Z_95).0 < 1e-12);
+    }
+}
@@ -0,0 +1,307 @@
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{circuit::Op, graphsim::NodeIdx, stabilizer::PauliString};
+
+/// Conjugate `row` by `op`, G P G†, for the gates `synthesize` emits.
+fn conjugate(row: &mut PauliString, op: &Op) {
+    let has = |row: &PauliString, qubit: NodeIdx| (row.x.contains(qubit), row.z.contains(qubit));
+    match *op {
+        Op::X { qubit } => row.negative ^= row.z.contains(qubit),
+        Op::Z { qubit } => row.negative ^= row.x.contains(qubit),
+        Op::H { qubit } => {
+            let (x, z) = has(row, qubit);
+            row.negative ^= x && z;
+            set(row, qubit, z, x);
+        }
+        Op::S { qubit } => {
+            let (x, z) = has(row, qubit);
+            row.negative ^= x && z;
+            set(row, qubit, x, z ^ x);
+        }
+        Op::Sdag { qubit } => {
+            let (x, z) = has(row, qubit);
+            row.negative ^= x && !z;
+            set(row, qubit, x, z ^ x);
+        }
+        Op::Cx { control, target } => {
+            let (xc, zc) = has(row, control);
+            let (xt, zt) = has(row, target);
+            row.negative ^= xc && zt && (xt == zc);
+            set(row, target, xt ^ xc, zt);
+            set(row, control, xc, zc ^ zt);
+        }
+        _ => unreachable!("Tableaus are only synthesized into H, S, S†, CX and Paulis"),
+    }
+}
+
+/// Set the X and Z bits of `row` on `qubit`.
+fn set(row: &mut PauliString, qubit: NodeIdx, x: bool, z: bool) {
+    for (part, bit) in [(&mut row.x, x), (&mut row.z, z)] {
+        match bit {
+            true => part.insert(qubit),
+            false => part.remove(qubit),
+        };
+    }
+}
+
+/// The rows of a Clifford tableau on `n` qubits: the images of X₀ … Xₙ₋₁ and then of
+/// Z₀ … Zₙ₋₁.
+///
+/// `tableau` has 2n rows of 2n bits, the X part of the image on every qubit followed by
+/// its Z part, and `signs` optionally says which images are negative. Raises
+/// `ValueError` unless the rows have the commutation relations of the Paulis they are
+/// the image of, so they form a Clifford.
+pub(crate) fn parse_tableau(
+    tableau: &[Vec<bool>],
+    signs: Option<&[bool]>,
+) -> PyResult<Vec<PauliString>> {
+    let size = tableau.len();
+    if size % 2 == 1 {
+        return Err(PyValueError::new_err(format!(
+            "A tableau has an even number of rows, not {size}"
+        )));
+    }
+    if let Some(signs) = signs
+        && signs.len() != size
+    {
+        return Err(PyValueError::new_err(format!(
+            "Got {} signs for {size} tableau rows",
+            signs.len()
+        )));
+    }
+    let qubit_amount = size / 2;
+    let rows = tableau
+        .iter()
+        .enumerate()
+        .map(|(idx, bits)| {
+            if bits.len() != size {
+                return Err(PyValueError::new_err(format!(
+                    "Tableau row {idx} has {} bits instead of {size}",
+                    bits.len()
+                )));
+            }
+            let mut row = PauliString {
+                negative: signs.is_some_and(|signs| signs[idx]),
+                ..PauliString::default()
+            };
+            for qubit in 0..qubit_amount {
+                set(&mut row, qubit, bits[qubit], bits[qubit_amount + qubit]);
+            }
+            Ok(row)
+        })
+        .collect::<PyResult<Vec<PauliString>>>()?;
+    for (idx, row) in rows.iter().enumerate() {
+        for (other, earlier) in rows[..idx].iter().enumerate() {
+            // only the images of Xᵢ and Zᵢ anticommute
+            if row.anticommutes(earlier) != (idx == other + qubit_amount) {
+                return Err(PyValueError::new_err(format!(
+                    "Tableau rows {other} and {idx} don't have the commutation of a Clifford"
+                )));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Gates on qubits 0 … n−1 performing the Clifford with tableau `rows`.
+///
+/// The rows are reduced to those of the identity one qubit at a time by conjugating them
+/// with H, S, S† and CX gates: the image of Xᵢ is turned into Xᵢ, after which the image
+/// of Zᵢ has Z or Y on qubit i and can be turned into Zᵢ without touching Xᵢ. All other
+/// rows commute with both, so they have cleared qubit i too. Paulis then fix the signs,
+/// and the inverse of these gates is the Clifford. Uses O(n²) gates.
+pub(crate) fn synthesize(rows: &[PauliString]) -> Vec<Op> {
+    let qubit_amount = rows.len() / 2;
+    let mut rows = rows.to_vec();
+    let mut reduction = Vec::new();
+    let mut apply = |rows: &mut Vec<PauliString>, op: Op| {
+        for row in rows.iter_mut() {
+            conjugate(row, &op);
+        }
+        reduction.push(op);
+    };
+
+    for qubit in 0..qubit_amount {
+        // image of Xᵢ to X on its support, then collected onto qubit i
+        let image = rows[qubit].clone();
+        for other in image.x.union(&image.z) {
+            match (image.x.contains(other), image.z.contains(other)) {
+                (true, true) => apply(&mut rows, Op::S { qubit: other }),
+                (false, true) => apply(&mut rows, Op::H { qubit: other }),
+                _ => {}
+            }
+        }
+        if !rows[qubit].x.contains(qubit) {
+            let other = rows[qubit]
+                .x
+                .iter()
+                .next()
+                .expect("Xᵢ has a non-trivial image");
+            apply(
+                &mut rows,
+                Op::Cx {
+                    control: other,
+                    target: qubit,
+                },
+            );
+        }
+        let support: Vec<NodeIdx> = rows[qubit].x.iter().filter(|&q| q != qubit).collect();
+        for other in support {
+            apply(
+                &mut rows,
+                Op::Cx {
+                    control: qubit,
+                    target: other,
+                },
+            );
+        }
+
+        // image of Zᵢ has Z or Y on qubit i, as it anticommutes with Xᵢ
+        let z_row = qubit_amount + qubit;
+        if rows[z_row].x.contains(qubit) {
+            // √X, fixing X and turning Y into Z
+            for op in [Op::H { qubit }, Op::S { qubit }, Op::H { qubit }] {
+                apply(&mut rows, op);
+            }
+        }
+        let image = rows[z_row].clone();
+        for other in image.x.union(&image.z).filter(|&q| q != qubit) {
+            match (image.x.contains(other), image.z.contains(other)) {
+                (true, true) => {
+                    apply(&mut rows, Op::S { qubit: other });
+                    apply(&mut rows, Op::H { qubit: other });
+                }
+                (true, false) => apply(&mut rows, Op::H { qubit: other }),
+                _ => {}
+            }
+            apply(
+                &mut rows,
+                Op::Cx {
+                    control: other,
+                    target: qubit,
+                },
+            );
+        }
+    }
+    for qubit in 0..qubit_amount {
+        if rows[qubit].negative {
+            apply(&mut rows, Op::Z { qubit });
+        }
+        if rows[qubit_amount + qubit].negative {
+            apply(&mut rows, Op::X { qubit });
+        }
+    }
+
+    reduction
+        .into_iter()
+        .rev()
+        .map(|op| match op {
+            Op::S { qubit } => Op::Sdag { qubit },
+            op => op,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{graphsim::GraphSim, stabilizer::canonical_stabilizers};
+
+    /// Tableau rows of the circuit `ops` on `qubit_amount` qubits.
+    fn tableau_of(ops: &[Op], qubit_amount: usize) -> Vec<PauliString> {
+        let mut rows: Vec<PauliString> = (0..2 * qubit_amount)
+            .map(|idx| {
+                let mut row = PauliString::default();
+                match idx < qubit_amount {
+                    true => row.x.insert(idx),
+                    false => row.z.insert(idx - qubit_amount),
+                };
+                row
+            })
+            .collect();
+        for op in ops {
+            for row in &mut rows {
+                conjugate(row, op);
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn test_synthesize_random_cliffords() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let qubit_amount = rng.random_range(1..6);
+            let ops: Vec<Op> = (0..30)
+                .map(|_| {
+                    let qubit = rng.random_range(0..qubit_amount);
+                    match rng.random_range(0..5) {
+                        0 => Op::H { qubit },
+                        1 => Op::S { qubit },
+                        2 => Op::X { qubit },
+                        3 => Op::Z { qubit },
+                        _ if qubit_amount == 1 => Op::Sdag { qubit },
+                        _ => Op::Cx {
+                            control: qubit,
+                            target: (qubit + rng.random_range(1..qubit_amount)) % qubit_amount,
+                        },
+                    }
+                })
+                .collect();
+            let rows = tableau_of(&ops, qubit_amount);
+            let synthesized = synthesize(&rows);
+            assert_eq!(tableau_of(&synthesized, qubit_amount), rows);
+
+            // both circuits take a random state to the same state
+            let mut sim = GraphSim::new(qubit_amount);
+            for qubit in 0..qubit_amount {
+                sim.h(qubit);
+                sim.rotate(qubit, crate::graphsim::Axis::Z, rng.random_range(0..4));
+            }
+            let mut expected = sim.clone();
+            for op in &ops {
+                op.apply(&mut expected);
+            }
+            for op in &synthesized {
+                op.apply(&mut sim);
+            }
+            assert_eq!(
+                canonical_stabilizers(&sim),
+                canonical_stabilizers(&expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_tableau() {
+        // CX from qubit 0 to 1: X₀ → X₀X₁, X₁ → X₁, Z₀ → Z₀, Z₁ → Z₀Z₁
+        let cx = [
+            vec![true, true, false, false],
+            vec![false, true, false, false],
+            vec![false, false, true, false],
+            vec![false, false, true, true],
+        ];
+        let rows = parse_tableau(&cx, None).unwrap();
+        assert_eq!(
+            rows,
+            tableau_of(
+                &[Op::Cx {
+                    control: 0,
+                    target: 1
+                }],
+                2
+            )
+        );
+        let signs = [false, false, true, false];
+        assert!(parse_tableau(&cx, Some(&signs)).unwrap()[2].negative);
+
+        assert!(parse_tableau(&cx[..3], None).is_err());
+        assert!(parse_tableau(&cx, Some(&signs[..2])).is_err());
+        // X₁ → Z₀ anticommutes with the image X₀X₁ of X₀
+        let mut invalid = cx.clone();
+        invalid[1] = cx[2].clone();
+        assert!(parse_tableau(&invalid, None).is_err());
+    }
+}
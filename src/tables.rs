@@ -0,0 +1,151 @@
+use crate::{
+    MEAS_AXES, SYMMETRIES,
+    graphsim::{Axis, Vop},
+};
+
+// VOP_TABLE, ADJ_TABLE, DETM_TABLE, CONJ_TABLE and CPHASE_TABLE, computed by build.rs
+// from the matrices of the VOPs
+include!(concat!(env!("OUT_DIR"), "/tables.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ket::vop_matrix;
+
+    fn names(vops: &[Vop]) -> String {
+        vops.iter()
+            .map(|vop| format!("{vop:?}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn axes(axes: &[Axis]) -> String {
+        axes.iter().map(|axis| axis.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tables_match_hand_written() {
+        // the tables as they were written out by hand before being generated
+        let vop_table = [
+            "IA XA YA ZA IB XB YB ZB IC XC YC ZC ID XD YD ZD IE XE YE ZE IF XF YF ZF",
+            "XA IA ZA YA YB ZB IB XB ZC YC XC IC XD ID ZD YD ZE YE XE IE YF ZF IF XF",
+            "YA ZA IA XA XB IB ZB YB YC ZC IC XC ZD YD XD ID XE IE ZE YE ZF YF XF IF",
+            "ZA YA XA IA ZB YB XB IB XC IC ZC YC YD ZD ID XD YE ZE IE XE XF IF ZF YF",
+            "IB XB YB ZB IA XA YA ZA IF XF YF ZF IE XE YE ZE ID XD YD ZD IC XC YC ZC",
+            "XB IB ZB YB YA ZA IA XA ZF YF XF IF XE IE ZE YE ZD YD XD ID YC ZC IC XC",
+            "YB ZB IB XB XA IA ZA YA YF ZF IF XF ZE YE XE IE XD ID ZD YD ZC YC XC IC",
+            "ZB YB XB IB ZA YA XA IA XF IF ZF YF YE ZE IE XE YD ZD ID XD XC IC ZC YC",
+            "IC XC YC ZC IE XE YE ZE IA XA YA ZA IF XF YF ZF IB XB YB ZB ID XD YD ZD",
+            "XC IC ZC YC YE ZE IE XE ZA YA XA IA XF IF ZF YF ZB YB XB IB YD ZD ID XD",
+            "YC ZC IC XC XE IE ZE YE YA ZA IA XA ZF YF XF IF XB IB ZB YB ZD YD XD ID",
+            "ZC YC XC IC ZE YE XE IE XA IA ZA YA YF ZF IF XF YB ZB IB XB XD ID ZD YD",
+            "ID XD YD ZD IF XF YF ZF IE XE YE ZE IA XA YA ZA IC XC YC ZC IB XB YB ZB",
+            "XD ID ZD YD YF ZF IF XF ZE YE XE IE XA IA ZA YA ZC YC XC IC YB ZB IB XB",
+            "YD ZD ID XD XF IF ZF YF YE ZE IE XE ZA YA XA IA XC IC ZC YC ZB YB XB IB",
+            "ZD YD XD ID ZF YF XF IF XE IE ZE YE YA ZA IA XA YC ZC IC XC XB IB ZB YB",
+            "IE XE YE ZE IC XC YC ZC ID XD YD ZD IB XB YB ZB IF XF YF ZF IA XA YA ZA",
+            "XE IE ZE YE YC ZC IC XC ZD YD XD ID XB IB ZB YB ZF YF XF IF YA ZA IA XA",
+            "YE ZE IE XE XC IC ZC YC YD ZD ID XD ZB YB XB IB XF IF ZF YF ZA YA XA IA",
+            "ZE YE XE IE ZC YC XC IC XD ID ZD YD YB ZB IB XB YF ZF IF XF XA IA ZA YA",
+            "IF XF YF ZF ID XD YD ZD IB XB YB ZB IC XC YC ZC IA XA YA ZA IE XE YE ZE",
+            "XF IF ZF YF YD ZD ID XD ZB YB XB IB XC IC ZC YC ZA YA XA IA YE ZE IE XE",
+            "YF ZF IF XF XD ID ZD YD YB ZB IB XB ZC YC XC IC XA IA ZA YA ZE YE XE IE",
+            "ZF YF XF IF ZD YD XD ID XB IB ZB YB YC ZC IC XC YA ZA IA XA XE IE ZE YE",
+        ];
+        for (row, expected) in VOP_TABLE.iter().zip(vop_table) {
+            assert_eq!(names(row), expected);
+        }
+        assert_eq!(
+            names(&ADJ_TABLE),
+            "IA XA YA ZA IB YB XB ZB IC ZC YC XC ID XD ZD YD IF YF ZF XF IE ZE XE YE"
+        );
+        assert_eq!(axes(&DETM_TABLE), "XXXXYYYYZZZZXXXXZZZZYYYY");
+        assert_eq!(
+            CONJ_TABLE.map(|row| axes(&row)),
+            [
+                "XXXXYYYYZZZZXXXXYYYYZZZZ",
+                "YYYYXXXXYYYYZZZZZZZZXXXX",
+                "ZZZZZZZZXXXXYYYYXXXXYYYY",
+            ]
+        );
+    }
+
+    /// Amplitudes of a two-qubit state as `(re, im)`.
+    type State = [(f64, f64); 4];
+
+    /// (C ⊗ T) CZ^e |++⟩, optionally with a CZ applied after.
+    fn two_qubit_state(edge: bool, control: Vop, target: Vop, cz: bool) -> State {
+        let (control, target) = (vop_matrix(control), vop_matrix(target));
+        let graph = |idx: usize| match edge && idx == 3 {
+            true => -0.5,
+            false => 0.5,
+        };
+        std::array::from_fn(|out| {
+            let mut amplitude = (0..4).fold((0.0, 0.0), |sum, idx| {
+                let (c, t) = (control[out / 2][idx / 2], target[out % 2][idx % 2]);
+                let factor = (c.0 * t.0 - c.1 * t.1, c.0 * t.1 + c.1 * t.0);
+                (sum.0 + graph(idx) * factor.0, sum.1 + graph(idx) * factor.1)
+            });
+            if cz && out == 3 {
+                amplitude = (-amplitude.0, -amplitude.1);
+            }
+            amplitude
+        })
+    }
+
+    /// Whether `a` and `b` are the same state up to a global phase.
+    fn same_state(a: &State, b: &State) -> bool {
+        // |⟨a|b⟩| is 1 for normalised states that are equal up to a phase
+        let overlap = a.iter().zip(b).fold((0.0, 0.0), |sum, (a, b)| {
+            (sum.0 + a.0 * b.0 + a.1 * b.1, sum.1 + a.0 * b.1 - a.1 * b.0)
+        });
+        (overlap.0.hypot(overlap.1) - 1.0).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_cphase_table_matches_matrices() {
+        let vops = || (0..SYMMETRIES).map(Vop::from_index);
+        let candidates: Vec<((bool, Vop, Vop), State)> = [false, true]
+            .into_iter()
+            .flat_map(|edge| vops().flat_map(move |c| vops().map(move |t| (edge, c, t))))
+            .map(|(edge, c, t)| ((edge, c, t), two_qubit_state(edge, c, t, false)))
+            .collect();
+        for edge in [false, true] {
+            for control in vops() {
+                for target in vops() {
+                    let applied = two_qubit_state(edge, control, target, true);
+                    // the entry is the first graph form in (e', C', T') order that keeps
+                    // diagonal VOPs diagonal
+                    let first = candidates
+                        .iter()
+                        .find(|((_, new_control, new_target), state)| {
+                            (!control.is_in_z() || new_control.is_in_z())
+                                && (!target.is_in_z() || new_target.is_in_z())
+                                && same_state(&applied, state)
+                        })
+                        .map(|&(form, _)| form);
+                    assert_eq!(
+                        first,
+                        Some(CPHASE_TABLE[usize::from(edge)][control as usize][target as usize]),
+                        "CZ after {control:?} ⊗ {target:?} with edge {edge}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cphase_commutes_with_diagonal_vops() {
+        let diagonal = [Vop::IA, Vop::ZA, Vop::YB, Vop::XB];
+        for had_edge in [false, true] {
+            for control in diagonal {
+                for target in diagonal {
+                    assert_eq!(
+                        CPHASE_TABLE[usize::from(had_edge)][control as usize][target as usize],
+                        (!had_edge, control, target)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Write};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+};
+
+/// Build the error for line `line` (counting from 1) of a text program.
+fn line_error(line: usize, message: impl Display) -> PyErr {
+    PyValueError::new_err(format!("Line {line}: {message}"))
+}
+
+fn parse_numbers(line: usize, args: &[&str]) -> PyResult<Vec<usize>> {
+    args.iter()
+        .map(|arg| {
+            arg.parse()
+                .map_err(|_| line_error(line, format!("Expected an index, got {arg:?}")))
+        })
+        .collect()
+}
+
+/// Parse a program in the line-based text format, see `Circuit.from_text`.
+pub(crate) fn parse(source: &str) -> PyResult<Circuit> {
+    let mut circuit = Circuit::new(0);
+    for (idx, raw) in source.lines().enumerate() {
+        let line = idx + 1;
+        let content = raw.split('#').next().unwrap_or_default();
+        let mut words = content.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let args = parse_numbers(line, &words.collect::<Vec<_>>())?;
+        let command = command.to_lowercase();
+
+        match command.as_str() {
+            "new_qubit" => match *args.as_slice() {
+                [] => circuit.add_qubits(1),
+                [count] => circuit.add_qubits(count),
+                _ => return Err(line_error(line, "new_qubit takes at most one count")),
+            },
+            "detector" | "observable" => {
+                let available = circuit.num_measurements();
+                if let Some(meas) = args.iter().find(|&&meas| meas >= available) {
+                    return Err(line_error(
+                        line,
+                        format!("Measurement {meas} does not exist yet"),
+                    ));
+                }
+                match command.as_str() {
+                    "detector" => circuit.detector(args)?,
+                    _ => circuit.observable(args)?,
+                };
+            }
+            name => {
+                let op = parse_op(name, &args).ok_or_else(|| {
+                    line_error(
+                        line,
+                        format!("Unknown command {name:?} with {} arguments", args.len()),
+                    )
+                })?;
+                let qubits = op.qubits();
+                if let Some(qubit) = qubits.iter().find(|&&q| q >= circuit.qubit_amount()) {
+                    return Err(line_error(line, format!("Qubit {qubit} does not exist")));
+                }
+                if qubits.len() == 2 && qubits[0] == qubits[1] {
+                    return Err(line_error(line, "A two-qubit gate needs distinct qubits"));
+                }
+                circuit.append(op);
+            }
+        }
+    }
+    Ok(circuit)
+}
+
+/// The operation called `name` on `qubits`, with `edge` a synonym of `cz`.
+pub(crate) fn parse_op(name: &str, qubits: &[NodeIdx]) -> Option<Op> {
+    Some(match (name, qubits) {
+        ("x", &[qubit]) => Op::X { qubit },
+        ("y", &[qubit]) => Op::Y { qubit },
+        ("z", &[qubit]) => Op::Z { qubit },
+        ("h", &[qubit]) => Op::H { qubit },
+        ("s", &[qubit]) => Op::S { qubit },
+        ("sdag", &[qubit]) => Op::Sdag { qubit },
+        ("sx", &[qubit]) => Op::Sx { qubit },
+        ("sxdag", &[qubit]) => Op::Sxdag { qubit },
+        ("sy", &[qubit]) => Op::Sy { qubit },
+        ("sydag", &[qubit]) => Op::Sydag { qubit },
+        ("cz" | "edge", &[control, target]) => Op::Cz { control, target },
+        ("cx", &[control, target]) => Op::Cx { control, target },
+        ("cy", &[control, target]) => Op::Cy { control, target },
+        ("swap", &[first, second]) => Op::Swap { first, second },
+        ("measure_x", &[qubit]) => Op::Measure {
+            qubit,
+            axis: Axis::X,
+        },
+        ("measure_y", &[qubit]) => Op::Measure {
+            qubit,
+            axis: Axis::Y,
+        },
+        ("measure_z", &[qubit]) => Op::Measure {
+            qubit,
+            axis: Axis::Z,
+        },
+        ("reset", &[qubit]) => Op::Reset { qubit },
+        _ => return None,
+    })
+}
+
+/// Write `circuit` in the text format, such that `parse` gives it back.
+pub(crate) fn write(circuit: &Circuit) -> String {
+    let mut text = String::new();
+    if circuit.qubit_amount() > 0 {
+        writeln!(text, "new_qubit {}", circuit.qubit_amount()).expect("Writing to a String");
+    }
+    for op in circuit.iter() {
+        writeln!(text, "{op}").expect("Writing to a String");
+    }
+    for (command, sets) in [
+        ("detector", &circuit.detectors),
+        ("observable", &circuit.observables),
+    ] {
+        for set in sets {
+            text.push_str(command);
+            for meas in set {
+                write!(text, " {meas}").expect("Writing to a String");
+            }
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Parse `source` in the text format of `Circuit.from_text` and run it.
+///
+/// Returns the simulator after running the program from |0…0⟩ and the measurement
+/// outcomes in order.
+#[pyfunction]
+pub fn run_text(source: &str) -> PyResult<(GraphSim, Vec<MeasurementResult>)> {
+    let circuit = parse(source)?;
+    let mut sim = GraphSim::new(circuit.qubit_amount());
+    let outcomes = circuit.execute(&mut sim);
+    Ok((sim, outcomes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_round_trip() {
+        let source = "
+            # a Bell pair in graph-state form
+            new_qubit 2
+            h 0
+            h 1
+            edge 0 1
+            H 1
+            new_qubit
+            cx 1 2
+            measure_Z 0  # parity partner
+            measure_z 2
+            detector 0 1
+            observable 0
+        ";
+        let circuit = parse(source).unwrap();
+        assert_eq!(circuit.qubit_amount(), 3);
+        assert_eq!(circuit.iter().count(), 7);
+        assert_eq!(circuit.detectors(), vec![vec![0, 1]]);
+        assert_eq!(parse(&write(&circuit)).unwrap(), circuit);
+
+        let (_, outcomes) = run_text(source).unwrap();
+        assert_eq!(outcomes[0], outcomes[1]);
+    }
+
+    #[test]
+    fn test_text_errors() {
+        assert!(parse("new_qubit\nh 1").is_err());
+        assert!(parse("new_qubit 2\ncz 1 1").is_err());
+        assert!(parse("new_qubit\nt 0").is_err());
+        assert!(parse("new_qubit\nh zero").is_err());
+        assert!(parse("new_qubit\ndetector 0").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt::{Display, Formatter};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::GraphSim,
+};
+
+/// A problem `GraphSim.validate` found in a circuit.
+#[pyclass(frozen, get_all, str)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// Index of the offending operation, `None` for detectors and observables.
+    op_index: Option<usize>,
+    /// Kind of problem: `"qubit_out_of_range"`, `"traced_qubit"`, `"same_qubit"` or
+    /// `"undefined_measurement"`.
+    kind: &'static str,
+    /// Human readable description.
+    message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.op_index {
+            Some(idx) => write!(fmt, "op {idx}: {}", self.message),
+            None => fmt.write_str(&self.message),
+        }
+    }
+}
+
+/// Every problem running `circuit` on `sim` would run into, in circuit order.
+pub(crate) fn validate(sim: &GraphSim, circuit: &Circuit) -> Vec<Problem> {
+    let mut problems = op_problems(sim, circuit.iter(), 0);
+    let measurements = circuit
+        .iter()
+        .filter(|op| matches!(op, Op::Measure { .. }))
+        .count();
+
+    for (name, sets) in [
+        ("Detector", &circuit.detectors),
+        ("Observable", &circuit.observables),
+    ] {
+        for (idx, set) in sets.iter().enumerate() {
+            for &meas in set.iter().filter(|&&meas| meas >= measurements) {
+                problems.push(Problem {
+                    op_index: None,
+                    kind: "undefined_measurement",
+                    message: format!(
+                        "{name} {idx} uses measurement {meas}, but there are {measurements}"
+                    ),
+                });
+            }
+        }
+    }
+    problems
+}
+
+/// The problems of applying `ops` to `sim`, numbered from `offset`.
+fn op_problems<'a>(
+    sim: &GraphSim,
+    ops: impl IntoIterator<Item = &'a Op>,
+    offset: usize,
+) -> Vec<Problem> {
+    let qubit_amount = sim.adjacency().len();
+    let mut problems = Vec::new();
+    let mut problem = |op_index, kind, message| {
+        problems.push(Problem {
+            op_index: Some(op_index),
+            kind,
+            message,
+        })
+    };
+
+    for (idx, op) in ops.into_iter().enumerate() {
+        let idx = offset + idx;
+        let qubits = op.qubits();
+        for &qubit in &qubits {
+            if qubit >= qubit_amount {
+                problem(
+                    idx,
+                    "qubit_out_of_range",
+                    format!("{op} uses qubit {qubit}, but there are {qubit_amount} qubits"),
+                );
+            } else if sim.is_traced(qubit) {
+                problem(
+                    idx,
+                    "traced_qubit",
+                    format!("{op} uses qubit {qubit}, which was traced out"),
+                );
+            }
+        }
+        if qubits.len() == 2 && qubits[0] == qubits[1] {
+            problem(
+                idx,
+                "same_qubit",
+                format!("{op} has the same control and target"),
+            );
+        }
+    }
+    problems
+}
+
+/// `ValueError` listing `problems`, if there are any.
+fn raise(problems: Vec<Problem>) -> PyResult<()> {
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(PyValueError::new_err(
+            problems
+                .iter()
+                .map(Problem::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        )),
+    }
+}
+
+/// Raise `ValueError` listing the problems of running `circuit` on `sim`, if it has any.
+pub(crate) fn check(sim: &GraphSim, circuit: &Circuit) -> PyResult<()> {
+    raise(validate(sim, circuit))
+}
+
+/// Raise `ValueError` if `ops`, numbered from `offset`, can't be applied to `sim`.
+pub(crate) fn check_ops(sim: &GraphSim, ops: &[Op], offset: usize) -> PyResult<()> {
+    raise(op_problems(sim, ops, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_finds_problems() {
+        let circuit =
+            Circuit::from_text("new_qubit 4\nh 0\ncx 0 3\nmeasure_z 1\nh 2\ndetector 0").unwrap();
+        let mut sim = GraphSim::new(3);
+        sim.trace_out(vec![2]);
+
+        let kinds: Vec<(Option<usize>, &str)> = validate(&sim, &circuit)
+            .iter()
+            .map(|problem| (problem.op_index, problem.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(Some(1), "qubit_out_of_range"), (Some(3), "traced_qubit")]
+        );
+        assert!(validate(&GraphSim::new(4), &circuit).is_empty());
+
+        // running refuses the circuit before touching the state
+        let before = sim.clone();
+        assert!(sim.run(&circuit).is_err());
+        assert_eq!(sim, before);
+        assert!(GraphSim::new(4).run(&circuit).is_ok());
+    }
+
+    #[test]
+    fn test_streamed_ops_are_numbered_from_offset() {
+        let ops = [
+            Op::H { qubit: 0 },
+            Op::Cz {
+                control: 1,
+                target: 1,
+            },
+        ];
+        let sim = GraphSim::new(2);
+        let problems = op_problems(&sim, &ops, 5);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            (problems[0].op_index, problems[0].kind),
+            (Some(6), "same_qubit")
+        );
+        assert!(check_ops(&sim, &ops, 5).is_err());
+        assert!(check_ops(&sim, &ops[..1], 0).is_ok());
+    }
+
+    #[test]
+    fn test_decoded_ops_are_checked() {
+        // rows given to `apply_ops`, whose second qubit only counts for two-qubit gates
+        let sim = GraphSim::new(2);
+        let rows = [(3, 1, 9), (7, 0, 5)];
+        let decoded: Vec<Op> = rows
+            .iter()
+            .map(|&(code, first, second)| Op::from_code(code, first, second).unwrap())
+            .collect();
+        assert!(check_ops(&sim, &decoded[..1], 0).is_ok());
+        assert_eq!(op_problems(&sim, &decoded, 0)[0].kind, "qubit_out_of_range");
+    }
+}
@@ -0,0 +1,166 @@
+use pyo3::prelude::*;
+
+use crate::{
+    SYMMETRIES,
+    graphsim::{Axis, Vop},
+    stabilizer::{PauliString, conjugation_table, row_reduce},
+    tables::{ADJ_TABLE, CONJ_TABLE, CPHASE_TABLE, DETM_TABLE},
+};
+
+type CphaseTable = [[[(bool, Vop, Vop); SYMMETRIES]; SYMMETRIES]; 2];
+
+fn pauli(qubit: usize, axis: Axis) -> PauliString {
+    let mut string = PauliString::default();
+    string.set(qubit, Some(axis));
+    string
+}
+
+/// Conjugate the two-qubit Pauli string `string` by `map`, given as the image of every
+/// single-qubit Pauli.
+fn conjugate(string: &PauliString, map: impl Fn(usize, Axis) -> PauliString) -> PauliString {
+    let mut image = PauliString {
+        negative: string.negative,
+        ..Default::default()
+    };
+    for qubit in 0..2 {
+        if let Some(axis) = string.get(qubit) {
+            // the factors on different qubits commute, so their images do too
+            image.mul_assign(&map(qubit, axis));
+        }
+    }
+    image
+}
+
+/// Canonical stabilizer generators of (`control` ⊗ `target`) CZ^`edge` |++⟩.
+fn two_qubit_stabilizers(
+    conjugation: &[[(bool, Axis); 3]],
+    edge: bool,
+    control: Vop,
+    target: Vop,
+) -> Vec<PauliString> {
+    let vops = [control, target];
+    let mut rows: Vec<PauliString> = (0..2)
+        .map(|qubit| {
+            let mut generator = pauli(qubit, Axis::X);
+            if edge {
+                generator.set(1 - qubit, Some(Axis::Z));
+            }
+            conjugate(&generator, |qubit, axis| {
+                let (negative, axis) = conjugation[vops[qubit] as usize][axis as usize];
+                PauliString {
+                    negative,
+                    ..pauli(qubit, axis)
+                }
+            })
+        })
+        .collect();
+    row_reduce(&mut rows, 2);
+    rows
+}
+
+/// Conjugate by CZ, which maps X and Y on one qubit to themselves times Z on the other.
+fn apply_cz(rows: &[PauliString]) -> Vec<PauliString> {
+    let mut rows: Vec<PauliString> = rows
+        .iter()
+        .map(|row| {
+            conjugate(row, |qubit, axis| {
+                let mut image = pauli(qubit, axis);
+                if axis != Axis::Z {
+                    image.set(1 - qubit, Some(Axis::Z));
+                }
+                image
+            })
+        })
+        .collect();
+    row_reduce(&mut rows, 2);
+    rows
+}
+
+/// Every entry of `table` that doesn't describe CZ applied to its two-qubit state, or
+/// that turns a VOP commuting with CZ into one that doesn't.
+fn cphase_errors(table: &CphaseTable) -> Vec<String> {
+    let conjugation = conjugation_table();
+    let mut errors = Vec::new();
+    for (had_edge, rows) in [false, true].into_iter().zip(table) {
+        for (control, row) in rows.iter().enumerate() {
+            for (target, &(edge, new_control, new_target)) in row.iter().enumerate() {
+                let (control, target) = (Vop::from_index(control), Vop::from_index(target));
+                let expected = apply_cz(&two_qubit_stabilizers(
+                    &conjugation,
+                    had_edge,
+                    control,
+                    target,
+                ));
+                let entry = format!(
+                    "CPHASE_TABLE[{}][{control:?}][{target:?}]",
+                    usize::from(had_edge)
+                );
+                if two_qubit_stabilizers(&conjugation, edge, new_control, new_target) != expected {
+                    errors.push(format!("{entry} gives a different state"));
+                }
+                if (control.is_in_z() && !new_control.is_in_z())
+                    || (target.is_in_z() && !new_target.is_in_z())
+                {
+                    errors.push(format!("{entry} moves a VOP out of the Z group"));
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// Check the lookup tables against each other and against how the gates act on Paulis.
+///
+/// The CPHASE table is recomputed from first principles: for every entry, CZ is applied
+/// to the stabilizers of the two-qubit state it starts from, and the result has to
+/// generate the same group as the stabilizers of the state the entry gives. Returns a
+/// description of every inconsistent entry, so an empty list means all tables are
+/// correct.
+#[pyfunction]
+pub fn verify_tables() -> Vec<String> {
+    let conjugation = conjugation_table();
+    let mut errors = Vec::new();
+    for idx in 0..SYMMETRIES {
+        let vop = Vop::from_index(idx);
+        if vop * ADJ_TABLE[idx] != Vop::IA || ADJ_TABLE[idx] * vop != Vop::IA {
+            errors.push(format!("ADJ_TABLE[{vop:?}] is not the inverse"));
+        }
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            if CONJ_TABLE[axis as usize][idx] != conjugation[idx][axis as usize].1 {
+                errors.push(format!("CONJ_TABLE[{axis}][{vop:?}] is not V{axis}V†"));
+            }
+        }
+        if DETM_TABLE[idx] != CONJ_TABLE[Axis::X as usize][ADJ_TABLE[idx] as usize] {
+            errors.push(format!("DETM_TABLE[{vop:?}] is not V†XV"));
+        }
+    }
+    errors.extend(cphase_errors(&CPHASE_TABLE));
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_are_consistent() {
+        assert_eq!(verify_tables(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_transposed_cphase_entry_is_found() {
+        let mut table = CPHASE_TABLE;
+        let (control, target) = (Vop::IB as usize, Vop::XC as usize);
+        let entry = table[0][control][target];
+        table[0][control][target] = table[0][target][control];
+        table[0][target][control] = entry;
+
+        assert_eq!(
+            cphase_errors(&table),
+            vec![
+                "CPHASE_TABLE[0][IB][XC] gives a different state",
+                "CPHASE_TABLE[0][XC][IB] gives a different state",
+            ]
+        );
+    }
+}
@@ -1,628 +0,0 @@
-use crate::{SYMMETRIES, graphsim::Vop};
-
-pub const VOP_TABLE: [[Vop; SYMMETRIES]; SYMMETRIES] = [
-    [
-        Vop::IA,
-        Vop::XA,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IB,
-        Vop::XB,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IC,
-        Vop::XC,
-        Vop::YC,
-        Vop::ZC,
-        Vop::ID,
-        Vop::XD,
-        Vop::YD,
-        Vop::ZD,
-        Vop::IE,
-        Vop::XE,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IF,
-        Vop::XF,
-        Vop::YF,
-        Vop::ZF,
-    ],
-    [
-        Vop::XA,
-        Vop::IA,
-        Vop::ZA,
-        Vop::YA,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IB,
-        Vop::XB,
-        Vop::ZC,
-        Vop::YC,
-        Vop::XC,
-        Vop::IC,
-        Vop::XD,
-        Vop::ID,
-        Vop::ZD,
-        Vop::YD,
-        Vop::ZE,
-        Vop::YE,
-        Vop::XE,
-        Vop::IE,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IF,
-        Vop::XF,
-    ],
-    [
-        Vop::YA,
-        Vop::ZA,
-        Vop::IA,
-        Vop::XA,
-        Vop::XB,
-        Vop::IB,
-        Vop::ZB,
-        Vop::YB,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IC,
-        Vop::XC,
-        Vop::ZD,
-        Vop::YD,
-        Vop::XD,
-        Vop::ID,
-        Vop::XE,
-        Vop::IE,
-        Vop::ZE,
-        Vop::YE,
-        Vop::ZF,
-        Vop::YF,
-        Vop::XF,
-        Vop::IF,
-    ],
-    [
-        Vop::ZA,
-        Vop::YA,
-        Vop::XA,
-        Vop::IA,
-        Vop::ZB,
-        Vop::YB,
-        Vop::XB,
-        Vop::IB,
-        Vop::XC,
-        Vop::IC,
-        Vop::ZC,
-        Vop::YC,
-        Vop::YD,
-        Vop::ZD,
-        Vop::ID,
-        Vop::XD,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IE,
-        Vop::XE,
-        Vop::XF,
-        Vop::IF,
-        Vop::ZF,
-        Vop::YF,
-    ],
-    [
-        Vop::IB,
-        Vop::XB,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IA,
-        Vop::XA,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IF,
-        Vop::XF,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IE,
-        Vop::XE,
-        Vop::YE,
-        Vop::ZE,
-        Vop::ID,
-        Vop::XD,
-        Vop::YD,
-        Vop::ZD,
-        Vop::IC,
-        Vop::XC,
-        Vop::YC,
-        Vop::ZC,
-    ],
-    [
-        Vop::XB,
-        Vop::IB,
-        Vop::ZB,
-        Vop::YB,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IA,
-        Vop::XA,
-        Vop::ZF,
-        Vop::YF,
-        Vop::XF,
-        Vop::IF,
-        Vop::XE,
-        Vop::IE,
-        Vop::ZE,
-        Vop::YE,
-        Vop::ZD,
-        Vop::YD,
-        Vop::XD,
-        Vop::ID,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IC,
-        Vop::XC,
-    ],
-    [
-        Vop::YB,
-        Vop::ZB,
-        Vop::IB,
-        Vop::XB,
-        Vop::XA,
-        Vop::IA,
-        Vop::ZA,
-        Vop::YA,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IF,
-        Vop::XF,
-        Vop::ZE,
-        Vop::YE,
-        Vop::XE,
-        Vop::IE,
-        Vop::XD,
-        Vop::ID,
-        Vop::ZD,
-        Vop::YD,
-        Vop::ZC,
-        Vop::YC,
-        Vop::XC,
-        Vop::IC,
-    ],
-    [
-        Vop::ZB,
-        Vop::YB,
-        Vop::XB,
-        Vop::IB,
-        Vop::ZA,
-        Vop::YA,
-        Vop::XA,
-        Vop::IA,
-        Vop::XF,
-        Vop::IF,
-        Vop::ZF,
-        Vop::YF,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IE,
-        Vop::XE,
-        Vop::YD,
-        Vop::ZD,
-        Vop::ID,
-        Vop::XD,
-        Vop::XC,
-        Vop::IC,
-        Vop::ZC,
-        Vop::YC,
-    ],
-    [
-        Vop::IC,
-        Vop::XC,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IE,
-        Vop::XE,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IA,
-        Vop::XA,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IF,
-        Vop::XF,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IB,
-        Vop::XB,
-        Vop::YB,
-        Vop::ZB,
-        Vop::ID,
-        Vop::XD,
-        Vop::YD,
-        Vop::ZD,
-    ],
-    [
-        Vop::XC,
-        Vop::IC,
-        Vop::ZC,
-        Vop::YC,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IE,
-        Vop::XE,
-        Vop::ZA,
-        Vop::YA,
-        Vop::XA,
-        Vop::IA,
-        Vop::XF,
-        Vop::IF,
-        Vop::ZF,
-        Vop::YF,
-        Vop::ZB,
-        Vop::YB,
-        Vop::XB,
-        Vop::IB,
-        Vop::YD,
-        Vop::ZD,
-        Vop::ID,
-        Vop::XD,
-    ],
-    [
-        Vop::YC,
-        Vop::ZC,
-        Vop::IC,
-        Vop::XC,
-        Vop::XE,
-        Vop::IE,
-        Vop::ZE,
-        Vop::YE,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IA,
-        Vop::XA,
-        Vop::ZF,
-        Vop::YF,
-        Vop::XF,
-        Vop::IF,
-        Vop::XB,
-        Vop::IB,
-        Vop::ZB,
-        Vop::YB,
-        Vop::ZD,
-        Vop::YD,
-        Vop::XD,
-        Vop::ID,
-    ],
-    [
-        Vop::ZC,
-        Vop::YC,
-        Vop::XC,
-        Vop::IC,
-        Vop::ZE,
-        Vop::YE,
-        Vop::XE,
-        Vop::IE,
-        Vop::XA,
-        Vop::IA,
-        Vop::ZA,
-        Vop::YA,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IF,
-        Vop::XF,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IB,
-        Vop::XB,
-        Vop::XD,
-        Vop::ID,
-        Vop::ZD,
-        Vop::YD,
-    ],
-    [
-        Vop::ID,
-        Vop::XD,
-        Vop::YD,
-        Vop::ZD,
-        Vop::IF,
-        Vop::XF,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IE,
-        Vop::XE,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IA,
-        Vop::XA,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IC,
-        Vop::XC,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IB,
-        Vop::XB,
-        Vop::YB,
-        Vop::ZB,
-    ],
-    [
-        Vop::XD,
-        Vop::ID,
-        Vop::ZD,
-        Vop::YD,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IF,
-        Vop::XF,
-        Vop::ZE,
-        Vop::YE,
-        Vop::XE,
-        Vop::IE,
-        Vop::XA,
-        Vop::IA,
-        Vop::ZA,
-        Vop::YA,
-        Vop::ZC,
-        Vop::YC,
-        Vop::XC,
-        Vop::IC,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IB,
-        Vop::XB,
-    ],
-    [
-        Vop::YD,
-        Vop::ZD,
-        Vop::ID,
-        Vop::XD,
-        Vop::XF,
-        Vop::IF,
-        Vop::ZF,
-        Vop::YF,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IE,
-        Vop::XE,
-        Vop::ZA,
-        Vop::YA,
-        Vop::XA,
-        Vop::IA,
-        Vop::XC,
-        Vop::IC,
-        Vop::ZC,
-        Vop::YC,
-        Vop::ZB,
-        Vop::YB,
-        Vop::XB,
-        Vop::IB,
-    ],
-    [
-        Vop::ZD,
-        Vop::YD,
-        Vop::XD,
-        Vop::ID,
-        Vop::ZF,
-        Vop::YF,
-        Vop::XF,
-        Vop::IF,
-        Vop::XE,
-        Vop::IE,
-        Vop::ZE,
-        Vop::YE,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IA,
-        Vop::XA,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IC,
-        Vop::XC,
-        Vop::XB,
-        Vop::IB,
-        Vop::ZB,
-        Vop::YB,
-    ],
-    [
-        Vop::IE,
-        Vop::XE,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IC,
-        Vop::XC,
-        Vop::YC,
-        Vop::ZC,
-        Vop::ID,
-        Vop::XD,
-        Vop::YD,
-        Vop::ZD,
-        Vop::IB,
-        Vop::XB,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IF,
-        Vop::XF,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IA,
-        Vop::XA,
-        Vop::YA,
-        Vop::ZA,
-    ],
-    [
-        Vop::XE,
-        Vop::IE,
-        Vop::ZE,
-        Vop::YE,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IC,
-        Vop::XC,
-        Vop::ZD,
-        Vop::YD,
-        Vop::XD,
-        Vop::ID,
-        Vop::XB,
-        Vop::IB,
-        Vop::ZB,
-        Vop::YB,
-        Vop::ZF,
-        Vop::YF,
-        Vop::XF,
-        Vop::IF,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IA,
-        Vop::XA,
-    ],
-    [
-        Vop::YE,
-        Vop::ZE,
-        Vop::IE,
-        Vop::XE,
-        Vop::XC,
-        Vop::IC,
-        Vop::ZC,
-        Vop::YC,
-        Vop::YD,
-        Vop::ZD,
-        Vop::ID,
-        Vop::XD,
-        Vop::ZB,
-        Vop::YB,
-        Vop::XB,
-        Vop::IB,
-        Vop::XF,
-        Vop::IF,
-        Vop::ZF,
-        Vop::YF,
-        Vop::ZA,
-        Vop::YA,
-        Vop::XA,
-        Vop::IA,
-    ],
-    [
-        Vop::ZE,
-        Vop::YE,
-        Vop::XE,
-        Vop::IE,
-        Vop::ZC,
-        Vop::YC,
-        Vop::XC,
-        Vop::IC,
-        Vop::XD,
-        Vop::ID,
-        Vop::ZD,
-        Vop::YD,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IB,
-        Vop::XB,
-        Vop::YF,
-        Vop::ZF,
-        Vop::IF,
-        Vop::XF,
-        Vop::XA,
-        Vop::IA,
-        Vop::ZA,
-        Vop::YA,
-    ],
-    [
-        Vop::IF,
-        Vop::XF,
-        Vop::YF,
-        Vop::ZF,
-        Vop::ID,
-        Vop::XD,
-        Vop::YD,
-        Vop::ZD,
-        Vop::IB,
-        Vop::XB,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IC,
-        Vop::XC,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IA,
-        Vop::XA,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IE,
-        Vop::XE,
-        Vop::YE,
-        Vop::ZE,
-    ],
-    [
-        Vop::XF,
-        Vop::IF,
-        Vop::ZF,
-        Vop::YF,
-        Vop::YD,
-        Vop::ZD,
-        Vop::ID,
-        Vop::XD,
-        Vop::ZB,
-        Vop::YB,
-        Vop::XB,
-        Vop::IB,
-        Vop::XC,
-        Vop::IC,
-        Vop::ZC,
-        Vop::YC,
-        Vop::ZA,
-        Vop::YA,
-        Vop::XA,
-        Vop::IA,
-        Vop::YE,
-        Vop::ZE,
-        Vop::IE,
-        Vop::XE,
-    ],
-    [
-        Vop::YF,
-        Vop::ZF,
-        Vop::IF,
-        Vop::XF,
-        Vop::XD,
-        Vop::ID,
-        Vop::ZD,
-        Vop::YD,
-        Vop::YB,
-        Vop::ZB,
-        Vop::IB,
-        Vop::XB,
-        Vop::ZC,
-        Vop::YC,
-        Vop::XC,
-        Vop::IC,
-        Vop::XA,
-        Vop::IA,
-        Vop::ZA,
-        Vop::YA,
-        Vop::ZE,
-        Vop::YE,
-        Vop::XE,
-        Vop::IE,
-    ],
-    [
-        Vop::ZF,
-        Vop::YF,
-        Vop::XF,
-        Vop::IF,
-        Vop::ZD,
-        Vop::YD,
-        Vop::XD,
-        Vop::ID,
-        Vop::XB,
-        Vop::IB,
-        Vop::ZB,
-        Vop::YB,
-        Vop::YC,
-        Vop::ZC,
-        Vop::IC,
-        Vop::XC,
-        Vop::YA,
-        Vop::ZA,
-        Vop::IA,
-        Vop::XA,
-        Vop::XE,
-        Vop::IE,
-        Vop::ZE,
-        Vop::YE,
-    ],
-];
@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use bit_set::BitSet;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    circuit::{Circuit, Op},
+    graphsim::{Axis, GraphSim, MeasurementResult, NodeIdx},
+    noise::NoiseModel,
+    stabilizer::conjugation_table,
+};
+
+/// Result of `estimate_fidelity`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FidelityWitness {
+    /// Number of shots run for every measurement setting.
+    shots: usize,
+    /// Qubits whose stabilizers are measured together, one list per setting.
+    settings: Vec<Vec<NodeIdx>>,
+    /// Fraction of the shots of each setting in which all its stabilizers were +1.
+    success_rates: Vec<f64>,
+    /// Lower bound on the fidelity with the target state, `sum(success_rates) - k + 1`
+    /// for `k` settings, clamped to 0.
+    fidelity_bound: f64,
+    /// Standard error of the bound from the finite number of shots.
+    standard_error: f64,
+}
+
+/// Colour the graph of `target` so no neighbours share a colour, by greedily colouring
+/// every component in breadth-first order, which takes two colours for bipartite graphs
+/// such as GHZ states and cluster states.
+fn colour_classes(target: &GraphSim) -> Vec<Vec<NodeIdx>> {
+    let adjacency = target.adjacency();
+    let mut colours: Vec<Option<usize>> = vec![None; adjacency.len()];
+    let mut classes: Vec<Vec<NodeIdx>> = Vec::new();
+    let mut queued = BitSet::with_capacity(adjacency.len());
+    for root in 0..adjacency.len() {
+        if !queued.insert(root) {
+            continue;
+        }
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            let colour = (0..)
+                .find(|&colour| adjacency[node].iter().all(|nb| colours[nb] != Some(colour)))
+                .expect("Some colour is free");
+            colours[node] = Some(colour);
+            if colour == classes.len() {
+                classes.push(Vec::new());
+            }
+            classes[colour].push(node);
+            queue.extend(adjacency[node].iter().filter(|&nb| queued.insert(nb)));
+        }
+    }
+    for class in &mut classes {
+        class.sort_unstable();
+    }
+    classes
+}
+
+/// Fraction of `shots` noisy runs of `circuit` in which all stabilizers of `target` at
+/// the qubits of `setting` are measured as +1.
+fn success_rate(
+    circuit: &Circuit,
+    noise: &NoiseModel,
+    target: &GraphSim,
+    setting: &[NodeIdx],
+    shots: usize,
+) -> f64 {
+    let table = conjugation_table();
+    let adjacency = target.adjacency();
+    let vops = target.vops();
+    let mut measured: Vec<NodeIdx> = setting
+        .iter()
+        .flat_map(|&node| std::iter::once(node).chain(adjacency[node].iter()))
+        .collect();
+    measured.sort_unstable();
+    measured.dedup();
+
+    // the X part of a stabilizer sits on the setting and its Z part on the neighbours,
+    // both conjugated by the VOPs of the target
+    let mut readout = circuit.clone();
+    let mut flipped = Vec::with_capacity(measured.len());
+    for &qubit in &measured {
+        let pauli = match setting.binary_search(&qubit) {
+            Ok(_) => Axis::X,
+            Err(_) => Axis::Z,
+        };
+        let (negative, axis) = table[vops[qubit] as usize][pauli as usize];
+        readout.append(Op::Measure { qubit, axis });
+        flipped.push(negative);
+    }
+
+    let mut rng = crate::rng::generator();
+    let mut faults = Vec::new();
+    let mut initial = GraphSim::new(circuit.qubit_amount());
+    initial.reseed(&mut rng);
+    let successes = (0..shots)
+        .filter(|_| {
+            let mut sim = initial.clone();
+            faults.clear();
+            let outcomes = noise.execute(&readout, &mut sim, &mut rng, &mut faults);
+            let signs = &outcomes[outcomes.len() - measured.len()..];
+            let minus = |qubit: NodeIdx| {
+                let idx = measured.binary_search(&qubit).expect("Support is measured");
+                (signs[idx] == MeasurementResult::MinusOne) ^ flipped[idx]
+            };
+            setting.iter().all(|&node| {
+                let parity = adjacency[node]
+                    .iter()
+                    .fold(minus(node), |parity, nb| parity ^ minus(nb));
+                !parity
+            })
+        })
+        .count();
+    successes as f64 / shots.max(1) as f64
+}
+
+/// Estimate a lower bound on the fidelity of the state `circuit` prepares under `noise`
+/// with the graph state `target`, from sampled stabilizer measurements.
+///
+/// This follows the witness protocols of experiments: the qubits are split into classes
+/// without neighbours in the graph of `target`, and for each class one measurement
+/// setting reads out all its stabilizers at once. The fidelity is at least the sum of the
+/// rates at which all stabilizers of a setting were +1, minus the number of settings
+/// plus one, which is the two-setting witness of Tóth and Gühne for GHZ and cluster
+/// states. Every setting is sampled for `shots` noisy shots including measurement errors,
+/// with the GIL released.
+///
+/// Without `target` the state `circuit` prepares without noise is the target, so the
+/// circuit can't contain measurements. Raises `ValueError` if `target` has a different
+/// number of qubits than `circuit`.
+#[pyfunction]
+#[pyo3(signature = (circuit, noise, shots, target = None))]
+pub fn estimate_fidelity(
+    py: Python<'_>,
+    circuit: &Circuit,
+    noise: &NoiseModel,
+    shots: usize,
+    target: Option<&GraphSim>,
+) -> PyResult<FidelityWitness> {
+    let target = match target {
+        Some(target) if target.vops().len() != circuit.qubit_amount() => {
+            return Err(PyValueError::new_err(format!(
+                "Target has {} qubits but the circuit {}",
+                target.vops().len(),
+                circuit.qubit_amount()
+            )));
+        }
+        Some(target) => target.clone(),
+        None if circuit.num_measurements() > 0 => {
+            return Err(PyValueError::new_err(
+                "A circuit with measurements needs an explicit target state",
+            ));
+        }
+        None => {
+            let mut ideal = GraphSim::new(circuit.qubit_amount());
+            for op in circuit.iter() {
+                op.apply(&mut ideal);
+            }
+            ideal
+        }
+    };
+    Ok(py.detach(|| witness(circuit, noise, &target, shots)))
+}
+
+/// Witness of `target` from `shots` noisy shots of `circuit` for every setting.
+fn witness(
+    circuit: &Circuit,
+    noise: &NoiseModel,
+    target: &GraphSim,
+    shots: usize,
+) -> FidelityWitness {
+    let settings = colour_classes(target);
+    let success_rates: Vec<f64> = settings
+        .iter()
+        .map(|setting| success_rate(circuit, noise, target, setting, shots))
+        .collect();
+    let bound = success_rates.iter().sum::<f64>() - (settings.len() as f64 - 1.0);
+    let variance: f64 = success_rates
+        .iter()
+        .map(|rate| rate * (1.0 - rate) / shots.max(1) as f64)
+        .sum();
+    FidelityWitness {
+        shots,
+        settings,
+        success_rates,
+        fidelity_bound: bound.max(0.0),
+        standard_error: variance.sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ghz_circuit(qubits: usize) -> Circuit {
+        let mut circuit = Circuit::new(qubits);
+        circuit.h(0);
+        for qubit in 1..qubits {
+            circuit.cx(0, qubit);
+        }
+        circuit
+    }
+
+    #[test]
+    fn test_ghz_witness() {
+        let circuit = ghz_circuit(4);
+        let mut ideal = GraphSim::new(4);
+        for op in circuit.iter() {
+            op.apply(&mut ideal);
+        }
+        let noiseless = witness(&circuit, &NoiseModel::default(), &ideal, 50);
+        assert_eq!(noiseless.settings, vec![vec![0], vec![1, 2, 3]]);
+        assert_eq!(noiseless.fidelity_bound, 1.0);
+        assert_eq!(noiseless.standard_error, 0.0);
+
+        let noise = NoiseModel::new(0.05, 0.1, 0.05, 0.0, None).unwrap();
+        let noisy = witness(&circuit, &noise, &ideal, 400);
+        assert!(noisy.fidelity_bound < 0.95);
+        assert!(noisy.standard_error > 0.0);
+
+        // the fidelity of the GHZ state with |0+00⟩ is 1/4
+        let mut product = GraphSim::new(4);
+        product.h(1);
+        let other = witness(&circuit, &NoiseModel::default(), &product, 400);
+        assert_eq!(other.settings.len(), 1);
+        assert!(other.fidelity_bound < 0.5);
+    }
+}